@@ -0,0 +1,47 @@
+mod message;
+mod message_arguments;
+
+use proc_macro::TokenStream;
+
+/// Implements `IntoArguments` for a struct with named fields.
+///
+/// Every field becomes an argument with the field's name. Field values are converted with `Into<Value>`,
+/// so numbers become numeric values and `DateTime`s become dates. Fields of type `Option<T>` are only
+/// passed to the message if they are `Some`.
+///
+/// Fields can be configured with the `mf2` attribute:
+/// - `#[mf2(rename = "userName")]` uses a different variable name
+/// - `#[mf2(skip)]` does not pass the field to the message
+#[proc_macro_derive(MessageArguments, attributes(mf2))]
+pub fn derive_message_arguments(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    message_arguments::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Parses and validates a message at compile time.
+///
+/// `message!("Hello {$name}!")` expands to the `Message`. Syntax and data model errors are reported
+/// as compile errors.
+///
+/// `message!(fn "Hello {$name}!")` expands to a function that formats the message. It takes the
+/// locale, followed by one argument per variable the message uses, in the order they first appear:
+///
+/// ```
+/// # use icu_messageformat_2_macros::message;
+/// let greeting = message!(fn "Hello {$name}!");
+/// let locale = "en".parse().expect("valid locale");
+/// assert_eq!(greeting(&locale, "World").as_deref(), Ok("Hello World!"));
+/// ```
+///
+/// The parameters take anything that converts into a `Value`. Like with any generic function, their
+/// types are fixed once the function is bound to a variable. Only the default functions are available
+/// to messages formatted this way.
+#[proc_macro]
+pub fn message(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as message::MessageInput);
+    message::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
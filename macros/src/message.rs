@@ -0,0 +1,350 @@
+use icu_messageformat_2::codegen::Codegen;
+use icu_messageformat_2::data_model::elements::{
+    self, Annotation, Attribute, AttributeValue, Declaration, Expression, FunctionAnnotation,
+    Literal, Markup, MarkupKind, Message, OptionValue, PatternElement, UnsupportedAnnotation,
+    VariableExpression, VariableRef, VariantKey,
+};
+use icu_messageformat_2::parser;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+/// The input of `message!`: an optional `fn` followed by the message
+pub struct MessageInput {
+    function: bool,
+    source: syn::LitStr,
+}
+
+impl Parse for MessageInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let function = input.parse::<Option<syn::Token![fn]>>()?.is_some();
+        let source = input.parse()?;
+        Ok(Self { function, source })
+    }
+}
+
+pub fn expand(input: &MessageInput) -> syn::Result<TokenStream> {
+    let source = input.source.value();
+    let message = parser::parse(&source).map_err(|error| {
+        let (line, column) = error.line_column(&source);
+        syn::Error::new(
+            span_at(&input.source, &source, error.offset),
+            format!("{} (line {line}, column {column})", error.reason),
+        )
+    })?;
+    message
+        .validate()
+        .map_err(|error| syn::Error::new(input.source.span(), error))?;
+
+    if !input.function {
+        return Ok(message_tokens(&message));
+    }
+
+    let function = Codegen::new()
+        .generate("message", &message)
+        .map_err(|error| syn::Error::new(input.source.span(), error))?;
+    let function: TokenStream = function
+        .parse()
+        .map_err(|error| syn::Error::new(input.source.span(), error))?;
+    Ok(quote! {
+        {
+            #function
+            message
+        }
+    })
+}
+
+/// The span of the character at `offset` in the string literal, where the compiler supports it
+fn span_at(literal: &syn::LitStr, source: &str, offset: usize) -> Span {
+    // Only plain string literals map offsets in the value to the same offsets in the token
+    let token = literal.token().to_string();
+    if token.len() != source.len() + 2 || token.get(1..token.len() - 1) != Some(source) {
+        return literal.span();
+    }
+
+    let start = offset + 1;
+    let end = source[offset..]
+        .chars()
+        .next()
+        .map_or(start, |c| start + c.len_utf8());
+    literal
+        .token()
+        .subspan(start..end)
+        .unwrap_or_else(|| literal.span())
+}
+
+fn string(value: &str) -> TokenStream {
+    quote!(::icu_messageformat_2::__private::String::from(#value))
+}
+
+fn message_tokens(message: &Message) -> TokenStream {
+    match message {
+        Message::Pattern(message) => {
+            let declarations = message.declarations.iter().map(declaration);
+            let pattern = message.pattern.iter().map(pattern_element);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Message::Pattern(
+                    ::icu_messageformat_2::data_model::elements::PatternMessage {
+                        declarations: ::icu_messageformat_2::__private::vec![#(#declarations),*],
+                        pattern: ::icu_messageformat_2::__private::vec![#(#pattern),*],
+                    }
+                )
+            }
+        }
+        Message::Select(message) => {
+            let declarations = message.declarations.iter().map(declaration);
+            let selectors = message.selectors.iter().map(expression);
+            let variants = message.variants.iter().map(|variant| {
+                let keys = variant.keys.iter().map(|key| match key {
+                    VariantKey::Literal(l) => {
+                        let l = literal(l);
+                        quote!(::icu_messageformat_2::data_model::elements::VariantKey::Literal(#l))
+                    }
+                    VariantKey::Catchall => {
+                        quote!(::icu_messageformat_2::data_model::elements::VariantKey::Catchall)
+                    }
+                });
+                let value = variant.value.iter().map(pattern_element);
+                quote! {
+                    ::icu_messageformat_2::data_model::elements::Variant {
+                        keys: ::icu_messageformat_2::__private::vec![#(#keys),*],
+                        value: ::icu_messageformat_2::__private::vec![#(#value),*],
+                    }
+                }
+            });
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Message::Select(
+                    ::icu_messageformat_2::data_model::elements::SelectMessage {
+                        declarations: ::icu_messageformat_2::__private::vec![#(#declarations),*],
+                        selectors: ::icu_messageformat_2::__private::vec![#(#selectors),*],
+                        variants: ::icu_messageformat_2::__private::vec![#(#variants),*],
+                    }
+                )
+            }
+        }
+    }
+}
+
+fn declaration(declaration: &Declaration) -> TokenStream {
+    match declaration {
+        Declaration::Input(input) => {
+            let name = string(&input.name);
+            let value = variable_expression(&input.value);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Declaration::Input(
+                    ::icu_messageformat_2::data_model::elements::InputDeclaration {
+                        name: #name,
+                        value: #value,
+                    }
+                )
+            }
+        }
+        Declaration::Local(local) => {
+            let name = string(&local.name);
+            let value = expression(&local.value);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Declaration::Local(
+                    ::icu_messageformat_2::data_model::elements::LocalDeclaration {
+                        name: #name,
+                        value: #value,
+                    }
+                )
+            }
+        }
+        Declaration::UnsupportedStatement(statement) => {
+            let keyword = string(&statement.keyword);
+            let body = statement.body.as_deref().map_or_else(
+                || quote!(::core::option::Option::None),
+                |body| {
+                    let body = string(body);
+                    quote!(::core::option::Option::Some(#body))
+                },
+            );
+            let expressions = statement.expressions.iter().map(expression);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Declaration::UnsupportedStatement(
+                    ::icu_messageformat_2::data_model::elements::UnsupportedStatement {
+                        keyword: #keyword,
+                        body: #body,
+                        expressions: ::icu_messageformat_2::__private::vec![#(#expressions),*],
+                    }
+                )
+            }
+        }
+    }
+}
+
+fn pattern_element(element: &PatternElement) -> TokenStream {
+    match element {
+        PatternElement::Literal(text) => {
+            let text = string(text);
+            quote!(::icu_messageformat_2::data_model::elements::PatternElement::Literal(#text))
+        }
+        PatternElement::Expression(e) => {
+            let e = expression(e);
+            quote!(::icu_messageformat_2::data_model::elements::PatternElement::Expression(#e))
+        }
+        PatternElement::Markup(m) => {
+            let m = markup(m);
+            quote!(::icu_messageformat_2::data_model::elements::PatternElement::Markup(#m))
+        }
+    }
+}
+
+fn markup(markup: &Markup) -> TokenStream {
+    let kind = match markup.kind {
+        MarkupKind::Open => quote!(Open),
+        MarkupKind::Standalone => quote!(Standalone),
+        MarkupKind::Close => quote!(Close),
+    };
+    let name = string(&markup.name);
+    let options = markup.options.iter().map(option);
+    let attributes = markup.attributes.iter().map(attribute);
+    quote! {
+        ::icu_messageformat_2::data_model::elements::Markup {
+            kind: ::icu_messageformat_2::data_model::elements::MarkupKind::#kind,
+            name: #name,
+            options: ::icu_messageformat_2::__private::vec![#(#options),*],
+            attributes: ::icu_messageformat_2::__private::vec![#(#attributes),*],
+        }
+    }
+}
+
+fn expression(expression: &Expression) -> TokenStream {
+    match expression {
+        Expression::Literal(e) => {
+            let arg = literal(&e.arg);
+            let annotation = optional_annotation(e.annotation.as_ref());
+            let attributes = e.attributes.iter().map(attribute);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Expression::Literal(
+                    ::icu_messageformat_2::data_model::elements::LiteralExpression {
+                        arg: #arg,
+                        annotation: #annotation,
+                        attributes: ::icu_messageformat_2::__private::vec![#(#attributes),*],
+                    }
+                )
+            }
+        }
+        Expression::Variable(e) => {
+            let e = variable_expression(e);
+            quote!(::icu_messageformat_2::data_model::elements::Expression::Variable(#e))
+        }
+        Expression::Function(e) => {
+            let annotation = function_annotation(&e.annotation);
+            let attributes = e.attributes.iter().map(attribute);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Expression::Function(
+                    ::icu_messageformat_2::data_model::elements::FunctionExpression {
+                        annotation: #annotation,
+                        attributes: ::icu_messageformat_2::__private::vec![#(#attributes),*],
+                    }
+                )
+            }
+        }
+        Expression::Unsupported(e) => {
+            let annotation = unsupported_annotation(&e.annotation);
+            let attributes = e.attributes.iter().map(attribute);
+            quote! {
+                ::icu_messageformat_2::data_model::elements::Expression::Unsupported(
+                    ::icu_messageformat_2::data_model::elements::UnsupportedExpression {
+                        annotation: #annotation,
+                        attributes: ::icu_messageformat_2::__private::vec![#(#attributes),*],
+                    }
+                )
+            }
+        }
+    }
+}
+
+fn variable_expression(expression: &VariableExpression) -> TokenStream {
+    let arg = variable(&expression.arg);
+    let annotation = optional_annotation(expression.annotation.as_ref());
+    let attributes = expression.attributes.iter().map(attribute);
+    quote! {
+        ::icu_messageformat_2::data_model::elements::VariableExpression {
+            arg: #arg,
+            annotation: #annotation,
+            attributes: ::icu_messageformat_2::__private::vec![#(#attributes),*],
+        }
+    }
+}
+
+fn optional_annotation(annotation: Option<&Annotation>) -> TokenStream {
+    match annotation {
+        Some(Annotation::Function(f)) => {
+            let f = function_annotation(f);
+            quote!(::core::option::Option::Some(
+                ::icu_messageformat_2::data_model::elements::Annotation::Function(#f)
+            ))
+        }
+        Some(Annotation::Unsupported(u)) => {
+            let u = unsupported_annotation(u);
+            quote!(::core::option::Option::Some(
+                ::icu_messageformat_2::data_model::elements::Annotation::Unsupported(#u)
+            ))
+        }
+        None => quote!(::core::option::Option::None),
+    }
+}
+
+fn function_annotation(annotation: &FunctionAnnotation) -> TokenStream {
+    let name = string(&annotation.name);
+    let options = annotation.options.iter().map(option);
+    quote! {
+        ::icu_messageformat_2::data_model::elements::FunctionAnnotation {
+            name: #name,
+            options: ::icu_messageformat_2::__private::vec![#(#options),*],
+        }
+    }
+}
+
+fn unsupported_annotation(annotation: &UnsupportedAnnotation) -> TokenStream {
+    let source = string(&annotation.source);
+    quote!(::icu_messageformat_2::data_model::elements::UnsupportedAnnotation { source: #source })
+}
+
+fn option(option: &elements::Option) -> TokenStream {
+    let name = string(&option.name);
+    let value = match &option.value {
+        OptionValue::Literal(l) => {
+            let l = literal(l);
+            quote!(::icu_messageformat_2::data_model::elements::OptionValue::Literal(#l))
+        }
+        OptionValue::Variable(v) => {
+            let v = variable(v);
+            quote!(::icu_messageformat_2::data_model::elements::OptionValue::Variable(#v))
+        }
+    };
+    quote!(::icu_messageformat_2::data_model::elements::Option { name: #name, value: #value })
+}
+
+fn attribute(attribute: &Attribute) -> TokenStream {
+    let name = string(&attribute.name);
+    let value = match &attribute.value {
+        Some(AttributeValue::Literal(l)) => {
+            let l = literal(l);
+            quote!(::core::option::Option::Some(
+                ::icu_messageformat_2::data_model::elements::AttributeValue::Literal(#l)
+            ))
+        }
+        Some(AttributeValue::Variable(v)) => {
+            let v = variable(v);
+            quote!(::core::option::Option::Some(
+                ::icu_messageformat_2::data_model::elements::AttributeValue::Variable(#v)
+            ))
+        }
+        None => quote!(::core::option::Option::None),
+    };
+    quote!(::icu_messageformat_2::data_model::elements::Attribute { name: #name, value: #value })
+}
+
+fn literal(literal: &Literal) -> TokenStream {
+    let value = string(&literal.value);
+    quote!(::icu_messageformat_2::data_model::elements::Literal { value: #value })
+}
+
+fn variable(variable: &VariableRef) -> TokenStream {
+    let name = string(&variable.name);
+    quote!(::icu_messageformat_2::data_model::elements::VariableRef { name: #name })
+}
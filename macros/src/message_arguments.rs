@@ -0,0 +1,137 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// How a single struct field is passed to the message
+struct FieldArgument<'a> {
+    ident: &'a syn::Ident,
+    name: String,
+    /// The type that is converted into a value. For `Option<T>` fields this is `T`.
+    value_type: &'a syn::Type,
+    optional: bool,
+}
+
+pub fn expand(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "MessageArguments can only be derived for structs",
+        ));
+    };
+
+    let syn::Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "MessageArguments can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut arguments = Vec::new();
+    for field in &fields.named {
+        if let Some(argument) = field_argument(field)? {
+            arguments.push(argument);
+        }
+    }
+
+    let inserts = arguments.iter().map(|argument| {
+        let ident = argument.ident;
+        let name = &argument.name;
+        if argument.optional {
+            quote! {
+                if let ::core::option::Option::Some(value) = self.#ident {
+                    arguments.insert(#name, value);
+                }
+            }
+        } else {
+            quote! {
+                arguments.insert(#name, self.#ident);
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (_, type_generics, _) = input.generics.split_for_impl();
+
+    // Generic field types need to be convertible into values
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for argument in &arguments {
+            let value_type = argument.value_type;
+            where_clause.predicates.push(syn::parse_quote!(
+                #value_type: ::core::convert::Into<::icu_messageformat_2::arguments::Value>
+            ));
+        }
+    }
+    generics.params.insert(0, syn::parse_quote!('__arguments));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::icu_messageformat_2::arguments::IntoArguments<'__arguments> for #ident #type_generics #where_clause {
+            fn into_arguments(self) -> ::icu_messageformat_2::arguments::Arguments<'__arguments> {
+                let mut arguments = ::icu_messageformat_2::arguments::Arguments::new();
+                #(#inserts)*
+                arguments
+            }
+        }
+    })
+}
+
+/// Reads the `mf2` attributes of a field. Returns `None` if the field is skipped.
+fn field_argument(field: &syn::Field) -> syn::Result<Option<FieldArgument<'_>>> {
+    let Some(ident) = &field.ident else {
+        return Err(syn::Error::new_spanned(field, "expected a named field"));
+    };
+
+    let mut name = ident.to_string().trim_start_matches("r#").to_string();
+    let mut skip = false;
+
+    for attribute in field.attrs.iter().filter(|a| a.path().is_ident("mf2")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                name = value.value();
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown mf2 attribute, expected `rename` or `skip`"))
+            }
+        })?;
+    }
+
+    if skip {
+        return Ok(None);
+    }
+
+    let option_inner = option_inner_type(&field.ty);
+    Ok(Some(FieldArgument {
+        ident,
+        name,
+        value_type: option_inner.unwrap_or(&field.ty),
+        optional: option_inner.is_some(),
+    }))
+}
+
+/// Returns `T` if the type is written as `Option<T>`
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,38 @@
+use icu_messageformat_2::data_model::elements::Message;
+use icu_messageformat_2::locale::Locale;
+use icu_messageformat_2_macros::message;
+
+fn en() -> Locale {
+    "en".parse().expect("valid locale")
+}
+
+#[test]
+fn it_expands_to_the_message() {
+    let message = message!(
+        ".input {$count :number}\n.match {$count}\none {{{$count} item}}\n* {{{$count} items}}"
+    );
+
+    assert_eq!(
+        message,
+        ".input {$count :number}\n.match {$count}\none {{{$count} item}}\n* {{{$count} items}}"
+            .parse::<Message>()
+            .expect("valid message")
+    );
+}
+
+#[test]
+fn it_expands_to_a_formatting_function() {
+    let items = message!(fn ".match {$count :number}\none {{{$count} item for {$user-name}}}\n* {{{$count} items for {$user-name}}}");
+
+    assert_eq!(items(&en(), 1, "Ann").as_deref(), Ok("1 item for Ann"));
+    assert_eq!(items(&en(), 3, "Bo").as_deref(), Ok("3 items for Bo"));
+    assert_eq!(
+        message!(fn "{$amount :number minimumFractionDigits=2}")(&en(), 2.5).as_deref(),
+        Ok("2.50")
+    );
+}
+
+#[test]
+fn it_reports_errors_at_compile_time() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
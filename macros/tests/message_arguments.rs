@@ -0,0 +1,85 @@
+use icu_messageformat_2::arguments::{DateTime, IntoArguments, Value};
+use icu_messageformat_2_macros::MessageArguments;
+
+#[derive(MessageArguments)]
+struct Order {
+    #[mf2(rename = "customerName")]
+    customer: String,
+    item_count: u32,
+    total: f64,
+    placed_at: DateTime,
+    note: Option<String>,
+    #[mf2(skip)]
+    #[allow(dead_code)]
+    internal_id: u64,
+}
+
+#[derive(MessageArguments)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn order() -> Order {
+    Order {
+        customer: "Ada".into(),
+        item_count: 3,
+        total: 12.5,
+        placed_at: DateTime::from_ymd(2024, 3, 1).expect("valid date"),
+        note: None,
+        internal_id: 7,
+    }
+}
+
+#[test]
+fn it_uses_field_names_as_variable_names() {
+    let arguments = order().into_arguments();
+
+    assert_eq!(
+        arguments.get("customerName").as_deref(),
+        Some(&Value::from("Ada"))
+    );
+    assert!(arguments.get("customer").is_none());
+    assert_eq!(
+        arguments.get("item_count").as_deref(),
+        Some(&Value::Integer(3))
+    );
+}
+
+#[test]
+fn it_maps_field_types_to_value_kinds() {
+    let arguments = order().into_arguments();
+
+    assert_eq!(arguments.get("total").as_deref(), Some(&Value::Float(12.5)));
+    assert!(matches!(
+        arguments.get("placed_at").as_deref(),
+        Some(Value::DateTime(_))
+    ));
+}
+
+#[test]
+fn it_skips_fields() {
+    let arguments = order().into_arguments();
+
+    assert!(arguments.get("internal_id").is_none());
+}
+
+#[test]
+fn it_only_passes_optional_fields_that_are_set() {
+    assert!(order().into_arguments().get("note").is_none());
+
+    let with_note = Order {
+        note: Some("fragile".into()),
+        ..order()
+    };
+    assert_eq!(
+        with_note.into_arguments().get("note").as_deref(),
+        Some(&Value::from("fragile"))
+    );
+}
+
+#[test]
+fn it_supports_generic_structs() {
+    let arguments = Wrapper { value: 1.5_f32 }.into_arguments();
+
+    assert_eq!(arguments.get("value").as_deref(), Some(&Value::Float(1.5)));
+}
@@ -0,0 +1,5 @@
+use icu_messageformat_2_macros::message;
+
+fn main() {
+    let _ = message!("Hello {$name");
+}
@@ -0,0 +1,286 @@
+mod value;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::BuildHasher;
+
+use crate::data_model::elements::VariableRef;
+
+pub use value::{CustomValue, DateTime, Decimal, ParseDateTimeError, ParseDecimalError, Value};
+
+type Lookup<'a> = Box<dyn Fn(&str) -> Option<Value> + 'a>;
+
+/// The external variables a message is formatted with.
+///
+/// Values are looked up by name, first in the values that were inserted directly and then in the lookup function, if there is one.
+#[derive(Default)]
+pub struct Arguments<'a> {
+    values: BTreeMap<String, Value>,
+    lookup: Option<Lookup<'a>>,
+}
+
+impl<'a> Arguments<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates arguments that are resolved lazily by calling `lookup` with the variable name
+    pub fn from_fn(lookup: impl Fn(&str) -> Option<Value> + 'a) -> Self {
+        Self {
+            values: BTreeMap::new(),
+            lookup: Some(Box::new(lookup)),
+        }
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Builder style variant of [`Arguments::insert`]
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Cow<'_, Value>> {
+        if let Some(value) = self.values.get(name) {
+            return Some(Cow::Borrowed(value));
+        }
+
+        self.lookup
+            .as_ref()
+            .and_then(|lookup| lookup(name))
+            .map(Cow::Owned)
+    }
+
+    /// Resolves a variable reference from the message against these arguments
+    pub fn resolve(&self, variable: &VariableRef) -> Option<Cow<'_, Value>> {
+        self.get(&variable.name)
+    }
+
+    /// The names of the values that were inserted directly. Values provided by a lookup function are not included.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+impl fmt::Debug for Arguments<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arguments")
+            .field("values", &self.values)
+            .field("lookup", &self.lookup.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Arguments<'_> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self {
+            values: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+            lookup: None,
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for Arguments<'_> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// Conversion into [`Arguments`], so that formatting functions can accept maps, lists of pairs or closures directly.
+pub trait IntoArguments<'a> {
+    fn into_arguments(self) -> Arguments<'a>;
+}
+
+impl<'a> IntoArguments<'a> for Arguments<'a> {
+    fn into_arguments(self) -> Self {
+        self
+    }
+}
+
+/// No arguments
+impl<'a> IntoArguments<'a> for () {
+    fn into_arguments(self) -> Arguments<'a> {
+        Arguments::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K: Into<String>, V: Into<Value>, S: BuildHasher> IntoArguments<'a> for HashMap<K, V, S> {
+    fn into_arguments(self) -> Arguments<'a> {
+        self.into_iter().collect()
+    }
+}
+
+impl<'a, K: Into<String>, V: Into<Value>> IntoArguments<'a> for BTreeMap<K, V> {
+    fn into_arguments(self) -> Arguments<'a> {
+        self.into_iter().collect()
+    }
+}
+
+impl<'a, K: Into<String>, V: Into<Value>> IntoArguments<'a> for Vec<(K, V)> {
+    fn into_arguments(self) -> Arguments<'a> {
+        self.into_iter().collect()
+    }
+}
+
+impl<'a, K: Into<String>, V: Into<Value>, const N: usize> IntoArguments<'a> for [(K, V); N] {
+    fn into_arguments(self) -> Arguments<'a> {
+        self.into_iter().collect()
+    }
+}
+
+impl<'a, K: AsRef<str>, V: Clone + Into<Value>> IntoArguments<'a> for &[(K, V)] {
+    fn into_arguments(self) -> Arguments<'a> {
+        self.iter().map(|(k, v)| (k.as_ref(), v.clone())).collect()
+    }
+}
+
+impl<'a, F: Fn(&str) -> Option<Value> + 'a> IntoArguments<'a> for F {
+    fn into_arguments(self) -> Arguments<'a> {
+        Arguments::from_fn(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_common_types_into_values() {
+        assert_eq!(Value::from("foo"), Value::String("foo".into()));
+        assert_eq!(Value::from(42_u8), Value::Integer(42));
+        assert_eq!(Value::from(1.5), Value::Float(1.5));
+        assert_eq!(Value::from(true), Value::Boolean(true));
+        assert_eq!(
+            Value::from(u64::MAX),
+            Value::Decimal(Decimal::new(u64::MAX.into(), 0))
+        );
+        assert_eq!(Value::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Value::from(i128::MIN).to_string(), i128::MIN.to_string());
+        assert_eq!(
+            Value::from(vec![1, 2]),
+            Value::List(vec![Value::Integer(1), Value::Integer(2)])
+        );
+    }
+
+    #[test]
+    fn it_parses_exact_decimals() {
+        let decimal: Decimal = "-12.50".parse().expect("valid decimal");
+
+        assert_eq!(decimal, Decimal::new(-1250, 2));
+        assert_eq!(decimal.to_string(), "-12.50");
+        assert_eq!(Decimal::new(5, 3).to_string(), "0.005");
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!(".".parse::<Decimal>().is_err());
+        assert!(format!("0.{}1", "0".repeat(999))
+            .parse::<Decimal>()
+            .is_err());
+        assert_eq!(Decimal::new(1, u32::MAX).scale(), Decimal::MAX_SCALE);
+        assert_eq!(Decimal::new(1, u32::MAX).to_string().len(), 1001);
+    }
+
+    #[test]
+    fn it_converts_unix_timestamps_into_dates() {
+        let date = DateTime::from_unix_timestamp(1_709_298_300);
+
+        assert_eq!(
+            date,
+            DateTime::from_ymd(2024, 3, 1)
+                .and_then(|d| d.with_hms(13, 5, 0))
+                .expect("valid date")
+        );
+        assert_eq!(date.to_string(), "2024-03-01T13:05:00");
+        assert_eq!(
+            DateTime::from_unix_timestamp(-1).to_string(),
+            "1969-12-31T23:59:59"
+        );
+        assert!(DateTime::from_ymd(2023, 2, 29).is_none());
+    }
+
+    #[test]
+    fn it_parses_iso_dates() {
+        assert_eq!(
+            "2024-03-01".parse(),
+            Ok(DateTime::from_ymd(2024, 3, 1).expect("valid date"))
+        );
+        assert_eq!(
+            "2024-03-01T13:05:09.123Z"
+                .parse::<DateTime>()
+                .map(|d| d.to_string()),
+            Ok("2024-03-01T13:05:09".into())
+        );
+        assert!("2024-3-1".parse::<DateTime>().is_err());
+        assert!("2024-02-30".parse::<DateTime>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_converts_hash_maps_into_arguments() {
+        let map: HashMap<String, i32> = HashMap::from([("count".into(), 3)]);
+        let arguments = map.into_arguments();
+        assert_eq!(arguments.get("count").as_deref(), Some(&Value::Integer(3)));
+    }
+
+    #[test]
+    fn it_converts_maps_and_pairs_into_arguments() {
+        let arguments = BTreeMap::from([("name", "World")]).into_arguments();
+        assert_eq!(
+            arguments.get("name").as_deref(),
+            Some(&Value::from("World"))
+        );
+
+        let pairs = [("a", 1), ("b", 2)];
+        let arguments = pairs.as_slice().into_arguments();
+        assert_eq!(arguments.get("b").as_deref(), Some(&Value::Integer(2)));
+        assert!(arguments.get("c").is_none());
+    }
+
+    #[test]
+    fn it_resolves_variables_through_closures() {
+        let arguments =
+            (|name: &str| (name == "user").then(|| Value::from("Ada"))).into_arguments();
+
+        let user = VariableRef {
+            name: "user".into(),
+        };
+        let other = VariableRef {
+            name: "other".into(),
+        };
+
+        assert_eq!(
+            arguments.resolve(&user).as_deref(),
+            Some(&Value::from("Ada"))
+        );
+        assert!(arguments.resolve(&other).is_none());
+    }
+
+    #[test]
+    fn it_prefers_inserted_values_over_the_lookup() {
+        let arguments =
+            Arguments::from_fn(|_| Some(Value::from("lookup"))).with("name", "inserted");
+
+        assert_eq!(
+            arguments.get("name").as_deref(),
+            Some(&Value::from("inserted"))
+        );
+        assert_eq!(
+            arguments.get("other").as_deref(),
+            Some(&Value::from("lookup"))
+        );
+    }
+}
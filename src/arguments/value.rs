@@ -0,0 +1,556 @@
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A value that can be passed to a message as an argument.
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    /// An exact decimal number, for values that must not be rounded through a float
+    Decimal(Decimal),
+    Boolean(bool),
+    DateTime(DateTime),
+    List(Vec<Self>),
+    /// An opaque value that only custom functions know how to interpret
+    Custom(Arc<dyn CustomValue>),
+}
+
+impl Value {
+    /// Wraps a custom value that only custom functions know how to interpret
+    pub fn custom(value: impl CustomValue + 'static) -> Self {
+        Self::Custom(Arc::new(value))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a float, if it is numeric
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            #[allow(clippy::cast_precision_loss)]
+            Self::Integer(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            Self::Decimal(d) => Some(d.to_f64()),
+            _ => None,
+        }
+    }
+
+    pub const fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer(_) | Self::Float(_) | Self::Decimal(_))
+    }
+
+    /// Returns the custom value if it is of type `T`
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Custom(c) => c.as_any().downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            #[allow(clippy::float_cmp)]
+            (Self::Float(a), Self::Float(b)) => a == b,
+            (Self::Decimal(a), Self::Decimal(b)) => a == b,
+            (Self::Boolean(a), Self::Boolean(b)) => a == b,
+            (Self::DateTime(a), Self::DateTime(b)) => a == b,
+            (Self::List(a), Self::List(b)) => a == b,
+            // Custom values are opaque, so only the same instance is considered equal
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => f.write_str(s),
+            Self::Integer(i) => i.fmt(f),
+            Self::Float(n) => n.fmt(f),
+            Self::Decimal(d) => d.fmt(f),
+            Self::Boolean(b) => b.fmt(f),
+            Self::DateTime(d) => d.fmt(f),
+            Self::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.fmt(f)?;
+                }
+                Ok(())
+            }
+            Self::Custom(c) => c.fmt(f),
+        }
+    }
+}
+
+/// A value of an application-defined type.
+///
+/// The `Display` implementation is used as the fallback formatting if no custom function handles the value.
+pub trait CustomValue: fmt::Debug + fmt::Display + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Self::String(value.clone())
+    }
+}
+
+impl From<Cow<'_, str>> for Value {
+    fn from(value: Cow<'_, str>) -> Self {
+        Self::String(value.into_owned())
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(value: $t) -> Self {
+                    Self::Integer(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+macro_rules! impl_from_wide_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(value: $t) -> Self {
+                    i64::try_from(value).map_or_else(|_| Self::Decimal(value.into()), Self::Integer)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_wide_integer!(u64, usize, isize, i128, u128);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::Float(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(value: Decimal) -> Self {
+        Self::Decimal(value)
+    }
+}
+
+impl From<DateTime> for Value {
+    fn from(value: DateTime) -> Self {
+        Self::DateTime(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<SystemTime> for Value {
+    fn from(value: SystemTime) -> Self {
+        Self::DateTime(value.into())
+    }
+}
+
+impl<T: Into<Self>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Self::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Self>> From<&[T]> for Value {
+    fn from(value: &[T]) -> Self {
+        Self::List(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+/// An exact decimal number, stored as `mantissa * 10^-scale`.
+///
+/// Unlike floats, decimals keep trailing zeros, so `1.50` formats as `1.50`. The mantissa is stored
+/// without its sign, so every `u128` and `i128` fits. The scale is at most [`Decimal::MAX_SCALE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    negative: bool,
+    mantissa: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// The largest scale, the same as the largest number of digits when formatting. Larger scales
+    /// would make the digits take up arbitrary amounts of memory.
+    pub const MAX_SCALE: u32 = 999;
+
+    /// Creates a decimal, with the scale capped at [`Decimal::MAX_SCALE`]
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Self {
+            negative: mantissa < 0,
+            mantissa: mantissa.unsigned_abs(),
+            scale: cap_scale(scale),
+        }
+    }
+
+    /// Creates a non-negative decimal, eg. for `u128` values above `i128::MAX`, with the scale
+    /// capped at [`Decimal::MAX_SCALE`]
+    pub const fn from_unsigned(mantissa: u128, scale: u32) -> Self {
+        Self {
+            negative: false,
+            mantissa,
+            scale: cap_scale(scale),
+        }
+    }
+
+    /// The mantissa, or `None` if it doesn't fit in an `i128`
+    pub fn mantissa(&self) -> Option<i128> {
+        let mantissa = i128::try_from(self.mantissa).ok()?;
+        Some(if self.negative { -mantissa } else { mantissa })
+    }
+
+    /// The number of digits after the decimal point
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub const fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The digits before the decimal point, without a sign
+    pub fn integer_digits(&self) -> String {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        if digits.len() > scale {
+            digits[..digits.len() - scale].to_string()
+        } else {
+            "0".into()
+        }
+    }
+
+    /// The digits after the decimal point, including trailing zeros
+    pub fn fraction_digits(&self) -> String {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        if digits.len() >= scale {
+            digits[digits.len() - scale..].to_string()
+        } else {
+            format!("{}{}", "0".repeat(scale - digits.len()), digits)
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or_else(|_| {
+            let magnitude = self.mantissa as f64;
+            if self.negative {
+                -magnitude
+            } else {
+                magnitude
+            }
+        })
+    }
+}
+
+const fn cap_scale(scale: u32) -> u32 {
+    if scale > Decimal::MAX_SCALE {
+        Decimal::MAX_SCALE
+    } else {
+        scale
+    }
+}
+
+impl From<i128> for Decimal {
+    fn from(value: i128) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl From<u128> for Decimal {
+    fn from(value: u128) -> Self {
+        Self::from_unsigned(value, 0)
+    }
+}
+
+macro_rules! impl_decimal_from_integer {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl From<$t> for Decimal {
+                fn from(value: $t) -> Self {
+                    // Lossless, `as` because there are no `From` impls for pointer-sized integers
+                    #[allow(clippy::cast_lossless)]
+                    Self::from(value as $wide)
+                }
+            }
+        )*
+    };
+}
+
+impl_decimal_from_integer!(i64 => i128, isize => i128, u64 => u128, usize => u128);
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative() {
+            f.write_str("-")?;
+        }
+        f.write_str(&self.integer_digits())?;
+        if self.scale > 0 {
+            write!(f, ".{}", self.fraction_digits())?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a string is not a valid decimal number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError;
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid decimal number")
+    }
+}
+
+impl core::error::Error for ParseDecimalError {}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = s.strip_prefix('-').map_or_else(
+            || (false, s.strip_prefix('+').unwrap_or(s)),
+            |rest| (true, rest),
+        );
+
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(ParseDecimalError);
+        }
+        if !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        {
+            return Err(ParseDecimalError);
+        }
+
+        let mantissa: u128 = format!("{integer}{fraction}")
+            .parse()
+            .map_err(|_| ParseDecimalError)?;
+        let scale = u32::try_from(fraction.len())
+            .ok()
+            .filter(|scale| *scale <= Self::MAX_SCALE)
+            .ok_or(ParseDecimalError)?;
+
+        Ok(Self {
+            negative: negative && mantissa != 0,
+            mantissa,
+            scale,
+        })
+    }
+}
+
+/// A calendar date and time of day in the proleptic Gregorian calendar, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Creates a date at midnight. Returns `None` if the date does not exist.
+    pub const fn from_ymd(year: i32, month: u8, day: u8) -> Option<Self> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        })
+    }
+
+    /// Sets the time of day. Returns `None` if the time does not exist.
+    pub const fn with_hms(self, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+
+        Some(Self {
+            hour,
+            minute,
+            second,
+            ..self
+        })
+    }
+
+    /// Converts seconds since the unix epoch into a UTC date and time
+    pub const fn from_unix_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(86_400);
+        let seconds_of_day = timestamp.rem_euclid(86_400);
+
+        // See: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Self {
+            year: year as i32,
+            month: month as u8,
+            day: day as u8,
+            hour: (seconds_of_day / 3600) as u8,
+            minute: (seconds_of_day % 3600 / 60) as u8,
+            second: (seconds_of_day % 60) as u8,
+        }
+    }
+}
+
+const fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<SystemTime> for DateTime {
+    fn from(value: SystemTime) -> Self {
+        let timestamp = match value.duration_since(UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_secs()).unwrap_or(i64::MAX),
+            Err(before) => -i64::try_from(before.duration().as_secs()).unwrap_or(i64::MAX),
+        };
+        Self::from_unix_timestamp(timestamp)
+    }
+}
+
+/// The error returned when a string is not a valid ISO 8601 date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDateTimeError;
+
+impl fmt::Display for ParseDateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid ISO 8601 date")
+    }
+}
+
+impl core::error::Error for ParseDateTimeError {}
+
+/// Parses ISO 8601 dates like `2024-03-01`, `2024-03-01T13:05` or `2024-03-01T13:05:00Z`
+impl FromStr for DateTime {
+    type Err = ParseDateTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn number<T: FromStr>(digits: Option<&str>, len: usize) -> Result<T, ParseDateTimeError> {
+            digits
+                .filter(|d| d.len() == len && d.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|d| d.parse().ok())
+                .ok_or(ParseDateTimeError)
+        }
+
+        let s = s.strip_suffix('Z').unwrap_or(s);
+        let (date, time) = s
+            .split_once(['T', ' '])
+            .map_or((s, None), |(d, t)| (d, Some(t)));
+
+        let mut parts = date.split('-');
+        let year = number(parts.next(), 4)?;
+        let month = number(parts.next(), 2)?;
+        let day = number(parts.next(), 2)?;
+        if parts.next().is_some() {
+            return Err(ParseDateTimeError);
+        }
+        let date = Self::from_ymd(year, month, day).ok_or(ParseDateTimeError)?;
+
+        let Some(time) = time else {
+            return Ok(date);
+        };
+        // Fractional seconds are ignored
+        let time = time.split_once('.').map_or(time, |(time, _)| time);
+        let mut parts = time.split(':');
+        let hour = number(parts.next(), 2)?;
+        let minute = number(parts.next(), 2)?;
+        let second = parts.next().map_or(Ok(0), |s| number(Some(s), 2))?;
+        if parts.next().is_some() {
+            return Err(ParseDateTimeError);
+        }
+        date.with_hms(hour, minute, second)
+            .ok_or(ParseDateTimeError)
+    }
+}
+
+/// Formats the date as ISO 8601, eg. `2024-03-01T13:05:00`
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
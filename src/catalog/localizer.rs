@@ -0,0 +1,202 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::arguments::IntoArguments;
+use crate::catalog::{Catalog, CatalogError};
+use crate::data_model::elements::Message;
+use crate::format::CompiledMessage;
+use crate::locale::Locale;
+
+/// Catalogs for several locales. Messages are looked up through the fallback chain of the
+/// requested locale, followed by the chain of the default locale.
+///
+/// With catalogs for `de` and `en`, and `en` as the default locale, a message for `de-CH` comes from
+/// the `de` catalog if it has it, and from the `en` catalog otherwise.
+#[derive(Debug)]
+pub struct Localizer {
+    default_locale: Locale,
+    catalogs: BTreeMap<Locale, Catalog>,
+}
+
+/// A message that was found for a locale
+#[derive(Debug, Clone, Copy)]
+pub struct Found<'l> {
+    /// The locale of the catalog the message came from
+    pub locale: &'l Locale,
+    pub message: &'l Message,
+    pub compiled: &'l CompiledMessage,
+}
+
+/// A formatted message, with the locale of the catalog it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Localized<'l> {
+    pub locale: &'l Locale,
+    pub text: String,
+}
+
+impl Localizer {
+    pub const fn new(default_locale: Locale) -> Self {
+        Self {
+            default_locale,
+            catalogs: BTreeMap::new(),
+        }
+    }
+
+    pub const fn default_locale(&self) -> &Locale {
+        &self.default_locale
+    }
+
+    /// Adds a catalog for its locale, returning the catalog that was previously stored for it
+    pub fn insert(&mut self, catalog: Catalog) -> Option<Catalog> {
+        self.catalogs.insert(catalog.locale().clone(), catalog)
+    }
+
+    /// Builder style variant of [`Localizer::insert`]
+    #[must_use]
+    pub fn with(mut self, catalog: Catalog) -> Self {
+        self.insert(catalog);
+        self
+    }
+
+    pub fn catalog(&self, locale: &Locale) -> Option<&Catalog> {
+        self.catalogs.get(locale)
+    }
+
+    pub fn catalog_mut(&mut self, locale: &Locale) -> Option<&mut Catalog> {
+        self.catalogs.get_mut(locale)
+    }
+
+    pub fn locales(&self) -> impl Iterator<Item = &Locale> {
+        self.catalogs.keys()
+    }
+
+    /// The locales that are searched for a message requested in `locale`, in order. Locales without
+    /// a catalog are included.
+    pub fn fallback_chain(&self, locale: &Locale) -> Vec<Locale> {
+        let mut chain = locale.fallback_chain();
+        for fallback in self.default_locale.fallback_chain() {
+            if !chain.contains(&fallback) {
+                chain.push(fallback);
+            }
+        }
+        chain
+    }
+
+    /// Finds the message `id` in the first catalog of the fallback chain that has it
+    pub fn find(&self, locale: &Locale, id: &str) -> Option<Found<'_>> {
+        self.fallback_chain(locale).iter().find_map(|fallback| {
+            let (locale, catalog) = self.catalogs.get_key_value(fallback)?;
+            Some(Found {
+                locale,
+                message: catalog.get(id)?,
+                compiled: catalog.compiled(id)?,
+            })
+        })
+    }
+
+    /// Formats the message `id` from the first catalog of the fallback chain that has it.
+    ///
+    /// # Errors
+    /// Returns an error if no catalog in the chain has the message, or if formatting it fails.
+    pub fn format<'a>(
+        &self,
+        locale: &Locale,
+        id: &str,
+        arguments: impl IntoArguments<'a>,
+    ) -> Result<Localized<'_>, CatalogError> {
+        let found = self
+            .find(locale, id)
+            .ok_or_else(|| CatalogError::UnknownMessage { id: id.into() })?;
+        let text = found
+            .compiled
+            .format(arguments)
+            .map_err(|error| CatalogError::Format {
+                id: id.into(),
+                error,
+            })?;
+        Ok(Localized {
+            locale: found.locale,
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(tag: &str) -> Locale {
+        tag.parse().expect("valid locale")
+    }
+
+    fn localizer() -> Localizer {
+        let mut en = Catalog::new(locale("en"));
+        en.insert_source("hello", "Hello").expect("valid message");
+        en.insert_source("bye", "Bye").expect("valid message");
+        en.insert_source(
+            "items",
+            ".input {$n :number}\n.match {$n}\none {{{$n} item}}\n* {{{$n} items}}",
+        )
+        .expect("valid message");
+
+        let mut de = Catalog::new(locale("de"));
+        de.insert_source("hello", "Hallo").expect("valid message");
+        de.insert_source(
+            "items",
+            ".input {$n :number}\n.match {$n}\none {{{$n} Artikel}}\n* {{{$n} Artikel}}",
+        )
+        .expect("valid message");
+
+        let mut de_ch = Catalog::new(locale("de-CH"));
+        de_ch
+            .insert_source("hello", "Grüezi")
+            .expect("valid message");
+
+        Localizer::new(locale("en")).with(en).with(de).with(de_ch)
+    }
+
+    #[test]
+    fn it_falls_back_to_the_nearest_locale() {
+        let localizer = localizer();
+        let format = |tag: &str, id: &str| {
+            localizer
+                .format(&locale(tag), id, [("n", 1234)])
+                .map(|localized| (localized.locale.to_string(), localized.text))
+        };
+
+        assert_eq!(
+            format("de-CH", "hello"),
+            Ok(("de-CH".into(), "Grüezi".into()))
+        );
+        assert_eq!(format("de-AT", "hello"), Ok(("de".into(), "Hallo".into())));
+        assert_eq!(
+            format("de-CH", "items"),
+            Ok(("de".into(), "1.234 Artikel".into()))
+        );
+        assert_eq!(format("de-CH", "bye"), Ok(("en".into(), "Bye".into())));
+        assert_eq!(format("fr", "hello"), Ok(("en".into(), "Hello".into())));
+        assert_eq!(
+            format("de", "missing"),
+            Err(CatalogError::UnknownMessage {
+                id: "missing".into()
+            })
+        );
+    }
+
+    #[test]
+    fn it_appends_the_chain_of_the_default_locale() {
+        let localizer = Localizer::new(locale("en-GB"));
+
+        assert_eq!(
+            localizer.fallback_chain(&locale("de-CH")),
+            [
+                locale("de-CH"),
+                locale("de"),
+                locale("en-GB"),
+                locale("en-001"),
+                locale("en")
+            ]
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Collections of the messages of an application: a [`Catalog`] for one locale, and a
+//! [`Localizer`] for several locales with fallbacks between them.
+
+mod localizer;
+
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::string::String;
+use core::fmt;
+
+use crate::arguments::IntoArguments;
+use crate::data_model::elements::Message;
+use crate::data_model::errors::{DataModelError, SyntaxError};
+use crate::format::{BidiIsolation, CompiledMessage, FormatError, FunctionRegistry};
+use crate::locale::Locale;
+
+pub use localizer::{Found, Localized, Localizer};
+
+/// The messages of one locale, by id.
+///
+/// Messages are validated and compiled when they are inserted, so a catalog only holds messages
+/// that can be formatted.
+#[derive(Debug)]
+pub struct Catalog {
+    locale: Locale,
+    registry: FunctionRegistry,
+    bidi_isolation: BidiIsolation,
+    strict: bool,
+    messages: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    message: Message,
+    compiled: CompiledMessage,
+}
+
+impl Catalog {
+    /// An empty catalog that formats messages with the default functions
+    pub fn new(locale: Locale) -> Self {
+        Self::with_registry(locale, FunctionRegistry::new())
+    }
+
+    /// An empty catalog that formats messages with the functions from `registry`
+    pub fn with_registry(locale: Locale, registry: FunctionRegistry) -> Self {
+        Self {
+            locale,
+            registry,
+            bidi_isolation: BidiIsolation::default(),
+            strict: false,
+            messages: BTreeMap::new(),
+        }
+    }
+
+    /// Sets how placeholders are isolated in all messages, see [`CompiledMessage::bidi_isolation`]
+    #[must_use]
+    pub fn bidi_isolation(mut self, strategy: BidiIsolation) -> Self {
+        self.bidi_isolation = strategy;
+        self.recompile()
+    }
+
+    /// Sets whether formatting fails on the first error in all messages, see [`CompiledMessage::strict`]
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self.recompile()
+    }
+
+    fn recompile(mut self) -> Self {
+        self.messages = core::mem::take(&mut self.messages)
+            .into_iter()
+            .map(|(id, entry)| {
+                let compiled = entry
+                    .compiled
+                    .bidi_isolation(self.bidi_isolation)
+                    .strict(self.strict);
+                (id, Entry { compiled, ..entry })
+            })
+            .collect();
+        self
+    }
+
+    pub const fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    pub const fn registry(&self) -> &FunctionRegistry {
+        &self.registry
+    }
+
+    /// Adds a message, returning the message that was previously stored under `id`.
+    ///
+    /// # Errors
+    /// Returns an error if the message is not valid according to the data model. The catalog is
+    /// left unchanged.
+    pub fn insert(
+        &mut self,
+        id: impl Into<String>,
+        message: Message,
+    ) -> Result<Option<Message>, CatalogError> {
+        let id = id.into();
+        let entry = self.entry(&id, message)?;
+        let previous = self.messages.insert(id, entry);
+        Ok(previous.map(|entry| entry.message))
+    }
+
+    /// Adds several messages, like [`Catalog::insert`].
+    ///
+    /// # Errors
+    /// Returns the error of the first message that is not valid. The catalog is left unchanged, no
+    /// message is added.
+    pub fn insert_all(
+        &mut self,
+        messages: impl IntoIterator<Item = (String, Message)>,
+    ) -> Result<(), CatalogError> {
+        let mut staged = BTreeMap::new();
+        for (id, message) in messages {
+            let entry = self.entry(&id, message)?;
+            staged.insert(id, entry);
+        }
+        self.messages.append(&mut staged);
+        Ok(())
+    }
+
+    fn entry(&self, id: &str, message: Message) -> Result<Entry, CatalogError> {
+        match message.compile(&self.registry, self.locale.clone()) {
+            Ok(compiled) => Ok(Entry {
+                message,
+                compiled: compiled
+                    .bidi_isolation(self.bidi_isolation)
+                    .strict(self.strict),
+            }),
+            Err(error) => Err(CatalogError::DataModel {
+                id: id.into(),
+                error,
+            }),
+        }
+    }
+
+    /// Parses a message and adds it, like [`Catalog::insert`].
+    ///
+    /// # Errors
+    /// Returns an error if the message can't be parsed or is not valid.
+    pub fn insert_source(
+        &mut self,
+        id: impl Into<String>,
+        source: &str,
+    ) -> Result<Option<Message>, CatalogError> {
+        let id = id.into();
+        match source.parse() {
+            Ok(message) => self.insert(id, message),
+            Err(error) => Err(CatalogError::Syntax { id, error }),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Message> {
+        self.messages.remove(id).map(|entry| entry.message)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Message> {
+        self.messages.get(id).map(|entry| &entry.message)
+    }
+
+    /// The compiled message, for formatting it in other ways than [`Catalog::format`]
+    pub fn compiled(&self, id: &str) -> Option<&CompiledMessage> {
+        self.messages.get(id).map(|entry| &entry.compiled)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.messages.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// The ids in sorted order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+
+    /// The messages and their ids, sorted by id
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.messages.iter())
+    }
+
+    /// Formats the message `id`.
+    ///
+    /// # Errors
+    /// Returns an error if there is no message `id`, or if formatting it fails.
+    pub fn format<'a>(
+        &self,
+        id: &str,
+        arguments: impl IntoArguments<'a>,
+    ) -> Result<String, CatalogError> {
+        let compiled = self
+            .compiled(id)
+            .ok_or_else(|| CatalogError::UnknownMessage { id: id.into() })?;
+        compiled
+            .format(arguments)
+            .map_err(|error| CatalogError::Format {
+                id: id.into(),
+                error,
+            })
+    }
+}
+
+impl<'c> IntoIterator for &'c Catalog {
+    type Item = (&'c str, &'c Message);
+    type IntoIter = Iter<'c>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the messages of a [`Catalog`] and their ids
+#[derive(Debug, Clone)]
+pub struct Iter<'c>(btree_map::Iter<'c, String, Entry>);
+
+impl<'c> Iterator for Iter<'c> {
+    type Item = (&'c str, &'c Message);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|(id, entry)| (id.as_str(), &entry.message))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// An error from a [`Catalog`], with the id of the message it concerns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogError {
+    /// There is no message with this id
+    UnknownMessage {
+        id: String,
+    },
+    Syntax {
+        id: String,
+        error: SyntaxError,
+    },
+    DataModel {
+        id: String,
+        error: DataModelError,
+    },
+    Format {
+        id: String,
+        error: FormatError,
+    },
+}
+
+impl CatalogError {
+    /// The id of the message the error concerns
+    pub fn id(&self) -> &str {
+        match self {
+            Self::UnknownMessage { id }
+            | Self::Syntax { id, .. }
+            | Self::DataModel { id, .. }
+            | Self::Format { id, .. } => id,
+        }
+    }
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownMessage { id } => write!(f, "there is no message {id:?}"),
+            Self::Syntax { id, error } => write!(f, "message {id:?}: {error}"),
+            Self::DataModel { id, error } => write!(f, "message {id:?}: {error}"),
+            Self::Format { id, error } => write!(f, "message {id:?}: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for CatalogError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::UnknownMessage { .. } => None,
+            Self::Syntax { error, .. } => Some(error),
+            Self::DataModel { error, .. } => Some(error),
+            Self::Format { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::errors::MissingFallbackVariant;
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new("en".parse().expect("valid locale"));
+        catalog
+            .insert_source("greeting", "Hello {$name}!")
+            .expect("valid message");
+        catalog
+            .insert_source("items", ".input {$count :number}\n.match {$count}\none {{{$count} item}}\n* {{{$count} items}}")
+            .expect("valid message");
+        catalog
+    }
+
+    #[test]
+    fn it_formats_messages_by_id() {
+        let catalog = catalog();
+
+        assert_eq!(
+            catalog.format("greeting", [("name", "Ann")]),
+            Ok("Hello Ann!".into())
+        );
+        assert_eq!(catalog.format("items", [("count", 1)]), Ok("1 item".into()));
+        assert_eq!(
+            catalog.format("missing", ()),
+            Err(CatalogError::UnknownMessage {
+                id: "missing".into()
+            })
+        );
+        assert_eq!(catalog.format("greeting", ()), Ok("Hello {$name}!".into()));
+        assert!(matches!(
+            catalog.strict(true).format("greeting", ()),
+            Err(CatalogError::Format {
+                error: FormatError::UnresolvedVariable(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_validates_inserted_messages() {
+        let mut catalog = catalog();
+
+        assert!(matches!(
+            catalog.insert_source("broken", "{$name"),
+            Err(CatalogError::Syntax { id, .. }) if id == "broken"
+        ));
+        assert_eq!(
+            catalog.insert_source("invalid", ".input {$n :number}\n.match {$n}\none {{one}}"),
+            Err(CatalogError::DataModel {
+                id: "invalid".into(),
+                error: DataModelError::MissingFallbackVariant(MissingFallbackVariant),
+            })
+        );
+        assert!(!catalog.contains("broken") && !catalog.contains("invalid"));
+
+        let previous = catalog
+            .insert_source("greeting", "Hi {$name}!")
+            .expect("valid message");
+        assert_eq!(
+            previous.map(|message| message.to_string()),
+            Some("Hello {$name}!".into())
+        );
+        assert_eq!(
+            catalog.format("greeting", [("name", "Bo")]),
+            Ok("Hi Bo!".into())
+        );
+    }
+
+    #[test]
+    fn it_iterates_in_id_order() {
+        let mut catalog = catalog();
+        catalog
+            .insert_source("about", "About")
+            .expect("valid message");
+
+        assert_eq!(
+            catalog.ids().collect::<Vec<_>>(),
+            ["about", "greeting", "items"]
+        );
+        assert_eq!((&catalog).into_iter().count(), 3);
+        assert_eq!(
+            catalog.remove("about").map(|message| message.to_string()),
+            Some("About".into())
+        );
+        assert_eq!(catalog.len(), 2);
+    }
+}
@@ -0,0 +1,632 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use crate::codegen::{Codegen, CodegenError};
+use crate::data_model::elements::{
+    Annotation, Declaration, Expression, FunctionAnnotation, Message, OptionValue, PatternElement,
+    SelectMessage, VariableExpression, VariantKey,
+};
+use crate::data_model::errors::{
+    DataModelError, MissingSelectorAnnotation, UnknownFunction, UnsupportedExpression,
+    UnsupportedStatement,
+};
+use crate::data_model::signature::ArgumentType;
+
+const KEYWORDS: [&str; 51] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+pub fn generate(codegen: &Codegen, name: &str, message: &Message) -> Result<String, CodegenError> {
+    if !is_identifier(name) {
+        return Err(CodegenError::InvalidName(name.into()));
+    }
+    message.validate()?;
+
+    let mut generator = Generator {
+        codegen,
+        krate: &codegen.crate_path,
+        body: String::new(),
+        indent: 1,
+        parameters: Vec::new(),
+        scope: BTreeMap::new(),
+        next_id: 0,
+        uses_locale: false,
+        writes: false,
+    };
+
+    match message {
+        Message::Pattern(message) => {
+            generator.declarations(&message.declarations)?;
+            generator.pattern(&message.pattern)?;
+        }
+        Message::Select(message) => {
+            generator.declarations(&message.declarations)?;
+            generator.selection(message)?;
+        }
+    }
+
+    Ok(generator.finish(name, message))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "_"
+        && !KEYWORDS.contains(&name)
+}
+
+/// Turns a variable name into a Rust identifier
+fn sanitize(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str())
+        || ident == "_"
+        || ident == "locale"
+        || ident.starts_with("__mf2_")
+    {
+        ident.push('_');
+    }
+    ident
+}
+
+/// The generated code for a resolved value
+#[derive(Debug, Clone, Default)]
+struct Resolved {
+    /// The Rust variable holding the value
+    value: Option<String>,
+    function: Option<FunctionCall>,
+}
+
+#[derive(Debug, Clone)]
+struct FunctionCall {
+    name: String,
+    path: String,
+    /// The Rust variable holding the options
+    options: String,
+}
+
+struct Parameter {
+    /// The name of the variable in the message
+    name: String,
+    ident: String,
+}
+
+struct Generator<'a> {
+    codegen: &'a Codegen,
+    krate: &'a str,
+    body: String,
+    indent: usize,
+    parameters: Vec<Parameter>,
+    /// The code each variable name currently refers to
+    scope: BTreeMap<String, Resolved>,
+    next_id: usize,
+    uses_locale: bool,
+    writes: bool,
+}
+
+impl Generator<'_> {
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.indent {
+            self.body.push_str("    ");
+        }
+        self.body.push_str(line);
+        self.body.push('\n');
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("__mf2_{prefix}{}", self.next_id)
+    }
+
+    fn finish(self, name: &str, message: &Message) -> String {
+        let krate = self.krate;
+        let mut out = String::new();
+
+        let _ = writeln!(out, "/// Formats the message:");
+        let _ = writeln!(out, "/// ```text");
+        for line in message.to_string().lines() {
+            let _ = writeln!(out, "/// {line}");
+        }
+        let _ = writeln!(out, "/// ```");
+        let _ = writeln!(out, "pub fn {name}(");
+        let _ = writeln!(out, "    locale: &{krate}::locale::Locale,");
+        let signature = message.signature();
+        let types: Vec<ArgumentType> = self
+            .parameters
+            .iter()
+            .map(|parameter| {
+                signature
+                    .get(&parameter.name)
+                    .map_or(ArgumentType::Any, |p| p.argument_type)
+            })
+            .collect();
+        for (parameter, argument_type) in self.parameters.iter().zip(&types) {
+            let rust_type = match argument_type {
+                ArgumentType::String => "&str".into(),
+                ArgumentType::Number => format!("impl {krate}::codegen::support::Number"),
+                ArgumentType::DateTime => {
+                    format!("impl ::core::convert::Into<{krate}::arguments::DateTime>")
+                }
+                ArgumentType::Any => {
+                    format!("impl ::core::convert::Into<{krate}::arguments::Value>")
+                }
+            };
+            let _ = writeln!(out, "    {}: {rust_type},", parameter.ident);
+        }
+        let _ = writeln!(
+            out,
+            ") -> ::core::result::Result<{krate}::codegen::support::String, {krate}::format::FormatError> {{"
+        );
+
+        if !self.uses_locale {
+            out.push_str("    let _ = locale;\n");
+        }
+        for (parameter, argument_type) in self.parameters.iter().zip(&types) {
+            let conversion = match argument_type {
+                ArgumentType::String => {
+                    format!("{krate}::arguments::Value::from({})", parameter.ident)
+                }
+                ArgumentType::DateTime => {
+                    format!(
+                        "{krate}::arguments::Value::DateTime({}.into())",
+                        parameter.ident
+                    )
+                }
+                ArgumentType::Number | ArgumentType::Any => format!("{}.into()", parameter.ident),
+            };
+            let _ = writeln!(
+                out,
+                "    let {}: {krate}::arguments::Value = {conversion};",
+                parameter.ident
+            );
+        }
+        let binding = if self.writes { "let mut" } else { "let" };
+        let _ = writeln!(
+            out,
+            "    {binding} __mf2_out = {krate}::codegen::support::String::new();"
+        );
+        out.push_str(&self.body);
+        out.push_str("    ::core::result::Result::Ok(__mf2_out)\n");
+        out.push_str("}\n");
+        out
+    }
+
+    /// The code for a variable. Variables that haven't been declared become parameters.
+    fn variable(&mut self, name: &str) -> Resolved {
+        if let Some(resolved) = self.scope.get(name) {
+            return resolved.clone();
+        }
+
+        let mut ident = sanitize(name);
+        while self.parameters.iter().any(|p| p.ident == ident) {
+            ident.push('_');
+        }
+        self.parameters.push(Parameter {
+            name: name.into(),
+            ident: ident.clone(),
+        });
+
+        let resolved = Resolved {
+            value: Some(ident),
+            function: None,
+        };
+        self.scope.insert(name.into(), resolved.clone());
+        resolved
+    }
+
+    fn declarations(&mut self, declarations: &[Declaration]) -> Result<(), CodegenError> {
+        for declaration in declarations {
+            match declaration {
+                Declaration::Input(input) => {
+                    let resolved = self.variable_expression(&input.value)?;
+                    self.scope.insert(input.name.clone(), resolved);
+                }
+                Declaration::Local(local) => {
+                    let resolved = self.expression(&local.value)?;
+                    self.scope.insert(local.name.clone(), resolved);
+                }
+                Declaration::UnsupportedStatement(statement) => {
+                    return Err(CodegenError::UnsupportedStatement(UnsupportedStatement {
+                        keyword: statement.keyword.clone(),
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn selection(&mut self, message: &SelectMessage) -> Result<(), CodegenError> {
+        let krate = self.krate;
+
+        let mut keys: Vec<Vec<&str>> = vec![Vec::new(); message.selectors.len()];
+        let variant_keys: Vec<Vec<Option<usize>>> = message
+            .variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .keys
+                    .iter()
+                    .zip(&mut keys)
+                    .map(|(key, selector_keys)| match key {
+                        VariantKey::Catchall => None,
+                        VariantKey::Literal(literal) => Some(
+                            selector_keys
+                                .iter()
+                                .position(|k| *k == literal.value)
+                                .unwrap_or_else(|| {
+                                    selector_keys.push(&literal.value);
+                                    selector_keys.len() - 1
+                                }),
+                        ),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut preferences = Vec::new();
+        for (i, (selector, keys)) in message.selectors.iter().zip(&keys).enumerate() {
+            let resolved = self.expression(selector)?;
+            let Some(function) = resolved.function else {
+                return Err(
+                    DataModelError::MissingSelectorAnnotation(MissingSelectorAnnotation {
+                        selector: i,
+                    })
+                    .into(),
+                );
+            };
+
+            let preference = self.fresh("p");
+            let operand = operand_code(resolved.value.as_deref());
+            let keys: Vec<String> = keys.iter().map(|key| format!("{key:?}")).collect();
+            self.uses_locale = true;
+            self.line(&format!(
+                "let {preference} = {krate}::format::Function::select(&{}, {krate}::format::FunctionInput {{ operand: {operand}, options: &{}, locale }}, &[{}]).map_err(|e| e.into_selection_error({:?}))?;",
+                function.path,
+                function.options,
+                keys.join(", "),
+                function.name,
+            ));
+            preferences.push(format!("&{preference}[..]"));
+        }
+
+        let variants: Vec<String> = variant_keys
+            .iter()
+            .map(|keys| {
+                let keys: Vec<String> = keys
+                    .iter()
+                    .map(|key| key.map_or_else(|| "None".into(), |key| format!("Some({key})")))
+                    .collect();
+                format!("&[{}][..]", keys.join(", "))
+            })
+            .collect();
+
+        self.line(&format!(
+            "match {krate}::codegen::support::select_variant(&[{}], &[{}]) {{",
+            preferences.join(", "),
+            variants.join(", ")
+        ));
+        self.indent += 1;
+
+        let fallback = variant_keys
+            .iter()
+            .position(|keys| keys.iter().all(Option::is_none));
+        for (i, variant) in message.variants.iter().enumerate() {
+            if Some(i) == fallback {
+                continue;
+            }
+            self.line(&format!("Some({i}) => {{"));
+            self.indent += 1;
+            self.pattern(&variant.value)?;
+            self.indent -= 1;
+            self.line("}");
+        }
+
+        self.line("_ => {");
+        self.indent += 1;
+        if let Some(fallback) = fallback {
+            self.pattern(&message.variants[fallback].value)?;
+        }
+        self.indent -= 1;
+        self.line("}");
+
+        self.indent -= 1;
+        self.line("}");
+        Ok(())
+    }
+
+    fn pattern(&mut self, pattern: &[PatternElement]) -> Result<(), CodegenError> {
+        let mut text = String::new();
+        for element in pattern {
+            match element {
+                PatternElement::Literal(literal) => text.push_str(literal),
+                PatternElement::Expression(expression) => {
+                    self.text(&core::mem::take(&mut text));
+                    let resolved = self.expression(expression)?;
+                    self.write(&resolved);
+                }
+                // Markup has no output when formatting to a string
+                PatternElement::Markup(_) => {}
+            }
+        }
+        self.text(&text);
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.writes = true;
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => self.line(&format!("__mf2_out.push({c:?});")),
+                _ => self.line(&format!("__mf2_out.push_str({text:?});")),
+            }
+        }
+    }
+
+    fn write(&mut self, resolved: &Resolved) {
+        let krate = self.krate;
+        match (&resolved.function, &resolved.value) {
+            (Some(function), value) => {
+                self.writes = true;
+                self.uses_locale = true;
+                self.line(&format!(
+                    "{krate}::format::Function::format(&{}, {krate}::format::FunctionInput {{ operand: {}, options: &{}, locale }}, &mut __mf2_out).map_err(|e| e.into_format_error({:?}))?;",
+                    function.path,
+                    operand_code(value.as_deref()),
+                    function.options,
+                    function.name,
+                ));
+            }
+            (None, Some(value)) => {
+                self.writes = true;
+                self.uses_locale = true;
+                self.line(&format!(
+                    "{krate}::codegen::support::write_value(&{value}, locale, &mut __mf2_out)?;"
+                ));
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Result<Resolved, CodegenError> {
+        match expression {
+            Expression::Literal(e) => {
+                let value = self.fresh("v");
+                let krate = self.krate;
+                self.line(&format!(
+                    "let {value} = {krate}::arguments::Value::from({:?});",
+                    e.arg.value
+                ));
+                let operand = Resolved {
+                    value: Some(value),
+                    function: None,
+                };
+                self.annotation(Some(operand), e.annotation.as_ref())
+            }
+            Expression::Variable(e) => self.variable_expression(e),
+            Expression::Function(e) => self.function(None, &e.annotation),
+            Expression::Unsupported(e) => {
+                Err(CodegenError::UnsupportedExpression(UnsupportedExpression {
+                    source: e.annotation.source.clone(),
+                }))
+            }
+        }
+    }
+
+    fn variable_expression(
+        &mut self,
+        expression: &VariableExpression,
+    ) -> Result<Resolved, CodegenError> {
+        let operand = self.variable(&expression.arg.name);
+        self.annotation(Some(operand), expression.annotation.as_ref())
+    }
+
+    fn annotation(
+        &mut self,
+        operand: Option<Resolved>,
+        annotation: Option<&Annotation>,
+    ) -> Result<Resolved, CodegenError> {
+        match annotation {
+            None => Ok(operand.unwrap_or_default()),
+            Some(Annotation::Function(function)) => self.function(operand, function),
+            Some(Annotation::Unsupported(unsupported)) => {
+                Err(CodegenError::UnsupportedExpression(UnsupportedExpression {
+                    source: unsupported.source.clone(),
+                }))
+            }
+        }
+    }
+
+    fn function(
+        &mut self,
+        operand: Option<Resolved>,
+        function: &FunctionAnnotation,
+    ) -> Result<Resolved, CodegenError> {
+        let Some(path) = self.codegen.functions.get(&function.name).cloned() else {
+            return Err(CodegenError::UnknownFunction(UnknownFunction {
+                name: function.name.clone(),
+            }));
+        };
+
+        let krate = self.krate;
+        let mut inserts = Vec::new();
+        for option in &function.options {
+            let value = match &option.value {
+                OptionValue::Literal(literal) => Some(format!(
+                    "{krate}::arguments::Value::from({:?})",
+                    literal.value
+                )),
+                OptionValue::Variable(variable) => self
+                    .variable(&variable.name)
+                    .value
+                    .map(|value| format!("{value}.clone()")),
+            };
+            if let Some(value) = value {
+                inserts.push((option.name.clone(), value));
+            }
+        }
+
+        // Calling the same function again keeps the options of the previous call
+        let previous_options = operand
+            .as_ref()
+            .and_then(|operand| operand.function.as_ref())
+            .filter(|previous| previous.name == function.name)
+            .map(|previous| previous.options.clone());
+
+        let options = self.fresh("o");
+        let binding = if inserts.is_empty() { "let" } else { "let mut" };
+        match previous_options {
+            Some(previous) => self.line(&format!("{binding} {options} = {previous}.clone();")),
+            None => self.line(&format!(
+                "{binding} {options} = {krate}::format::Options::new();"
+            )),
+        }
+        for (name, value) in inserts {
+            self.line(&format!("{options}.insert({name:?}, {value});"));
+        }
+
+        Ok(Resolved {
+            value: operand.and_then(|operand| operand.value),
+            function: Some(FunctionCall {
+                name: function.name.clone(),
+                path,
+                options,
+            }),
+        })
+    }
+}
+
+fn operand_code(value: Option<&str>) -> String {
+    value.map_or_else(|| "None".into(), |value| format!("Some(&{value})"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::elements::*;
+
+    fn variable(name: &str, function: std::option::Option<&str>) -> Expression {
+        Expression::Variable(VariableExpression {
+            arg: VariableRef { name: name.into() },
+            annotation: function.map(|name| {
+                Annotation::Function(FunctionAnnotation {
+                    name: name.into(),
+                    options: vec![],
+                })
+            }),
+            attributes: vec![],
+        })
+    }
+
+    fn pattern(elements: Vec<PatternElement>) -> Message {
+        Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern: elements,
+        })
+    }
+
+    #[test]
+    fn it_turns_variables_into_parameters() {
+        let message = pattern(vec![
+            PatternElement::Expression(variable("user-name", None)),
+            PatternElement::Expression(variable("type", None)),
+            PatternElement::Expression(variable("user-name", None)),
+        ]);
+
+        let source = Codegen::new()
+            .generate("f", &message)
+            .expect("valid message");
+
+        assert!(source.contains(
+            "    user_name: impl ::core::convert::Into<::icu_messageformat_2::arguments::Value>,\n"
+        ));
+        assert!(source.contains("    type_: impl"));
+        assert_eq!(source.matches("user_name: impl").count(), 1);
+    }
+
+    #[test]
+    fn it_types_parameters_by_their_functions() {
+        let message = pattern(vec![
+            PatternElement::Expression(variable("count", Some("integer"))),
+            PatternElement::Expression(variable("name", Some("string"))),
+            PatternElement::Expression(variable("when", Some("date"))),
+        ]);
+
+        let source = Codegen::new()
+            .generate("f", &message)
+            .expect("valid message");
+
+        assert!(
+            source.contains("    count: impl ::icu_messageformat_2::codegen::support::Number,\n")
+        );
+        assert!(source.contains("    name: &str,\n"));
+        assert!(source.contains(
+            "    when: impl ::core::convert::Into<::icu_messageformat_2::arguments::DateTime>,\n"
+        ));
+        assert!(!source.contains("::std::"));
+    }
+
+    #[test]
+    fn it_calls_functions_directly() {
+        let message = pattern(vec![PatternElement::Expression(variable(
+            "n",
+            Some("shout"),
+        ))]);
+
+        let source = Codegen::new()
+            .function("shout", "crate::Shout")
+            .generate("f", &message)
+            .expect("valid message");
+
+        assert!(source.contains("::icu_messageformat_2::format::Function::format(&crate::Shout, "));
+    }
+
+    #[test]
+    fn it_rejects_unknown_functions_and_names() {
+        let message = pattern(vec![PatternElement::Expression(variable(
+            "n",
+            Some("shout"),
+        ))]);
+
+        assert_eq!(
+            Codegen::new().generate("f", &message),
+            Err(CodegenError::UnknownFunction(UnknownFunction {
+                name: "shout".into()
+            }))
+        );
+        assert_eq!(
+            Codegen::new().generate("fn", &pattern(vec![])),
+            Err(CodegenError::InvalidName("fn".into()))
+        );
+    }
+
+    #[test]
+    fn it_generates_constant_messages() {
+        let source = Codegen::new()
+            .generate(
+                "hello",
+                &pattern(vec![PatternElement::Literal("Hello \"World\"".into())]),
+            )
+            .expect("valid message");
+
+        assert!(source.contains("    let _ = locale;\n"));
+        assert!(source.contains("__mf2_out.push_str(\"Hello \\\"World\\\"\");"));
+    }
+}
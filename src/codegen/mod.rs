@@ -0,0 +1,136 @@
+//! Generates Rust source code for messages, for use in build scripts.
+//!
+//! Every message becomes a function that takes the locale and one parameter per external
+//! variable, and formats the message without looking at a [`Message`] at runtime:
+//!
+//! ```
+//! # use icu_messageformat_2::codegen::Codegen;
+//! # use icu_messageformat_2::data_model::elements::*;
+//! let message = Message::Pattern(PatternMessage {
+//!     declarations: vec![],
+//!     pattern: vec![
+//!         PatternElement::Literal("Hello ".into()),
+//!         PatternElement::Expression(Expression::Variable(VariableExpression {
+//!             arg: VariableRef { name: "name".into() },
+//!             annotation: None,
+//!             attributes: vec![],
+//!         })),
+//!     ],
+//! });
+//!
+//! let source = Codegen::new().generate("greeting", &message).unwrap();
+//! assert!(source.contains("pub fn greeting("));
+//! ```
+
+mod generator;
+#[doc(hidden)]
+pub mod support;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use crate::data_model::elements::Message;
+use crate::data_model::errors::{
+    DataModelError, UnknownFunction, UnsupportedExpression, UnsupportedStatement,
+};
+
+/// Configures how messages are turned into Rust code
+#[derive(Debug, Clone)]
+pub struct Codegen {
+    crate_path: String,
+    /// Rust expressions implementing `Function`, by function name
+    functions: BTreeMap<String, String>,
+}
+
+impl Codegen {
+    /// A generator that knows the default functions and refers to this crate as `::icu_messageformat_2`
+    pub fn new() -> Self {
+        let codegen = Self {
+            crate_path: "::icu_messageformat_2".into(),
+            functions: BTreeMap::new(),
+        };
+        codegen.with_default_functions()
+    }
+
+    fn with_default_functions(mut self) -> Self {
+        let functions = format!("{}::format::functions", self.crate_path);
+        for (name, path) in [
+            ("string", format!("{functions}::StringFunction")),
+            ("number", format!("{functions}::Number")),
+            ("integer", format!("{functions}::Integer")),
+            (
+                "datetime",
+                format!("{functions}::DateTimeFunction::DATETIME"),
+            ),
+            ("date", format!("{functions}::DateTimeFunction::DATE")),
+            ("time", format!("{functions}::DateTimeFunction::TIME")),
+        ] {
+            self.functions.insert(name.into(), path);
+        }
+        self
+    }
+
+    /// Sets the path this crate is available under in the generated code, eg. when it is re-exported.
+    /// This resets the paths of the default functions.
+    #[must_use]
+    pub fn crate_path(mut self, path: impl Into<String>) -> Self {
+        self.crate_path = path.into();
+        self.with_default_functions()
+    }
+
+    /// Makes a custom function available. `path` is a Rust expression for a value implementing
+    /// `Function`, eg. `crate::i18n::Shout` for a unit struct.
+    #[must_use]
+    pub fn function(mut self, name: impl Into<String>, path: impl Into<String>) -> Self {
+        self.functions.insert(name.into(), path.into());
+        self
+    }
+
+    /// Generates a public function called `name` that formats the message.
+    ///
+    /// # Errors
+    /// Returns an error if the message is invalid, uses functions that weren't made available,
+    /// or contains unsupported expressions or statements.
+    pub fn generate(&self, name: &str, message: &Message) -> Result<String, CodegenError> {
+        generator::generate(self, name, message)
+    }
+}
+
+impl Default for Codegen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reasons a message cannot be turned into code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    DataModel(DataModelError),
+    UnknownFunction(UnknownFunction),
+    UnsupportedExpression(UnsupportedExpression),
+    UnsupportedStatement(UnsupportedStatement),
+    /// The name of the generated function is not a valid Rust identifier
+    InvalidName(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DataModel(e) => e.fmt(f),
+            Self::UnknownFunction(e) => write!(f, "unknown function :{}", e.name),
+            Self::UnsupportedExpression(e) => write!(f, "unsupported expression {}", e.source),
+            Self::UnsupportedStatement(e) => write!(f, "unsupported statement .{}", e.keyword),
+            Self::InvalidName(name) => write!(f, "{name:?} is not a valid function name"),
+        }
+    }
+}
+
+impl core::error::Error for CodegenError {}
+
+impl From<DataModelError> for CodegenError {
+    fn from(value: DataModelError) -> Self {
+        Self::DataModel(value)
+    }
+}
@@ -0,0 +1,37 @@
+//! Runtime helpers for generated code. Not part of the public API.
+
+use core::fmt;
+
+pub use alloc::string::String;
+
+use crate::arguments::{Decimal, Value};
+use crate::format::{self, FormatError};
+use crate::locale::Locale;
+
+/// Formats a value that has no annotation
+///
+/// # Errors
+/// Returns an error if the value cannot be formatted.
+pub fn write_value(
+    value: &Value,
+    locale: &Locale,
+    out: &mut dyn fmt::Write,
+) -> Result<(), FormatError> {
+    format::write_value(value, locale, out)
+}
+
+/// Picks the variant to format, see [`format::select_variant`]
+pub fn select_variant(preferences: &[&[usize]], variants: &[&[Option<usize>]]) -> Option<usize> {
+    format::select_variant(preferences, variants)
+}
+
+/// The values that generated code accepts for variables used with `:number` or `:integer`
+pub trait Number: Into<Value> {}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {})*
+    };
+}
+
+impl_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, Decimal);
@@ -0,0 +1,434 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{Diagnostic, DiagnosticKind};
+use crate::convert::scope::{Scope, Unresolvable, Value};
+use crate::convert::tree::{self, Tree};
+use crate::data_model::elements::{
+    Expression, FunctionAnnotation, Message, OptionValue, PatternElement,
+};
+use crate::resource::Resource;
+
+/// Converts a MessageFormat 2 resource to a Fluent resource.
+///
+/// Declarations are resolved into the placeables that use them, selectors become nested select
+/// expressions, and dots in ids become dashes. Messages that Fluent can't express, like ones with
+/// markup or with an id that becomes the Fluent id of an earlier message, are left out and reported
+/// along with the other diagnostics.
+pub fn serialize(resource: &Resource) -> (String, Vec<Diagnostic>) {
+    let mut out = String::new();
+    let mut diagnostics = Vec::new();
+    // The message ids by the Fluent ids they were written as
+    let mut written: BTreeMap<String, String> = BTreeMap::new();
+    if let Some(comment) = &resource.comment {
+        write_comment(&mut out, "###", comment);
+        out.push('\n');
+    }
+
+    for (id, entry) in resource.entries() {
+        let mut writer = Writer::default();
+        let value = fluent_id(&id)
+            .and_then(|fluent| match written.get(&fluent) {
+                Some(earlier) => Err(DiagnosticKind::DuplicateId {
+                    fluent,
+                    earlier: earlier.clone(),
+                }),
+                None => Ok(fluent),
+            })
+            .and_then(|fluent| writer.message(&entry.message).map(|value| (fluent, value)));
+        let report = |kind| Diagnostic {
+            line: None,
+            id: Some(id.clone()),
+            kind,
+        };
+        diagnostics.extend(writer.warnings.into_iter().map(report));
+        match value {
+            Ok((fluent, value)) => {
+                written.insert(fluent.clone(), id.clone());
+                if let Some(comment) = &entry.comment {
+                    write_comment(&mut out, "#", comment);
+                }
+                if value.contains('\n') {
+                    out.push_str(&format!("{fluent} =\n{}\n", indent(&value, 4)));
+                } else {
+                    out.push_str(&format!("{fluent} = {value}\n"));
+                }
+            }
+            Err(kind) => diagnostics.push(report(kind)),
+        }
+    }
+    (out, diagnostics)
+}
+
+const fn unresolvable(reason: Unresolvable) -> DiagnosticKind {
+    match reason {
+        Unresolvable::Operand => DiagnosticKind::Operand,
+        Unresolvable::Unsupported => DiagnosticKind::Unsupported,
+    }
+}
+
+struct Selector {
+    /// The selector in Fluent syntax, like `$count`
+    expression: String,
+    /// Whether the selector selects plural categories, which have `other` as the default
+    plural: bool,
+}
+
+#[derive(Default)]
+struct Writer {
+    scope: Scope,
+    /// Diagnostics that don't keep the message from being written
+    warnings: Vec<DiagnosticKind>,
+}
+
+impl Writer {
+    fn message(&mut self, message: &Message) -> Result<String, DiagnosticKind> {
+        let declarations = match message {
+            Message::Pattern(message) => &message.declarations,
+            Message::Select(message) => &message.declarations,
+        };
+        self.scope = Scope::new(declarations).map_err(|(_, reason)| unresolvable(reason))?;
+        let value = match message {
+            Message::Pattern(message) => self.pattern(&message.pattern)?,
+            Message::Select(message) => {
+                let selectors = message
+                    .selectors
+                    .iter()
+                    .map(|selector| self.selector(selector))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.tree(&tree::nest(message), &selectors)?
+            }
+        };
+        Ok(if value.is_empty() {
+            string_literal("")
+        } else {
+            value
+        })
+    }
+
+    fn resolve(&self, expression: &Expression) -> Result<Value, DiagnosticKind> {
+        self.scope.resolve(expression).map_err(unresolvable)
+    }
+
+    fn selector(&mut self, expression: &Expression) -> Result<Selector, DiagnosticKind> {
+        let Value::Variable { name, annotation } = self.resolve(expression)? else {
+            return Err(DiagnosticKind::Operand);
+        };
+        let Some(function) = annotation else {
+            return Ok(Selector {
+                expression: variable(&name)?,
+                plural: false,
+            });
+        };
+
+        let select = function
+            .options
+            .iter()
+            .find(|option| option.name == "select");
+        let select = select.map(|option| match &option.value {
+            OptionValue::Literal(literal) => literal.value.as_str(),
+            OptionValue::Variable(_) => "",
+        });
+        let plural = matches!(function.name.as_str(), "number" | "integer")
+            && matches!(select, None | Some("plural" | "ordinal"));
+        // Fluent selects plural categories of numbers and matches strings by default
+        let plain = match function.name.as_str() {
+            "string" => function.options.is_empty(),
+            "number" => {
+                function.options.len() == usize::from(select.is_some()) && select != Some("ordinal")
+            }
+            _ => false,
+        };
+        let expression = if plain {
+            variable(&name)?
+        } else {
+            self.call(&name, &function)?
+        };
+        Ok(Selector { expression, plural })
+    }
+
+    fn tree(&mut self, tree: &Tree<'_>, selectors: &[Selector]) -> Result<String, DiagnosticKind> {
+        let (selector, cases, catchall) = match tree {
+            Tree::Pattern(pattern) => return self.pattern(pattern),
+            Tree::Select {
+                selector,
+                cases,
+                catchall,
+            } => (&selectors[*selector], cases, catchall),
+        };
+
+        // The catch-all variant becomes the default variant `*[other]`. For plural categories, an
+        // `other` variant matches all numbers the others don't, so it becomes the default instead.
+        let other = cases.iter().any(|(key, _)| key.value == "other");
+        if other && !selector.plural {
+            return Err(DiagnosticKind::Key {
+                key: "other".into(),
+            });
+        }
+        let mut out = format!("{{ {} ->\n", selector.expression);
+        for (key, tree) in cases {
+            if !is_identifier(&key.value) && !is_number(&key.value) {
+                return Err(DiagnosticKind::Key {
+                    key: key.value.clone(),
+                });
+            }
+            let value = self.tree(tree, selectors)?;
+            write_variant(&mut out, &key.value, key.value == "other", &value);
+        }
+        if !other {
+            let value = self.tree(catchall, selectors)?;
+            write_variant(&mut out, "other", true, &value);
+        }
+        out.push('}');
+        Ok(out)
+    }
+
+    fn pattern(&mut self, pattern: &[PatternElement]) -> Result<String, DiagnosticKind> {
+        let mut out = String::new();
+        for (index, element) in pattern.iter().enumerate() {
+            let last = index + 1 == pattern.len();
+            match element {
+                PatternElement::Literal(text) => write_text(&mut out, text, last),
+                PatternElement::Expression(expression) => match self.resolve(expression)? {
+                    Value::Text(text) => write_text(&mut out, &text, last),
+                    Value::Variable { name, annotation } => {
+                        let placeable = match annotation {
+                            Some(function) => self.call(&name, &function)?,
+                            None => variable(&name)?,
+                        };
+                        out.push_str(&format!("{{ {placeable} }}"));
+                    }
+                },
+                PatternElement::Markup(_) => return Err(DiagnosticKind::Markup),
+            }
+        }
+        Ok(out)
+    }
+
+    /// The function call for a formatted variable, like `NUMBER($n, type: "ordinal")`
+    fn call(
+        &mut self,
+        name: &str,
+        function: &FunctionAnnotation,
+    ) -> Result<String, DiagnosticKind> {
+        let variable = variable(name)?;
+        let mut options = Vec::new();
+        for option in &function.options {
+            match &option.value {
+                OptionValue::Literal(value) => {
+                    options.push((option.name.as_str(), value.value.as_str()))
+                }
+                OptionValue::Variable(_) => {
+                    return Err(DiagnosticKind::Option {
+                        name: option.name.clone(),
+                    })
+                }
+            }
+        }
+
+        let (fluent, options) = match function.name.as_str() {
+            "string" => match options.first() {
+                Some((name, _)) => {
+                    return Err(DiagnosticKind::Option {
+                        name: (*name).into(),
+                    })
+                }
+                None => return Ok(variable),
+            },
+            kind @ ("number" | "integer") => {
+                let mut fluent = Vec::new();
+                for (name, value) in options {
+                    match (name, value) {
+                        ("select", "ordinal") => fluent.push(("type", "ordinal")),
+                        ("select", "plural") => fluent.push(("type", "cardinal")),
+                        // Fluent has no exact selection, which MessageFormat 2 uses for numeric keys
+                        ("select", _) => {}
+                        _ => fluent.push((name, value)),
+                    }
+                }
+                if kind == "integer" {
+                    fluent.push(("maximumFractionDigits", "0"));
+                }
+                ("NUMBER".into(), fluent)
+            }
+            kind @ ("date" | "time") => {
+                let options = options
+                    .into_iter()
+                    .map(|(name, value)| match (kind, name) {
+                        ("date", "style") => ("dateStyle", value),
+                        ("time", "style") => ("timeStyle", value),
+                        _ => (name, value),
+                    })
+                    .collect();
+                ("DATETIME".into(), options)
+            }
+            "datetime" => ("DATETIME".into(), options),
+            custom => {
+                let fluent = custom.to_uppercase().replace('-', "_");
+                if !is_identifier(&fluent) {
+                    return Err(DiagnosticKind::Name {
+                        name: custom.into(),
+                    });
+                }
+                self.warnings.push(DiagnosticKind::Function {
+                    name: fluent.clone(),
+                });
+                (fluent, options)
+            }
+        };
+
+        let mut call = format!("{fluent}({variable}");
+        for (name, value) in options {
+            if !is_identifier(name) {
+                return Err(DiagnosticKind::Name { name: name.into() });
+            }
+            let value = if is_number(value) {
+                value.into()
+            } else {
+                quote(value)
+            };
+            call.push_str(&format!(", {name}: {value}"));
+        }
+        call.push(')');
+        Ok(call)
+    }
+}
+
+/// A variable reference, like `$count`
+fn variable(name: &str) -> Result<String, DiagnosticKind> {
+    if is_identifier(name) {
+        Ok(format!("${name}"))
+    } else {
+        Err(DiagnosticKind::Name { name: name.into() })
+    }
+}
+
+/// The Fluent id of a message id, whose dots separate sections
+fn fluent_id(id: &str) -> Result<String, DiagnosticKind> {
+    let fluent = id.replace('.', "-");
+    if is_identifier(&fluent) {
+        Ok(fluent)
+    } else {
+        Err(DiagnosticKind::Name { name: id.into() })
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn is_number(value: &str) -> bool {
+    let digits = |digits: &str| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+    let value = value.strip_prefix('-').unwrap_or(value);
+    match value.split_once('.') {
+        Some((integer, fraction)) => digits(integer) && digits(fraction),
+        None => digits(value),
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' | '\r' => quoted.push_str(&format!("\\u{:04X}", u32::from(c))),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A placeable with a string literal, for text that can't be written as is
+fn string_literal(value: &str) -> String {
+    format!("{{{}}}", quote(value))
+}
+
+/// Writes text of a pattern. Whitespace that Fluent would trim and characters that would end the
+/// pattern or a line of it are written as string literals.
+fn write_text(out: &mut String, text: &str, last: bool) {
+    let (mut text, trailing) = if last {
+        text.split_at(text.trim_end().len())
+    } else {
+        (text, "")
+    };
+    if out.is_empty() {
+        let content = text.trim_start_matches([' ', '\n']);
+        let leading = &text[..text.len() - content.len()];
+        if !leading.is_empty() {
+            out.push_str(&string_literal(leading));
+        }
+        text = content;
+    }
+
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        let mut content = line;
+        if out.is_empty() || out.ends_with('\n') {
+            content = line.trim_start_matches(' ');
+            if content.len() < line.len() {
+                out.push_str(&string_literal(&line[..line.len() - content.len()]));
+            } else if let Some(c @ ('[' | '*' | '.')) = content.chars().next() {
+                out.push_str(&string_literal(&c.to_string()));
+                content = &content[1..];
+            }
+        }
+        for c in content.chars() {
+            match c {
+                '{' | '}' => out.push_str(&string_literal(&c.to_string())),
+                _ => out.push(c),
+            }
+        }
+    }
+    if !trailing.is_empty() {
+        out.push_str(&string_literal(trailing));
+    }
+}
+
+fn write_variant(out: &mut String, key: &str, default: bool, value: &str) {
+    let marker = if default { '*' } else { ' ' };
+    let value = if value.is_empty() {
+        string_literal("")
+    } else {
+        value.into()
+    };
+    if value.contains('\n') {
+        out.push_str(&format!("   {marker}[{key}]\n{}\n", indent(&value, 8)));
+    } else {
+        out.push_str(&format!("   {marker}[{key}] {value}\n"));
+    }
+}
+
+fn write_comment(out: &mut String, marker: &str, comment: &str) {
+    for line in comment.split('\n') {
+        if line.is_empty() {
+            out.push_str(&format!("{marker}\n"));
+        } else {
+            out.push_str(&format!("{marker} {line}\n"));
+        }
+    }
+}
+
+/// Indents the lines of a value, except empty lines
+fn indent(value: &str, width: usize) -> String {
+    value
+        .split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{:width$}{line}", "")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
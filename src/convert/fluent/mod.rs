@@ -0,0 +1,608 @@
+//! Converts between Fluent resources (`.ftl` files) and MessageFormat 2 resources.
+//! See: https://projectfluent.org/fluent/guide/
+//!
+//! [`parse`] maps Fluent to MessageFormat 2:
+//!
+//! - Placeables like `{ $name }` become variable expressions.
+//! - Select expressions become selectors and variants, like in [`mf1`](super::mf1). Selectors on
+//!   variables are `:number` if all keys are numbers or plural categories, and `:string`
+//!   otherwise. The default variant gets the catch-all key `*`.
+//! - `NUMBER()` and `DATETIME()` become `:number` and `:datetime`, with the same options. The
+//!   `type` option of `NUMBER()` becomes `select`.
+//! - References to terms and other messages are replaced by their values.
+//! - Comments on messages are kept, and `###` comments become the comment of the resource.
+//!
+//! Attributes and term parameters have no equivalent and are reported as [`Diagnostic`]s, like
+//! custom functions, which become MessageFormat 2 functions with the same name in lowercase.
+//! Ids with dots are written with dashes by [`serialize`], which goes the other way.
+
+mod export;
+mod syntax;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::convert::tree::{self, function, variable, Node, Select};
+use crate::data_model::elements::{
+    Annotation, Expression, FunctionAnnotation, Literal, LiteralExpression,
+};
+use crate::resource::{Entry, Resource};
+
+use syntax::{Element, Pattern};
+
+pub use export::serialize;
+
+/// Something in a Fluent resource or a MessageFormat 2 message that could not be converted exactly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The line in the Fluent resource, for diagnostics from [`parse`]
+    pub line: Option<usize>,
+    /// The id of the message
+    pub id: Option<String>,
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// An entry that isn't valid Fluent, which was left out
+    Syntax(String),
+    /// An attribute of a message, which was left out
+    Attribute { name: String },
+    /// Arguments of a term, which were ignored. Selects in the term use their default variant.
+    TermParameters { term: String },
+    /// A reference to a message or term that doesn't exist, or that refers back to itself
+    Reference { name: String },
+    /// A custom function. It is imported in lowercase, but only exported if its name is valid in
+    /// Fluent.
+    Function { name: String },
+    /// Markup, which Fluent doesn't have. The message was left out.
+    Markup,
+    /// Reserved or private-use syntax. The message was left out.
+    Unsupported,
+    /// A literal formatted with a function, or a function without an operand. The message was
+    /// left out.
+    Operand,
+    /// An option with a variable value. The message was left out.
+    Option { name: String },
+    /// An id or variable name that isn't a Fluent identifier. The message was left out.
+    Name { name: String },
+    /// A variant key that isn't a Fluent identifier or number. The message was left out.
+    Key { key: String },
+    /// An id that becomes the same Fluent id as the id of an earlier message, like `cart.items`
+    /// and `cart-items`. The message was left out.
+    DuplicateId { fluent: String, earlier: String },
+    /// Selects that are nested too deeply or have too many combinations to become the variants of
+    /// a message. The message was left out.
+    TooComplex(String),
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{line}: ")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, "{id}: ")?;
+        }
+        match &self.kind {
+            DiagnosticKind::Syntax(reason) => write!(f, "{reason}, the entry was skipped"),
+            DiagnosticKind::Attribute { name } => write!(f, "the attribute .{name} was left out"),
+            DiagnosticKind::TermParameters { term } => {
+                write!(f, "the parameters of -{term} were ignored")
+            }
+            DiagnosticKind::Reference { name } => write!(f, "{name} can't be resolved"),
+            DiagnosticKind::Function { name } => {
+                write!(f, "the custom function {name} has to be provided")
+            }
+            DiagnosticKind::Markup => f.write_str("Fluent has no markup"),
+            DiagnosticKind::Unsupported => f.write_str("Fluent has no reserved syntax"),
+            DiagnosticKind::Operand => {
+                f.write_str("Fluent can only format variables with functions")
+            }
+            DiagnosticKind::Option { name } => write!(f, "the option {name} has a variable value"),
+            DiagnosticKind::Name { name } => write!(f, "{name} is not a Fluent identifier"),
+            DiagnosticKind::Key { key } => write!(f, "{key} is not a valid Fluent variant key"),
+            DiagnosticKind::DuplicateId { fluent, earlier } => {
+                write!(f, "{fluent} is already the Fluent id of {earlier}")
+            }
+            DiagnosticKind::TooComplex(reason) => write!(f, "{reason}, the message was left out"),
+        }
+    }
+}
+
+/// Converts a Fluent resource to a MessageFormat 2 resource. Terms are resolved into the messages
+/// that use them and don't become entries.
+///
+/// Entries that can't be parsed are left out, so the result is always a resource, along with
+/// diagnostics about everything that could not be converted exactly.
+pub fn parse(source: &str) -> (Resource, Vec<Diagnostic>) {
+    let entries = syntax::parse(source);
+    let mut importer = Importer {
+        messages: BTreeMap::new(),
+        terms: BTreeMap::new(),
+        diagnostics: Vec::new(),
+        line: 0,
+        id: String::new(),
+        stack: Vec::new(),
+    };
+    for entry in &entries {
+        if let syntax::Entry::Message(message) = entry {
+            let map = if message.term {
+                &mut importer.terms
+            } else {
+                &mut importer.messages
+            };
+            map.insert(message.id.as_str(), message);
+        }
+    }
+
+    let mut resource = Resource::new();
+    for entry in &entries {
+        match entry {
+            syntax::Entry::Message(message) if !message.term => {
+                importer.line = message.line;
+                importer.id.clone_from(&message.id);
+                importer.stack = vec![message.id.clone()];
+                for (name, _) in &message.attributes {
+                    importer.report(DiagnosticKind::Attribute { name: name.clone() });
+                }
+                if let Some(value) = &message.value {
+                    let nodes = importer.pattern(value, None);
+                    match tree::message(&nodes) {
+                        Ok(converted) => resource.sections[0].entries.push(Entry {
+                            key: message.id.clone(),
+                            comment: message.comment.clone(),
+                            message: converted,
+                        }),
+                        Err(error) => {
+                            importer.report(DiagnosticKind::TooComplex(error.to_string()));
+                        }
+                    }
+                }
+            }
+            syntax::Entry::Message(_) => {}
+            syntax::Entry::ResourceComment(comment) => match &mut resource.comment {
+                Some(existing) => {
+                    existing.push_str("\n\n");
+                    existing.push_str(comment);
+                }
+                None => resource.comment = Some(comment.clone()),
+            },
+            syntax::Entry::Junk { line, reason } => importer.diagnostics.push(Diagnostic {
+                line: Some(*line),
+                id: None,
+                kind: DiagnosticKind::Syntax(reason.clone()),
+            }),
+        }
+    }
+    (resource, importer.diagnostics)
+}
+
+struct Importer<'a> {
+    messages: BTreeMap<&'a str, &'a syntax::Message>,
+    terms: BTreeMap<&'a str, &'a syntax::Message>,
+    diagnostics: Vec<Diagnostic>,
+    /// The line and id of the message that is converted
+    line: usize,
+    id: String,
+    /// The messages and terms that are being resolved, to find cycles
+    stack: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn report(&mut self, kind: DiagnosticKind) {
+        let diagnostic = Diagnostic {
+            line: Some(self.line),
+            id: Some(self.id.clone()),
+            kind,
+        };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Converts a pattern. `term` is the term the pattern is in, whose variables are its parameters.
+    fn pattern(&mut self, pattern: &'a Pattern, term: Option<&str>) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        for element in pattern {
+            match element {
+                Element::Text(text) => push(&mut nodes, text),
+                Element::Placeable(expression) => self.placeable(expression, term, &mut nodes),
+            }
+        }
+        nodes
+    }
+
+    fn placeable(
+        &mut self,
+        expression: &'a syntax::Expression,
+        term: Option<&str>,
+        nodes: &mut Vec<Node>,
+    ) {
+        match expression {
+            syntax::Expression::String(text) | syntax::Expression::Number(text) => {
+                push(nodes, text)
+            }
+            syntax::Expression::Variable(name) => match term {
+                Some(term) => self.report(DiagnosticKind::TermParameters { term: term.into() }),
+                None => nodes.push(Node::Expression(variable(name.clone(), None))),
+            },
+            syntax::Expression::Function { name, arguments } => {
+                if let Some(expression) = self.function(name, arguments, term) {
+                    nodes.push(Node::Expression(expression));
+                }
+            }
+            syntax::Expression::Message { id, attribute } => {
+                let message = self.messages.get(id.as_str()).copied();
+                let name = attribute
+                    .as_ref()
+                    .map_or_else(|| id.clone(), |attribute| format!("{id}.{attribute}"));
+                self.reference(message, attribute.as_deref(), &name, None, nodes);
+            }
+            syntax::Expression::Term {
+                id,
+                attribute,
+                arguments,
+            } => {
+                if arguments.is_some() {
+                    self.report(DiagnosticKind::TermParameters { term: id.clone() });
+                }
+                let term = self.terms.get(id.as_str()).copied();
+                self.reference(
+                    term,
+                    attribute.as_deref(),
+                    &format!("-{id}"),
+                    Some(id),
+                    nodes,
+                );
+            }
+            syntax::Expression::Select { selector, variants } => {
+                let default = variants.iter().find(|variant| variant.default);
+                match self.selector(selector, variants, term) {
+                    Ok(selector) => {
+                        let cases = variants
+                            .iter()
+                            .map(|variant| {
+                                (
+                                    (!variant.default).then(|| variant.key.clone()),
+                                    self.pattern(&variant.value, term),
+                                )
+                            })
+                            .collect();
+                        nodes.push(Node::Select(Select { selector, cases }));
+                    }
+                    // The variant is known without formatting
+                    Err(key) => {
+                        let variant = variants
+                            .iter()
+                            .find(|variant| Some(&variant.key) == key.as_ref())
+                            .or(default);
+                        if let Some(variant) = variant {
+                            let value = self.pattern(&variant.value, term);
+                            nodes.extend(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Inlines the value or an attribute of a message or term
+    fn reference(
+        &mut self,
+        message: Option<&'a syntax::Message>,
+        attribute: Option<&str>,
+        name: &str,
+        term: Option<&str>,
+        nodes: &mut Vec<Node>,
+    ) {
+        let pattern = message.and_then(|message| {
+            attribute.map_or(message.value.as_ref(), |attribute| {
+                message
+                    .attributes
+                    .iter()
+                    .find(|(name, _)| name == attribute)
+                    .map(|(_, value)| value)
+            })
+        });
+        match pattern {
+            Some(pattern) if !self.stack.iter().any(|resolving| resolving == name) => {
+                self.stack.push(name.into());
+                let value = self.pattern(pattern, term);
+                self.stack.pop();
+                nodes.extend(value);
+            }
+            _ => {
+                self.report(DiagnosticKind::Reference { name: name.into() });
+                push(nodes, &format!("{{{name}}}"));
+            }
+        }
+    }
+
+    /// The selector of a select expression, or the key of the variant if it is known already
+    fn selector(
+        &mut self,
+        selector: &'a syntax::Expression,
+        variants: &[syntax::Variant],
+        term: Option<&str>,
+    ) -> Result<Expression, Option<String>> {
+        match selector {
+            syntax::Expression::Variable(name) if term.is_none() => {
+                let numeric = variants.iter().all(|variant| {
+                    variant.key.parse::<f64>().is_ok()
+                        || ["zero", "one", "two", "few", "many", "other"]
+                            .contains(&variant.key.as_str())
+                });
+                let annotation = function(if numeric { "number" } else { "string" }, &[]);
+                Ok(variable(name.clone(), Some(annotation)))
+            }
+            syntax::Expression::Variable(_) => {
+                if let Some(term) = term {
+                    self.report(DiagnosticKind::TermParameters { term: term.into() });
+                }
+                Err(None)
+            }
+            syntax::Expression::Function { name, arguments } => {
+                self.function(name, arguments, term).ok_or(None)
+            }
+            syntax::Expression::String(key) | syntax::Expression::Number(key) => {
+                Err(Some(key.clone()))
+            }
+            syntax::Expression::Term {
+                id,
+                attribute: Some(attribute),
+                ..
+            } => {
+                // Terms often select on their own attributes, which are known
+                let value = self.terms.get(id.as_str()).and_then(|term| {
+                    term.attributes
+                        .iter()
+                        .find(|(name, _)| name == attribute)
+                        .and_then(|(_, value)| match value.as_slice() {
+                            [Element::Text(text)] => Some(text.clone()),
+                            _ => None,
+                        })
+                });
+                if value.is_none() {
+                    self.report(DiagnosticKind::Reference {
+                        name: format!("-{id}.{attribute}"),
+                    });
+                }
+                Err(value)
+            }
+            syntax::Expression::Message { .. }
+            | syntax::Expression::Term { .. }
+            | syntax::Expression::Select { .. } => Err(None),
+        }
+    }
+
+    /// Converts a function call to an annotated expression, if its operand can be converted
+    fn function(
+        &mut self,
+        name: &str,
+        arguments: &syntax::Arguments,
+        term: Option<&str>,
+    ) -> Option<Expression> {
+        let mut options = Vec::new();
+        for (option, value) in &arguments.named {
+            let value = match value {
+                syntax::Expression::String(value) | syntax::Expression::Number(value) => {
+                    value.as_str()
+                }
+                _ => continue,
+            };
+            options.push(match (name, option.as_str(), value) {
+                ("NUMBER", "type", "cardinal") => ("select", "plural"),
+                ("NUMBER", "type", _) => ("select", value),
+                _ => (option.as_str(), value),
+            });
+        }
+        let annotation = match name {
+            "NUMBER" => function("number", &options),
+            "DATETIME" => function("datetime", &options),
+            _ => {
+                self.report(DiagnosticKind::Function { name: name.into() });
+                let name = name.to_lowercase().replace('_', "-");
+                FunctionAnnotation {
+                    name,
+                    ..function("", &options)
+                }
+            }
+        };
+
+        match (arguments.positional.first(), term) {
+            (Some(syntax::Expression::Variable(_)), Some(term)) => {
+                self.report(DiagnosticKind::TermParameters { term: term.into() });
+                None
+            }
+            (Some(syntax::Expression::Variable(operand)), None) => {
+                Some(variable(operand.clone(), Some(annotation)))
+            }
+            (
+                Some(syntax::Expression::String(operand) | syntax::Expression::Number(operand)),
+                _,
+            ) => Some(Expression::Literal(LiteralExpression {
+                arg: Literal {
+                    value: operand.clone(),
+                },
+                annotation: Some(Annotation::Function(annotation)),
+                attributes: Vec::new(),
+            })),
+            _ => {
+                self.report(DiagnosticKind::Operand);
+                None
+            }
+        }
+    }
+}
+
+fn push(nodes: &mut Vec<Node>, text: &str) {
+    if let Some(Node::Text(last)) = nodes.last_mut() {
+        last.push_str(text);
+    } else {
+        nodes.push(Node::Text(text.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+
+    const SHOP: &str = r#"### Messages of the shop
+
+-brand = Shopy
+    .gender = feminine
+
+# Shown on the home page
+welcome = Welcome to { -brand }, { $name }!
+brand-intro = { -brand.gender ->
+    [feminine] She is { -brand }.
+   *[other] It is { -brand }.
+}
+emails =
+    You have { $count ->
+        [0] no emails
+        [one] one email
+       *[other] { NUMBER($count, minimumFractionDigits: 0) } emails
+    }.
+    Read them now.
+ranking = { NUMBER($place, type: "ordinal") ->
+    [one] {$place}st
+   *[other] {$place}th
+}
+login = Log in
+    .placeholder = email@example.com
+"#;
+
+    #[test]
+    fn it_converts_fluent_resources() {
+        let (resource, diagnostics) = parse(SHOP);
+        let messages: Vec<(String, String)> = resource
+            .entries()
+            .map(|(id, entry)| (id, entry.message.to_string()))
+            .collect();
+
+        assert_eq!(resource.comment.as_deref(), Some("Messages of the shop"));
+        assert_eq!(
+            resource.sections[0].entries[0].comment.as_deref(),
+            Some("Shown on the home page")
+        );
+        assert_eq!(
+            messages,
+            [
+                ("welcome".into(), "Welcome to Shopy, {$name}!".into()),
+                ("brand-intro".into(), "She is Shopy.".into()),
+                (
+                    "emails".into(),
+                    ".match {$count :number}\n0 {{You have no emails.\nRead them now.}}\none {{You have one email.\nRead them now.}}\n* {{You have {$count :number minimumFractionDigits=0} emails.\nRead them now.}}".into()
+                ),
+                (
+                    "ranking".into(),
+                    ".match {$place :number select=ordinal}\none {{{$place}st}}\n* {{{$place}th}}".into()
+                ),
+                ("login".into(), "Log in".into()),
+            ]
+        );
+        assert_eq!(
+            diagnostics,
+            [Diagnostic {
+                line: Some(23),
+                id: Some("login".into()),
+                kind: DiagnosticKind::Attribute {
+                    name: "placeholder".into()
+                },
+            }]
+        );
+
+        let catalog = resource
+            .into_catalog("en".parse().expect("valid locale"))
+            .expect("valid messages");
+        assert_eq!(catalog.format("ranking", [("place", 2)]), Ok("2th".into()));
+        assert_eq!(catalog.format("ranking", [("place", 1)]), Ok("1st".into()));
+    }
+
+    #[test]
+    fn it_reports_what_doesnt_convert() {
+        let (resource, diagnostics) = parse(
+            "-thing = { $case ->\n   *[nominative] Ding\n    [genitive] Dinges\n}\nof = des { -thing(case: \"genitive\") }\nbroken = { $x\nloop = { loop }\nshout = { SHOUT($x) }\n",
+        );
+
+        assert_eq!(
+            resource
+                .entries()
+                .map(|(_, entry)| entry.message.to_string())
+                .collect::<Vec<_>>(),
+            ["des Ding", "\\{loop\\}", "{$x :shout}"]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "5: of: the parameters of -thing were ignored",
+                "6: expected `}` on line 7, the entry was skipped",
+                "7: loop: loop can't be resolved",
+                "8: shout: the custom function SHOUT has to be provided",
+            ]
+        );
+
+        let deep = format!("k = {}\"x\"{}\n", "{ ".repeat(50_000), " }".repeat(50_000));
+        let (resource, diagnostics) = parse(&deep);
+        assert_eq!(resource.entries().count(), 0);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "1: expressions are nested more than 64 levels deep on line 1, the entry was skipped"
+        );
+    }
+
+    #[test]
+    fn it_round_trips_through_mf2() {
+        let (resource, _) = parse(SHOP);
+        let (ftl, diagnostics) = serialize(&resource);
+        assert!(diagnostics.is_empty());
+
+        let (again, diagnostics) = parse(&ftl);
+        assert!(diagnostics.is_empty(), "{diagnostics:?}\n{ftl}");
+        assert_eq!(again, resource, "{ftl}");
+
+        let mut catalog = Catalog::new("en".parse().expect("valid locale"));
+        catalog
+            .insert_source(
+                "cart.total",
+                "Total: {$sum :number style=percent} {#b}!{/b}",
+            )
+            .expect("valid message");
+        catalog
+            .insert_source("cart.items", "{$n :integer} {|{x}|}  ")
+            .expect("valid message");
+        let (ftl, diagnostics) = serialize(&Resource::from_catalog(&catalog));
+        assert_eq!(
+            ftl,
+            "cart-items = { NUMBER($n, maximumFractionDigits: 0) } {\"{\"}x{\"}\"}{\"  \"}\n"
+        );
+        assert_eq!(
+            diagnostics,
+            [Diagnostic {
+                line: None,
+                id: Some("cart.total".into()),
+                kind: DiagnosticKind::Markup,
+            }]
+        );
+
+        catalog
+            .insert_source("cart-items", "Items")
+            .expect("valid message");
+        let (ftl, diagnostics) = serialize(&Resource::from_catalog(&catalog));
+        assert_eq!(ftl, "cart-items = Items\n");
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "cart.items: cart-items is already the Fluent id of cart-items"
+        );
+    }
+}
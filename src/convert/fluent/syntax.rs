@@ -0,0 +1,561 @@
+//! Parses the syntax of Fluent resources.
+//! See: https://github.com/projectfluent/fluent/blob/master/spec/fluent.ebnf
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub enum Entry {
+    Message(Message),
+    /// The text of a `###` comment
+    ResourceComment(String),
+    /// Lines that could not be parsed
+    Junk {
+        line: usize,
+        reason: String,
+    },
+}
+
+/// A message or a term
+pub struct Message {
+    pub id: String,
+    pub term: bool,
+    pub line: usize,
+    /// The text of the `#` comment directly before the message
+    pub comment: Option<String>,
+    pub value: Option<Pattern>,
+    pub attributes: Vec<(String, Pattern)>,
+}
+
+pub type Pattern = Vec<Element>;
+
+pub enum Element {
+    Text(String),
+    Placeable(Expression),
+}
+
+pub enum Expression {
+    String(String),
+    Number(String),
+    Variable(String),
+    Message {
+        id: String,
+        attribute: Option<String>,
+    },
+    Term {
+        id: String,
+        attribute: Option<String>,
+        arguments: Option<Arguments>,
+    },
+    Function {
+        name: String,
+        arguments: Arguments,
+    },
+    Select {
+        selector: Box<Self>,
+        variants: Vec<Variant>,
+    },
+}
+
+#[derive(Default)]
+pub struct Arguments {
+    pub positional: Vec<Expression>,
+    /// Named arguments, whose values are string or number literals
+    pub named: Vec<(String, Expression)>,
+}
+
+pub struct Variant {
+    pub key: String,
+    pub default: bool,
+    pub value: Pattern,
+}
+
+/// Parses a resource. Entries that can't be parsed become junk, like in other Fluent parsers.
+pub fn parse(source: &str) -> Vec<Entry> {
+    let source = source.replace("\r\n", "\n");
+    let mut parser = Parser {
+        source: &source,
+        position: 0,
+        depth: 0,
+    };
+    let mut entries = Vec::new();
+    // The level and lines of the comment before the current line
+    let mut comment: Option<(usize, Vec<String>)> = None;
+
+    while parser.peek().is_some() {
+        let start = parser.position;
+        let line = parser.line();
+        let rest = parser.rest_of_line();
+
+        if rest.trim_start_matches(' ').is_empty() {
+            parser.skip_line();
+            if let Some((3, lines)) = comment.take() {
+                entries.push(Entry::ResourceComment(lines.join("\n")));
+            }
+            continue;
+        }
+
+        if rest.starts_with('#') {
+            let level = rest.chars().take_while(|c| *c == '#').count();
+            let text = &rest[level..];
+            if level <= 3 && (text.is_empty() || text.starts_with(' ')) {
+                let text = text.strip_prefix(' ').unwrap_or(text).into();
+                match &mut comment {
+                    Some((current, lines)) if *current == level => lines.push(text),
+                    _ => {
+                        if let Some((3, lines)) = comment.take() {
+                            entries.push(Entry::ResourceComment(lines.join("\n")));
+                        }
+                        comment = Some((level, vec![text]));
+                    }
+                }
+                parser.skip_line();
+                continue;
+            }
+        }
+
+        let comment = match comment.take() {
+            Some((1, lines)) => Some(lines.join("\n")),
+            Some((3, lines)) => {
+                entries.push(Entry::ResourceComment(lines.join("\n")));
+                None
+            }
+            _ => None,
+        };
+        match parser.message(line, comment) {
+            Ok(message) => entries.push(Entry::Message(message)),
+            Err(reason) => {
+                parser.position = start;
+                parser.skip_junk();
+                entries.push(Entry::Junk { line, reason });
+            }
+        }
+    }
+    if let Some((3, lines)) = comment {
+        entries.push(Entry::ResourceComment(lines.join("\n")));
+    }
+    entries
+}
+
+/// A part of a pattern before indentation is removed
+enum Raw {
+    Text(String),
+    Indent(usize),
+    Placeable(Expression),
+}
+
+/// How deeply expressions can be nested in placeables and arguments, so that untrusted
+/// resources can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'s> {
+    source: &'s str,
+    /// The byte offset of the next character
+    position: usize,
+    /// How many expressions the parser is in
+    depth: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn rest_of_line(&self) -> &'s str {
+        self.rest().split('\n').next().unwrap_or_default()
+    }
+
+    fn line(&self) -> usize {
+        self.source[..self.position].matches('\n').count() + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected `{c}` on line {}", self.line()))
+        }
+    }
+
+    fn skip_line(&mut self) {
+        self.position += self.rest_of_line().len();
+        self.eat('\n');
+    }
+
+    /// Skips to the next line that starts an entry or a comment
+    fn skip_junk(&mut self) {
+        self.skip_line();
+        while self
+            .peek()
+            .is_some_and(|c| !(c == '#' || c == '-' || c.is_ascii_alphabetic()))
+        {
+            self.skip_line();
+        }
+    }
+
+    fn blank_inline(&mut self) {
+        while self.eat(' ') {}
+    }
+
+    /// Skips spaces and line breaks
+    fn blank(&mut self) {
+        while self.eat(' ') || self.eat('\n') {}
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.position;
+        if !self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("expected an identifier on line {}", self.line()));
+        }
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            self.bump();
+        }
+        Ok(self.source[start..self.position].into())
+    }
+
+    fn message(&mut self, line: usize, comment: Option<String>) -> Result<Message, String> {
+        let term = self.eat('-');
+        let id = self.identifier()?;
+        self.blank_inline();
+        self.expect('=')?;
+        let value = self.pattern()?;
+
+        let mut attributes = Vec::new();
+        loop {
+            let start = self.position;
+            self.blank();
+            if self.rest().starts_with('.') && self.source[..self.position].ends_with(' ') {
+                self.bump();
+                let name = self.identifier()?;
+                self.blank_inline();
+                self.expect('=')?;
+                let value = self
+                    .pattern()?
+                    .ok_or_else(|| format!("expected a value for .{name}"))?;
+                attributes.push((name, value));
+            } else {
+                self.position = start;
+                break;
+            }
+        }
+
+        if !(self.eat('\n') || self.peek().is_none()) {
+            return Err(format!(
+                "unexpected `{}` on line {}",
+                self.rest_of_line().trim(),
+                self.line()
+            ));
+        }
+        if value.is_none() && (term || attributes.is_empty()) {
+            return Err(format!(
+                "expected a value for {}{id}",
+                if term { "-" } else { "" }
+            ));
+        }
+        Ok(Message {
+            id,
+            term,
+            line,
+            comment,
+            value,
+            attributes,
+        })
+    }
+
+    /// Parses a pattern, which continues on indented lines. Returns `None` for an empty pattern.
+    fn pattern(&mut self) -> Result<Option<Pattern>, String> {
+        self.blank_inline();
+        let mut raw = Vec::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some('\n') => {
+                    let start = self.position;
+                    let mut newlines = 0;
+                    let mut indent = 0;
+                    while self.eat('\n') {
+                        newlines += 1;
+                        indent = 0;
+                        while self.eat(' ') {
+                            indent += 1;
+                        }
+                    }
+                    // A line continues the pattern if it is indented and doesn't start a variant
+                    // or an attribute or end a select, or if it starts with a placeable
+                    let continues = match self.peek() {
+                        Some('{') => true,
+                        Some('[' | '*' | '.' | '}') | None => false,
+                        Some(_) => indent > 0,
+                    };
+                    if !continues {
+                        self.position = start;
+                        break;
+                    }
+                    if !raw.is_empty() {
+                        raw.push(Raw::Text("\n".repeat(newlines)));
+                    }
+                    raw.push(Raw::Indent(indent));
+                }
+                Some('{') => raw.push(Raw::Placeable(self.placeable()?)),
+                Some('}') => return Err(format!("unbalanced `}}` on line {}", self.line())),
+                Some(_) => {
+                    let start = self.position;
+                    while self.peek().is_some_and(|c| !matches!(c, '{' | '}' | '\n')) {
+                        self.bump();
+                    }
+                    raw.push(Raw::Text(self.source[start..self.position].into()));
+                }
+            }
+        }
+
+        let common = raw
+            .iter()
+            .filter_map(|raw| match raw {
+                Raw::Indent(indent) => Some(*indent),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(0);
+        let mut pattern: Pattern = Vec::new();
+        for raw in raw {
+            let text = match raw {
+                Raw::Text(text) => text,
+                Raw::Indent(indent) => " ".repeat(indent - common),
+                Raw::Placeable(expression) => {
+                    pattern.push(Element::Placeable(expression));
+                    continue;
+                }
+            };
+            if text.is_empty() {
+                continue;
+            }
+            match pattern.last_mut() {
+                Some(Element::Text(last)) => last.push_str(&text),
+                _ => pattern.push(Element::Text(text)),
+            }
+        }
+        if let Some(Element::Text(last)) = pattern.last_mut() {
+            last.truncate(last.trim_end().len());
+            if last.is_empty() {
+                pattern.pop();
+            }
+        }
+        Ok((!pattern.is_empty()).then_some(pattern))
+    }
+
+    fn placeable(&mut self) -> Result<Expression, String> {
+        self.expect('{')?;
+        self.blank();
+        let expression = self.inline_expression()?;
+        self.blank();
+        if !self.rest().starts_with("->") {
+            self.expect('}')?;
+            return Ok(expression);
+        }
+
+        self.position += 2;
+        let mut variants: Vec<Variant> = Vec::new();
+        loop {
+            self.blank();
+            let default = self.eat('*');
+            if !self.eat('[') {
+                if default {
+                    return Err(format!("expected `[` on line {}", self.line()));
+                }
+                break;
+            }
+            self.blank();
+            let key = if self.peek().is_some_and(|c| c == '-' || c.is_ascii_digit()) {
+                self.number()?
+            } else {
+                self.identifier()?
+            };
+            self.blank();
+            self.expect(']')?;
+            let value = self.pattern()?.unwrap_or_default();
+            variants.push(Variant {
+                key,
+                default,
+                value,
+            });
+        }
+        self.expect('}')?;
+
+        if variants.iter().filter(|variant| variant.default).count() != 1 {
+            return Err(format!(
+                "expected one default variant before line {}",
+                self.line()
+            ));
+        }
+        Ok(Expression::Select {
+            selector: Box::new(expression),
+            variants,
+        })
+    }
+
+    fn inline_expression(&mut self) -> Result<Expression, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!(
+                "expressions are nested more than {MAX_DEPTH} levels deep on line {}",
+                self.line()
+            ));
+        }
+        self.depth += 1;
+        let expression = self.nested_expression();
+        self.depth -= 1;
+        expression
+    }
+
+    fn nested_expression(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('"') => self.string().map(Expression::String),
+            Some('-') if self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                self.number().map(Expression::Number)
+            }
+            Some(c) if c.is_ascii_digit() => self.number().map(Expression::Number),
+            Some('$') => {
+                self.bump();
+                self.identifier().map(Expression::Variable)
+            }
+            Some('{') => self.placeable(),
+            Some('-') => {
+                self.bump();
+                let id = self.identifier()?;
+                let attribute = self.attribute_accessor()?;
+                self.blank();
+                let arguments = if self.peek() == Some('(') {
+                    Some(self.arguments()?)
+                } else {
+                    None
+                };
+                Ok(Expression::Term {
+                    id,
+                    attribute,
+                    arguments,
+                })
+            }
+            _ => {
+                let id = self.identifier()?;
+                if self.peek() == Some('(') {
+                    let arguments = self.arguments()?;
+                    return Ok(Expression::Function {
+                        name: id,
+                        arguments,
+                    });
+                }
+                let attribute = self.attribute_accessor()?;
+                Ok(Expression::Message { id, attribute })
+            }
+        }
+    }
+
+    fn attribute_accessor(&mut self) -> Result<Option<String>, String> {
+        if self.eat('.') {
+            self.identifier().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Arguments, String> {
+        self.expect('(')?;
+        let mut arguments = Arguments::default();
+        loop {
+            self.blank();
+            if self.eat(')') {
+                return Ok(arguments);
+            }
+
+            let start = self.position;
+            let named = self.identifier().ok().filter(|_| {
+                self.blank();
+                self.eat(':')
+            });
+            if let Some(name) = named {
+                self.blank();
+                let value = match self.peek() {
+                    Some('"') => Expression::String(self.string()?),
+                    _ => Expression::Number(self.number()?),
+                };
+                arguments.named.push((name, value));
+            } else {
+                self.position = start;
+                arguments.positional.push(self.inline_expression()?);
+            }
+
+            self.blank();
+            if !self.eat(',') {
+                self.expect(')')?;
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some(c @ ('\\' | '"')) => value.push(c),
+                    Some(u @ ('u' | 'U')) => {
+                        let length = if u == 'u' { 4 } else { 6 };
+                        let hex = self.rest().get(..length).unwrap_or_default();
+                        let c = u32::from_str_radix(hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid escape on line {}", self.line()))?;
+                        self.position += length;
+                        value.push(c);
+                    }
+                    _ => return Err(format!("invalid escape on line {}", self.line())),
+                },
+                Some('\n') | None => {
+                    return Err(format!("unterminated string on line {}", self.line()))
+                }
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<String, String> {
+        let start = self.position;
+        self.eat('-');
+        let digits = |parser: &mut Self| {
+            let start = parser.position;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.bump();
+            }
+            parser.position > start
+        };
+        if !digits(self) || (self.eat('.') && !digits(self)) {
+            return Err(format!("expected a number on line {}", self.line()));
+        }
+        Ok(self.source[start..self.position].into())
+    }
+}
@@ -0,0 +1,423 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{PluralForms, PoEntry, PoFile, SELECTOR_COMMENT};
+use crate::convert::scope::{Scope, Unresolvable, Value};
+use crate::convert::tree::{push_text, variable};
+use crate::data_model::elements::{
+    Message, OptionValue, PatternElement, PatternMessage, VariantKey,
+};
+use crate::format::plural::{self, PluralOperands, PluralRuleType};
+
+/// Writes a PO file.
+///
+/// Plural messages that select on one `:number` or `:integer` variable are written with
+/// `msgid_plural` and a `msgstr[n]` for each of the `Plural-Forms` of the file. A variable other
+/// than `$n` is kept in an extracted comment like `#. selector: $count`. Declarations are resolved
+/// into the expressions that use them.
+///
+/// # Errors
+/// Returns an error for the first entry whose messages can't be written, like messages with
+/// several selectors.
+pub fn serialize(file: &PoFile) -> Result<String, ExportError> {
+    let mut out = String::new();
+    write_comments(&mut out, "#", &file.comments);
+    let header: String = file
+        .header
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\n"))
+        .collect();
+    write_string(&mut out, "msgid", "");
+    write_string(&mut out, "msgstr", &header);
+
+    let forms = file.header("Plural-Forms").map(PluralForms::parse);
+    let language = file.language();
+    for entry in &file.entries {
+        let (selector, strings) =
+            strings(entry, forms.as_ref(), &language).map_err(|kind| ExportError {
+                entry: entry
+                    .context
+                    .clone()
+                    .unwrap_or_else(|| entry.source.to_string()),
+                kind,
+            })?;
+        out.push('\n');
+        write_comments(&mut out, "#", &entry.comments);
+        write_comments(&mut out, "#.", &entry.extracted_comments);
+        if let Some(selector) = selector {
+            out.push_str(&format!("#. {SELECTOR_COMMENT}{selector}\n"));
+        }
+        if !entry.references.is_empty() {
+            out.push_str(&format!("#: {}\n", entry.references.join(" ")));
+        }
+        if !entry.flags.is_empty() {
+            out.push_str(&format!("#, {}\n", entry.flags.join(", ")));
+        }
+        for (keyword, value) in strings {
+            write_string(&mut out, &keyword, &value);
+        }
+    }
+    Ok(out)
+}
+
+/// A message that can't be written to a PO file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportError {
+    /// The context of the entry, or its source message if it has none
+    pub entry: String,
+    pub kind: ExportErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportErrorKind {
+    /// A selector that doesn't select the plural category of a `:number` or `:integer` variable,
+    /// more than one selector, or a translation that selects on another variable than its source
+    Selector,
+    /// Reserved or private-use syntax
+    Unsupported,
+    /// A literal that is formatted with a function, or a function without an operand
+    Operand,
+    /// A plural translation in a file without a valid `Plural-Forms` header
+    PluralForms(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.entry)?;
+        match &self.kind {
+            ExportErrorKind::Selector => {
+                f.write_str("gettext can only select on the plural category of one number")
+            }
+            ExportErrorKind::Unsupported => f.write_str("reserved syntax can't be resolved"),
+            ExportErrorKind::Operand => {
+                f.write_str("a function without a variable can't be resolved")
+            }
+            ExportErrorKind::PluralForms(reason) => write!(f, "invalid Plural-Forms: {reason}"),
+        }
+    }
+}
+
+impl core::error::Error for ExportError {}
+
+/// The text of a message: one pattern, or the selected variable and the variants of a plural with
+/// their keys
+enum Text {
+    Single(String),
+    Plural(String, Vec<(VariantKey, String)>),
+}
+
+impl Text {
+    fn new(message: &Message) -> Result<Self, ExportErrorKind> {
+        let declarations = match message {
+            Message::Pattern(message) => &message.declarations,
+            Message::Select(message) => &message.declarations,
+        };
+        let scope = Scope::new(declarations).map_err(|(_, reason)| unresolvable(reason))?;
+        let message = match message {
+            Message::Pattern(message) => {
+                return pattern(&scope, &message.pattern).map(Self::Single)
+            }
+            Message::Select(message) => message,
+        };
+
+        let [selector] = message.selectors.as_slice() else {
+            return Err(ExportErrorKind::Selector);
+        };
+        let Value::Variable {
+            name,
+            annotation: Some(function),
+        } = scope.resolve(selector).map_err(unresolvable)?
+        else {
+            return Err(ExportErrorKind::Selector);
+        };
+        let plural = function.options.iter().all(|option| {
+            option.name != "select" || matches!(&option.value, OptionValue::Literal(literal) if literal.value == "plural")
+        });
+        if !matches!(function.name.as_str(), "number" | "integer") || !plural {
+            return Err(ExportErrorKind::Selector);
+        }
+        message
+            .variants
+            .iter()
+            .map(|variant| Ok((variant.keys[0].clone(), pattern(&scope, &variant.value)?)))
+            .collect::<Result<_, _>>()
+            .map(|variants| Self::Plural(name, variants))
+    }
+
+    /// The text for `n`, picked like formatting would
+    fn select(&self, n: u64, language: &str) -> &str {
+        let variants = match self {
+            Self::Single(text) => return text,
+            Self::Plural(_, variants) => variants,
+        };
+        let category = plural::category(
+            language,
+            PluralRuleType::Cardinal,
+            &PluralOperands::from_integer(n),
+        );
+        let find = |key: &str| {
+            variants.iter().find(|(variant, _)| match variant {
+                VariantKey::Literal(literal) => literal.value == key,
+                VariantKey::Catchall => false,
+            })
+        };
+        find(&n.to_string())
+            .or_else(|| find(category.as_str()))
+            .or_else(|| {
+                variants
+                    .iter()
+                    .find(|(key, _)| *key == VariantKey::Catchall)
+            })
+            .map_or("", |(_, text)| text)
+    }
+
+    fn catchall(&self) -> &str {
+        match self {
+            Self::Single(text) => text,
+            Self::Plural(_, variants) => variants
+                .iter()
+                .find(|(key, _)| *key == VariantKey::Catchall)
+                .map_or("", |(_, text)| text),
+        }
+    }
+}
+
+const fn unresolvable(reason: Unresolvable) -> ExportErrorKind {
+    match reason {
+        Unresolvable::Operand => ExportErrorKind::Operand,
+        Unresolvable::Unsupported => ExportErrorKind::Unsupported,
+    }
+}
+
+/// A pattern in MessageFormat 2 syntax, with declared variables resolved
+fn pattern(scope: &Scope, pattern: &[PatternElement]) -> Result<String, ExportErrorKind> {
+    let mut resolved = Vec::new();
+    for element in pattern {
+        match element {
+            PatternElement::Literal(text) => push_text(&mut resolved, text),
+            PatternElement::Expression(expression) => {
+                match scope.resolve(expression).map_err(unresolvable)? {
+                    Value::Text(text) => push_text(&mut resolved, &text),
+                    Value::Variable { name, annotation } => {
+                        resolved.push(PatternElement::Expression(variable(name, annotation)));
+                    }
+                }
+            }
+            PatternElement::Markup(_) => resolved.push(element.clone()),
+        }
+    }
+    let message = PatternMessage {
+        declarations: Vec::new(),
+        pattern: resolved,
+    };
+    Ok(message.to_string())
+}
+
+/// Keywords like `msgid` and their strings
+type Strings = Vec<(String, String)>;
+
+/// The keywords and strings of an entry, with the selected variable if it is not `$n`
+fn strings(
+    entry: &PoEntry,
+    forms: Option<&Result<PluralForms, String>>,
+    language: &str,
+) -> Result<(Option<String>, Strings), ExportErrorKind> {
+    let source = Text::new(&entry.source)?;
+    let translation = entry.translation.as_ref().map(Text::new).transpose()?;
+
+    let mut strings = Vec::new();
+    if let Some(context) = &entry.context {
+        strings.push(("msgctxt".into(), context.clone()));
+    }
+    // `msgid` is the singular by convention, whatever the language of the source is
+    strings.push(("msgid".into(), source.select(1, "en").into()));
+    let selector = match (&source, &translation) {
+        (Text::Plural(source, _), Some(Text::Plural(translation, _))) if source != translation => {
+            return Err(ExportErrorKind::Selector)
+        }
+        (Text::Plural(selector, _), _) | (_, Some(Text::Plural(selector, _))) => selector,
+        _ => {
+            let translation = translation
+                .as_ref()
+                .map_or("", |translation| translation.catchall());
+            strings.push(("msgstr".into(), translation.into()));
+            return Ok((None, strings));
+        }
+    };
+
+    strings.push(("msgid_plural".into(), source.catchall().into()));
+    let samples = match (forms, &translation) {
+        (Some(Ok(forms)), _) => forms.samples().map_err(ExportErrorKind::PluralForms)?,
+        // Templates have two empty forms
+        (_, None) => Vec::from([1, 2]),
+        (Some(Err(reason)), Some(_)) => return Err(ExportErrorKind::PluralForms(reason.clone())),
+        (None, Some(_)) => return Err(ExportErrorKind::PluralForms("missing header".into())),
+    };
+    for (index, n) in samples.into_iter().enumerate() {
+        let form = translation
+            .as_ref()
+            .map_or("", |translation| translation.select(n, language));
+        strings.push((format!("msgstr[{index}]"), form.into()));
+    }
+    Ok(((selector != "n").then(|| selector.clone()), strings))
+}
+
+fn write_comments(out: &mut String, marker: &str, comments: &[String]) {
+    for comment in comments {
+        if comment.is_empty() {
+            out.push_str(&format!("{marker}\n"));
+        } else {
+            out.push_str(&format!("{marker} {comment}\n"));
+        }
+    }
+}
+
+/// Writes a keyword and its string, with one line per line of the string
+fn write_string(out: &mut String, keyword: &str, value: &str) {
+    if value.contains('\n') {
+        out.push_str(&format!("{keyword} \"\"\n"));
+        for line in value.split_inclusive('\n') {
+            out.push_str(&format!("\"{}\"\n", escape(line)));
+        }
+    } else {
+        out.push_str(&format!("{keyword} \"{}\"\n", escape(value)));
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::resource::Resource;
+
+    #[test]
+    fn it_writes_templates() {
+        let source: Resource = "# The title of the cart page\ncart-title = Your cart\ncart-items =\n  .input {$count :number}\n  .match {$count}\n  one {{{$count} item}}\n  * {{{$count} items\n  in \"your\" cart}}\n"
+            .parse()
+            .expect("valid resource");
+        assert_eq!(
+            serialize(&PoFile::template(&source)),
+            Ok(r#"msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#. The title of the cart page
+msgctxt "cart-title"
+msgid "Your cart"
+msgstr ""
+
+#. selector: $count
+msgctxt "cart-items"
+msgid "{$count :number} item"
+msgid_plural ""
+"{$count :number} items\n"
+"in \"your\" cart"
+msgstr[0] ""
+msgstr[1] ""
+"#
+            .into())
+        );
+
+        // The variable comes back from its comment
+        let file: PoFile = serialize(&PoFile::template(&source))
+            .expect("writable template")
+            .parse()
+            .expect("valid PO file");
+        assert!(file.entries[1].extracted_comments.is_empty());
+        assert_eq!(
+            file.entries[1].source.to_string(),
+            ".match {$count :number}\none {{{$count :number} item}}\n* {{{$count :number} items\nin \"your\" cart}}"
+        );
+    }
+
+    #[test]
+    fn it_writes_translations_for_the_plural_forms() {
+        let source: Resource = "items =\n  .match {$n :integer}\n  0 {{no items}}\n  one {{one item}}\n  * {{{$n} items}}\n"
+            .parse()
+            .expect("valid resource");
+        let mut catalog = Catalog::new("pl".parse().expect("valid locale"));
+        catalog
+            .insert_source("items", ".match {$n :integer}\none {{{$n} przedmiot}}\nfew {{{$n} przedmioty}}\n* {{{$n} przedmiotów}}")
+            .expect("valid message");
+        let mut file = PoFile::template(&source);
+        file.translate(&catalog);
+
+        let po = serialize(&file).expect("valid PO file");
+        assert!(
+            po.contains("\"Language: pl\\n\"\n\"Plural-Forms: nplurals=3;"),
+            "{po}"
+        );
+        assert!(
+            po.ends_with("msgctxt \"items\"\nmsgid \"one item\"\nmsgid_plural \"{$n} items\"\nmsgstr[0] \"{$n} przedmiot\"\nmsgstr[1] \"{$n} przedmioty\"\nmsgstr[2] \"{$n} przedmiotów\"\n"),
+            "{po}"
+        );
+
+        // The selector is `$n` again, but only with the keys of the forms
+        let parsed: PoFile = po.parse().expect("valid PO file");
+        let catalog = parsed
+            .into_catalog("pl".parse().expect("valid locale"))
+            .expect("valid messages");
+        assert_eq!(
+            catalog.format("items", [("n", 22)]),
+            Ok("22 przedmioty".into())
+        );
+        assert_eq!(
+            catalog.format("items", [("n", 5)]),
+            Ok("5 przedmiotów".into())
+        );
+    }
+
+    #[test]
+    fn it_reports_messages_that_cant_be_written() {
+        let mut file = PoFile::new(&"de".parse().expect("valid locale"));
+        let source = ".match {$a :string} {$b :string}\n* * {{x}}"
+            .parse()
+            .expect("valid message");
+        file.entries.push(PoEntry {
+            context: Some("pair".into()),
+            comments: Vec::new(),
+            extracted_comments: Vec::new(),
+            references: Vec::new(),
+            flags: Vec::new(),
+            source,
+            translation: None,
+        });
+        assert_eq!(
+            serialize(&file).map_err(|error| error.to_string()),
+            Err("pair: gettext can only select on the plural category of one number".into())
+        );
+
+        // Only one variable can be kept for the entry
+        file.entries[0].source = ".match {$count :number}\none {{one file}}\n* {{{$count} files}}"
+            .parse()
+            .expect("valid message");
+        file.entries[0].translation = Some(
+            ".match {$n :number}\none {{eine Datei}}\n* {{{$n} Dateien}}"
+                .parse()
+                .expect("valid message"),
+        );
+        assert_eq!(
+            serialize(&file).map_err(|error| error.kind),
+            Err(ExportErrorKind::Selector)
+        );
+    }
+}
@@ -0,0 +1,674 @@
+//! Converts between gettext PO and POT files and MessageFormat 2 messages.
+//! See: https://www.gnu.org/software/gettext/manual/html_node/PO-Files.html
+//!
+//! The strings of a PO file hold MessageFormat 2 patterns, so translators see placeholders like
+//! `{$name}`, and braces in text are escaped like `\{`. Strings that aren't valid patterns, like
+//! the ones of existing gettext files, are kept as text, with `%d` style placeholders as they are.
+//! So are strings with literal placeholders like `{word}`, which MessageFormat 2 has no use for.
+//!
+//! Entries with `msgid_plural` become messages that select on `{$n :number}`, or on the variable of
+//! an extracted comment like `#. selector: $count`, which [`serialize`] adds for the variables that
+//! are not `$n`. The keys of the `msgstr[n]` forms are the CLDR plural categories of the
+//! `Language` of the file, found by evaluating its `Plural-Forms` header: with `plural=(n != 1)` in German, `msgstr[0]` becomes the
+//! `one` variant and `msgstr[1]` the catch-all variant. Forms for a single number that don't have
+//! a category of their own, like `n==0`, get that number as key. `msgid` and `msgid_plural` become
+//! the `one` and catch-all variants of the source message.
+//!
+//! [`serialize`] goes the other way and picks the variant for each form like formatting would,
+//! with the smallest number that selects it.
+//!
+//! `msgctxt`, comments, references and flags are kept in [`PoEntry`]. [`PoFile::template`] and
+//! [`PoFile::into_catalog`] use the context as the id of a message, which is how MessageFormat 2
+//! ids are kept in PO files.
+
+mod export;
+mod plural_forms;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::str::FromStr;
+
+use crate::catalog::{Catalog, CatalogError};
+use crate::convert::tree::{function, variable};
+use crate::data_model::elements::{
+    Expression, Literal, LiteralExpression, Message, PatternElement, PatternMessage, SelectMessage,
+    Variant, VariantKey,
+};
+use crate::locale::Locale;
+use crate::parser::is_name;
+use crate::resource::Resource;
+
+pub use export::{serialize, ExportError, ExportErrorKind};
+pub use plural_forms::PluralForms;
+
+/// The extracted comment with the variable of a plural entry, followed by its name
+const SELECTOR_COMMENT: &str = "selector: $";
+
+/// The entries of a PO or POT file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoFile {
+    /// The translator comments of the header entry
+    pub comments: Vec<String>,
+    /// The fields of the header entry in file order, like `Language` and `Plural-Forms`
+    pub header: Vec<(String, String)>,
+    pub entries: Vec<PoEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoEntry {
+    /// `msgctxt`
+    pub context: Option<String>,
+    /// Translator comments, `# ...`
+    pub comments: Vec<String>,
+    /// Comments from the source code, `#. ...`
+    pub extracted_comments: Vec<String>,
+    /// Source references like `src/main.rs:12`, `#: ...`
+    pub references: Vec<String>,
+    /// Flags like `fuzzy`, `#, ...`
+    pub flags: Vec<String>,
+    /// The message of `msgid`, and `msgid_plural`
+    pub source: Message,
+    /// The message of `msgstr`, or none if the entry isn't translated, like in templates
+    pub translation: Option<Message>,
+}
+
+impl PoFile {
+    /// An empty file for translations into `locale`, with the `Plural-Forms` that match its plural
+    /// categories
+    pub fn new(locale: &Locale) -> Self {
+        let mut file = Self {
+            comments: Vec::new(),
+            header: Vec::new(),
+            entries: Vec::new(),
+        };
+        file.set_locale(locale);
+        file
+    }
+
+    /// A template for translating the messages of `source`. The id of each message becomes its
+    /// context, and its comment an extracted comment.
+    pub fn template(source: &Resource) -> Self {
+        let entries = source
+            .entries()
+            .map(|(id, entry)| PoEntry {
+                context: Some(id),
+                comments: Vec::new(),
+                extracted_comments: entry
+                    .comment
+                    .iter()
+                    .flat_map(|comment| comment.lines())
+                    .map(Into::into)
+                    .collect(),
+                references: Vec::new(),
+                flags: Vec::new(),
+                source: entry.message.clone(),
+                translation: None,
+            })
+            .collect();
+        Self {
+            comments: Vec::new(),
+            header: vec![("Content-Type".into(), "text/plain; charset=UTF-8".into())],
+            entries,
+        }
+    }
+
+    /// Fills in the translations from `catalog` for the entries whose context is one of its ids,
+    /// and sets the header for its locale
+    pub fn translate(&mut self, catalog: &Catalog) {
+        self.set_locale(catalog.locale());
+        for entry in &mut self.entries {
+            if let Some(message) = entry.context.as_deref().and_then(|id| catalog.get(id)) {
+                entry.translation = Some(message.clone());
+            }
+        }
+    }
+
+    /// A field of the header
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a field of the header, keeping its position if it exists
+    pub fn set_header(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self
+            .header
+            .iter_mut()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.header.push((name.into(), value)),
+        }
+    }
+
+    fn set_locale(&mut self, locale: &Locale) {
+        self.set_header("Language", locale.to_string().replace('-', "_"));
+        self.set_header("Plural-Forms", PluralForms::for_language(locale.language()));
+    }
+
+    /// The language of the `Language` header, like `pt` for `pt_BR`
+    fn language(&self) -> String {
+        let language = self.header("Language").unwrap_or_default();
+        language
+            .split(['_', '-', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+
+    /// Puts the translated messages into a catalog for `locale`. Their ids are their contexts, or
+    /// the text of `msgid` for entries without one.
+    ///
+    /// # Errors
+    /// Returns an error if a message is not valid according to the data model.
+    pub fn into_catalog(self, locale: Locale) -> Result<Catalog, CatalogError> {
+        let mut catalog = Catalog::new(locale);
+        for entry in self.entries {
+            if let Some(translation) = entry.translation {
+                let id = entry.context.unwrap_or_else(|| entry.source.to_string());
+                catalog.insert(id, translation)?;
+            }
+        }
+        Ok(catalog)
+    }
+}
+
+impl FromStr for PoFile {
+    type Err = PoError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        parse(source)
+    }
+}
+
+/// An error in a PO file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoError {
+    /// The 1-based line of the error
+    pub line: usize,
+    pub kind: PoErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoErrorKind {
+    /// A line that is not a comment, a keyword or a string
+    InvalidLine(String),
+    /// A string with an unknown escape or without a closing quote
+    InvalidString,
+    /// An entry without `msgid` or `msgstr`
+    Incomplete,
+    /// A plural entry in a file without a valid `Plural-Forms` header, or with forms that don't
+    /// match the plural categories of its language
+    PluralForms(String),
+}
+
+impl fmt::Display for PoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            PoErrorKind::InvalidLine(line) => write!(f, "invalid line `{line}`"),
+            PoErrorKind::InvalidString => f.write_str("invalid string"),
+            PoErrorKind::Incomplete => f.write_str("the entry needs a msgid and a msgstr"),
+            PoErrorKind::PluralForms(reason) => write!(f, "invalid Plural-Forms: {reason}"),
+        }
+    }
+}
+
+impl core::error::Error for PoError {}
+
+/// Parses a PO or POT file.
+///
+/// # Errors
+/// Returns the first error in the file. Plural forms are only checked if there is a translated
+/// plural entry.
+pub fn parse(source: &str) -> Result<PoFile, PoError> {
+    let mut file = PoFile {
+        comments: Vec::new(),
+        header: Vec::new(),
+        entries: Vec::new(),
+    };
+    let mut raw = Vec::new();
+    let mut current = RawEntry::default();
+
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        let error = |kind| PoError { line: number, kind };
+
+        if line.is_empty() {
+            current.finish(&mut raw)?;
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if !current.strings.is_empty() {
+                current.finish(&mut raw)?;
+            }
+            current.line.get_or_insert(number);
+            match comment.chars().next() {
+                Some('.') => current.extracted_comments.push(comment[1..].trim().into()),
+                Some(':') => current
+                    .references
+                    .extend(comment[1..].split_whitespace().map(Into::into)),
+                Some(',') => current.flags.extend(
+                    comment[1..]
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|flag| !flag.is_empty())
+                        .map(Into::into),
+                ),
+                // Previous strings and obsolete entries
+                Some('|' | '~') => {}
+                _ => current
+                    .comments
+                    .push(comment.strip_prefix(' ').unwrap_or(comment).into()),
+            }
+            continue;
+        }
+        if line.starts_with('"') {
+            let value = unquote(line).ok_or_else(|| error(PoErrorKind::InvalidString))?;
+            let (_, last) = current
+                .strings
+                .last_mut()
+                .ok_or_else(|| error(PoErrorKind::InvalidLine(line.into())))?;
+            last.push_str(&value);
+            continue;
+        }
+
+        let (keyword, rest) = line.split_once([' ', '\t']).unwrap_or((line, ""));
+        let keyword = match keyword {
+            "msgctxt" | "msgid"
+                if current
+                    .strings
+                    .iter()
+                    .any(|(keyword, _)| keyword == "msgid") =>
+            {
+                current.finish(&mut raw)?;
+                keyword
+            }
+            "msgctxt" | "msgid" | "msgid_plural" | "msgstr" => keyword,
+            _ if keyword.starts_with("msgstr[") && keyword.ends_with(']') => keyword,
+            _ => return Err(error(PoErrorKind::InvalidLine(line.into()))),
+        };
+        let value = unquote(rest.trim()).ok_or_else(|| error(PoErrorKind::InvalidString))?;
+        current.line.get_or_insert(number);
+        current.strings.push((keyword.into(), value));
+    }
+    current.finish(&mut raw)?;
+
+    for entry in raw {
+        if entry.string("msgid") == Some("") && entry.string("msgctxt").is_none() {
+            for field in entry.string("msgstr").unwrap_or_default().lines() {
+                if let Some((name, value)) = field.split_once(':') {
+                    file.header.push((name.trim().into(), value.trim().into()));
+                }
+            }
+            file.comments = entry.comments;
+        } else {
+            let entry = entry.convert(&file)?;
+            file.entries.push(entry);
+        }
+    }
+    Ok(file)
+}
+
+/// An entry with its strings before they are parsed
+#[derive(Default)]
+struct RawEntry {
+    /// The line the entry starts on
+    line: Option<usize>,
+    comments: Vec<String>,
+    extracted_comments: Vec<String>,
+    references: Vec<String>,
+    flags: Vec<String>,
+    /// The keywords and their strings
+    strings: Vec<(String, String)>,
+}
+
+impl RawEntry {
+    fn finish(&mut self, raw: &mut Vec<Self>) -> Result<(), PoError> {
+        let entry = core::mem::take(self);
+        if entry.strings.is_empty() {
+            // Comments that don't belong to an entry, like the ones of obsolete entries
+            return Ok(());
+        }
+        if entry.string("msgid").is_none()
+            || !entry
+                .strings
+                .iter()
+                .any(|(keyword, _)| keyword.starts_with("msgstr"))
+        {
+            return Err(PoError {
+                line: entry.line.unwrap_or(1),
+                kind: PoErrorKind::Incomplete,
+            });
+        }
+        raw.push(entry);
+        Ok(())
+    }
+
+    fn string(&self, keyword: &str) -> Option<&str> {
+        self.strings
+            .iter()
+            .find(|(name, _)| name == keyword)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn convert(mut self, file: &PoFile) -> Result<PoEntry, PoError> {
+        let line = self.line.unwrap_or(1);
+        let error = |kind| PoError { line, kind };
+
+        // The variable of a plural entry, if it is not `$n`
+        let comment = self
+            .extracted_comments
+            .iter()
+            .position(|comment| comment.strip_prefix(SELECTOR_COMMENT).is_some_and(is_name));
+        let selector: String = match comment {
+            Some(index) if self.string("msgid_plural").is_some() => {
+                self.extracted_comments.remove(index)[SELECTOR_COMMENT.len()..].into()
+            }
+            _ => "n".into(),
+        };
+
+        let msgid = pattern(self.string("msgid").unwrap_or_default());
+        let (source, translation) = match self.string("msgid_plural") {
+            None => {
+                let msgstr = self.string("msgstr").unwrap_or_default();
+                let translation = if msgstr.is_empty() {
+                    None
+                } else {
+                    Some(pattern_message(pattern(msgstr)))
+                };
+                (pattern_message(msgid), translation)
+            }
+            Some(plural) => {
+                let source = plural_message(
+                    &selector,
+                    vec![
+                        (
+                            VariantKey::Literal(Literal {
+                                value: "one".into(),
+                            }),
+                            msgid,
+                        ),
+                        (VariantKey::Catchall, pattern(plural)),
+                    ],
+                );
+                let mut forms = Vec::new();
+                for (keyword, value) in &self.strings {
+                    let Some(index) = keyword.strip_prefix("msgstr[") else {
+                        continue;
+                    };
+                    let index: usize = index
+                        .trim_end_matches(']')
+                        .parse()
+                        .map_err(|_| error(PoErrorKind::InvalidLine(keyword.clone())))?;
+                    forms.push((index, value.as_str()));
+                }
+                forms.sort_by_key(|(index, _)| *index);
+                if let Some(index) = (0..forms.len()).find(|index| forms[*index].0 != *index) {
+                    let reason = format!("msgstr[{index}] is missing or repeated");
+                    return Err(error(PoErrorKind::PluralForms(reason)));
+                }
+                let forms: Vec<&str> = forms.into_iter().map(|(_, form)| form).collect();
+                let translation = if forms.iter().all(|form| form.is_empty()) {
+                    None
+                } else {
+                    let plural_forms = file
+                        .header("Plural-Forms")
+                        .ok_or_else(|| "missing header".to_string())
+                        .and_then(PluralForms::parse)
+                        .map_err(|reason| error(PoErrorKind::PluralForms(reason)))?;
+                    let keys = plural_forms
+                        .keys(&file.language())
+                        .map_err(|reason| error(PoErrorKind::PluralForms(reason)))?;
+                    if forms.len() != keys.len() {
+                        let reason =
+                            format!("expected {} forms, found {}", keys.len(), forms.len());
+                        return Err(error(PoErrorKind::PluralForms(reason)));
+                    }
+                    let mut variants = Vec::new();
+                    for (key, form) in keys.into_iter().zip(forms) {
+                        variants.push((key, pattern(form)));
+                    }
+                    // Forms only cover integers, the last one is the closest to what other numbers need
+                    if !variants.iter().any(|(key, _)| *key == VariantKey::Catchall) {
+                        let last = variants
+                            .last()
+                            .map(|(_, value)| value.clone())
+                            .unwrap_or_default();
+                        variants.push((VariantKey::Catchall, last));
+                    }
+                    Some(plural_message(&selector, variants))
+                };
+                (source, translation)
+            }
+        };
+
+        Ok(PoEntry {
+            context: self.string("msgctxt").map(Into::into),
+            comments: self.comments,
+            extracted_comments: self.extracted_comments,
+            references: self.references,
+            flags: self.flags,
+            source,
+            translation,
+        })
+    }
+}
+
+/// The pattern of a string, or the string as text if it isn't a pattern of MessageFormat 2
+fn pattern(source: &str) -> Vec<PatternElement> {
+    match source.parse::<Message>() {
+        Ok(Message::Pattern(message))
+            if message.declarations.is_empty()
+                && !message.pattern.iter().any(|element| {
+                    matches!(
+                        element,
+                        PatternElement::Expression(Expression::Literal(LiteralExpression {
+                            annotation: None,
+                            ..
+                        }))
+                    )
+                }) =>
+        {
+            message.pattern
+        }
+        _ if source.is_empty() => Vec::new(),
+        _ => vec![PatternElement::Literal(source.into())],
+    }
+}
+
+const fn pattern_message(pattern: Vec<PatternElement>) -> Message {
+    Message::Pattern(PatternMessage {
+        declarations: Vec::new(),
+        pattern,
+    })
+}
+
+/// A message that selects on `{$selector :number}`
+fn plural_message(selector: &str, variants: Vec<(VariantKey, Vec<PatternElement>)>) -> Message {
+    Message::Select(SelectMessage {
+        declarations: Vec::new(),
+        selectors: vec![variable(selector.into(), Some(function("number", &[])))],
+        variants: variants
+            .into_iter()
+            .map(|(key, value)| Variant {
+                keys: vec![key],
+                value,
+            })
+            .collect(),
+    })
+}
+
+/// The value of a quoted string
+fn unquote(string: &str) -> Option<String> {
+    let inner = string.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                c @ ('"' | '\\') => c,
+                _ => return None,
+            }),
+            '"' => return None,
+            _ => value.push(c),
+        }
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GERMAN: &str = r#"# German translations of the shop
+msgid ""
+msgstr ""
+"Language: de\n"
+"Plural-Forms: nplurals=2; plural=(n != 1);\n"
+
+# Keep it short
+#. The title of the cart page
+#: src/cart.rs:12 src/cart.rs:40
+msgctxt "cart.title"
+msgid "Your cart"
+msgstr "Ihr Warenkorb"
+
+#: src/cart.rs:20
+#, fuzzy
+msgctxt "cart.items"
+msgid "{$n} item"
+msgid_plural "{$n} items"
+msgstr[0] "{$n} Artikel"
+msgstr[1] ""
+"{$n} Artikel\n"
+"im Warenkorb"
+
+msgid "Hello {$name}!"
+msgstr ""
+"Hallo {$name}!"
+"""#;
+
+    #[test]
+    fn it_parses_po_files() {
+        let file: PoFile = GERMAN.parse().expect("valid PO file");
+        assert_eq!(file.comments, ["German translations of the shop"]);
+        assert_eq!(
+            file.header("plural-forms"),
+            Some("nplurals=2; plural=(n != 1);")
+        );
+
+        let title = &file.entries[0];
+        assert_eq!(title.context.as_deref(), Some("cart.title"));
+        assert_eq!(title.comments, ["Keep it short"]);
+        assert_eq!(title.extracted_comments, ["The title of the cart page"]);
+        assert_eq!(title.references, ["src/cart.rs:12", "src/cart.rs:40"]);
+
+        let items = &file.entries[1];
+        assert_eq!(items.flags, ["fuzzy"]);
+        assert_eq!(
+            items.source.to_string(),
+            ".match {$n :number}\none {{{$n} item}}\n* {{{$n} items}}"
+        );
+        assert_eq!(
+            items
+                .translation
+                .as_ref()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some(".match {$n :number}\none {{{$n} Artikel}}\n* {{{$n} Artikel\nim Warenkorb}}")
+        );
+
+        let catalog = file
+            .into_catalog("de".parse().expect("valid locale"))
+            .expect("valid messages");
+        assert_eq!(
+            catalog.format("cart.items", [("n", 3)]),
+            Ok("3 Artikel\nim Warenkorb".into())
+        );
+        assert_eq!(
+            catalog.format("Hello {$name}!", [("name", "Ada")]),
+            Ok("Hallo Ada!".into())
+        );
+    }
+
+    #[test]
+    fn it_keeps_plural_forms_without_a_category() {
+        let file: PoFile = "msgid \"\"\nmsgstr \"Language: en\\nPlural-Forms: nplurals=3; plural=(n==0 ? 0 : n==1 ? 1 : 2);\\n\"\n\nmsgid \"file\"\nmsgid_plural \"files\"\nmsgstr[0] \"no files\"\nmsgstr[1] \"a file\"\nmsgstr[2] \"{$n} files\"\n"
+            .parse()
+            .expect("valid PO file");
+        assert_eq!(
+            file.entries[0]
+                .translation
+                .as_ref()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some(".match {$n :number}\n0 {{no files}}\none {{a file}}\n* {{{$n} files}}")
+        );
+
+        // The index of a form decides its key, not where it is in the entry
+        let file: PoFile = "msgid \"\"\nmsgstr \"Language: de\\nPlural-Forms: nplurals=2; plural=(n != 1);\\n\"\n\nmsgid \"file\"\nmsgid_plural \"files\"\nmsgstr[1] \"{$n} Dateien\"\nmsgstr[0] \"eine Datei\"\n"
+            .parse()
+            .expect("valid PO file");
+        assert_eq!(
+            file.entries[0]
+                .translation
+                .as_ref()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some(".match {$n :number}\none {{eine Datei}}\n* {{{$n} Dateien}}")
+        );
+    }
+
+    #[test]
+    fn it_keeps_strings_that_arent_patterns_as_text() {
+        let file: PoFile = "msgid \"\"\nmsgstr \"Language: de\\n\"\n\nmsgid \"Use {braces} and C:\\\\path\"\nmsgstr \"Nimm {Klammern} und C:\\\\Pfad\"\n\nmsgid \"Hello {user}\"\nmsgstr \"\"\n"
+            .parse()
+            .expect("valid PO file");
+        assert_eq!(file.entries[1].source.to_string(), "Hello \\{user\\}");
+        let catalog = file
+            .into_catalog("de".parse().expect("valid locale"))
+            .expect("valid messages");
+        assert_eq!(
+            catalog.format("Use \\{braces\\} and C:\\\\path", ()),
+            Ok("Nimm {Klammern} und C:\\Pfad".into())
+        );
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        let error = |source: &str| source.parse::<PoFile>().expect_err("invalid PO file");
+        assert_eq!(
+            error("msgid \"a\"\nmsgstr \"b\"\nnonsense\n"),
+            PoError {
+                line: 3,
+                kind: PoErrorKind::InvalidLine("nonsense".into())
+            }
+        );
+        assert_eq!(error("msgid \"a\n").kind, PoErrorKind::InvalidString);
+        assert_eq!(error("\nmsgid \"a\"\n\n").kind, PoErrorKind::Incomplete);
+        assert_eq!(
+            error("msgid \"a\"\nmsgid_plural \"b\"\nmsgstr[0] \"c\"\nmsgstr[1] \"d\"\n")
+                .to_string(),
+            "line 1: invalid Plural-Forms: missing header"
+        );
+        assert_eq!(
+            error("msgid \"\"\nmsgstr \"Plural-Forms: nplurals=999999999999; plural=0;\\n\"\n\nmsgid \"a\"\nmsgid_plural \"b\"\nmsgstr[0] \"c\"\n")
+                .kind,
+            PoErrorKind::PluralForms("nplurals must be between 1 and 6, found 999999999999".into())
+        );
+        assert_eq!(
+            error("msgid \"a\"\nmsgid_plural \"b\"\nmsgstr[0] \"c\"\nmsgstr[2] \"d\"\n").kind,
+            PoErrorKind::PluralForms("msgstr[1] is missing or repeated".into())
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! The `Plural-Forms` header of gettext, like `nplurals=2; plural=(n != 1);`, and how its forms
+//! relate to CLDR plural categories.
+//!
+//! See: https://www.gnu.org/software/gettext/manual/html_node/Plural-forms.html
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::data_model::elements::{Literal, VariantKey};
+use crate::format::plural::{self, PluralCategory, PluralOperands, PluralRuleType};
+
+/// The numbers the forms are tried with. Plural rules repeat every 100 numbers, except for the
+/// first ones.
+const SAMPLES: u64 = 1000;
+
+/// The most forms a language needs, which are the six CLDR plural categories
+const MAX_FORMS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluralForms {
+    count: usize,
+    plural: Expr,
+}
+
+/// The C expression that selects a form
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    N,
+    Number(u64),
+    Not(Box<Self>),
+    Binary(Box<Self>, &'static str, Box<Self>),
+    Conditional(Box<Self>, Box<Self>, Box<Self>),
+}
+
+impl PluralForms {
+    /// Parses the value of the header
+    ///
+    /// # Errors
+    /// Returns the reason if `nplurals` is missing or not between 1 and 6, or `plural` is not a
+    /// valid expression.
+    pub fn parse(header: &str) -> Result<Self, String> {
+        let mut count = None;
+        let mut plural = None;
+        for part in header.split(';') {
+            let Some((name, value)) = part.split_once('=') else {
+                continue;
+            };
+            match name.trim() {
+                "nplurals" => count = Some(value.trim()),
+                "plural" => plural = Some(value),
+                _ => {}
+            }
+        }
+
+        let count = count.ok_or_else(|| "missing nplurals".to_string())?;
+        let count = count
+            .parse()
+            .ok()
+            .filter(|count| (1..=MAX_FORMS).contains(count))
+            .ok_or_else(|| format!("nplurals must be between 1 and {MAX_FORMS}, found {count}"))?;
+        let mut parser = Parser {
+            source: plural
+                .ok_or_else(|| "missing plural".to_string())?
+                .as_bytes(),
+            position: 0,
+        };
+        let plural = parser.conditional();
+        parser.blank();
+        match plural {
+            Some(plural) if parser.position == parser.source.len() => Ok(Self { count, plural }),
+            _ => Err("invalid plural expression".into()),
+        }
+    }
+
+    /// The header for a language, with the forms that match its CLDR categories
+    pub fn for_language(language: &str) -> &'static str {
+        match language {
+            "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "my" | "lo" | "km" | "yue" => "nplurals=1; plural=0;",
+            "fr" | "pt" | "hi" | "bn" | "fa" | "gu" | "kn" | "mr" | "zu" => "nplurals=2; plural=(n > 1);",
+            "ru" | "uk" | "be" => {
+                "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<12 || n%100>14) ? 1 : 2);"
+            }
+            "pl" => "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<12 || n%100>14) ? 1 : 2);",
+            "cs" | "sk" => "nplurals=3; plural=(n==1 ? 0 : n>=2 && n<=4 ? 1 : 2);",
+            "ar" => {
+                "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5);"
+            }
+            "he" => "nplurals=3; plural=(n==1 ? 0 : n==2 ? 1 : 2);",
+            _ => "nplurals=2; plural=(n != 1);",
+        }
+    }
+
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The form for `n`
+    pub fn index(&self, n: u64) -> usize {
+        usize::try_from(self.plural.evaluate(n)).unwrap_or(usize::MAX)
+    }
+
+    /// The smallest number that selects each form
+    ///
+    /// # Errors
+    /// Returns the reason if a form is never selected, or the expression selects a form that
+    /// doesn't exist.
+    pub fn samples(&self) -> Result<Vec<u64>, String> {
+        Ok(self.numbers()?.iter().map(|numbers| numbers[0]).collect())
+    }
+
+    /// The variant key of each form: the CLDR category of `language` that the form stands for, or
+    /// the number if the form is only used for one. The form for `other` gets the catch-all key.
+    ///
+    /// # Errors
+    /// Returns the reason if the forms don't line up with the categories of the language.
+    pub fn keys(&self, language: &str) -> Result<Vec<VariantKey>, String> {
+        let numbers = self.numbers()?;
+        let categories: Vec<Vec<PluralCategory>> = numbers
+            .iter()
+            .map(|numbers| {
+                let mut categories = Vec::new();
+                for n in numbers {
+                    let category = plural::category(
+                        language,
+                        PluralRuleType::Cardinal,
+                        &PluralOperands::from_integer(*n),
+                    );
+                    if !categories.contains(&category) {
+                        categories.push(category);
+                    }
+                }
+                categories
+            })
+            .collect();
+
+        // Forms for a single number that shares its category with other forms, like `n==0`, are
+        // selected by the number
+        let exact: Vec<bool> = (0..self.count)
+            .map(|index| {
+                numbers[index].len() == 1
+                    && (0..self.count).any(|other| {
+                        other != index && categories[other].contains(&categories[index][0])
+                    })
+            })
+            .collect();
+
+        (0..self.count)
+            .map(|index| {
+                if exact[index] {
+                    return Ok(VariantKey::Literal(Literal {
+                        value: numbers[index][0].to_string(),
+                    }));
+                }
+                let shared = (0..self.count).any(|other| {
+                    other != index
+                        && !exact[other]
+                        && categories[other]
+                            .iter()
+                            .any(|c| categories[index].contains(c))
+                });
+                match categories[index].as_slice() {
+                    [PluralCategory::Other] if !shared => Ok(VariantKey::Catchall),
+                    [category] if !shared => Ok(VariantKey::Literal(Literal {
+                        value: category.as_str().into(),
+                    })),
+                    _ => Err(format!(
+                        "form {index} doesn't match a plural category of {language}"
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    /// The sample numbers that select each form
+    fn numbers(&self) -> Result<Vec<Vec<u64>>, String> {
+        let mut numbers = vec![Vec::new(); self.count];
+        for n in 0..SAMPLES {
+            let index = self.index(n);
+            numbers
+                .get_mut(index)
+                .ok_or_else(|| {
+                    format!(
+                        "form {index} is selected for {n}, but there are only {}",
+                        self.count
+                    )
+                })?
+                .push(n);
+        }
+        if let Some(index) = numbers.iter().position(Vec::is_empty) {
+            return Err(format!("form {index} is never selected"));
+        }
+        Ok(numbers)
+    }
+}
+
+impl Expr {
+    fn evaluate(&self, n: u64) -> u64 {
+        match self {
+            Self::N => n,
+            Self::Number(number) => *number,
+            Self::Not(operand) => u64::from(operand.evaluate(n) == 0),
+            Self::Conditional(condition, then, otherwise) => {
+                if condition.evaluate(n) == 0 {
+                    otherwise.evaluate(n)
+                } else {
+                    then.evaluate(n)
+                }
+            }
+            Self::Binary(left, operator, right) => {
+                let (left, right) = (left.evaluate(n), right.evaluate(n));
+                match *operator {
+                    "||" => u64::from(left != 0 || right != 0),
+                    "&&" => u64::from(left != 0 && right != 0),
+                    "==" => u64::from(left == right),
+                    "!=" => u64::from(left != right),
+                    "<" => u64::from(left < right),
+                    ">" => u64::from(left > right),
+                    "<=" => u64::from(left <= right),
+                    ">=" => u64::from(left >= right),
+                    "+" => left.wrapping_add(right),
+                    "-" => left.wrapping_sub(right),
+                    "*" => left.wrapping_mul(right),
+                    "/" => left.checked_div(right).unwrap_or(0),
+                    _ => left.checked_rem(right).unwrap_or(0),
+                }
+            }
+        }
+    }
+}
+
+/// The binary operators from the lowest to the highest precedence
+const PRECEDENCE: [&[&str]; 6] = [
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<=", ">=", "<", ">"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+struct Parser<'s> {
+    source: &'s [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn blank(&mut self) {
+        while self
+            .source
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.blank();
+        if self.source[self.position..].starts_with(token.as_bytes()) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn conditional(&mut self) -> Option<Expr> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Some(condition);
+        }
+        let then = self.conditional()?;
+        if !self.eat(":") {
+            return None;
+        }
+        let otherwise = self.conditional()?;
+        Some(Expr::Conditional(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    fn binary(&mut self, level: usize) -> Option<Expr> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(operator) = PRECEDENCE[level].iter().find(|operator| self.eat(operator)) {
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(Box::new(left), operator, Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.eat("!") {
+            return Some(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expression = self.conditional()?;
+            return self.eat(")").then_some(expression);
+        }
+        if self.eat("n") {
+            return Some(Expr::N);
+        }
+        let start = self.position;
+        while self
+            .source
+            .get(self.position)
+            .is_some_and(u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        core::str::from_utf8(&self.source[start..self.position])
+            .ok()?
+            .parse()
+            .ok()
+            .map(Expr::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(header: &str, language: &str) -> Vec<String> {
+        let forms = PluralForms::parse(header).expect("valid header");
+        forms
+            .keys(language)
+            .expect("forms match the categories")
+            .into_iter()
+            .map(|key| match key {
+                VariantKey::Literal(literal) => literal.value,
+                VariantKey::Catchall => "*".into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_evaluates_plural_expressions() {
+        let forms = PluralForms::parse(PluralForms::for_language("ru")).expect("valid header");
+        assert_eq!(forms.count(), 3);
+        assert_eq!(
+            [1, 21, 2, 14, 5, 111].map(|n| forms.index(n)),
+            [0, 0, 1, 2, 2, 2]
+        );
+        assert_eq!(forms.samples(), Ok(vec![1, 2, 0]));
+
+        assert_eq!(
+            PluralForms::parse("nplurals=2; plural=n != 1 ?"),
+            Err("invalid plural expression".into())
+        );
+        assert_eq!(
+            PluralForms::parse("nplurals=1000000000; plural=0;"),
+            Err("nplurals must be between 1 and 6, found 1000000000".into())
+        );
+        assert!(PluralForms::parse("nplurals=0; plural=0;").is_err());
+        assert!(PluralForms::parse("plural=0;").is_err());
+        let broken = PluralForms::parse("nplurals=2; plural=n;").expect("valid header");
+        assert_eq!(
+            broken.samples(),
+            Err("form 2 is selected for 2, but there are only 2".into())
+        );
+    }
+
+    #[test]
+    fn it_derives_plural_categories() {
+        assert_eq!(keys("nplurals=2; plural=(n != 1);", "de"), ["one", "*"]);
+        assert_eq!(keys(PluralForms::for_language("fr"), "fr"), ["one", "*"]);
+        assert_eq!(
+            keys(PluralForms::for_language("pl"), "pl"),
+            ["one", "few", "many"]
+        );
+        assert_eq!(
+            keys(PluralForms::for_language("ar"), "ar"),
+            ["zero", "one", "two", "few", "many", "*"]
+        );
+        assert_eq!(keys("nplurals=1; plural=0;", "ja"), ["*"]);
+        // A form of its own for zero, which is `other` in English
+        assert_eq!(
+            keys("nplurals=3; plural=(n==0 ? 0 : n==1 ? 1 : 2);", "en"),
+            ["0", "one", "*"]
+        );
+        assert!(PluralForms::parse("nplurals=2; plural=(n > 1);")
+            .expect("valid header")
+            .keys("en")
+            .is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::convert::scope::{Scope, Unresolvable, Value};
+use crate::convert::tree::{self, Tree};
+use crate::data_model::elements::{
+    Expression, FunctionAnnotation, Message, OptionValue, PatternElement,
+};
+use crate::parser::is_number_literal;
+
+/// Converts a message to MessageFormat 1.
+///
+/// Declarations are resolved into the placeholders and selectors that use them, and selectors
+/// become nested `plural`, `selectordinal` and `select` arguments.
+///
+/// # Errors
+/// Returns an error naming the first part of the message that MessageFormat 1 can't express, like
+/// markup or a custom function.
+pub fn serialize(message: &Message) -> Result<String, ExportError> {
+    let declarations = match message {
+        Message::Pattern(message) => &message.declarations,
+        Message::Select(message) => &message.declarations,
+    };
+    let mut writer = Writer {
+        scope: Scope::new(declarations).map_err(|(node, reason)| unresolvable(node, reason))?,
+        out: String::new(),
+    };
+    match message {
+        Message::Pattern(message) => writer.pattern(&message.pattern, None)?,
+        Message::Select(message) => {
+            let selectors = message
+                .selectors
+                .iter()
+                .map(|selector| writer.selector(selector))
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.tree(&tree::nest(message), &selectors, None)?;
+        }
+    }
+    Ok(writer.out)
+}
+
+/// A part of a message that can't be expressed in MessageFormat 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportError {
+    /// The part of the message, in MessageFormat 2 syntax
+    pub node: String,
+    pub kind: ExportErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportErrorKind {
+    Markup,
+    /// A function other than `:string`, `:number`, `:integer`, `:date`, `:time` and `:datetime`
+    Function {
+        name: String,
+    },
+    /// An option that has no argument style, or a variable option value
+    Option {
+        name: String,
+    },
+    /// Reserved or private-use syntax
+    Unsupported,
+    /// A literal that is formatted with a function, or a function without an operand
+    Operand,
+    /// A selector that is not a `:number`, `:integer` or `:string` variable
+    Selector,
+    /// A variable name that isn't a valid argument name, like `user-name`
+    Name,
+    /// A variant key that isn't a plural category or a select keyword
+    Key,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.node)?;
+        match &self.kind {
+            ExportErrorKind::Markup => f.write_str("markup has no MessageFormat 1 equivalent"),
+            ExportErrorKind::Function { name } => {
+                write!(f, "the function :{name} has no MessageFormat 1 equivalent")
+            }
+            ExportErrorKind::Option { name } => {
+                write!(f, "the option {name} has no MessageFormat 1 equivalent")
+            }
+            ExportErrorKind::Unsupported => {
+                f.write_str("reserved syntax has no MessageFormat 1 equivalent")
+            }
+            ExportErrorKind::Operand => f.write_str("MessageFormat 1 can only format variables"),
+            ExportErrorKind::Selector => {
+                f.write_str("MessageFormat 1 can only select on :number, :integer and :string")
+            }
+            ExportErrorKind::Name => f.write_str("not a valid MessageFormat 1 argument name"),
+            ExportErrorKind::Key => {
+                f.write_str("not a valid MessageFormat 1 plural category or select keyword")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ExportError {}
+
+fn error(node: &impl ToString, kind: ExportErrorKind) -> ExportError {
+    ExportError {
+        node: node.to_string(),
+        kind,
+    }
+}
+
+const fn unresolvable(node: String, reason: Unresolvable) -> ExportError {
+    let kind = match reason {
+        Unresolvable::Operand => ExportErrorKind::Operand,
+        Unresolvable::Unsupported => ExportErrorKind::Unsupported,
+    };
+    ExportError { node, kind }
+}
+
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+struct Selector {
+    name: String,
+    kind: &'static str,
+    /// The variable `#` stands for in the cases of a plural
+    pound: Option<String>,
+}
+
+struct Writer {
+    scope: Scope,
+    out: String,
+}
+
+impl Writer {
+    fn resolve(&self, expression: &Expression) -> Result<Value, ExportError> {
+        self.scope
+            .resolve(expression)
+            .map_err(|reason| unresolvable(expression.to_string(), reason))
+    }
+
+    fn selector(&self, expression: &Expression) -> Result<Selector, ExportError> {
+        let Value::Variable { name, annotation } = self.resolve(expression)? else {
+            return Err(error(expression, ExportErrorKind::Operand));
+        };
+        let annotation = annotation.ok_or_else(|| error(expression, ExportErrorKind::Selector))?;
+        let kind = match (
+            annotation.name.as_str(),
+            literal_option(&annotation, "select"),
+        ) {
+            ("number" | "integer", None | Some("plural")) => "plural",
+            ("number" | "integer", Some("ordinal")) => "selectordinal",
+            ("number" | "integer", Some("exact")) | ("string", _) => "select",
+            _ => return Err(error(expression, ExportErrorKind::Selector)),
+        };
+        Ok(Selector {
+            name: argument_name(&name).ok_or_else(|| error(expression, ExportErrorKind::Name))?,
+            kind,
+            pound: (kind != "select").then_some(name),
+        })
+    }
+
+    /// Writes nested selects as nested arguments. `pound` is the variable of the plural the
+    /// pattern is directly in.
+    fn tree(
+        &mut self,
+        tree: &Tree<'_>,
+        selectors: &[Selector],
+        pound: Option<&str>,
+    ) -> Result<(), ExportError> {
+        let (selector, cases, catchall) = match tree {
+            Tree::Pattern(pattern) => return self.pattern(pattern, pound),
+            Tree::Select {
+                selector,
+                cases,
+                catchall,
+            } => (&selectors[*selector], cases, catchall),
+        };
+
+        self.out
+            .push_str(&format!("{{{}, {},", selector.name, selector.kind));
+        let plural = selector.kind != "select";
+        // On a plural, the `other` key is the `other` category, which MessageFormat 1 also uses as
+        // its catch-all
+        let other = cases
+            .iter()
+            .find(|(key, _)| plural && key.value == "other")
+            .map(|(_, tree)| tree);
+        for (key, tree) in cases {
+            let valid = if plural {
+                PLURAL_CATEGORIES.contains(&key.value.as_str()) || is_number_literal(&key.value)
+            } else {
+                key.value != "other" && key.value.chars().all(|c| c.is_alphanumeric() || c == '_')
+            };
+            if !valid {
+                return Err(error(key, ExportErrorKind::Key));
+            }
+            if key.value == "other" {
+                continue;
+            }
+            let exact = if plural && is_number_literal(&key.value) {
+                "="
+            } else {
+                ""
+            };
+            self.out.push_str(&format!(" {exact}{} {{", key.value));
+            self.tree(tree, selectors, selector.pound.as_deref())?;
+            self.out.push('}');
+        }
+        if let Some(other) = other.filter(|other| *other != catchall.as_ref()) {
+            // The categories without a case of their own still select the catch-all
+            for category in PLURAL_CATEGORIES {
+                if category != "other" && !cases.iter().any(|(key, _)| key.value == category) {
+                    self.out.push_str(&format!(" {category} {{"));
+                    self.tree(catchall, selectors, selector.pound.as_deref())?;
+                    self.out.push('}');
+                }
+            }
+            self.out.push_str(" other {");
+            self.tree(other, selectors, selector.pound.as_deref())?;
+        } else {
+            self.out.push_str(" other {");
+            self.tree(catchall, selectors, selector.pound.as_deref())?;
+        }
+        self.out.push_str("}}");
+        Ok(())
+    }
+
+    fn pattern(
+        &mut self,
+        pattern: &[PatternElement],
+        pound: Option<&str>,
+    ) -> Result<(), ExportError> {
+        for element in pattern {
+            match element {
+                PatternElement::Literal(text) => write_text(&mut self.out, text, pound.is_some()),
+                PatternElement::Expression(expression) => match self.resolve(expression)? {
+                    Value::Text(text) => write_text(&mut self.out, &text, pound.is_some()),
+                    Value::Variable { name, annotation } => {
+                        let argument = argument(expression, &name, annotation.as_ref(), pound)?;
+                        self.out.push_str(&argument);
+                    }
+                },
+                PatternElement::Markup(markup) => {
+                    return Err(error(markup, ExportErrorKind::Markup))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The argument for a formatted variable, like `{n, number, integer}`
+fn argument(
+    expression: &Expression,
+    name: &str,
+    annotation: Option<&FunctionAnnotation>,
+    pound: Option<&str>,
+) -> Result<String, ExportError> {
+    let argument = argument_name(name).ok_or_else(|| error(expression, ExportErrorKind::Name))?;
+    let Some(function) = annotation else {
+        return Ok(format!("{{{argument}}}"));
+    };
+
+    // `select` only changes how a number is selected on, not how it is formatted
+    let mut options = Vec::new();
+    for option in function
+        .options
+        .iter()
+        .filter(|option| option.name != "select")
+    {
+        match &option.value {
+            OptionValue::Literal(value) => {
+                options.push((option.name.as_str(), value.value.as_str()))
+            }
+            OptionValue::Variable(_) => {
+                return Err(error(
+                    expression,
+                    ExportErrorKind::Option {
+                        name: option.name.clone(),
+                    },
+                ))
+            }
+        }
+    }
+    let unsupported = |name: &str| error(expression, ExportErrorKind::Option { name: name.into() });
+    let style = |name: &str| match options.iter().find(|(option, _)| *option == name) {
+        Some((_, style @ ("short" | "medium" | "long" | "full"))) => Ok(Some(*style)),
+        Some(_) => Err(unsupported(name)),
+        None => Ok(None),
+    };
+    let only = |allowed: &[&str]| match options.iter().find(|(name, _)| !allowed.contains(name)) {
+        Some((name, _)) => Err(unsupported(name)),
+        None => Ok(()),
+    };
+
+    match function.name.as_str() {
+        "string" => only(&[]).map(|()| format!("{{{argument}}}")),
+        "number" if options.is_empty() && pound == Some(name) => Ok("#".into()),
+        "number" => match options.as_slice() {
+            [] => Ok(format!("{{{argument}, number}}")),
+            [("style", "percent")] => Ok(format!("{{{argument}, number, percent}}")),
+            [(name, _), ..] => Err(unsupported(name)),
+        },
+        "integer" => only(&[]).map(|()| format!("{{{argument}, number, integer}}")),
+        kind @ ("date" | "time") => {
+            only(&["style"])?;
+            let style = style("style")?
+                .map(|style| format!(", {style}"))
+                .unwrap_or_default();
+            Ok(format!("{{{argument}, {kind}{style}}}"))
+        }
+        "datetime" => {
+            only(&["dateStyle", "timeStyle"])?;
+            Ok(match (style("dateStyle")?, style("timeStyle")?) {
+                (Some(style), None) => format!("{{{argument}, date, {style}}}"),
+                (None, Some(style)) => format!("{{{argument}, time, {style}}}"),
+                (date, time) => {
+                    // MessageFormat 1 has no style for both, but ICU accepts skeletons
+                    let date = match date.unwrap_or("medium") {
+                        "short" => "yMd",
+                        "medium" => "yMMMd",
+                        "long" => "yMMMMd",
+                        _ => "yMMMMEEEEd",
+                    };
+                    let time = match time.unwrap_or("short") {
+                        "short" => "jmm",
+                        "medium" => "jmmss",
+                        "long" => "jmmssz",
+                        _ => "jmmsszzzz",
+                    };
+                    format!("{{{argument}, date, ::{date}{time}}}")
+                }
+            })
+        }
+        name => Err(error(
+            expression,
+            ExportErrorKind::Function { name: name.into() },
+        )),
+    }
+}
+
+fn literal_option<'a>(function: &'a FunctionAnnotation, name: &str) -> Option<&'a str> {
+    function
+        .options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.value {
+            OptionValue::Literal(literal) => Some(literal.value.as_str()),
+            OptionValue::Variable(_) => None,
+        })
+}
+
+/// The argument name for a variable. `$arg0` becomes the numbered argument `0` again.
+fn argument_name(name: &str) -> Option<String> {
+    match name.strip_prefix("arg") {
+        Some(number) if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) => {
+            Some(number.into())
+        }
+        _ => name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_')
+            .then(|| name.into()),
+    }
+}
+
+/// Writes text with the syntax characters quoted: `{`, `}` and in plurals `#`
+fn write_text(out: &mut String, text: &str, in_plural: bool) {
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '\'' => out.push_str("''"),
+            '{' | '}' => {
+                if !quoted {
+                    out.push('\'');
+                    quoted = true;
+                }
+                out.push(c);
+            }
+            '#' if in_plural => {
+                if !quoted {
+                    out.push('\'');
+                    quoted = true;
+                }
+                out.push(c);
+            }
+            _ => {
+                if quoted {
+                    out.push('\'');
+                    quoted = false;
+                }
+                out.push(c);
+            }
+        }
+    }
+    if quoted {
+        out.push('\'');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::mf1::parse;
+
+    fn export(source: &str) -> Result<String, ExportError> {
+        serialize(&source.parse().expect("valid message"))
+    }
+
+    #[test]
+    fn it_exports_nested_selects() {
+        let source = "{gender, select, female {{n, plural, one {She has # cat} other {She has # cats}}} other {{n, plural, one {They have # cat} other {They have # cats}}}}";
+        assert_eq!(
+            serialize(&parse(source).expect("valid message")).as_deref(),
+            Ok(source)
+        );
+
+        let source = "{n, plural, =0 {none '{'#'}' ''} other {# {n, number, percent}}}";
+        assert_eq!(
+            serialize(&parse(source).expect("valid message")).as_deref(),
+            Ok(source)
+        );
+
+        assert_eq!(
+            export(".input {$a :string}\n.input {$n :number}\n.match {$a} {$n}\nx one {{A}}\nx * {{B}}\n* * {{C}}").as_deref(),
+            Ok("{a, select, x {{n, plural, one {A} other {B}}} other {C}}")
+        );
+        assert_eq!(
+            export(".input {$n :number select=ordinal}\n.match {$n}\n1 {{first}}\ntwo {{{$n}nd}}\n* {{{$n :number}th}}").as_deref(),
+            Ok("{n, selectordinal, =1 {first} two {#nd} other {#th}}")
+        );
+    }
+
+    #[test]
+    fn it_merges_the_other_category_with_the_catch_all() {
+        assert_eq!(
+            export(".input {$n :number}\n.match {$n}\none {{{$n} item}}\nother {{{$n} items}}\n* {{{$n} items}}").as_deref(),
+            Ok("{n, plural, one {# item} other {# items}}")
+        );
+        assert_eq!(
+            export(".input {$n :number}\n.match {$n}\none {{One}}\nother {{Other}}\n* {{Rest}}").as_deref(),
+            Ok("{n, plural, one {One} zero {Rest} two {Rest} few {Rest} many {Rest} other {Other}}")
+        );
+        assert_eq!(
+            export(".input {$n :number}\n.match {$n}\n-1.5e2 {{A}}\n* {{B}}").as_deref(),
+            Ok("{n, plural, =-1.5e2 {A} other {B}}")
+        );
+        for key in [".", "1.2.3", "01"] {
+            let source =
+                format!(".input {{$n :number}}\n.match {{$n}}\n|{key}| {{{{A}}}}\n* {{{{B}}}}");
+            assert_eq!(
+                export(&source).map_err(|error| error.kind),
+                Err(ExportErrorKind::Key)
+            );
+        }
+        assert_eq!(
+            export(".input {$s :string}\n.match {$s}\nother {{A}}\n* {{B}}")
+                .map_err(|error| error.kind),
+            Err(ExportErrorKind::Key)
+        );
+    }
+
+    #[test]
+    fn it_exports_formatted_arguments() {
+        assert_eq!(
+            export(".input {$n :number}\n.local $d = {$when :date style=long}\n{{{$n} on {$d} at {$when :time}, {$arg0} {|it's|}}}").as_deref(),
+            Ok("{n, number} on {when, date, long} at {when, time}, {0} it''s")
+        );
+        assert_eq!(
+            export("{$t :datetime} {$t :datetime timeStyle=short}").as_deref(),
+            Ok("{t, date, ::yMMMdjmm} {t, time, short}")
+        );
+        assert_eq!(
+            export(".local $i = {$n :integer}\n{{{$i}}}").as_deref(),
+            Ok("{n, number, integer}")
+        );
+    }
+
+    #[test]
+    fn it_names_what_cant_be_exported() {
+        let error = export("Hi {#b}there{/b}").expect_err("markup");
+        assert_eq!(
+            error.to_string(),
+            "{#b}: markup has no MessageFormat 1 equivalent"
+        );
+
+        let error = export("{$x :shout}").expect_err("custom function");
+        assert_eq!(
+            error.kind,
+            ExportErrorKind::Function {
+                name: "shout".into()
+            }
+        );
+        assert_eq!(error.node, "{$x :shout}");
+
+        let error = export("{$n :number minimumFractionDigits=2}").expect_err("option");
+        assert_eq!(
+            error.kind,
+            ExportErrorKind::Option {
+                name: "minimumFractionDigits".into()
+            }
+        );
+
+        assert_eq!(
+            export("{$user-name}").map_err(|error| error.kind),
+            Err(ExportErrorKind::Name)
+        );
+        assert_eq!(
+            export("{|5| :number}").map_err(|error| error.kind),
+            Err(ExportErrorKind::Operand)
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! Converts messages between MessageFormat 2 and ICU MessageFormat 1, the syntax of ICU4C, ICU4J
+//! and most JavaScript libraries before MessageFormat 2. [`parse`] converts a MessageFormat 1
+//! message:
+//!
+//! ```text
+//! {count, plural, =0 {No items} one {# item} other {# items}}
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! .match {$count :number}
+//! 0 {{No items}}
+//! one {{{$count :number} item}}
+//! * {{{$count :number} items}}
+//! ```
+//!
+//! - `plural` and `selectordinal` arguments become `:number` selectors, and `select` arguments
+//!   become `:string` selectors. The `other` case becomes the catch-all key `*`.
+//! - Nested arguments become one message with several selectors, which has a variant for each
+//!   combination of cases that can be reached.
+//! - `#` becomes the number of the enclosing `plural` or `selectordinal`: `{$count :number}`.
+//! - `number`, `date` and `time` arguments become `:number`, `:integer`, `:date` and `:time`
+//!   expressions.
+//! - Numbered arguments like `{0}` become variables like `$arg0`, names can't start with a digit.
+//! - Apostrophes quote syntax characters like in ICU: `'{'` is a literal `{`, and `''` is `'`.
+//!
+//! [`serialize`] goes the other way, for applications that still use MessageFormat 1.
+//!
+//! See: https://unicode-org.github.io/icu/userguide/format_parse/messages/
+
+mod export;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::convert::tree::{self, function, variable, Case, ExpansionError, Node, Select};
+use crate::data_model::elements::{Expression, Message};
+use crate::data_model::errors::SyntaxError;
+use crate::parser::is_name;
+
+pub use export::{serialize, ExportError, ExportErrorKind};
+
+/// Converts a MessageFormat 1 message.
+///
+/// # Errors
+/// Returns an error if the message is not well-formed, or if it uses a feature that has no
+/// MessageFormat 2 equivalent, like `offset:` or `spellout` arguments.
+pub fn parse(source: &str) -> Result<Message, SyntaxError> {
+    let mut parser = Parser {
+        source,
+        position: 0,
+        depth: 0,
+    };
+    let nodes = parser.message(None)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unmatched `}`"));
+    }
+    tree::message(&nodes).map_err(|error| parser.error(error.to_string()))
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    /// The byte offset of the next character
+    position: usize,
+    /// How many selects the parser is in
+    depth: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> SyntaxError {
+        SyntaxError {
+            offset: self.position,
+            reason: reason.into(),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SyntaxError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Takes characters up to whitespace or one of `stop`
+    fn word(&mut self, stop: &[char]) -> &'s str {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !stop.contains(&c))
+        {
+            self.bump();
+        }
+        &self.source[start..self.position]
+    }
+
+    /// Parses text and arguments up to a `}` or the end. `pound` is the number of the enclosing
+    /// plural, which `#` stands for.
+    fn message(&mut self, pound: Option<&Expression>) -> Result<Vec<Node>, SyntaxError> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match (c, pound) {
+                ('}', _) => break,
+                ('{', _) => {
+                    flush(&mut text, &mut nodes);
+                    nodes.push(self.argument()?);
+                }
+                ('#', Some(pound)) => {
+                    self.bump();
+                    flush(&mut text, &mut nodes);
+                    nodes.push(Node::Expression(pound.clone()));
+                }
+                ('\'', _) => self.apostrophe(pound.is_some(), &mut text),
+                _ => {
+                    self.bump();
+                    text.push(c);
+                }
+            }
+        }
+        flush(&mut text, &mut nodes);
+        Ok(nodes)
+    }
+
+    /// `''` is an apostrophe, and an apostrophe before a syntax character starts quoted text
+    fn apostrophe(&mut self, in_plural: bool, text: &mut String) {
+        self.bump();
+        match self.peek() {
+            Some('\'') => {
+                self.bump();
+                text.push('\'');
+            }
+            Some('{' | '}' | '|') => self.quoted(text),
+            Some('#') if in_plural => self.quoted(text),
+            _ => text.push('\''),
+        }
+    }
+
+    /// Quoted text runs to the next single apostrophe, or to the end
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.bump() {
+            if c != '\'' {
+                text.push(c);
+            } else if self.eat('\'') {
+                text.push('\'');
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn argument(&mut self) -> Result<Node, SyntaxError> {
+        self.expect('{')?;
+        self.whitespace();
+        let name = self.argument_name()?;
+        self.whitespace();
+        if self.eat('}') {
+            return Ok(Node::Expression(variable(name, None)));
+        }
+        if !self.eat(',') {
+            return Err(self.error("expected `,` or `}`"));
+        }
+
+        self.whitespace();
+        let kind_start = self.position;
+        let kind = self.word(&[',', '{', '}']);
+        self.whitespace();
+        match kind {
+            "plural" | "selectordinal" => {
+                self.expect(',')?;
+                self.whitespace();
+                if self.rest().starts_with("offset:") {
+                    return Err(self.error("`offset:` has no MessageFormat 2 equivalent"));
+                }
+                let options: &[(&str, &str)] = if kind == "plural" {
+                    &[]
+                } else {
+                    &[("select", "ordinal")]
+                };
+                let selector = variable(name.clone(), Some(function("number", options)));
+                let pound = variable(name, Some(function("number", &[])));
+                let cases = self.cases(Some(&pound), true)?;
+                Ok(Node::Select(Select { selector, cases }))
+            }
+            "select" => {
+                self.expect(',')?;
+                let selector = variable(name, Some(function("string", &[])));
+                let cases = self.cases(None, false)?;
+                Ok(Node::Select(Select { selector, cases }))
+            }
+            "number" | "date" | "time" => {
+                let style = if self.eat(',') {
+                    self.whitespace();
+                    let start = self.position;
+                    while self.peek().is_some_and(|c| c != '}') {
+                        self.bump();
+                    }
+                    Some((start, self.source[start..self.position].trim_end()))
+                } else {
+                    None
+                };
+                self.expect('}')?;
+
+                let annotation = match (kind, style) {
+                    ("number", None) => function("number", &[]),
+                    ("number", Some((_, "integer"))) => function("integer", &[]),
+                    ("number", Some((_, "percent"))) => function("number", &[("style", "percent")]),
+                    (_, None) => function(kind, &[]),
+                    (
+                        "date" | "time",
+                        Some((_, style @ ("short" | "medium" | "long" | "full"))),
+                    ) => function(kind, &[("style", style)]),
+                    (_, Some((offset, style))) => {
+                        return Err(SyntaxError {
+                            offset,
+                            reason: format!(
+                                "the {kind} style `{style}` has no MessageFormat 2 equivalent"
+                            ),
+                        })
+                    }
+                };
+                Ok(Node::Expression(variable(name, Some(annotation))))
+            }
+            "" => Err(self.error("expected an argument type")),
+            _ => Err(SyntaxError {
+                offset: kind_start,
+                reason: format!("`{kind}` arguments have no MessageFormat 2 equivalent"),
+            }),
+        }
+    }
+
+    fn argument_name(&mut self) -> Result<String, SyntaxError> {
+        let start = self.position;
+        let name = self.word(&[',', '{', '}']);
+        if name.is_empty() {
+            Err(self.error("expected an argument name"))
+        } else if name.chars().all(|c| c.is_ascii_digit()) {
+            Ok(format!("arg{name}"))
+        } else if is_name(name) {
+            Ok(name.into())
+        } else {
+            Err(SyntaxError {
+                offset: start,
+                reason: format!("`{name}` is not a valid variable name"),
+            })
+        }
+    }
+
+    /// Parses the cases of a select up to its closing `}`. Plurals also have `=n` cases.
+    fn cases(
+        &mut self,
+        pound: Option<&Expression>,
+        plural: bool,
+    ) -> Result<Vec<Case>, SyntaxError> {
+        let mut cases: Vec<Case> = Vec::new();
+        loop {
+            self.whitespace();
+            if self.peek().is_none() {
+                return Err(self.error("expected `}`"));
+            }
+            if self.eat('}') {
+                break;
+            }
+
+            let start = self.position;
+            let exact = plural && self.eat('=');
+            let key = self.word(&['{', '}']);
+            if key.is_empty() {
+                return Err(self.error(if exact {
+                    "expected a number"
+                } else {
+                    "expected a case keyword"
+                }));
+            }
+            let key = (exact || key != "other").then(|| String::from(key));
+            if cases.iter().any(|(case, _)| *case == key) {
+                return Err(SyntaxError {
+                    offset: start,
+                    reason: format!("duplicate case `{}`", &self.source[start..self.position]),
+                });
+            }
+
+            self.whitespace();
+            self.expect('{')?;
+            // Expanding checks the depth too, but deeper messages would overflow the stack first
+            if self.depth == tree::MAX_DEPTH {
+                return Err(self.error(ExpansionError::Depth.to_string()));
+            }
+            self.depth += 1;
+            let nodes = self.message(pound)?;
+            self.depth -= 1;
+            self.expect('}')?;
+            cases.push((key, nodes));
+        }
+
+        if cases.iter().any(|(case, _)| case.is_none()) {
+            Ok(cases)
+        } else {
+            Err(SyntaxError {
+                offset: self.position - 1,
+                reason: "expected an `other` case".into(),
+            })
+        }
+    }
+}
+
+fn flush(text: &mut String, nodes: &mut Vec<Node>) {
+    if !text.is_empty() {
+        nodes.push(Node::Text(core::mem::take(text)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+
+    fn convert(source: &str) -> String {
+        parse(source).expect("valid message").to_string()
+    }
+
+    #[test]
+    fn it_converts_arguments_and_quotes() {
+        assert_eq!(
+            convert("It''s '{quoted}' for {name} on {d, date, short} at {t,time} x{0, number, integer} {p,number,percent}"),
+            "It's \\{quoted\\} for {$name} on {$d :date style=short} at {$t :time} x{$arg0 :integer} {$p :number style=percent}"
+        );
+        assert_eq!(convert("# and don't"), "# and don't");
+    }
+
+    #[test]
+    fn it_converts_plurals_and_selects() {
+        assert_eq!(
+            convert("You have {count, plural, =0 {no items} one {# item} other {# items}}."),
+            ".match {$count :number}\n0 {{You have no items.}}\none {{You have {$count :number} item.}}\n* {{You have {$count :number} items.}}"
+        );
+        assert_eq!(
+            convert("{n, selectordinal, one {#st} two {#nd} few {#rd} other {'#'#th}}"),
+            ".match {$n :number select=ordinal}\none {{{$n :number}st}}\ntwo {{{$n :number}nd}}\nfew {{{$n :number}rd}}\n* {{#{$n :number}th}}"
+        );
+
+        let source = "{gender, select, female {{n, plural, one {She has # cat} other {She has # cats}}} other {{n, plural, one {They have # cat} other {They have # cats}}}}";
+        let message = parse(source).expect("valid message");
+        assert_eq!(
+            message.to_string(),
+            ".match {$gender :string} {$n :number}\nfemale one {{She has {$n :number} cat}}\nfemale * {{She has {$n :number} cats}}\n* one {{They have {$n :number} cat}}\n* * {{They have {$n :number} cats}}"
+        );
+
+        let mut catalog = Catalog::new("en".parse().expect("valid locale"));
+        catalog.insert("cats", message).expect("valid message");
+        assert_eq!(
+            catalog.format("cats", [("gender", "female"), ("n", "1")]),
+            Ok("She has 1 cat".into())
+        );
+        assert_eq!(
+            catalog.format("cats", [("gender", "male"), ("n", "3")]),
+            Ok("They have 3 cats".into())
+        );
+    }
+
+    #[test]
+    fn it_refines_the_catch_all_with_nested_cases() {
+        assert_eq!(
+            convert("{n, plural, =0 {none} other {{n, plural, one {one} other {many}}}}"),
+            ".match {$n :number}\n0 {{none}}\none {{one}}\n* {{many}}"
+        );
+        // The explicit case wins over the nested one, even after it
+        assert_eq!(
+            convert("{n, plural, other {{n, plural, one {B} other {C}}} one {A}}"),
+            ".match {$n :number}\none {{A}}\n* {{C}}"
+        );
+    }
+
+    #[test]
+    fn it_reports_unsupported_syntax() {
+        let error = |source| parse(source).expect_err("unsupported message");
+
+        assert_eq!(
+            error("{n, plural, offset:1 other {#}}").reason,
+            "`offset:` has no MessageFormat 2 equivalent"
+        );
+        assert_eq!(error("{n, spellout}").offset, 4);
+        assert_eq!(
+            error("{n, plural, one {#}}").reason,
+            "expected an `other` case"
+        );
+        assert_eq!(
+            error("{n, number, ::currency/EUR}").reason,
+            "the number style `::currency/EUR` has no MessageFormat 2 equivalent"
+        );
+        assert_eq!(error("a}").reason, "unmatched `}`");
+        assert_eq!(error("{n, select, a {x} other {y}").reason, "expected `}`");
+    }
+
+    #[test]
+    fn it_limits_the_expansion_of_selects() {
+        let nested = (0..20).fold(String::from("x"), |inner, i| {
+            format!("{{v{i}, select, a {{{inner}}} other {{y}}}}")
+        });
+        assert_eq!(
+            parse(&nested).expect_err("too deep").reason,
+            "selects are nested more than 16 levels deep"
+        );
+        let deep = "{a, select, other {".repeat(10_000);
+        assert_eq!(
+            parse(&deep).expect_err("too deep").reason,
+            "selects are nested more than 16 levels deep"
+        );
+
+        let sequence: String = (0..13)
+            .map(|i| format!("{{v{i}, select, a {{a}} other {{b}}}}"))
+            .collect();
+        assert_eq!(
+            parse(&sequence).expect_err("too many variants").reason,
+            "selects have more than 4096 combinations of cases"
+        );
+    }
+}
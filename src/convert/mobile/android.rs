@@ -0,0 +1,659 @@
+//! Android string resources, the `res/values/strings.xml` files of apps:
+//!
+//! ```xml
+//! <resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+//!     <!-- Shown on the home page -->
+//!     <string name="greeting">Hello <b><xliff:g id="name">%1$s</xliff:g></b>!</string>
+//!     <plurals name="items">
+//!         <item quantity="one"><xliff:g id="count">%1$d</xliff:g> item</item>
+//!         <item quantity="other"><xliff:g id="count">%1$d</xliff:g> items</item>
+//!     </plurals>
+//! </resources>
+//! ```
+//!
+//! - `<string>` elements become pattern messages, and `<plurals>` become messages that select on
+//!   the plural category of the first argument, or of `$count` if the items don't use it. The
+//!   `other` quantity becomes the catch-all key.
+//! - Placeholders in `<xliff:g>` elements become variables named by the `id`, the convention for
+//!   text that must not be translated. Other placeholders become `$arg1`, `$arg2` and so on. `%d`
+//!   becomes `:integer`, and `%.2f` becomes `:number` with two fraction digits.
+//! - HTML tags like `<b>` become markup, with their attributes as options.
+//! - Comments become the comment of the entry that follows them. A comment at the start of the
+//!   file that is followed by an empty line becomes the comment of the resource.
+//! - Other resources, like `<string-array>`, are left out.
+//!
+//! Whitespace is collapsed and escapes like `\'` and `\n` are resolved like Android does.
+//!
+//! See: https://developer.android.com/guide/topics/resources/string-resource
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::printf::{self, Argument, Arguments, Piece, ANDROID};
+use super::{
+    function, is_plural, resolve, xml_comment, ExportError, ExportErrorKind, ParseError,
+    ParseErrorKind, Problem,
+};
+use crate::convert::tree::{self, variable, Node, Select};
+use crate::convert::xml::{self, escape, Element};
+use crate::data_model::elements::{
+    self, Expression, Literal, Markup, MarkupKind, Message, OptionValue, PatternElement, VariantKey,
+};
+use crate::format::plural::PluralCategory;
+use crate::resource::{Entry, Resource};
+
+/// Reads the `<string>` and `<plurals>` resources of a file.
+///
+/// # Errors
+/// Returns an error if the file isn't well-formed, or a plural has an unknown quantity.
+pub fn parse(source: &str) -> Result<Resource, ParseError> {
+    let root = xml::parse(source).map_err(|(line, reason)| ParseError {
+        line: Some(line),
+        id: None,
+        kind: ParseErrorKind::Syntax(reason),
+    })?;
+    if root.local_name() != "resources" {
+        return Err(ParseError {
+            line: None,
+            id: None,
+            kind: ParseErrorKind::Invalid(format!("expected <resources>, found <{}>", root.name)),
+        });
+    }
+
+    let mut resource = Resource::new();
+    let mut comment: Option<String> = None;
+    for child in &root.children {
+        let element = match child {
+            xml::Node::Comment(text) => {
+                let text = text.trim();
+                comment = Some(
+                    comment
+                        .take()
+                        .map_or_else(|| text.into(), |comment| format!("{comment}\n{text}")),
+                );
+                continue;
+            }
+            xml::Node::Text(text) => {
+                let first = resource.comment.is_none() && resource.sections[0].entries.is_empty();
+                if first && text.matches('\n').count() > 1 {
+                    resource.comment = comment.take();
+                }
+                continue;
+            }
+            xml::Node::Element(element) => element,
+        };
+        let comment = comment.take();
+        if !matches!(element.local_name(), "string" | "plurals") {
+            continue;
+        }
+        let name = element.attribute("name").ok_or_else(|| ParseError {
+            line: None,
+            id: None,
+            kind: ParseErrorKind::Invalid(format!("<{}> without a name", element.name)),
+        })?;
+        let message = if element.local_name() == "string" {
+            tree::message(&Content::new(element).nodes(&BTreeMap::new()))
+                .map_err(|error| ParseErrorKind::TooComplex(error.to_string()))
+        } else {
+            plurals(element)
+        }
+        .map_err(|kind| ParseError {
+            line: None,
+            id: Some(name.into()),
+            kind,
+        })?;
+        resource.sections[0].entries.push(Entry {
+            key: name.into(),
+            comment,
+            message,
+        });
+    }
+    Ok(resource)
+}
+
+/// Writes the messages of a resource to a `strings.xml` file.
+///
+/// Messages that select on the plural category of one `:number` or `:integer` variable become `<plurals>`, and the variable is the
+/// first argument of their placeholders.
+///
+/// # Errors
+/// Returns an error for the first message that Android resources can't express, like one with
+/// several selectors or an id that isn't a resource name.
+pub fn serialize(resource: &Resource) -> Result<String, ExportError> {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources xmlns:xliff=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+    );
+    if let Some(comment) = &resource.comment {
+        out.push_str(&format!("    <!-- {} -->\n\n", xml_comment(comment)));
+    }
+    for (id, entry) in resource.entries() {
+        let error = |(node, kind): Problem| ExportError {
+            id: id.clone(),
+            node,
+            kind,
+        };
+        if !is_resource_name(&id) {
+            return Err(error((id.clone(), ExportErrorKind::Name)));
+        }
+        if let Some(comment) = &entry.comment {
+            out.push_str(&format!("    <!-- {} -->\n", xml_comment(comment)));
+        }
+        out.push_str(&resource_element(&id, &entry.message).map_err(error)?);
+    }
+    out.push_str("</resources>\n");
+    Ok(out)
+}
+
+fn is_resource_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.'))
+}
+
+fn plurals(element: &Element) -> Result<Message, ParseErrorKind> {
+    let items: Vec<(&str, Content)> = element
+        .elements("item")
+        .map(|item| {
+            (
+                item.attribute("quantity").unwrap_or_default(),
+                Content::new(item),
+            )
+        })
+        .collect();
+
+    // The quantity is usually passed as the first argument as well
+    let mut names = BTreeMap::new();
+    for (_, content) in &items {
+        names.extend(content.names());
+    }
+    let uses_first = items
+        .iter()
+        .any(|(_, content)| content.positions().contains(&1));
+    let name = match names.get(&1) {
+        Some(name) => name.clone(),
+        None if uses_first => "arg1".into(),
+        None => "count".into(),
+    };
+
+    let mut cases = Vec::new();
+    for (quantity, content) in &items {
+        let key = match PluralCategory::from_name(quantity) {
+            Some(PluralCategory::Other) => None,
+            Some(_) => Some((*quantity).into()),
+            None => return Err(ParseErrorKind::Quantity((*quantity).into())),
+        };
+        cases.push((key, content.nodes(&names)));
+    }
+    if !cases.iter().any(|(key, _)| key.is_none()) {
+        let (_, last) = items
+            .last()
+            .ok_or_else(|| ParseErrorKind::Invalid("<plurals> without items".into()))?;
+        cases.push((None, last.nodes(&names)));
+    }
+    tree::message(&[Node::Select(Select {
+        selector: variable(name, Some(tree::function("integer", &[]))),
+        cases,
+    })])
+    .map_err(|error| ParseErrorKind::TooComplex(error.to_string()))
+}
+
+/// A part of the content of a `<string>` or `<item>`, with whitespace and escapes resolved
+enum Part {
+    Text(String),
+    /// The start of an `<xliff:g>` element with its id
+    NameStart(String),
+    NameEnd,
+    Markup(Markup),
+}
+
+/// The content of a `<string>` or `<item>`
+struct Content {
+    parts: Vec<Part>,
+    /// Whether the text is in double quotes, where whitespace is kept
+    quoted: bool,
+    /// Whether whitespace was collapsed, which becomes a space before the next text
+    space: bool,
+    started: bool,
+}
+
+impl Content {
+    fn new(element: &Element) -> Self {
+        let mut content = Self {
+            parts: Vec::new(),
+            quoted: false,
+            space: false,
+            started: false,
+        };
+        content.element(element);
+        content
+    }
+
+    fn element(&mut self, element: &Element) {
+        for child in &element.children {
+            match child {
+                xml::Node::Text(text) => self.text(text),
+                xml::Node::Comment(_) => {}
+                xml::Node::Element(child) if child.local_name() == "g" => {
+                    self.parts.push(Part::NameStart(
+                        child.attribute("id").unwrap_or_default().into(),
+                    ));
+                    self.element(child);
+                    self.parts.push(Part::NameEnd);
+                }
+                xml::Node::Element(child) => {
+                    let options = child
+                        .attributes
+                        .iter()
+                        .map(|(name, value)| elements::Option {
+                            name: name.clone(),
+                            value: OptionValue::Literal(Literal {
+                                value: value.clone(),
+                            }),
+                        })
+                        .collect();
+                    let markup = |kind, options| Markup {
+                        kind,
+                        name: child.name.clone(),
+                        options,
+                        attributes: Vec::new(),
+                    };
+                    self.flush();
+                    self.started = true;
+                    if child.children.is_empty() {
+                        self.parts
+                            .push(Part::Markup(markup(MarkupKind::Standalone, options)));
+                    } else {
+                        self.parts
+                            .push(Part::Markup(markup(MarkupKind::Open, options)));
+                        self.element(child);
+                        self.flush();
+                        self.parts
+                            .push(Part::Markup(markup(MarkupKind::Close, Vec::new())));
+                    }
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => self.push('\n'),
+                    Some('t') => self.push('\t'),
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                        {
+                            self.push(c);
+                        }
+                    }
+                    // `\'`, `\"`, `\\`, `\@` and `\?`
+                    Some(c) => self.push(c),
+                    None => {}
+                },
+                '"' => self.quoted = !self.quoted,
+                c if c.is_whitespace() && !self.quoted => self.space = true,
+                c => self.push(c),
+            }
+        }
+    }
+
+    /// Writes a space for collapsed whitespace, unless it is at the start
+    fn flush(&mut self) {
+        if self.space && self.started {
+            self.push_raw(' ');
+        }
+        self.space = false;
+    }
+
+    fn push(&mut self, c: char) {
+        self.flush();
+        self.started = true;
+        self.push_raw(c);
+    }
+
+    fn push_raw(&mut self, c: char) {
+        if let Some(Part::Text(text)) = self.parts.last_mut() {
+            text.push(c);
+        } else {
+            self.parts.push(Part::Text(c.to_string()));
+        }
+    }
+
+    /// The placeholders in order, with their positions and the id of the `<xliff:g>` they are in
+    fn arguments(&self) -> Vec<(usize, Argument, Option<&str>)> {
+        let mut arguments = Vec::new();
+        let mut name = None;
+        let mut next = 0;
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => {
+                    for piece in printf::split(text) {
+                        if let Piece::Argument(argument) = piece {
+                            let position = argument.position.unwrap_or_else(|| {
+                                next += 1;
+                                next
+                            });
+                            arguments.push((position, argument, name));
+                        }
+                    }
+                }
+                Part::NameStart(id) => name = Some(id.as_str()),
+                Part::NameEnd => name = None,
+                Part::Markup(_) => {}
+            }
+        }
+        arguments
+    }
+
+    fn positions(&self) -> Vec<usize> {
+        self.arguments()
+            .into_iter()
+            .map(|(position, _, _)| position)
+            .collect()
+    }
+
+    /// The names of the placeholders in `<xliff:g>` elements by their position
+    fn names(&self) -> BTreeMap<usize, String> {
+        self.arguments()
+            .into_iter()
+            .filter_map(|(position, _, name)| {
+                Some((position, name.filter(|name| !name.is_empty())?.into()))
+            })
+            .collect()
+    }
+
+    fn nodes(&self, names: &BTreeMap<usize, String>) -> Vec<Node> {
+        let mut names = names.clone();
+        names.extend(self.names());
+        let mut nodes = Vec::new();
+        let mut next = 0;
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => {
+                    for piece in printf::split(text) {
+                        match piece {
+                            Piece::Text(text) => nodes.push(Node::Text(text)),
+                            Piece::Argument(argument) => {
+                                let position = argument.position.unwrap_or_else(|| {
+                                    next += 1;
+                                    next
+                                });
+                                let name = names
+                                    .get(&position)
+                                    .cloned()
+                                    .unwrap_or_else(|| format!("arg{position}"));
+                                nodes.push(Node::Expression(argument.expression(name)));
+                            }
+                        }
+                    }
+                }
+                Part::Markup(markup) => nodes.push(Node::Markup(markup.clone())),
+                Part::NameStart(_) | Part::NameEnd => {}
+            }
+        }
+        nodes
+    }
+}
+
+fn resource_element(id: &str, message: &Message) -> Result<String, Problem> {
+    let message = resolve(message)?;
+    let arguments = Arguments::new(&message);
+    let message = match &message {
+        Message::Pattern(message) => {
+            return Ok(format!(
+                "    <string name=\"{id}\">{}</string>\n",
+                pattern(&message.pattern, &arguments)?
+            ))
+        }
+        Message::Select(message) => message,
+    };
+
+    let selector = match message.selectors.as_slice() {
+        [selector @ Expression::Variable(variable)]
+            if is_plural(function(variable.annotation.as_ref())) =>
+        {
+            selector
+        }
+        selectors => {
+            let selectors: Vec<String> = selectors.iter().map(ToString::to_string).collect();
+            return Err((selectors.join(" "), ExportErrorKind::Selector));
+        }
+    };
+    let other = VariantKey::Literal(Literal {
+        value: "other".into(),
+    });
+    let has_other = message
+        .variants
+        .iter()
+        .any(|variant| variant.keys[0] == other);
+
+    let mut out = format!("    <plurals name=\"{id}\">\n");
+    for variant in &message.variants {
+        let quantity = match &variant.keys[0] {
+            // An `other` key is selected before the catch-all
+            VariantKey::Catchall if has_other => continue,
+            VariantKey::Catchall => "other",
+            VariantKey::Literal(literal) if PluralCategory::from_name(&literal.value).is_some() => {
+                &literal.value
+            }
+            VariantKey::Literal(literal) => {
+                return Err((
+                    format!("{} {}", selector, literal.value),
+                    ExportErrorKind::Key,
+                ));
+            }
+        };
+        out.push_str(&format!(
+            "        <item quantity=\"{quantity}\">{}</item>\n",
+            pattern(&variant.value, &arguments)?
+        ));
+    }
+    out.push_str("    </plurals>\n");
+    Ok(out)
+}
+
+/// Writes a resolved pattern as the content of a `<string>` or `<item>`
+fn pattern(pattern: &[PatternElement], arguments: &Arguments) -> Result<String, Problem> {
+    let mut out = String::new();
+    for (index, element) in pattern.iter().enumerate() {
+        match element {
+            PatternElement::Literal(text) => {
+                let mut escaped = String::new();
+                for c in text.chars() {
+                    match c {
+                        '\\' | '"' | '\'' => {
+                            escaped.push('\\');
+                            escaped.push(c);
+                        }
+                        '\n' => escaped.push_str("\\n"),
+                        '\t' => escaped.push_str("\\t"),
+                        // Strings that start with `@` or `?` are references to other resources
+                        '@' | '?' if index == 0 && escaped.is_empty() => {
+                            escaped.push('\\');
+                            escaped.push(c);
+                        }
+                        '%' if arguments.len() > 0 => escaped.push_str("%%"),
+                        c => escaped.push(c),
+                    }
+                }
+                out.push_str(&escape(&escaped));
+            }
+            PatternElement::Expression(Expression::Variable(variable)) => {
+                let position = arguments.position(&variable.arg.name);
+                let argument = Argument::new(position, function(variable.annotation.as_ref()))
+                    .map_err(|kind| (element.to_string(), kind))?;
+                out.push_str(&format!(
+                    "<xliff:g id=\"{}\">{}</xliff:g>",
+                    escape(&variable.arg.name),
+                    argument.write(&ANDROID)
+                ));
+            }
+            PatternElement::Expression(_) => {
+                return Err((element.to_string(), ExportErrorKind::Operand))
+            }
+            PatternElement::Markup(markup) => {
+                let mut attributes = String::new();
+                for option in &markup.options {
+                    let OptionValue::Literal(literal) = &option.value else {
+                        return Err((
+                            element.to_string(),
+                            ExportErrorKind::Option {
+                                name: option.name.clone(),
+                            },
+                        ));
+                    };
+                    attributes.push_str(&format!(
+                        " {}=\"{}\"",
+                        option.name,
+                        escape(&literal.value)
+                    ));
+                }
+                out.push_str(&match markup.kind {
+                    MarkupKind::Open => format!("<{}{attributes}>", markup.name),
+                    MarkupKind::Standalone => format!("<{}{attributes}/>", markup.name),
+                    MarkupKind::Close => format!("</{}>", markup.name),
+                });
+            }
+        }
+    }
+
+    // Whitespace is collapsed outside of double quotes
+    fn text(element: Option<&PatternElement>) -> &str {
+        match element {
+            Some(PatternElement::Literal(text)) => text,
+            _ => "",
+        }
+    }
+    let collapsed = text(pattern.first()).starts_with(char::is_whitespace)
+        || text(pattern.last()).ends_with(char::is_whitespace)
+        || pattern.iter().any(|element| {
+            let text = text(Some(element));
+            text.contains("  ") || text.contains(['\r', '\u{a0}'])
+        });
+    Ok(if collapsed { format!("\"{out}\"") } else { out })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRINGS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+    <!-- The strings of the app -->
+
+    <!-- Shown on the home page -->
+    <string name="greeting">Hello <b><xliff:g id="name" example="Ada">%1$s</xliff:g></b>!</string>
+    <string name="quote">He said \"it\'s
+        50%\"   and  "kept  this"</string>
+    <string-array name="planets"><item>Mercury</item></string-array>
+    <plurals name="items">
+        <item quantity="one">One item in %2$s</item>
+        <item quantity="few"><xliff:g id="count">%1$d</xliff:g> items in %2$s</item>
+        <item quantity="other"><xliff:g id="count">%1$d</xliff:g> items in %2$s</item>
+    </plurals>
+</resources>
+"#;
+
+    #[test]
+    fn it_parses_strings_xml() {
+        let resource = parse(STRINGS).expect("valid resources");
+        assert_eq!(resource.comment.as_deref(), Some("The strings of the app"));
+        let entries: Vec<(String, Option<&str>, String)> = resource
+            .entries()
+            .map(|(id, entry)| (id, entry.comment.as_deref(), entry.message.to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("greeting".into(), Some("Shown on the home page"), "Hello {#b}{$name}{/b}!".into()),
+                ("quote".into(), None, "He said \"it's 50%\" and kept  this".into()),
+                (
+                    "items".into(),
+                    None,
+                    ".match {$count :integer}\none {{One item in {$arg2}}}\nfew {{{$count :integer} items in {$arg2}}}\n* {{{$count :integer} items in {$arg2}}}"
+                        .into()
+                ),
+            ]
+        );
+
+        let error = parse(
+            "<resources><plurals name=\"x\"><item quantity=\"lots\">x</item></plurals></resources>",
+        );
+        assert_eq!(
+            error.map_err(|error| error.to_string()),
+            Err("x: lots is not a plural category".into())
+        );
+    }
+
+    #[test]
+    fn it_writes_strings_xml() {
+        let resource: Resource = "# Greetings\n\ngreeting = {{@{$name} says \"hi\"  {#a href=x}100%{/a}}}\nitems =\n  .input {$n :number}\n  .match {$n}\n  one {{One: {$n}}}\n  * {{{$n} items for {$user}}}\n"
+            .parse()
+            .expect("valid resource");
+        let strings = serialize(&resource).expect("messages that Android can express");
+        assert_eq!(
+            strings,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+    <!-- Greetings -->
+
+    <string name="greeting">"\@<xliff:g id="name">%1$s</xliff:g> says \&quot;hi\&quot;  <a href="x">100%%</a>"</string>
+    <plurals name="items">
+        <item quantity="one">One: <xliff:g id="n">%1$s</xliff:g></item>
+        <item quantity="other"><xliff:g id="n">%1$s</xliff:g> items for <xliff:g id="user">%2$s</xliff:g></item>
+    </plurals>
+</resources>
+"#
+        );
+
+        let reparsed = parse(&strings).expect("valid resources");
+        assert_eq!(
+            reparsed
+                .entries()
+                .map(|(_, entry)| entry.message.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "@{$name} says \"hi\"  {#a href=x }100%{/a}",
+                ".match {$n :integer}\none {{One: {$n}}}\n* {{{$n} items for {$user}}}"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_what_android_cant_express() {
+        let error = |source: &str| {
+            let resource: Resource = source.parse().expect("valid resource");
+            serialize(&resource).map_err(|error| (error.node, error.kind))
+        };
+        assert_eq!(
+            error("items =\n  .match {$n :number} {$m :number}\n  * * {{x}}\n"),
+            Err((
+                "{$n :number} {$m :number}".into(),
+                ExportErrorKind::Selector
+            ))
+        );
+        assert_eq!(
+            error("items =\n  .match {$n :number}\n  0 {{none}}\n  * {{x}}\n"),
+            Err(("{$n :number} 0".into(), ExportErrorKind::Key))
+        );
+        assert_eq!(
+            error("due = Due {$date :datetime}"),
+            Err((
+                "{$date :datetime}".into(),
+                ExportErrorKind::Function {
+                    name: "datetime".into()
+                }
+            ))
+        );
+        assert_eq!(
+            error("user-name = x"),
+            Err(("user-name".into(), ExportErrorKind::Name))
+        );
+    }
+}
@@ -0,0 +1,877 @@
+//! Apple string resources: `.strings` files for messages without selectors, and `.stringsdict`
+//! property lists for plurals.
+//!
+//! ```text
+//! /* Shown on the home page */
+//! "greeting" = "Hello %1$@!";
+//! ```
+//!
+//! A `.stringsdict` entry has a format string with variables like `%#@count@`, and a dictionary for
+//! each variable with a string for each plural category of the number it is given. They become
+//! messages that select on the variables, which keep their names:
+//!
+//! ```xml
+//! <key>items</key>
+//! <dict>
+//!     <key>NSStringLocalizedFormatKey</key>
+//!     <string>%1$#@count@</string>
+//!     <key>count</key>
+//!     <dict>
+//!         <key>NSStringFormatSpecTypeKey</key>
+//!         <string>NSStringPluralRuleType</string>
+//!         <key>NSStringFormatValueTypeKey</key>
+//!         <string>ld</string>
+//!         <key>one</key>
+//!         <string>%1$ld item</string>
+//!         <key>other</key>
+//!         <string>%1$ld items</string>
+//!     </dict>
+//! </dict>
+//! ```
+//!
+//! Apple uses the `zero` string for 0 in all languages, so it becomes the key `0`. Placeholders
+//! without a variable name become `$arg1`, `$arg2` and so on, `%ld` becomes `:integer`, `%g`
+//! becomes `:number` and `%.2f` becomes `:number` with two fraction digits.
+//!
+//! See: https://developer.apple.com/documentation/xcode/localizing-strings-that-contain-plurals
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::printf::{self, Argument, Arguments, Conversion, Piece, APPLE};
+use super::{
+    function, is_plural, resolve, xml_comment, ExportError, ExportErrorKind, ParseError,
+    ParseErrorKind, Problem,
+};
+use crate::convert::tree::{self, variable, Node, Select, Tree};
+use crate::convert::xml::{self, escape, Element};
+use crate::data_model::elements::{Expression, Message, PatternElement, SelectMessage};
+use crate::format::plural::PluralCategory;
+use crate::resource::{Entry, Resource};
+
+/// Reads the messages of a `.strings` file.
+///
+/// # Errors
+/// Returns an error if the file isn't well-formed.
+pub fn parse_strings(source: &str) -> Result<Resource, ParseError> {
+    let mut parser = StringsParser {
+        source,
+        position: 0,
+    };
+    let mut resource = Resource::new();
+    let mut comment: Option<String> = None;
+    loop {
+        let blank = parser.blank();
+        if blank.matches('\n').count() > 1
+            && resource.comment.is_none()
+            && resource.sections[0].entries.is_empty()
+        {
+            resource.comment = comment.take();
+        }
+        let rest = parser.rest();
+        if rest.is_empty() {
+            return Ok(resource);
+        }
+        let text = if rest.starts_with("/*") {
+            parser.skip_past("*/")?
+        } else if rest.starts_with("//") {
+            parser.skip_past("\n").unwrap_or_else(|_| {
+                parser.position = source.len();
+                rest
+            })
+        } else {
+            let (key, value) = parser.entry()?;
+            let entry = Stringsdict {
+                format: &value,
+                variables: BTreeMap::new(),
+                names: BTreeMap::new(),
+            };
+            let nodes = entry
+                .nodes(&value, None, &mut 0, 0)
+                .map_err(|kind| ParseError {
+                    line: None,
+                    id: Some(key.clone()),
+                    kind,
+                })?;
+            let message = tree::message(&nodes).map_err(|error| ParseError {
+                line: None,
+                id: Some(key.clone()),
+                kind: ParseErrorKind::TooComplex(error.to_string()),
+            })?;
+            resource.sections[0].entries.push(Entry {
+                key,
+                comment: comment.take(),
+                message,
+            });
+            continue;
+        };
+        let text = text
+            .trim_start_matches(['/', '*'])
+            .trim_end_matches(['*', '/'])
+            .trim();
+        comment = Some(
+            comment
+                .take()
+                .map_or_else(|| text.into(), |comment| format!("{comment}\n{text}")),
+        );
+    }
+}
+
+/// Reads the messages of a `.stringsdict` file.
+///
+/// # Errors
+/// Returns an error if the file isn't a well-formed property list of plural rules, or a plural has
+/// a string for something other than a plural category.
+pub fn parse_stringsdict(source: &str) -> Result<Resource, ParseError> {
+    let error = |id: Option<&str>, kind| ParseError {
+        line: None,
+        id: id.map(Into::into),
+        kind,
+    };
+    let root = xml::parse(source).map_err(|(line, reason)| ParseError {
+        line: Some(line),
+        id: None,
+        kind: ParseErrorKind::Syntax(reason),
+    })?;
+    let entries = root
+        .element("dict")
+        .filter(|_| root.local_name() == "plist")
+        .ok_or_else(|| {
+            error(
+                None,
+                ParseErrorKind::Invalid("expected a <plist> with a <dict>".into()),
+            )
+        })?;
+
+    let mut resource = Resource::new();
+    for (id, value, comment) in dict(entries).map_err(|reason| error(None, reason))? {
+        let entry = Stringsdict::new(value).map_err(|kind| error(Some(id), kind))?;
+        let mut next = 0;
+        let nodes = entry
+            .nodes(entry.format, None, &mut next, 0)
+            .map_err(|kind| error(Some(id), kind))?;
+        let message = tree::message(&nodes)
+            .map_err(|reason| error(Some(id), ParseErrorKind::TooComplex(reason.to_string())))?;
+        resource.sections[0].entries.push(Entry {
+            key: id.into(),
+            comment,
+            message,
+        });
+    }
+    Ok(resource)
+}
+
+/// Writes the messages of a resource that have no selectors to a `.strings` file. Select messages
+/// are left out, they are written to the `.stringsdict` file by [`serialize_stringsdict`].
+///
+/// # Errors
+/// Returns an error for the first message that `.strings` files can't express, like one with
+/// markup.
+pub fn serialize_strings(resource: &Resource) -> Result<String, ExportError> {
+    let mut out = String::new();
+    if let Some(comment) = &resource.comment {
+        out.push_str(&format!("/* {} */\n\n", comment.replace("*/", "* /")));
+    }
+    for (id, entry) in resource.entries() {
+        let error = |(node, kind): Problem| ExportError {
+            id: id.clone(),
+            node,
+            kind,
+        };
+        let message = resolve(&entry.message).map_err(error)?;
+        let Message::Pattern(pattern) = &message else {
+            continue;
+        };
+        let value = format_string(&pattern.pattern, &Arguments::new(&message)).map_err(error)?;
+        if let Some(comment) = &entry.comment {
+            out.push_str(&format!("/* {} */\n", comment.replace("*/", "* /")));
+        }
+        out.push_str(&format!("{} = {};\n\n", quote(&id), quote(&value)));
+    }
+    Ok(out)
+}
+
+/// Writes the messages of a resource that select on plural categories to a `.stringsdict` file.
+/// Messages without selectors are left out, they are written to the `.strings` file by
+/// [`serialize_strings`].
+///
+/// Each select on a variable becomes a `.stringsdict` variable. Variables for the same selector
+/// in different variants get a number, like `count_2`, and all of them are written with the
+/// position of the selector, so they import as the same variable.
+///
+/// # Errors
+/// Returns an error for the first message that `.stringsdict` files can't express, like one that
+/// selects on a string or has a numeric key other than 0.
+pub fn serialize_stringsdict(resource: &Resource) -> Result<String, ExportError> {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n<dict>\n",
+    );
+    for (id, entry) in resource.entries() {
+        let error = |(node, kind): Problem| ExportError {
+            id: id.clone(),
+            node,
+            kind,
+        };
+        let message = resolve(&entry.message).map_err(error)?;
+        let Message::Select(select) = &message else {
+            continue;
+        };
+        for selector in &select.selectors {
+            let Expression::Variable(variable) = selector else {
+                continue;
+            };
+            if !is_plural(function(variable.annotation.as_ref())) {
+                return Err(error((selector.to_string(), ExportErrorKind::Selector)));
+            }
+        }
+
+        let mut writer = DictWriter {
+            message: select,
+            arguments: Arguments::new(&message),
+            variables: Vec::new(),
+        };
+        let format = writer.tree(&tree::nest(select)).map_err(error)?;
+        if let Some(comment) = &entry.comment {
+            out.push_str(&format!("\t<!-- {} -->\n", xml_comment(comment)));
+        }
+        out.push_str(&format!(
+            "\t<key>{}</key>\n\t<dict>\n\t\t<key>NSStringLocalizedFormatKey</key>\n\t\t<string>{}</string>\n",
+            escape(&id),
+            escape(&format)
+        ));
+        for (name, dict) in writer.variables {
+            out.push_str(&format!(
+                "\t\t<key>{}</key>\n\t\t<dict>\n{dict}\t\t</dict>\n",
+                escape(&name)
+            ));
+        }
+        out.push_str("\t</dict>\n");
+    }
+    out.push_str("</dict>\n</plist>\n");
+    Ok(out)
+}
+
+struct StringsParser<'s> {
+    source: &'s str,
+    position: usize,
+}
+
+impl<'s> StringsParser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn error(&self, reason: &str) -> ParseError {
+        ParseError {
+            line: Some(self.source[..self.position].matches('\n').count() + 1),
+            id: None,
+            kind: ParseErrorKind::Syntax(reason.into()),
+        }
+    }
+
+    fn blank(&mut self) -> &'s str {
+        let rest = self.rest();
+        let length = rest.len() - rest.trim_start().len();
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<&'s str, ParseError> {
+        let rest = self.rest();
+        let length = rest
+            .find(end)
+            .ok_or_else(|| self.error(&format!("expected `{end}`")))?;
+        self.position += length + end.len();
+        Ok(&rest[..length])
+    }
+
+    fn expect(&mut self, token: char) -> Result<(), ParseError> {
+        self.blank();
+        if self.rest().starts_with(token) {
+            self.position += token.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{token}`")))
+        }
+    }
+
+    /// Reads `"key" = "value";`, where keys without spaces don't need quotes
+    fn entry(&mut self) -> Result<(String, String), ParseError> {
+        let key = if self.rest().starts_with('"') {
+            self.string()?
+        } else {
+            let rest = self.rest();
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-')))
+                .unwrap_or(rest.len());
+            if length == 0 {
+                return Err(self.error("expected a key"));
+            }
+            self.position += length;
+            rest[..length].into()
+        };
+        self.expect('=')?;
+        self.blank();
+        let value = self.string()?;
+        self.expect(';')?;
+        Ok((key, value))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += index + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('U' | 'u') => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let mut code = u32::from_str_radix(&hex, 16).ok();
+                        // Characters outside the BMP are escaped as UTF-16 surrogate pairs
+                        if let Some(high @ 0xD800..=0xDBFF) = code {
+                            let rest = chars.as_str();
+                            let low = rest
+                                .strip_prefix("\\U")
+                                .or_else(|| rest.strip_prefix("\\u"))
+                                .and_then(|rest| rest.get(..4))
+                                .and_then(|low| u32::from_str_radix(low, 16).ok());
+                            code = match low {
+                                Some(low @ 0xDC00..=0xDFFF) => {
+                                    chars.nth(5);
+                                    Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                                }
+                                _ => None,
+                            };
+                        }
+                        let c = code.and_then(char::from_u32);
+                        value.push(
+                            c.ok_or_else(|| self.error(&format!("invalid escape `\\U{hex}`")))?,
+                        );
+                    }
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        self.position = self.source.len();
+        Err(self.error("unterminated string"))
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A key of a `<dict>` with its value and the comment before it
+type DictEntry<'e> = (&'e str, &'e Element, Option<String>);
+
+/// The keys of a `<dict>` with their values and the comments before them
+fn dict(element: &Element) -> Result<Vec<DictEntry<'_>>, ParseErrorKind> {
+    let mut entries = Vec::new();
+    let mut key = None;
+    let mut comment: Option<String> = None;
+    for child in &element.children {
+        match child {
+            xml::Node::Comment(text) => {
+                let text = text.trim();
+                comment = Some(
+                    comment
+                        .take()
+                        .map_or_else(|| text.into(), |comment| format!("{comment}\n{text}")),
+                );
+            }
+            xml::Node::Text(_) => {}
+            xml::Node::Element(child) if child.local_name() == "key" && key.is_none() => {
+                key = Some(child);
+            }
+            xml::Node::Element(child) => {
+                let key = key.take().ok_or_else(|| {
+                    ParseErrorKind::Invalid(format!("<{}> without a <key>", child.name))
+                })?;
+                entries.push((
+                    key.children
+                        .iter()
+                        .find_map(|node| match node {
+                            xml::Node::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .unwrap_or_default(),
+                    child,
+                    comment.take(),
+                ));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// An entry of a `.stringsdict` file
+struct Stringsdict<'e> {
+    format: &'e str,
+    variables: BTreeMap<&'e str, &'e Element>,
+    /// The names of the arguments by their position
+    names: BTreeMap<usize, String>,
+}
+
+/// How deeply variables can be nested in the strings of other variables
+const MAX_DEPTH: usize = 8;
+
+impl<'e> Stringsdict<'e> {
+    fn new(value: &'e Element) -> Result<Self, ParseErrorKind> {
+        let mut format = None;
+        let mut variables = BTreeMap::new();
+        for (key, value, _) in dict(value)? {
+            if key == "NSStringLocalizedFormatKey" {
+                format = Some(string(value)?);
+            } else if value.local_name() == "dict" {
+                variables.insert(key, value);
+            }
+        }
+        let format = format
+            .ok_or_else(|| ParseErrorKind::Invalid("missing NSStringLocalizedFormatKey".into()))?;
+
+        // Variables name the arguments at their positions. In the strings of variables, only
+        // variables with a position are known to stand for an argument.
+        let mut names = BTreeMap::new();
+        let mut next = 0;
+        for piece in printf::split(format) {
+            if let Piece::Argument(argument) = piece {
+                let position = argument.position.unwrap_or_else(|| {
+                    next += 1;
+                    next
+                });
+                if let Conversion::Variable(name) = argument.conversion {
+                    names.entry(position).or_insert(name);
+                }
+            }
+        }
+        for dict in variables.values() {
+            for (_, value, _) in self::dict(dict)? {
+                let Ok(text) = string(value) else {
+                    continue;
+                };
+                for piece in printf::split(text) {
+                    if let Piece::Argument(Argument {
+                        position: Some(position),
+                        conversion: Conversion::Variable(name),
+                        ..
+                    }) = piece
+                    {
+                        names.entry(position).or_insert(name);
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            format,
+            variables,
+            names,
+        })
+    }
+
+    /// The nodes of a format string. Placeholders without a position in the strings of a variable
+    /// are its number.
+    fn nodes(
+        &self,
+        text: &str,
+        variable: Option<&str>,
+        next: &mut usize,
+        depth: usize,
+    ) -> Result<Vec<Node>, ParseErrorKind> {
+        let mut nodes = Vec::new();
+        for piece in printf::split(text) {
+            let argument = match piece {
+                Piece::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Piece::Argument(argument) => argument,
+            };
+            let position = argument.position.or_else(|| {
+                variable.is_none().then(|| {
+                    *next += 1;
+                    *next
+                })
+            });
+            let known = position
+                .and_then(|position| self.names.get(&position))
+                .cloned();
+            match &argument.conversion {
+                Conversion::Variable(key) => {
+                    let name = known.unwrap_or_else(|| key.clone());
+                    nodes.push(Node::Select(self.select(key, name, depth)?));
+                }
+                _ => {
+                    let name = match (known, position, variable) {
+                        (Some(name), _, _) => name,
+                        (None, Some(position), _) => format!("arg{position}"),
+                        (None, None, variable) => variable.unwrap_or_default().into(),
+                    };
+                    nodes.push(Node::Expression(argument.expression(name)));
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn select(&self, key: &str, name: String, depth: usize) -> Result<Select, ParseErrorKind> {
+        let variable_dict = self
+            .variables
+            .get(key)
+            .ok_or_else(|| ParseErrorKind::Invalid(format!("missing the variable {key}")))?;
+        if depth == MAX_DEPTH {
+            return Err(ParseErrorKind::Invalid(format!(
+                "the variable {key} is nested too deeply"
+            )));
+        }
+
+        let mut annotation = None;
+        let mut cases = Vec::new();
+        let mut last = None;
+        for (form, value, _) in dict(variable_dict)? {
+            match form {
+                "NSStringFormatSpecTypeKey" if string(value)? != "NSStringPluralRuleType" => {
+                    return Err(ParseErrorKind::Invalid(format!(
+                        "{key} is not a plural rule"
+                    )));
+                }
+                "NSStringFormatSpecTypeKey" => {}
+                "NSStringFormatValueTypeKey" => {
+                    if let [Piece::Argument(argument)] =
+                        printf::split(&format!("%{}", string(value)?)).as_slice()
+                    {
+                        annotation = argument.expression(name.clone()).into();
+                    }
+                }
+                form => {
+                    let key = match PluralCategory::from_name(form) {
+                        Some(PluralCategory::Zero) => Some("0".into()),
+                        Some(PluralCategory::Other) => None,
+                        Some(category) => Some(category.as_str().into()),
+                        None => return Err(ParseErrorKind::Quantity(form.into())),
+                    };
+                    let text = string(value)?;
+                    cases.push((key, self.nodes(text, Some(&name), &mut 0, depth + 1)?));
+                    last = Some(text);
+                }
+            }
+        }
+        if !cases.iter().any(|(key, _)| key.is_none()) {
+            let last = last.ok_or_else(|| {
+                ParseErrorKind::Invalid(format!("the variable {key} has no strings"))
+            })?;
+            cases.push((None, self.nodes(last, Some(&name), &mut 0, depth + 1)?));
+        }
+
+        // Plural rules select on numbers
+        let selector = match annotation {
+            Some(Expression::Variable(selector)) if selector.annotation.is_some() => {
+                Expression::Variable(selector)
+            }
+            _ => variable(name, Some(tree::function("number", &[]))),
+        };
+        Ok(Select { selector, cases })
+    }
+}
+
+fn string(element: &Element) -> Result<&str, ParseErrorKind> {
+    if element.local_name() != "string" {
+        return Err(ParseErrorKind::Invalid(format!(
+            "expected <string>, found <{}>",
+            element.name
+        )));
+    }
+    Ok(element
+        .children
+        .iter()
+        .find_map(|node| match node {
+            xml::Node::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .unwrap_or_default())
+}
+
+/// Writes the format string of a resolved pattern
+fn format_string(pattern: &[PatternElement], arguments: &Arguments) -> Result<String, Problem> {
+    let mut out = String::new();
+    for element in pattern {
+        match element {
+            PatternElement::Literal(text) if arguments.len() > 0 => {
+                out.push_str(&text.replace('%', "%%"))
+            }
+            PatternElement::Literal(text) => out.push_str(text),
+            PatternElement::Expression(Expression::Variable(variable)) => {
+                let position = arguments.position(&variable.arg.name);
+                let argument = Argument::new(position, function(variable.annotation.as_ref()))
+                    .map_err(|kind| (element.to_string(), kind))?;
+                out.push_str(&argument.write(&APPLE));
+            }
+            PatternElement::Expression(_) => {
+                return Err((element.to_string(), ExportErrorKind::Operand))
+            }
+            PatternElement::Markup(_) => {
+                return Err((element.to_string(), ExportErrorKind::Markup))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Writes the variables of a `.stringsdict` entry
+struct DictWriter<'m> {
+    message: &'m SelectMessage,
+    arguments: Arguments,
+    /// The names of the variables with the content of their dictionaries
+    variables: Vec<(String, String)>,
+}
+
+impl DictWriter<'_> {
+    fn tree(&mut self, tree: &Tree) -> Result<String, Problem> {
+        let (selector, cases, catchall) = match tree {
+            Tree::Pattern(pattern) => return format_string(pattern, &self.arguments),
+            Tree::Select {
+                selector,
+                cases,
+                catchall,
+            } => (*selector, cases, catchall),
+        };
+        let expression = &self.message.selectors[selector];
+        let Expression::Variable(variable) = expression else {
+            return Err((expression.to_string(), ExportErrorKind::Selector));
+        };
+        let name = &variable.arg.name;
+        let position = self.arguments.position(name);
+        let argument = Argument::new(position, function(variable.annotation.as_ref()))
+            .map_err(|kind| (expression.to_string(), kind))?;
+        let value_type = argument.write(&APPLE);
+        let value_type =
+            value_type.trim_start_matches(|c: char| c == '%' || c.is_ascii_digit() || c == '$');
+
+        // Reserve the name before the nested variables take theirs
+        let mut key = name.clone();
+        let mut count = 1;
+        while self.variables.iter().any(|(other, _)| *other == key) {
+            count += 1;
+            key = format!("{name}_{count}");
+        }
+        let index = self.variables.len();
+        self.variables.push((key.clone(), String::new()));
+
+        let mut dict = format!(
+            "\t\t\t<key>NSStringFormatSpecTypeKey</key>\n\t\t\t<string>NSStringPluralRuleType</string>\n\t\t\t\
+             <key>NSStringFormatValueTypeKey</key>\n\t\t\t<string>{value_type}</string>\n"
+        );
+        let mut forms: Vec<&str> = Vec::new();
+        let mut cases: Vec<(&str, &Tree)> = cases
+            .iter()
+            .map(|(literal, tree)| {
+                let form = match literal.value.as_str() {
+                    "0" => "zero",
+                    value => PluralCategory::from_name(value)
+                        .map(PluralCategory::as_str)
+                        .ok_or_else(|| (format!("{expression} {value}"), ExportErrorKind::Key))?,
+                };
+                Ok((form, tree))
+            })
+            .collect::<Result<_, Problem>>()?;
+        cases.push(("other", catchall));
+        for (form, tree) in cases {
+            if forms.contains(&form) {
+                // An `other` key is selected before the catch-all
+                if form == "other" {
+                    continue;
+                }
+                return Err((format!("{expression} {form}"), ExportErrorKind::Key));
+            }
+            forms.push(form);
+            let text = self.tree(tree)?;
+            dict.push_str(&format!(
+                "\t\t\t<key>{form}</key>\n\t\t\t<string>{}</string>\n",
+                escape(&text)
+            ));
+        }
+        self.variables[index].1 = dict;
+        Ok(format!("%{position}$#@{key}@"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRINGSDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<!-- Files in a folder -->
+	<key>files</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%#@files@ in %2$@</string>
+		<key>files</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>lu</string>
+			<key>zero</key>
+			<string>No files</string>
+			<key>one</key>
+			<string>One file</string>
+			<key>other</key>
+			<string>%lu files</string>
+		</dict>
+	</dict>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn it_parses_strings_files() {
+        let resource = parse_strings(
+            "/* Generated */\n\n/* Shown on the home page */\n\"greeting\" = \"Hello %@, you have %d%% of \\\"%2$.1f\\\"\";\n// Unquoted\nbye = \"Bye\\n\\U263A\\UD83D\\ude00\";\n",
+        )
+        .expect("valid strings file");
+        assert_eq!(resource.comment.as_deref(), Some("Generated"));
+        let entries: Vec<(String, Option<&str>, String)> = resource
+            .entries()
+            .map(|(id, entry)| (id, entry.comment.as_deref(), entry.message.to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "greeting".into(),
+                    Some("Shown on the home page"),
+                    "Hello {$arg1}, you have {$arg2 :integer}% of \"{$arg2 :number minimumFractionDigits=1 maximumFractionDigits=1}\"".into()
+                ),
+                ("bye".into(), Some("Unquoted"), "Bye\n\u{263a}\u{1f600}".into()),
+            ]
+        );
+
+        let error = parse_strings("\"a\" = \"b\";\n\"c\" \"d\";").expect_err("missing =");
+        assert_eq!(error.to_string(), "2: expected `=`");
+        let error = parse_strings("\"a\" = \"\\UD83D\";").expect_err("unpaired surrogate");
+        assert_eq!(error.to_string(), "1: invalid escape `\\UD83D`");
+    }
+
+    #[test]
+    fn it_parses_stringsdict_files() {
+        let resource = parse_stringsdict(STRINGSDICT).expect("valid stringsdict");
+        let (id, entry) = resource.entries().next().expect("one entry");
+        assert_eq!(id, "files");
+        assert_eq!(entry.comment.as_deref(), Some("Files in a folder"));
+        assert_eq!(
+            entry.message.to_string(),
+            ".match {$files :integer}\n0 {{No files in {$arg2}}}\none {{One file in {$arg2}}}\n* {{{$files :integer} files in {$arg2}}}"
+        );
+
+        let error = parse_stringsdict(&STRINGSDICT.replace("<key>one</key>", "<key>single</key>"));
+        assert_eq!(
+            error.map_err(|error| error.to_string()),
+            Err("files: single is not a plural category".into())
+        );
+    }
+
+    #[test]
+    fn it_writes_strings_and_stringsdict_files() {
+        let resource: Resource = "# Shown on the home page\ngreeting = Hello {$name}, 100% \"{$n :integer}\"\nitems =\n  .input {$n :integer}\n  .match {$n} {$m :number}\n  one one {{One item of one}}\n  one * {{One item of {$m}}}\n  * * {{{$n} items of {$m}}}\n"
+            .parse()
+            .expect("valid resource");
+
+        assert_eq!(
+            serialize_strings(&resource),
+            Ok("/* Shown on the home page */\n\"greeting\" = \"Hello %1$@, 100%% \\\"%2$ld\\\"\";\n\n".into())
+        );
+        let stringsdict = serialize_stringsdict(&resource).expect("plural messages");
+        assert_eq!(
+            stringsdict,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>items</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%1$#@n@</string>
+		<key>n</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>ld</string>
+			<key>one</key>
+			<string>%2$#@m@</string>
+			<key>other</key>
+			<string>%1$ld items of %2$g</string>
+		</dict>
+		<key>m</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>g</string>
+			<key>one</key>
+			<string>One item of one</string>
+			<key>other</key>
+			<string>One item of %2$g</string>
+		</dict>
+	</dict>
+</dict>
+</plist>
+"#
+        );
+
+        let reparsed = parse_stringsdict(&stringsdict).expect("valid stringsdict");
+        let (_, entry) = reparsed.entries().next().expect("one entry");
+        assert_eq!(
+            entry.message.to_string(),
+            ".match {$n :integer} {$m :number}\none one {{One item of one}}\none * {{One item of {$m :number}}}\n* * {{{$n :integer} items of {$m :number}}}"
+        );
+
+        // A variable named like a numbered one doesn't share its key
+        let resource: Resource = "items =\n  .match {$n_2 :integer} {$n :integer}\n  one one {{A}}\n  one * {{B}}\n  * one {{C}}\n  * * {{D}}\n"
+            .parse()
+            .expect("valid resource");
+        let stringsdict = serialize_stringsdict(&resource).expect("plural messages");
+        for key in ["n_2", "n", "n_3"] {
+            assert_eq!(
+                stringsdict.matches(&format!("<key>{key}</key>")).count(),
+                1,
+                "{stringsdict}"
+            );
+        }
+
+        let resource: Resource = "kind =\n  .match {$kind :string}\n  a {{A}}\n  * {{B}}\n"
+            .parse()
+            .expect("valid resource");
+        assert_eq!(
+            serialize_stringsdict(&resource).map_err(|error| error.kind),
+            Err(ExportErrorKind::Selector)
+        );
+    }
+}
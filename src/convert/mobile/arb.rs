@@ -0,0 +1,510 @@
+//! The ARB files of Flutter: JSON objects that map message ids to ICU MessageFormat 1 messages,
+//! with metadata about each message in an `@id` entry:
+//!
+//! ```json
+//! {
+//!   "@@locale": "en",
+//!   "items": "{count, plural, =0{No items} one{One item} other{{count} items}}",
+//!   "@items": {
+//!     "description": "The items in the cart",
+//!     "placeholders": {
+//!       "count": {
+//!         "type": "int"
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Messages are converted like in [`mf1`](crate::convert::mf1). Flutter formats placeholders by
+//! the `type` in their metadata, so `int` placeholders become `:integer`, `num` and `double`
+//! become `:number`, and `DateTime` becomes `:date` with the format `yMd` and `:time` with `jm`.
+//! Other `DateTime` formats have no function to become, and are reported. Descriptions become the
+//! comments of the entries.
+//!
+//! See: https://github.com/google/app-resource-bundle/wiki/ApplicationResourceBundleSpecification
+
+use alloc::collections::BTreeMap;
+
+use super::{function, resolve, ExportError, ExportErrorKind, ParseError, ParseErrorKind, Problem};
+use crate::convert::mf1;
+use crate::convert::tree;
+use crate::data_model::elements::{
+    Annotation, Expression, FunctionAnnotation, Message, PatternElement, PatternMessage,
+    SelectMessage, Variant,
+};
+use crate::load::tree::Node;
+use crate::locale::Locale;
+use crate::resource::{Entry, Resource};
+
+/// Reads the messages of an ARB file, and its `@@locale` if it has one.
+///
+/// # Errors
+/// Returns an error if the file isn't a JSON object of messages, a message isn't valid
+/// MessageFormat 1, or a `DateTime` placeholder has a format other than `yMd` or `jm`.
+pub fn parse(source: &str) -> Result<(Option<Locale>, Resource), ParseError> {
+    let error = |id: Option<&str>, kind| ParseError {
+        line: None,
+        id: id.map(Into::into),
+        kind,
+    };
+    let node: Node = serde_json::from_str(source)
+        .map_err(|e| error(None, ParseErrorKind::Syntax(e.to_string())))?;
+    let Node::Namespace(entries) = node else {
+        return Err(error(
+            None,
+            ParseErrorKind::Invalid("expected an object of messages".into()),
+        ));
+    };
+
+    let mut locale = None;
+    let mut metadata = BTreeMap::new();
+    for (key, value) in &entries {
+        match (key.as_str(), value) {
+            ("@@locale", Node::Message(value)) => {
+                let parsed = value.parse().map_err(|_| {
+                    error(
+                        None,
+                        ParseErrorKind::Invalid(format!("invalid @@locale `{value}`")),
+                    )
+                })?;
+                locale = Some(parsed);
+            }
+            (key, Node::Namespace(entries)) if key.starts_with('@') => {
+                metadata.insert(&key[1..], entries.as_slice());
+            }
+            _ => {}
+        }
+    }
+
+    let mut resource = Resource::new();
+    for (id, value) in &entries {
+        if id.starts_with('@') {
+            continue;
+        }
+        let Node::Message(source) = value else {
+            return Err(error(
+                Some(id),
+                ParseErrorKind::Invalid("expected a message".into()),
+            ));
+        };
+        let message =
+            mf1::parse(source).map_err(|e| error(Some(id), ParseErrorKind::Message(e)))?;
+        let metadata = metadata.get(id.as_str()).copied().unwrap_or_default();
+        resource.sections[0].entries.push(Entry {
+            key: id.clone(),
+            comment: field(metadata, "description").map(Into::into),
+            message: annotate(
+                message,
+                &placeholders(metadata).map_err(|e| error(Some(id), e))?,
+            ),
+        });
+    }
+    Ok((locale, resource))
+}
+
+/// Writes the messages of a resource to an ARB file for `locale`, with the placeholders of each
+/// message and their types in its metadata.
+///
+/// # Errors
+/// Returns an error for the first message that can't be written, like one with markup or an id
+/// that isn't a Dart identifier.
+pub fn serialize(resource: &Resource, locale: &Locale) -> Result<String, ExportError> {
+    let mut items = vec![format!("  \"@@locale\": {}", string(&locale.to_string()))];
+    for (id, entry) in resource.entries() {
+        let error = |(node, kind): Problem| ExportError {
+            id: id.clone(),
+            node,
+            kind,
+        };
+        if !is_identifier(&id) {
+            return Err(error((id.clone(), ExportErrorKind::Name)));
+        }
+        let (message, placeholders) =
+            strip(&resolve(&entry.message).map_err(error)?).map_err(error)?;
+        let source =
+            mf1::serialize(&message).map_err(|e| error((e.node, export_error_kind(e.kind))))?;
+        items.push(format!("  {}: {}", string(&id), string(&source)));
+
+        let mut fields = Vec::new();
+        if let Some(comment) = &entry.comment {
+            fields.push(format!("    \"description\": {}", string(comment)));
+        }
+        if !placeholders.is_empty() {
+            let placeholders: Vec<String> = placeholders
+                .iter()
+                .map(|(name, kind, format)| {
+                    let mut properties = format!("        \"type\": {}", string(kind));
+                    if let Some(format) = format {
+                        properties.push_str(&format!(",\n        \"format\": {}", string(format)));
+                    }
+                    format!("      {}: {{\n{properties}\n      }}", string(name))
+                })
+                .collect();
+            fields.push(format!(
+                "    \"placeholders\": {{\n{}\n    }}",
+                placeholders.join(",\n")
+            ));
+        }
+        if !fields.is_empty() {
+            items.push(format!(
+                "  {}: {{\n{}\n  }}",
+                string(&format!("@{id}")),
+                fields.join(",\n")
+            ));
+        }
+    }
+    Ok(format!("{{\n{}\n}}\n", items.join(",\n")))
+}
+
+fn string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+fn is_identifier(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn field<'n>(entries: &'n [(String, Node)], name: &str) -> Option<&'n str> {
+    entries.iter().find_map(|(key, value)| match value {
+        Node::Message(value) if key == name => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+/// The annotations for the placeholders of a message, by their names. A `DateTime` placeholder
+/// needs one of the formats that have a function of their own.
+fn placeholders(
+    metadata: &[(String, Node)],
+) -> Result<BTreeMap<&str, FunctionAnnotation>, ParseErrorKind> {
+    let mut annotations = BTreeMap::new();
+    let placeholders = metadata.iter().find_map(|(key, value)| match value {
+        Node::Namespace(placeholders) if key == "placeholders" => Some(placeholders),
+        _ => None,
+    });
+    for (name, properties) in placeholders.into_iter().flatten() {
+        let Node::Namespace(properties) = properties else {
+            continue;
+        };
+        let function = match (field(properties, "type"), field(properties, "format")) {
+            (Some("int"), _) => "integer",
+            (Some("num" | "double"), _) => "number",
+            (Some("DateTime"), Some("yMd")) => "date",
+            (Some("DateTime"), Some("jm")) => "time",
+            (Some("DateTime"), format) => {
+                return Err(ParseErrorKind::Invalid(format!(
+                    "unsupported DateTime format `{}` of placeholder `{name}`",
+                    format.unwrap_or_default()
+                )))
+            }
+            _ => continue,
+        };
+        annotations.insert(name.as_str(), tree::function(function, &[]));
+    }
+    Ok(annotations)
+}
+
+/// Adds the annotations for the types of placeholders to variables without one
+fn annotate(message: Message, annotations: &BTreeMap<&str, FunctionAnnotation>) -> Message {
+    let pattern = |pattern: Vec<PatternElement>| -> Vec<PatternElement> {
+        pattern
+            .into_iter()
+            .map(|element| match element {
+                PatternElement::Expression(Expression::Variable(mut expression)) => {
+                    if expression.annotation.is_none() {
+                        expression.annotation = annotations
+                            .get(expression.arg.name.as_str())
+                            .cloned()
+                            .map(Annotation::Function);
+                    }
+                    PatternElement::Expression(Expression::Variable(expression))
+                }
+                element => element,
+            })
+            .collect()
+    };
+    match message {
+        Message::Pattern(message) => Message::Pattern(PatternMessage {
+            declarations: message.declarations,
+            pattern: pattern(message.pattern),
+        }),
+        Message::Select(message) => Message::Select(SelectMessage {
+            declarations: message.declarations,
+            selectors: message.selectors,
+            variants: message
+                .variants
+                .into_iter()
+                .map(|variant| Variant {
+                    keys: variant.keys,
+                    value: pattern(variant.value),
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// A placeholder with its type and format
+type Placeholder = (String, &'static str, Option<&'static str>);
+
+/// Takes the annotations off the placeholders of a resolved message, because Flutter formats them
+/// by their type. Returns the placeholders in the order they are used, selectors first.
+fn strip(message: &Message) -> Result<(Message, Vec<Placeholder>), Problem> {
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+    let mut add = |expression: &Expression| -> Result<(), Problem> {
+        let Expression::Variable(variable) = expression else {
+            return Ok(());
+        };
+        if placeholders
+            .iter()
+            .any(|(name, _, _)| *name == variable.arg.name)
+        {
+            return Ok(());
+        }
+        let (kind, format) =
+            match function(variable.annotation.as_ref()).map(|function| function.name.as_str()) {
+                None | Some("string") => ("String", None),
+                Some("integer") => ("int", None),
+                Some("number") => ("num", None),
+                Some("date") => ("DateTime", Some("yMd")),
+                Some("time") => ("DateTime", Some("jm")),
+                Some(name) => {
+                    return Err((
+                        expression.to_string(),
+                        ExportErrorKind::Function { name: name.into() },
+                    ))
+                }
+            };
+        placeholders.push((variable.arg.name.clone(), kind, format));
+        Ok(())
+    };
+
+    let mut pattern = |pattern: &[PatternElement]| -> Result<Vec<PatternElement>, Problem> {
+        pattern
+            .iter()
+            .map(|element| match element {
+                PatternElement::Expression(expression @ Expression::Variable(variable)) => {
+                    let options = function(variable.annotation.as_ref())
+                        .map_or(&[][..], |function| &function.options);
+                    if let Some(option) = options.first() {
+                        let name = option.name.clone();
+                        return Err((expression.to_string(), ExportErrorKind::Option { name }));
+                    }
+                    add(expression)?;
+                    Ok(PatternElement::Expression(tree::variable(
+                        variable.arg.name.clone(),
+                        None,
+                    )))
+                }
+                element => Ok(element.clone()),
+            })
+            .collect()
+    };
+
+    let message = match message {
+        Message::Pattern(message) => Message::Pattern(PatternMessage {
+            declarations: Vec::new(),
+            pattern: pattern(&message.pattern)?,
+        }),
+        Message::Select(message) => {
+            let variants = message
+                .variants
+                .iter()
+                .map(|variant| {
+                    Ok(Variant {
+                        keys: variant.keys.clone(),
+                        value: pattern(&variant.value)?,
+                    })
+                })
+                .collect::<Result<_, Problem>>()?;
+            Message::Select(SelectMessage {
+                declarations: Vec::new(),
+                selectors: message.selectors.clone(),
+                variants,
+            })
+        }
+    };
+    // Selectors come first, like they do for the positions of placeholders in other formats
+    if let Message::Select(select) = &message {
+        let mut selected: Vec<Placeholder> = Vec::new();
+        for selector in &select.selectors {
+            if let Expression::Variable(variable) = selector {
+                let kind = match function(variable.annotation.as_ref())
+                    .map(|function| function.name.as_str())
+                {
+                    Some("integer") => "int",
+                    Some("number") => "num",
+                    _ => "String",
+                };
+                if !selected
+                    .iter()
+                    .any(|(name, _, _)| *name == variable.arg.name)
+                {
+                    selected.push((variable.arg.name.clone(), kind, None));
+                }
+            }
+        }
+        placeholders
+            .retain(|(name, _, _)| selected.iter().all(|(selector, _, _)| selector != name));
+        selected.append(&mut placeholders);
+        placeholders = selected;
+    }
+    Ok((message, placeholders))
+}
+
+fn export_error_kind(kind: mf1::ExportErrorKind) -> ExportErrorKind {
+    match kind {
+        mf1::ExportErrorKind::Markup => ExportErrorKind::Markup,
+        mf1::ExportErrorKind::Function { name } => ExportErrorKind::Function { name },
+        mf1::ExportErrorKind::Option { name } => ExportErrorKind::Option { name },
+        mf1::ExportErrorKind::Unsupported => ExportErrorKind::Unsupported,
+        mf1::ExportErrorKind::Operand => ExportErrorKind::Operand,
+        mf1::ExportErrorKind::Selector => ExportErrorKind::Selector,
+        mf1::ExportErrorKind::Name => ExportErrorKind::Name,
+        mf1::ExportErrorKind::Key => ExportErrorKind::Key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(source: &str) -> Message {
+        source.parse().expect("valid message")
+    }
+
+    #[test]
+    fn it_parses_messages_with_typed_placeholders() {
+        let source = r#"{
+            "@@locale": "de",
+            "@@last_modified": "2024-01-01",
+            "items": "{count, plural, one{Ein Artikel} other{{count} Artikel}} seit {since}",
+            "@items": {
+                "description": "The items in the cart",
+                "placeholders": {
+                    "count": { "type": "int" },
+                    "since": { "type": "DateTime", "format": "yMd" }
+                }
+            },
+            "hello": "Hallo {name}"
+        }"#;
+        let (locale, resource) = parse(source).expect("valid ARB");
+        assert_eq!(locale, Some("de".parse().expect("valid locale")));
+        let entries: Vec<_> = resource.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, "items");
+        assert_eq!(
+            entries[0].1.comment.as_deref(),
+            Some("The items in the cart")
+        );
+        assert_eq!(
+            entries[0].1.message,
+            message(
+                ".match {$count :number}\n\
+                 one {{Ein Artikel seit {$since :date}}}\n\
+                 * {{{$count :integer} Artikel seit {$since :date}}}"
+            )
+        );
+        assert_eq!(entries[1].1.message, message("Hallo {$name}"));
+    }
+
+    #[test]
+    fn it_writes_messages_and_placeholders() {
+        let mut resource = Resource::new();
+        for (key, comment, source) in [
+            ("greeting", None, "Hello {$name}"),
+            (
+                "items",
+                Some("The items in the cart"),
+                ".input {$count :integer}\n.local $when = {$since :time}\n.match {$count}\n\
+                 one {{One item since {$when}}}\n* {{{$count} items since {$when}}}",
+            ),
+        ] {
+            resource.sections[0].entries.push(Entry {
+                key: key.into(),
+                comment: comment.map(Into::into),
+                message: message(source),
+            });
+        }
+        let written =
+            serialize(&resource, &"en".parse().expect("valid locale")).expect("exportable");
+        assert_eq!(
+            written,
+            r#"{
+  "@@locale": "en",
+  "greeting": "Hello {name}",
+  "@greeting": {
+    "placeholders": {
+      "name": {
+        "type": "String"
+      }
+    }
+  },
+  "items": "{count, plural, one {One item since {since}} other {{count} items since {since}}}",
+  "@items": {
+    "description": "The items in the cart",
+    "placeholders": {
+      "count": {
+        "type": "int"
+      },
+      "since": {
+        "type": "DateTime",
+        "format": "jm"
+      }
+    }
+  }
+}
+"#
+        );
+        let (_, parsed) = parse(&written).expect("valid ARB");
+        let entries: Vec<_> = parsed.entries().collect();
+        assert_eq!(entries[0].1.message, message("Hello {$name}"));
+        assert_eq!(
+            entries[1].1.comment.as_deref(),
+            Some("The items in the cart")
+        );
+    }
+
+    #[test]
+    fn it_reports_messages_it_cannot_read_or_write() {
+        let error = parse(r#"{"broken": "{count, plural, one{x}"}"#).expect_err("invalid message");
+        assert_eq!(error.id.as_deref(), Some("broken"));
+        assert!(matches!(error.kind, ParseErrorKind::Message(_)));
+        assert!(matches!(
+            parse("[]").map_err(|e| e.kind),
+            Err(ParseErrorKind::Invalid(_))
+        ));
+        let error = parse(
+            r#"{
+                "due": "Due {when}",
+                "@due": { "placeholders": { "when": { "type": "DateTime", "format": "MMMd" } } }
+            }"#,
+        )
+        .expect_err("unsupported format");
+        assert_eq!(error.id.as_deref(), Some("due"));
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::Invalid(
+                "unsupported DateTime format `MMMd` of placeholder `when`".into()
+            )
+        );
+
+        let export = |key: &str, source: &str| {
+            let mut resource = Resource::new();
+            resource.sections[0].entries.push(Entry {
+                key: key.into(),
+                comment: None,
+                message: message(source),
+            });
+            serialize(&resource, &Locale::und()).map_err(|e| e.kind)
+        };
+        assert_eq!(export("cart.items", "Items"), Err(ExportErrorKind::Name));
+        assert_eq!(export("link", "{#a}Link{/a}"), Err(ExportErrorKind::Markup));
+        assert_eq!(
+            export("when", "{$when :datetime}"),
+            Err(ExportErrorKind::Function {
+                name: "datetime".into()
+            })
+        );
+    }
+}
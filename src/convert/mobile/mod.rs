@@ -0,0 +1,239 @@
+//! Converts between MessageFormat 2 resources and the string resources of mobile apps:
+//!
+//! - [`android`]: `strings.xml` files with `<string>` and `<plurals>` elements
+//! - [`apple`]: `.strings` files, and `.stringsdict` files for plurals
+//! - [`arb`]: the ARB files of Flutter, behind the `json` feature
+//!
+//! Android and Apple format strings have positional placeholders like `%1$s`. Both keep the names
+//! of the variables where their formats allow it: in `<xliff:g id="name">` elements on Android,
+//! and in the names of `.stringsdict` variables like `%#@count@`. Other placeholders are numbered
+//! in the order their variables are first used, and are imported as `$arg1`, `$arg2` and so on.
+//! ARB placeholders have names, which are the names of the variables.
+//!
+//! The plural selection of these formats becomes `.match` on a `:number` or `:integer` variable,
+//! and declarations are resolved into the expressions that use them when writing.
+
+pub mod android;
+pub mod apple;
+#[cfg(feature = "json")]
+pub mod arb;
+mod printf;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::convert::scope::{Scope, Unresolvable, Value};
+use crate::convert::tree::{push_text, variable};
+use crate::data_model::elements::{
+    Annotation, FunctionAnnotation, Message, OptionValue, PatternElement, PatternMessage,
+    SelectMessage, Variant,
+};
+use crate::data_model::errors::SyntaxError;
+
+/// A resource file that can't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line of the error, if it is known
+    pub line: Option<usize>,
+    /// The id of the message
+    pub id: Option<String>,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The file isn't well-formed XML, JSON or `.strings` syntax
+    Syntax(String),
+    /// The file is well-formed, but not a resource file of the format
+    Invalid(String),
+    /// A plural form that isn't a plural category
+    Quantity(String),
+    /// An ARB message that isn't valid MessageFormat 1
+    Message(SyntaxError),
+    /// Plurals that are nested too deeply or have too many combinations to become the variants of
+    /// a message
+    TooComplex(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{line}: ")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, "{id}: ")?;
+        }
+        match &self.kind {
+            ParseErrorKind::Syntax(reason)
+            | ParseErrorKind::Invalid(reason)
+            | ParseErrorKind::TooComplex(reason) => f.write_str(reason),
+            ParseErrorKind::Quantity(quantity) => write!(f, "{quantity} is not a plural category"),
+            ParseErrorKind::Message(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// A message that can't be written to a resource file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportError {
+    /// The id of the message
+    pub id: String,
+    /// The part of the message, in MessageFormat 2 syntax
+    pub node: String,
+    pub kind: ExportErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportErrorKind {
+    /// Markup, in a format without it
+    Markup,
+    /// A function without a placeholder equivalent, like `:datetime` on Android
+    Function { name: String },
+    /// An option without a placeholder equivalent
+    Option { name: String },
+    /// Reserved or private-use syntax
+    Unsupported,
+    /// A literal that is formatted with a function, or a function without an operand
+    Operand,
+    /// A selector that the format can't express, like more than one selector on Android
+    Selector,
+    /// A variant key that isn't a plural category
+    Key,
+    /// An id or variable name that isn't valid in the format
+    Name,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: ", self.id, self.node)?;
+        match &self.kind {
+            ExportErrorKind::Markup => f.write_str("the format has no markup"),
+            ExportErrorKind::Function { name } => {
+                write!(f, "the function :{name} has no placeholder equivalent")
+            }
+            ExportErrorKind::Option { name } => {
+                write!(f, "the option {name} has no placeholder equivalent")
+            }
+            ExportErrorKind::Unsupported => f.write_str("reserved syntax can't be resolved"),
+            ExportErrorKind::Operand => {
+                f.write_str("a function without a variable can't be resolved")
+            }
+            ExportErrorKind::Selector => f.write_str("the format can't select on this"),
+            ExportErrorKind::Key => f.write_str("not a plural category the format has a form for"),
+            ExportErrorKind::Name => f.write_str("not a valid name in the format"),
+        }
+    }
+}
+
+impl core::error::Error for ExportError {}
+
+/// A part of a message that can't be exported, and why
+type Problem = (String, ExportErrorKind);
+
+const fn unresolvable(node: String, reason: Unresolvable) -> Problem {
+    let kind = match reason {
+        Unresolvable::Operand => ExportErrorKind::Operand,
+        Unresolvable::Unsupported => ExportErrorKind::Unsupported,
+    };
+    (node, kind)
+}
+
+/// The message without declarations: they are resolved into the expressions that use them, which
+/// are variables with their annotations, or text. Placeholders without an annotation that are also
+/// selectors get the annotation of the selector, so a count is formatted as a number.
+fn resolve(message: &Message) -> Result<Message, Problem> {
+    let declarations = match message {
+        Message::Pattern(message) => &message.declarations,
+        Message::Select(message) => &message.declarations,
+    };
+    let scope = Scope::new(declarations).map_err(|(node, reason)| unresolvable(node, reason))?;
+
+    let mut selected: Vec<(String, Option<FunctionAnnotation>)> = Vec::new();
+    let mut selectors = Vec::new();
+    if let Message::Select(message) = message {
+        for selector in &message.selectors {
+            match scope.resolve(selector) {
+                Ok(Value::Variable { name, annotation }) => {
+                    selected.push((name.clone(), annotation.clone()));
+                    selectors.push(variable(name, annotation));
+                }
+                Ok(Value::Text(_)) => {
+                    return Err((selector.to_string(), ExportErrorKind::Selector))
+                }
+                Err(reason) => return Err(unresolvable(selector.to_string(), reason)),
+            }
+        }
+    }
+
+    let pattern = |pattern: &[PatternElement]| -> Result<Vec<PatternElement>, Problem> {
+        let mut resolved = Vec::new();
+        for element in pattern {
+            match element {
+                PatternElement::Literal(text) => push_text(&mut resolved, text),
+                PatternElement::Markup(_) => resolved.push(element.clone()),
+                PatternElement::Expression(expression) => match scope.resolve(expression) {
+                    Ok(Value::Text(text)) => push_text(&mut resolved, &text),
+                    Ok(Value::Variable { name, annotation }) => {
+                        let annotation = annotation.or_else(|| {
+                            selected
+                                .iter()
+                                .find(|(selector, _)| *selector == name)
+                                .and_then(|(_, annotation)| annotation.clone())
+                        });
+                        resolved.push(PatternElement::Expression(variable(name, annotation)));
+                    }
+                    Err(reason) => return Err(unresolvable(expression.to_string(), reason)),
+                },
+            }
+        }
+        Ok(resolved)
+    };
+
+    Ok(match message {
+        Message::Pattern(message) => Message::Pattern(PatternMessage {
+            declarations: Vec::new(),
+            pattern: pattern(&message.pattern)?,
+        }),
+        Message::Select(message) => Message::Select(SelectMessage {
+            declarations: Vec::new(),
+            selectors,
+            variants: message
+                .variants
+                .iter()
+                .map(|variant| {
+                    Ok(Variant {
+                        keys: variant.keys.clone(),
+                        value: pattern(&variant.value)?,
+                    })
+                })
+                .collect::<Result<_, Problem>>()?,
+        }),
+    })
+}
+
+/// The function annotation of a resolved expression
+const fn function(annotation: Option<&Annotation>) -> Option<&FunctionAnnotation> {
+    match annotation {
+        Some(Annotation::Function(function)) => Some(function),
+        _ => None,
+    }
+}
+
+/// Whether a resolved selector selects the cardinal plural category of a number
+fn is_plural(annotation: Option<&FunctionAnnotation>) -> bool {
+    annotation.is_some_and(|function| {
+        matches!(function.name.as_str(), "number" | "integer")
+            && function.options.iter().all(|option| {
+                option.name != "select"
+                    || matches!(&option.value, OptionValue::Literal(literal) if literal.value == "plural")
+            })
+    })
+}
+
+/// Escapes a comment for XML, where it can't contain `--`
+fn xml_comment(comment: &str) -> String {
+    comment.replace("--", "- -")
+}
@@ -0,0 +1,386 @@
+//! The printf-style placeholders of Android and Apple resources, like `%1$s`, `%d` and `%@`.
+//! Placeholders are positional, so the variables of a message are numbered in the order they are
+//! used.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::ExportErrorKind;
+use crate::convert::tree::{function, variable};
+use crate::data_model::elements::{
+    Expression, FunctionAnnotation, Message, OptionValue, PatternElement,
+};
+
+/// A piece of a format string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece {
+    Text(String),
+    Argument(Argument),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    /// The 1-based position, if it is given like in `%2$s`
+    pub position: Option<usize>,
+    /// The digits after the decimal point, like in `%.2f`
+    pub precision: Option<usize>,
+    pub conversion: Conversion,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// `%s` and `%@`
+    String,
+    /// `%d`, `%i` and `%u`, with any length modifier like in `%ld`
+    Integer,
+    /// `%f`
+    Float,
+    /// A number written as it is: `%s` on Android and `%g` on Apple
+    Number,
+    /// A variable of a `.stringsdict` entry, like `%#@count@`
+    Variable(String),
+}
+
+/// The conversions of a platform
+pub struct Dialect {
+    pub string: &'static str,
+    pub integer: &'static str,
+    pub number: &'static str,
+}
+
+pub const ANDROID: Dialect = Dialect {
+    string: "s",
+    integer: "d",
+    number: "s",
+};
+
+pub const APPLE: Dialect = Dialect {
+    string: "@",
+    integer: "ld",
+    number: "g",
+};
+
+/// Splits a format string into text and placeholders. `%%` is a percent sign, and a `%` that
+/// doesn't start a supported placeholder is kept as text, like platforms do for strings that
+/// aren't formatted.
+pub fn split(format: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('%') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(tail) = rest.strip_prefix("%%") {
+            text.push('%');
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("%n") {
+            text.push('\n');
+            rest = tail;
+        } else if let Some((argument, length)) = argument(&rest[1..]) {
+            if !text.is_empty() {
+                pieces.push(Piece::Text(core::mem::take(&mut text)));
+            }
+            pieces.push(Piece::Argument(argument));
+            rest = &rest[1 + length..];
+        } else {
+            text.push('%');
+            rest = &rest[1..];
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    pieces
+}
+
+/// Reads a placeholder after its `%`, and returns its length
+fn argument(source: &str) -> Option<(Argument, usize)> {
+    let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let mut length = 0;
+
+    let count = digits(source);
+    let mut position = None;
+    if count > 0 && source[count..].starts_with('$') {
+        position = Some(
+            source[..count]
+                .parse()
+                .ok()
+                .filter(|position| *position > 0)?,
+        );
+        length = count + 1;
+    }
+
+    if let Some(variable) = source[length..].strip_prefix("#@") {
+        let end = variable.find('@')?;
+        let name = &variable[..end];
+        if name.is_empty() {
+            return None;
+        }
+        let argument = Argument {
+            position,
+            precision: None,
+            conversion: Conversion::Variable(name.into()),
+        };
+        return Some((argument, length + 2 + end + 1));
+    }
+
+    let mut precision = None;
+    if let Some(rest) = source[length..].strip_prefix('.') {
+        let count = digits(rest);
+        precision = Some(rest[..count].parse().ok()?);
+        length += 1 + count;
+    }
+    let modifiers = source[length..]
+        .find(|c| !matches!(c, 'h' | 'l' | 'q' | 'z' | 't' | 'j' | 'L'))
+        .unwrap_or(source.len() - length);
+    length += modifiers;
+
+    let conversion = match source[length..].chars().next()? {
+        's' | 'S' | '@' => Conversion::String,
+        'd' | 'i' | 'u' => Conversion::Integer,
+        'f' | 'F' => Conversion::Float,
+        'g' | 'G' => Conversion::Number,
+        _ => return None,
+    };
+    let argument = Argument {
+        position,
+        precision,
+        conversion,
+    };
+    Some((argument, length + 1))
+}
+
+impl Argument {
+    /// The placeholder for a variable at `position` with a resolved annotation
+    ///
+    /// # Errors
+    /// Returns the reason if the annotation has no placeholder equivalent.
+    pub fn new(
+        position: usize,
+        annotation: Option<&FunctionAnnotation>,
+    ) -> Result<Self, ExportErrorKind> {
+        let mut argument = Self {
+            position: Some(position),
+            precision: None,
+            conversion: Conversion::String,
+        };
+        let Some(function) = annotation else {
+            return Ok(argument);
+        };
+        let (mut minimum, mut maximum) = (None, None);
+        for option in &function.options {
+            let value = match &option.value {
+                OptionValue::Literal(literal) => literal.value.parse::<usize>().ok(),
+                OptionValue::Variable(_) => None,
+            };
+            match option.name.as_str() {
+                "select" => {}
+                "minimumFractionDigits" if function.name == "number" && value.is_some() => {
+                    minimum = value
+                }
+                "maximumFractionDigits" if function.name == "number" && value.is_some() => {
+                    maximum = value
+                }
+                name => return Err(ExportErrorKind::Option { name: name.into() }),
+            }
+        }
+        argument.conversion = match function.name.as_str() {
+            "string" => Conversion::String,
+            "integer" => Conversion::Integer,
+            // Only a fixed number of fraction digits can be written as a precision
+            "number" if minimum.is_some() && minimum == maximum => {
+                argument.precision = minimum;
+                Conversion::Float
+            }
+            "number" if minimum.is_none() && maximum.is_none() => Conversion::Number,
+            "number" => {
+                let name = if minimum.is_some() {
+                    "minimumFractionDigits"
+                } else {
+                    "maximumFractionDigits"
+                };
+                return Err(ExportErrorKind::Option { name: name.into() });
+            }
+            name => return Err(ExportErrorKind::Function { name: name.into() }),
+        };
+        Ok(argument)
+    }
+
+    /// Writes the placeholder with the conversions of a platform
+    pub fn write(&self, dialect: &Dialect) -> String {
+        let mut out = String::from("%");
+        if let Some(position) = self.position {
+            out.push_str(&format!("{position}$"));
+        }
+        if let Some(precision) = self.precision {
+            out.push_str(&format!(".{precision}"));
+        }
+        match &self.conversion {
+            Conversion::String => out.push_str(dialect.string),
+            Conversion::Integer => out.push_str(dialect.integer),
+            Conversion::Float => out.push('f'),
+            Conversion::Number => out.push_str(dialect.number),
+            Conversion::Variable(name) => out.push_str(&format!("#@{name}@")),
+        }
+        out
+    }
+
+    /// The expression for the placeholder, formatting the variable `name`
+    pub fn expression(&self, name: String) -> Expression {
+        let annotation = match (&self.conversion, self.precision) {
+            (Conversion::String | Conversion::Variable(_), _) => None,
+            (Conversion::Integer, _) => Some(function("integer", &[])),
+            (Conversion::Float, None) | (Conversion::Number, _) => Some(function("number", &[])),
+            (Conversion::Float, Some(precision)) => {
+                let digits = precision.to_string();
+                Some(function(
+                    "number",
+                    &[
+                        ("minimumFractionDigits", &digits),
+                        ("maximumFractionDigits", &digits),
+                    ],
+                ))
+            }
+        };
+        variable(name, annotation)
+    }
+}
+
+/// The names of the variables of a message by their position
+#[derive(Debug, Default)]
+pub struct Arguments(Vec<String>);
+
+impl Arguments {
+    /// Numbers the variables of a message with its declarations resolved: the selectors first,
+    /// then the placeholders in the order they are used.
+    pub fn new(message: &Message) -> Self {
+        let mut arguments = Self::default();
+        let patterns: Vec<&[PatternElement]> = match message {
+            Message::Pattern(message) => alloc::vec![message.pattern.as_slice()],
+            Message::Select(message) => {
+                for selector in &message.selectors {
+                    if let Expression::Variable(variable) = selector {
+                        arguments.add(&variable.arg.name);
+                    }
+                }
+                message
+                    .variants
+                    .iter()
+                    .map(|variant| variant.value.as_slice())
+                    .collect()
+            }
+        };
+        for element in patterns.into_iter().flatten() {
+            if let PatternElement::Expression(Expression::Variable(variable)) = element {
+                arguments.add(&variable.arg.name);
+            }
+        }
+        arguments
+    }
+
+    fn add(&mut self, name: &str) {
+        if !self.0.iter().any(|existing| existing == name) {
+            self.0.push(name.into());
+        }
+    }
+
+    /// The 1-based position of a variable
+    pub fn position(&self, name: &str) -> usize {
+        self.0
+            .iter()
+            .position(|existing| existing == name)
+            .map_or(0, |index| index + 1)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn argument(
+        position: Option<usize>,
+        precision: Option<usize>,
+        conversion: Conversion,
+    ) -> Piece {
+        Piece::Argument(Argument {
+            position,
+            precision,
+            conversion,
+        })
+    }
+
+    #[test]
+    fn it_splits_format_strings() {
+        assert_eq!(
+            split("%1$s has %2$.2f%% of %ld, %#@count@ and %2$#@files@ at 100% %x"),
+            vec![
+                argument(Some(1), None, Conversion::String),
+                Piece::Text(" has ".into()),
+                argument(Some(2), Some(2), Conversion::Float),
+                Piece::Text("% of ".into()),
+                argument(None, None, Conversion::Integer),
+                Piece::Text(", ".into()),
+                argument(None, None, Conversion::Variable("count".into())),
+                Piece::Text(" and ".into()),
+                argument(Some(2), None, Conversion::Variable("files".into())),
+                Piece::Text(" at 100% %x".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_writes_placeholders_for_annotations() {
+        let write = |annotation: &str| {
+            let expression: Message = format!("{{$n {annotation}}}")
+                .parse()
+                .expect("valid message");
+            let Message::Pattern(message) = expression else {
+                unreachable!()
+            };
+            let PatternElement::Expression(Expression::Variable(variable)) = &message.pattern[0]
+            else {
+                unreachable!()
+            };
+            Argument::new(2, super::super::function(variable.annotation.as_ref()))
+                .map(|argument| (argument.write(&ANDROID), argument.write(&APPLE)))
+        };
+        assert_eq!(write(""), Ok(("%2$s".into(), "%2$@".into())));
+        assert_eq!(write(":integer"), Ok(("%2$d".into(), "%2$ld".into())));
+        assert_eq!(write(":number"), Ok(("%2$s".into(), "%2$g".into())));
+        assert_eq!(
+            write(":number minimumFractionDigits=1 maximumFractionDigits=1"),
+            Ok(("%2$.1f".into(), "%2$.1f".into()))
+        );
+        assert_eq!(
+            write(":number minimumFractionDigits=2"),
+            Err(ExportErrorKind::Option {
+                name: "minimumFractionDigits".into()
+            })
+        );
+        assert_eq!(
+            write(":number maximumFractionDigits=2"),
+            Err(ExportErrorKind::Option {
+                name: "maximumFractionDigits".into()
+            })
+        );
+        assert_eq!(
+            write(":date"),
+            Err(ExportErrorKind::Function {
+                name: "date".into()
+            })
+        );
+        assert_eq!(
+            write(":number useGrouping=never"),
+            Err(ExportErrorKind::Option {
+                name: "useGrouping".into()
+            })
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Conversions between MessageFormat 2 messages and other message formats.
+
+pub mod fluent;
+pub mod gettext;
+pub mod mf1;
+pub mod mobile;
+mod scope;
+mod tree;
+pub mod xliff;
+mod xml;
@@ -0,0 +1,112 @@
+//! Resolves declarations into the expressions that use them, for formats without declarations.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use crate::data_model::elements::{Annotation, Declaration, Expression, FunctionAnnotation};
+
+/// What an expression stands for once declarations are resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    Variable {
+        name: String,
+        annotation: Option<FunctionAnnotation>,
+    },
+}
+
+/// Why a declaration or an expression can't be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unresolvable {
+    /// A literal that is formatted with a function other than `:string`, or a function without an
+    /// operand
+    Operand,
+    /// Reserved or private-use syntax
+    Unsupported,
+}
+
+/// The values of the declared variables
+#[derive(Debug, Default)]
+pub struct Scope(BTreeMap<String, Value>);
+
+impl Scope {
+    /// Resolves the declarations of a message in order.
+    ///
+    /// # Errors
+    /// Returns the declaration or expression that can't be resolved, in MessageFormat 2 syntax.
+    pub fn new(declarations: &[Declaration]) -> Result<Self, (String, Unresolvable)> {
+        let mut scope = Self::default();
+        for declaration in declarations {
+            let (name, value) = match declaration {
+                Declaration::Input(input) => (
+                    &input.name,
+                    scope.resolve(&Expression::Variable(input.value.clone())),
+                ),
+                Declaration::Local(local) => (&local.name, scope.resolve(&local.value)),
+                Declaration::UnsupportedStatement(_) => {
+                    return Err((declaration.to_string(), Unresolvable::Unsupported))
+                }
+            };
+            let value = value.map_err(|reason| (declaration.to_string(), reason))?;
+            scope.0.insert(name.clone(), value);
+        }
+        Ok(scope)
+    }
+
+    /// Resolves an expression. An annotation on a variable with the same function as its
+    /// declaration inherits its options, like in formatting.
+    ///
+    /// # Errors
+    /// Returns why the expression has no value that other formats can express.
+    pub fn resolve(&self, expression: &Expression) -> Result<Value, Unresolvable> {
+        let (base, annotation) = match expression {
+            Expression::Literal(literal) => {
+                (Value::Text(literal.arg.value.clone()), &literal.annotation)
+            }
+            Expression::Variable(variable) => {
+                let name = &variable.arg.name;
+                let base = self
+                    .0
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| Value::Variable {
+                        name: name.clone(),
+                        annotation: None,
+                    });
+                (base, &variable.annotation)
+            }
+            Expression::Function(_) => return Err(Unresolvable::Operand),
+            Expression::Unsupported(_) => return Err(Unresolvable::Unsupported),
+        };
+
+        match (base, annotation) {
+            (base, None) => Ok(base),
+            (_, Some(Annotation::Unsupported(_))) => Err(Unresolvable::Unsupported),
+            (Value::Text(text), Some(Annotation::Function(function)))
+                if function.name == "string" =>
+            {
+                Ok(Value::Text(text))
+            }
+            (Value::Text(_), Some(Annotation::Function(_))) => Err(Unresolvable::Operand),
+            (
+                Value::Variable {
+                    name,
+                    annotation: base,
+                },
+                Some(Annotation::Function(function)),
+            ) => {
+                let mut function = function.clone();
+                if let Some(base) = base.filter(|base| base.name == function.name) {
+                    let mut options = base.options;
+                    options.retain(|option| function.options.iter().all(|o| o.name != option.name));
+                    options.append(&mut function.options);
+                    function.options = options;
+                }
+                Ok(Value::Variable {
+                    name,
+                    annotation: Some(function),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Selection as a tree of selects nested in patterns, which is how most other message formats
+//! express it, while MessageFormat 2 has one list of selectors and a variant for each combination
+//! of their keys.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::data_model::elements::{
+    self, Annotation, Expression, FunctionAnnotation, Literal, Markup, Message, OptionValue,
+    PatternElement, PatternMessage, SelectMessage, VariableExpression, VariableRef, Variant,
+    VariantKey,
+};
+
+/// A pattern of a format that nests selects inside of patterns
+pub enum Node {
+    Text(String),
+    Expression(Expression),
+    Markup(Markup),
+    Select(Select),
+}
+
+pub struct Select {
+    /// An annotated expression
+    pub selector: Expression,
+    pub cases: Vec<Case>,
+}
+
+/// A case of a select and its pattern. The catch-all case has no key.
+pub type Case = (Option<String>, Vec<Node>);
+
+impl Select {
+    /// The case for `key`, or the catch-all case if there is none
+    fn case(&self, key: Option<&str>) -> &Case {
+        self.cases
+            .iter()
+            .find(|(case, _)| case.as_deref() == key)
+            .or_else(|| self.cases.iter().find(|(case, _)| case.is_none()))
+            .expect("every select has a catch-all case")
+    }
+}
+
+/// A variable expression
+pub fn variable(name: String, annotation: Option<FunctionAnnotation>) -> Expression {
+    Expression::Variable(VariableExpression {
+        arg: VariableRef { name },
+        annotation: annotation.map(Annotation::Function),
+        attributes: vec![],
+    })
+}
+
+/// A function annotation with literal options
+pub fn function(name: &str, options: &[(&str, &str)]) -> FunctionAnnotation {
+    FunctionAnnotation {
+        name: name.into(),
+        options: options
+            .iter()
+            .map(|(name, value)| elements::Option {
+                name: (*name).into(),
+                value: OptionValue::Literal(Literal {
+                    value: (*value).into(),
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// How deeply selects can be nested in the cases of other selects
+pub const MAX_DEPTH: usize = 16;
+
+/// How many variants the selects of a message can expand to
+pub const MAX_VARIANTS: usize = 4096;
+
+/// Nested selects that can't be turned into the variants of one message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpansionError {
+    /// The selects are nested more than [`MAX_DEPTH`] levels deep
+    Depth,
+    /// The selects have more than [`MAX_VARIANTS`] combinations of cases
+    Variants,
+}
+
+impl fmt::Display for ExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Depth => write!(f, "selects are nested more than {MAX_DEPTH} levels deep"),
+            Self::Variants => write!(
+                f,
+                "selects have more than {MAX_VARIANTS} combinations of cases"
+            ),
+        }
+    }
+}
+
+/// The case chosen for each selector: unset, `Some(None)` for the catch-all, or `Some(Some(key))`
+type Choice<'n> = Vec<Option<Pick<'n>>>;
+
+/// A case chosen for a selector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pick<'n> {
+    key: Option<&'n str>,
+    /// Whether the key was chosen by a nested select in the catch-all case of the same selector
+    refined: bool,
+}
+
+/// Turns the nested selects into the selectors and variants of one message
+pub fn message(nodes: &[Node]) -> Result<Message, ExpansionError> {
+    let mut selectors = Vec::new();
+    collect_selectors(nodes, &mut selectors, 0)?;
+    if selectors.is_empty() {
+        let (_, pattern) = expand(nodes, &selectors, vec![], 0)?.swap_remove(0);
+        return Ok(Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern,
+        }));
+    }
+
+    // The variants, and whether their keys come from refining a catch-all case
+    let mut variants: Vec<(Variant, bool)> = Vec::new();
+    for (choice, value) in expand(nodes, &selectors, vec![None; selectors.len()], 0)? {
+        let refined = choice.iter().flatten().any(|pick| pick.refined);
+        let keys: Vec<VariantKey> = choice
+            .into_iter()
+            .map(|pick| {
+                pick.and_then(|pick| pick.key)
+                    .map_or(VariantKey::Catchall, |key| {
+                        VariantKey::Literal(Literal { value: key.into() })
+                    })
+            })
+            .collect();
+        // A catch-all case can lead to the same keys as an explicit case, which takes precedence
+        // wherever it is in the source
+        match variants
+            .iter_mut()
+            .find(|(variant, _)| variant.keys == keys)
+        {
+            Some(existing) if existing.1 && !refined => {
+                *existing = (Variant { keys, value }, false)
+            }
+            Some(_) => {}
+            None => variants.push((Variant { keys, value }, refined)),
+        }
+    }
+    let variants = variants.into_iter().map(|(variant, _)| variant).collect();
+
+    Ok(Message::Select(SelectMessage {
+        declarations: vec![],
+        selectors: selectors.into_iter().cloned().collect(),
+        variants,
+    }))
+}
+
+/// The distinct selectors of all selects, in source order
+fn collect_selectors<'n>(
+    nodes: &'n [Node],
+    selectors: &mut Vec<&'n Expression>,
+    depth: usize,
+) -> Result<(), ExpansionError> {
+    if depth > MAX_DEPTH {
+        return Err(ExpansionError::Depth);
+    }
+    for node in nodes {
+        if let Node::Select(select) = node {
+            if !selectors.contains(&&select.selector) {
+                selectors.push(&select.selector);
+            }
+            for (_, nodes) in &select.cases {
+                collect_selectors(nodes, selectors, depth + 1)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The patterns that `nodes` can produce, with the cases that lead to them
+fn expand<'n>(
+    nodes: &'n [Node],
+    selectors: &[&Expression],
+    choice: Choice<'n>,
+    depth: usize,
+) -> Result<Vec<(Choice<'n>, Vec<PatternElement>)>, ExpansionError> {
+    if depth > MAX_DEPTH {
+        return Err(ExpansionError::Depth);
+    }
+    let mut results = vec![(choice, Vec::new())];
+    for node in nodes {
+        let mut expanded = Vec::new();
+        for (choice, mut pattern) in results {
+            match node {
+                Node::Text(text) => {
+                    push_text(&mut pattern, text);
+                    expanded.push((choice, pattern));
+                }
+                Node::Expression(expression) => {
+                    pattern.push(PatternElement::Expression(expression.clone()));
+                    expanded.push((choice, pattern));
+                }
+                Node::Markup(markup) => {
+                    pattern.push(PatternElement::Markup(markup.clone()));
+                    expanded.push((choice, pattern));
+                }
+                Node::Select(select) => {
+                    let index = selectors
+                        .iter()
+                        .position(|selector| **selector == select.selector)
+                        .expect("all selectors were collected");
+                    // A selector that is already decided on picks the same case again, but the
+                    // catch-all still allows any of the cases of a nested select
+                    let cases: Vec<_> = match choice[index] {
+                        Some(Pick { key: Some(key), .. }) => vec![select.case(Some(key))],
+                        _ => select.cases.iter().collect(),
+                    };
+                    for (key, nodes) in cases {
+                        let mut choice = choice.clone();
+                        match choice[index] {
+                            None => {
+                                choice[index] = Some(Pick {
+                                    key: key.as_deref(),
+                                    refined: false,
+                                });
+                            }
+                            Some(Pick { key: None, .. }) if key.is_some() => {
+                                choice[index] = Some(Pick {
+                                    key: key.as_deref(),
+                                    refined: true,
+                                });
+                            }
+                            Some(_) => {}
+                        }
+                        for (choice, tail) in expand(nodes, selectors, choice, depth + 1)? {
+                            let mut pattern = pattern.clone();
+                            for element in tail {
+                                match element {
+                                    PatternElement::Literal(text) => push_text(&mut pattern, &text),
+                                    element => pattern.push(element),
+                                }
+                            }
+                            expanded.push((choice, pattern));
+                        }
+                    }
+                }
+            }
+            if expanded.len() > MAX_VARIANTS {
+                return Err(ExpansionError::Variants);
+            }
+        }
+        results = expanded;
+    }
+    Ok(results)
+}
+
+/// Appends text, joining it with text at the end of the pattern
+pub fn push_text(pattern: &mut Vec<PatternElement>, text: &str) {
+    if let Some(PatternElement::Literal(last)) = pattern.last_mut() {
+        last.push_str(text);
+    } else {
+        pattern.push(PatternElement::Literal(text.into()));
+    }
+}
+
+/// The variants of a select message as nested selects
+#[derive(Debug, PartialEq, Eq)]
+pub enum Tree<'m> {
+    Pattern(&'m [PatternElement]),
+    Select {
+        /// The index of the selector
+        selector: usize,
+        cases: Vec<(&'m Literal, Self)>,
+        catchall: Box<Self>,
+    },
+}
+
+/// Nests the variants of a message, one level per selector. Levels where all variants have the
+/// catch-all key are left out.
+pub fn nest(message: &SelectMessage) -> Tree<'_> {
+    let variants: Vec<&Variant> = message.variants.iter().collect();
+    nest_level(&variants, 0, message.selectors.len())
+}
+
+fn nest_level<'m>(variants: &[&'m Variant], level: usize, levels: usize) -> Tree<'m> {
+    if level == levels {
+        // Among the variants that match, the one with the most specific keys is selected
+        let best = variants.iter().min_by_key(|variant| {
+            variant
+                .keys
+                .iter()
+                .map(|key| *key == VariantKey::Catchall)
+                .collect::<Vec<_>>()
+        });
+        return Tree::Pattern(best.map_or(&[], |variant| &variant.value));
+    }
+
+    let mut keys = Vec::new();
+    for variant in variants {
+        if let VariantKey::Literal(key) = &variant.keys[level] {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    let matching = |key: Option<&Literal>| -> Vec<&'m Variant> {
+        variants
+            .iter()
+            .copied()
+            .filter(|variant| match &variant.keys[level] {
+                VariantKey::Literal(literal) => Some(literal) == key,
+                VariantKey::Catchall => true,
+            })
+            .collect()
+    };
+
+    let catchall = nest_level(&matching(None), level + 1, levels);
+    if keys.is_empty() {
+        return catchall;
+    }
+    Tree::Select {
+        selector: level,
+        cases: keys
+            .into_iter()
+            .map(|key| (key, nest_level(&matching(Some(key)), level + 1, levels)))
+            .collect(),
+        catchall: Box::new(catchall),
+    }
+}
@@ -0,0 +1,759 @@
+//! Exports catalogs to XLIFF 2.0 documents for translation tools, and imports the translations.
+//! See: https://docs.oasis-open.org/xliff/xliff-core/v2.0/xliff-core-v2.0.html
+//!
+//! Each message becomes a `<unit>` named by its id. Expressions and markup are kept in the
+//! `<originalData>` of the unit and referenced with `<ph>` elements, and pairs of open and close
+//! markup become `<pc>` elements around their content, so translation tools show them as codes
+//! that translators move around but can't change.
+//!
+//! Select messages become a `<group>` with a unit for each combination of keys, or for each
+//! variant if there are too many combinations. Selectors on numbers get the plural categories of
+//! the target language, so translators fill in the `few` and `many` variants of Polish even if the
+//! source only has `one` and `*`. The declarations and selectors of a message, and the keys of each
+//! variant, are kept in `<mda:metadata>` elements of the metadata module:
+//!
+//! ```xml
+//! <group id="g1" name="items">
+//!   <mda:metadata>
+//!     <mda:metaGroup category="messageformat">
+//!       <mda:meta type="match">.match {$count :number}</mda:meta>
+//!     </mda:metaGroup>
+//!   </mda:metadata>
+//!   <unit id="u1" name="items">
+//!     <mda:metadata>
+//!       <mda:metaGroup category="messageformat">
+//!         <mda:meta type="keys">one</mda:meta>
+//!       </mda:metaGroup>
+//!     </mda:metadata>
+//!     <originalData>
+//!       <data id="d1">{$count}</data>
+//!     </originalData>
+//!     <segment>
+//!       <source><ph id="1" dataRef="d1" disp="{$count}"/> item</source>
+//!     </segment>
+//!   </unit>
+//! </group>
+//! ```
+//!
+//! [`parse`] rebuilds the messages from the targets of the units and validates them like
+//! [`Catalog::insert`].
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+
+use crate::catalog::{Catalog, CatalogError};
+use crate::convert::scope::{Scope, Value};
+use crate::convert::tree::MAX_VARIANTS;
+use crate::convert::xml::{self, escape, Element, Node};
+use crate::data_model::elements::{
+    Declaration, Expression, Literal, MarkupKind, Message, OptionValue, PatternElement,
+    SelectMessage, Variant, VariantKey,
+};
+use crate::format::plural::{self, PluralCategory, PluralRuleType};
+
+/// Writes the messages of `source` and their translations in `target` to an XLIFF 2.0 document.
+///
+/// The languages of the document are the locales of the catalogs, and an empty `target` catalog
+/// gives a document without translations.
+///
+/// A translation is only written if it has the same declarations and selectors as the source
+/// message, because they are kept with the source.
+pub fn serialize(source: &Catalog, target: &Catalog) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        language: target.locale().language(),
+        units: 0,
+        groups: 0,
+    };
+    writer.out.push_str(&format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" \
+         xmlns:mda=\"urn:oasis:names:tc:xliff:metadata:2.0\" version=\"2.0\" srcLang=\"{}\" trgLang=\"{}\">\n",
+        escape(&source.locale().to_string()),
+        escape(&target.locale().to_string())
+    ));
+    writer
+        .out
+        .push_str("  <file id=\"f1\" xml:space=\"preserve\">\n");
+    for (id, message) in source.iter() {
+        writer.message(id, message, target.get(id));
+    }
+    writer.out.push_str("  </file>\n</xliff>\n");
+    writer.out
+}
+
+/// An XLIFF document that can't be imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XliffError {
+    /// The document is not well-formed XML
+    Xml { line: usize, reason: String },
+    /// The document is not an XLIFF 2 document with the metadata written by [`serialize`]
+    Invalid(String),
+    /// A translated message is not valid
+    Message(CatalogError),
+}
+
+impl fmt::Display for XliffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::Invalid(reason) => write!(f, "invalid XLIFF document: {reason}"),
+            Self::Message(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for XliffError {}
+
+/// Reads the translations of an XLIFF 2.0 document into a catalog for its target language.
+/// Messages with units that aren't translated yet are left out.
+///
+/// # Errors
+/// Returns an error if the document isn't well-formed or misses the metadata of a message, or if a
+/// translated message is not valid.
+pub fn parse(document: &str) -> Result<Catalog, XliffError> {
+    let root = xml::parse(document).map_err(|(line, reason)| XliffError::Xml { line, reason })?;
+    if root.local_name() != "xliff" {
+        return Err(XliffError::Invalid(format!(
+            "unexpected root element <{}>",
+            root.name
+        )));
+    }
+    let language = root
+        .attribute("trgLang")
+        .ok_or_else(|| XliffError::Invalid("missing trgLang".into()))?;
+    let locale = language
+        .parse()
+        .map_err(|_| XliffError::Invalid(format!("invalid trgLang `{language}`")))?;
+
+    let mut catalog = Catalog::new(locale);
+    for file in root.elements("file") {
+        import(file, &mut catalog)?;
+    }
+    Ok(catalog)
+}
+
+struct Writer<'l> {
+    out: String,
+    /// The target language
+    language: &'l str,
+    units: usize,
+    groups: usize,
+}
+
+impl Writer<'_> {
+    fn message(&mut self, id: &str, message: &Message, translation: Option<&Message>) {
+        let message = match message {
+            Message::Pattern(message) => {
+                let translation = match translation {
+                    Some(Message::Pattern(translation))
+                        if translation.declarations == message.declarations =>
+                    {
+                        Some(translation.pattern.as_slice())
+                    }
+                    _ => None,
+                };
+                let declarations = declarations(&message.declarations);
+                let metadata = (!message.declarations.is_empty())
+                    .then_some(("declarations", declarations.as_str()));
+                self.unit(id, "    ", metadata, &message.pattern, translation);
+                return;
+            }
+            Message::Select(message) => message,
+        };
+        let translation = match translation {
+            Some(Message::Select(translation))
+                if translation.declarations == message.declarations
+                    && translation.selectors == message.selectors =>
+            {
+                Some(translation)
+            }
+            _ => None,
+        };
+
+        self.groups += 1;
+        let frame = SelectMessage {
+            variants: Vec::new(),
+            ..message.clone()
+        };
+        self.out.push_str(&format!(
+            "    <group id=\"g{}\" name=\"{}\">\n",
+            self.groups,
+            escape(id)
+        ));
+        write_metadata(&mut self.out, "      ", "match", &frame.to_string());
+        // Messages with too many combinations only get units for the keys of their variants
+        let combinations = combinations(&self.keys(message)).unwrap_or_else(|| {
+            message
+                .variants
+                .iter()
+                .map(|variant| variant.keys.clone())
+                .collect()
+        });
+        for keys in combinations {
+            let Some(variant) = best_variant(&message.variants, &keys) else {
+                continue;
+            };
+            let translation =
+                translation.and_then(|translation| best_variant(&translation.variants, &keys));
+            let keys = keys
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.unit(
+                id,
+                "      ",
+                Some(("keys", &keys)),
+                &variant.value,
+                translation.map(|variant| variant.value.as_slice()),
+            );
+        }
+        self.out.push_str("    </group>\n");
+    }
+
+    /// The keys for each selector: the plural categories of the target language and exact numbers
+    /// for selectors on numbers, and the keys of the source otherwise
+    fn keys(&self, message: &SelectMessage) -> Vec<Vec<VariantKey>> {
+        let scope = Scope::new(&message.declarations).unwrap_or_default();
+        message
+            .selectors
+            .iter()
+            .enumerate()
+            .map(|(index, selector)| {
+                let mut keys: Vec<VariantKey> = Vec::new();
+                let literals =
+                    message
+                        .variants
+                        .iter()
+                        .filter_map(|variant| match &variant.keys[index] {
+                            VariantKey::Literal(literal) => Some(literal),
+                            VariantKey::Catchall => None,
+                        });
+                let rule = plural_rule(&scope, selector);
+                for literal in literals {
+                    let key = VariantKey::Literal(literal.clone());
+                    let exact = literal.value.parse::<f64>().is_ok();
+                    if (rule.is_none() || exact) && !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+                if let Some(rule) = rule {
+                    for category in plural::categories(self.language, rule) {
+                        if category != PluralCategory::Other {
+                            keys.push(VariantKey::Literal(Literal {
+                                value: category.as_str().into(),
+                            }));
+                        }
+                    }
+                }
+                keys.push(VariantKey::Catchall);
+                keys
+            })
+            .collect()
+    }
+
+    fn unit(
+        &mut self,
+        id: &str,
+        indent: &str,
+        metadata: Option<(&str, &str)>,
+        source: &[PatternElement],
+        target: Option<&[PatternElement]>,
+    ) {
+        self.units += 1;
+        self.out.push_str(&format!(
+            "{indent}<unit id=\"u{}\" name=\"{}\">\n",
+            self.units,
+            escape(id)
+        ));
+        let inner = format!("{indent}  ");
+        if let Some((kind, value)) = metadata {
+            write_metadata(&mut self.out, &inner, kind, value);
+        }
+
+        let mut data = Vec::new();
+        let source = inline(source, &mut data);
+        let target = target.map(|target| inline(target, &mut data));
+        if !data.is_empty() {
+            self.out.push_str(&format!("{inner}<originalData>\n"));
+            for (index, content) in data.iter().enumerate() {
+                self.out.push_str(&format!(
+                    "{inner}  <data id=\"d{}\">{}</data>\n",
+                    index + 1,
+                    escape(content)
+                ));
+            }
+            self.out.push_str(&format!("{inner}</originalData>\n"));
+        }
+        self.out.push_str(&format!(
+            "{inner}<segment>\n{inner}  <source>{source}</source>\n"
+        ));
+        if let Some(target) = target {
+            self.out
+                .push_str(&format!("{inner}  <target>{target}</target>\n"));
+        }
+        self.out
+            .push_str(&format!("{inner}</segment>\n{indent}</unit>\n"));
+    }
+}
+
+fn declarations(declarations: &[Declaration]) -> String {
+    declarations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write_metadata(out: &mut String, indent: &str, kind: &str, value: &str) {
+    out.push_str(&format!(
+        "{indent}<mda:metadata>\n{indent}  <mda:metaGroup category=\"messageformat\">\n{indent}    \
+         <mda:meta type=\"{kind}\">{}</mda:meta>\n{indent}  </mda:metaGroup>\n{indent}</mda:metadata>\n",
+        escape(value)
+    ));
+}
+
+/// The plural rule a selector selects with, if it selects on a number
+fn plural_rule(scope: &Scope, selector: &Expression) -> Option<PluralRuleType> {
+    let Ok(Value::Variable {
+        annotation: Some(function),
+        ..
+    }) = scope.resolve(selector)
+    else {
+        return None;
+    };
+    if !matches!(function.name.as_str(), "number" | "integer") {
+        return None;
+    }
+    let select = function
+        .options
+        .iter()
+        .find(|option| option.name == "select");
+    match select.map(|option| &option.value) {
+        None => Some(PluralRuleType::Cardinal),
+        Some(OptionValue::Literal(literal)) if literal.value == "plural" => {
+            Some(PluralRuleType::Cardinal)
+        }
+        Some(OptionValue::Literal(literal)) if literal.value == "ordinal" => {
+            Some(PluralRuleType::Ordinal)
+        }
+        Some(_) => None,
+    }
+}
+
+/// All combinations of one key for each selector, or `None` if there are more than
+/// [`MAX_VARIANTS`]
+fn combinations(keys: &[Vec<VariantKey>]) -> Option<Vec<Vec<VariantKey>>> {
+    let count = keys
+        .iter()
+        .try_fold(1, |count: usize, options| count.checked_mul(options.len()));
+    if count.is_none_or(|count| count > MAX_VARIANTS) {
+        return None;
+    }
+    let mut combinations = vec![Vec::new()];
+    for options in keys {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination: Vec<VariantKey>| {
+                options.iter().map(move |key| {
+                    let mut combination = combination.clone();
+                    combination.push(key.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+    Some(combinations)
+}
+
+/// The variant that formatting selects for the keys: among the ones whose keys match or are the
+/// catch-all, the one with the most specific keys
+fn best_variant<'v>(variants: &'v [Variant], keys: &[VariantKey]) -> Option<&'v Variant> {
+    variants
+        .iter()
+        .filter(|variant| {
+            variant
+                .keys
+                .iter()
+                .zip(keys)
+                .all(|(key, wanted)| *key == VariantKey::Catchall || key == wanted)
+        })
+        .min_by_key(|variant| {
+            variant
+                .keys
+                .iter()
+                .map(|key| *key == VariantKey::Catchall)
+                .collect::<Vec<_>>()
+        })
+}
+
+/// The inline content for a pattern. The MessageFormat 2 syntax of expressions and markup is added
+/// to `data`, which is shared by the source and the target of a unit.
+fn inline(pattern: &[PatternElement], data: &mut Vec<String>) -> String {
+    // Open and close markup that are properly nested become `<pc>` elements
+    let mut pairs = BTreeMap::new();
+    let mut open = Vec::new();
+    for (index, element) in pattern.iter().enumerate() {
+        if let PatternElement::Markup(markup) = element {
+            match markup.kind {
+                MarkupKind::Open => open.push((index, &markup.name)),
+                MarkupKind::Close if open.last().is_some_and(|(_, name)| **name == markup.name) => {
+                    if let Some((start, _)) = open.pop() {
+                        pairs.insert(start, index);
+                    }
+                }
+                MarkupKind::Close | MarkupKind::Standalone => {}
+            }
+        }
+    }
+    let closes: Vec<usize> = pairs.values().copied().collect();
+
+    let mut reference = |content: String, used: &mut BTreeMap<usize, usize>| {
+        let index = data
+            .iter()
+            .position(|existing| *existing == content)
+            .unwrap_or_else(|| {
+                data.push(content);
+                data.len() - 1
+            });
+        let count = used.entry(index).or_insert(0);
+        *count += 1;
+        // Codes that occur more than once need ids of their own
+        let id = if *count == 1 {
+            format!("{}", index + 1)
+        } else {
+            format!("{}_{count}", index + 1)
+        };
+        (id, format!("d{}", index + 1))
+    };
+    let mut used = BTreeMap::new();
+    let mut out = String::new();
+    for (index, element) in pattern.iter().enumerate() {
+        match element {
+            PatternElement::Literal(text) => out.push_str(&escape(text)),
+            PatternElement::Markup(_) if closes.contains(&index) => out.push_str("</pc>"),
+            PatternElement::Markup(markup) if pairs.contains_key(&index) => {
+                let end = match &pattern[pairs[&index]] {
+                    PatternElement::Markup(end) => end.to_string(),
+                    _ => String::new(),
+                };
+                let start = markup.to_string();
+                let (id, start_ref) = reference(start.clone(), &mut used);
+                let (_, end_ref) = reference(end.clone(), &mut used);
+                out.push_str(&format!(
+                    "<pc id=\"{id}\" dataRefStart=\"{start_ref}\" dataRefEnd=\"{end_ref}\" dispStart=\"{}\" dispEnd=\"{}\">",
+                    escape(&start),
+                    escape(&end)
+                ));
+            }
+            PatternElement::Expression(_) | PatternElement::Markup(_) => {
+                let content = element.to_string();
+                let (id, data_ref) = reference(content.clone(), &mut used);
+                out.push_str(&format!(
+                    "<ph id=\"{id}\" dataRef=\"{data_ref}\" disp=\"{}\"/>",
+                    escape(&content)
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Adds the translated messages in a file or group to the catalog
+fn import(container: &Element, catalog: &mut Catalog) -> Result<(), XliffError> {
+    for child in &container.children {
+        let Node::Element(element) = child else {
+            continue;
+        };
+        match element.local_name() {
+            "group" => match metadata(element, "match") {
+                Some(frame) => {
+                    let id = name(element)?;
+                    let mut source = frame;
+                    for unit in element.elements("unit") {
+                        let keys = metadata(unit, "keys").ok_or_else(|| {
+                            XliffError::Invalid(format!("the variant of {id} has no keys"))
+                        })?;
+                        let Some(pattern) = target(unit)? else {
+                            source.clear();
+                            break;
+                        };
+                        source.push_str(&format!("\n{keys} {{{{{pattern}}}}}"));
+                    }
+                    if !source.is_empty() {
+                        catalog
+                            .insert_source(id, &source)
+                            .map_err(XliffError::Message)?;
+                    }
+                }
+                None => import(element, catalog)?,
+            },
+            "unit" => {
+                if let Some(pattern) = target(element)? {
+                    let mut source = metadata(element, "declarations")
+                        .map(|declarations| declarations + "\n")
+                        .unwrap_or_default();
+                    source.push_str(&format!("{{{{{pattern}}}}}"));
+                    catalog
+                        .insert_source(name(element)?, &source)
+                        .map_err(XliffError::Message)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The id of the message of a unit or group
+fn name(element: &Element) -> Result<&str, XliffError> {
+    element
+        .attribute("name")
+        .or_else(|| element.attribute("id"))
+        .ok_or_else(|| XliffError::Invalid(format!("<{}> without a name", element.name)))
+}
+
+fn metadata(element: &Element, kind: &str) -> Option<String> {
+    element
+        .element("metadata")?
+        .elements("metaGroup")
+        .flat_map(|group| group.elements("meta"))
+        .find(|meta| meta.attribute("type") == Some(kind))
+        .map(Element::text)
+}
+
+/// The translated pattern of a unit in MessageFormat 2 syntax, or none if a segment isn't
+/// translated
+fn target(unit: &Element) -> Result<Option<String>, XliffError> {
+    let mut data = BTreeMap::new();
+    if let Some(original) = unit.element("originalData") {
+        for item in original.elements("data") {
+            if let Some(id) = item.attribute("id") {
+                data.insert(id, item.text());
+            }
+        }
+    }
+
+    let mut pattern = String::new();
+    for child in &unit.children {
+        let Node::Element(part) = child else {
+            continue;
+        };
+        let content = match (part.local_name(), part.element("target")) {
+            ("segment" | "ignorable", Some(target)) => target,
+            ("ignorable", None) => part
+                .element("source")
+                .ok_or_else(|| XliffError::Invalid("<ignorable> without a source".into()))?,
+            ("segment", None) => return Ok(None),
+            _ => continue,
+        };
+        write_content(content, &data, &mut pattern)?;
+    }
+    Ok(Some(pattern))
+}
+
+/// Writes inline content as a pattern, replacing codes with their original data
+fn write_content(
+    element: &Element,
+    data: &BTreeMap<&str, String>,
+    pattern: &mut String,
+) -> Result<(), XliffError> {
+    let reference = |element: &Element, attribute: &str| {
+        element
+            .attribute(attribute)
+            .and_then(|id| data.get(id))
+            .ok_or_else(|| XliffError::Invalid(format!("<{}> without original data", element.name)))
+    };
+    for child in &element.children {
+        match child {
+            Node::Text(text) => text.chars().for_each(|c| push_escaped(pattern, c)),
+            Node::Comment(_) => {}
+            Node::Element(inline) => match inline.local_name() {
+                "ph" | "sc" | "ec" => pattern.push_str(reference(inline, "dataRef")?),
+                "pc" => {
+                    pattern.push_str(reference(inline, "dataRefStart")?);
+                    write_content(inline, data, pattern)?;
+                    pattern.push_str(reference(inline, "dataRefEnd")?);
+                }
+                // Annotations only mark up the text
+                "mrk" => write_content(inline, data, pattern)?,
+                "sm" | "em" => {}
+                "cp" => {
+                    let c = inline
+                        .attribute("hex")
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| {
+                            XliffError::Invalid("<cp> without a valid code point".into())
+                        })?;
+                    push_escaped(pattern, c);
+                }
+                _ => return Err(XliffError::Invalid(format!("unexpected <{}>", inline.name))),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Adds a character of text to a pattern, escaped if it is special in patterns
+fn push_escaped(pattern: &mut String, c: char) {
+    if matches!(c, '\\' | '{' | '}') {
+        pattern.push('\\');
+    }
+    pattern.push(c);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog(locale: &str, messages: &[(&str, &str)]) -> Catalog {
+        let mut catalog = Catalog::new(locale.parse().expect("valid locale"));
+        for (id, source) in messages {
+            catalog.insert_source(*id, source).expect("valid message");
+        }
+        catalog
+    }
+
+    #[test]
+    fn it_protects_expressions_and_markup() {
+        let source = catalog("en", &[("greeting", "Hello {#b}{$name}{/b} & {$name}!")]);
+        let target = catalog("de", &[("greeting", "Hallo {#b}{$name}{/b} & {$name}!")]);
+
+        assert_eq!(
+            serialize(&source, &target),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" xmlns:mda="urn:oasis:names:tc:xliff:metadata:2.0" version="2.0" srcLang="en" trgLang="de">
+  <file id="f1" xml:space="preserve">
+    <unit id="u1" name="greeting">
+      <originalData>
+        <data id="d1">{#b}</data>
+        <data id="d2">{/b}</data>
+        <data id="d3">{$name}</data>
+      </originalData>
+      <segment>
+        <source>Hello <pc id="1" dataRefStart="d1" dataRefEnd="d2" dispStart="{#b}" dispEnd="{/b}"><ph id="3" dataRef="d3" disp="{$name}"/></pc> &amp; <ph id="3_2" dataRef="d3" disp="{$name}"/>!</source>
+        <target>Hallo <pc id="1" dataRefStart="d1" dataRefEnd="d2" dispStart="{#b}" dispEnd="{/b}"><ph id="3" dataRef="d3" disp="{$name}"/></pc> &amp; <ph id="3_2" dataRef="d3" disp="{$name}"/>!</target>
+      </segment>
+    </unit>
+  </file>
+</xliff>
+"#
+        );
+    }
+
+    #[test]
+    fn it_expands_select_messages_for_the_target_language() {
+        let source = catalog(
+            "en",
+            &[(
+                "items",
+                ".input {$count :number}\n.match {$count}\n0 {{No items}}\none {{One item}}\n* {{{$count} items}}",
+            )],
+        );
+        let document = serialize(&source, &catalog("pl", &[]));
+
+        let units: Vec<&str> = document
+            .lines()
+            .filter(|line| line.contains("type=\"keys\"") || line.contains("<source>"))
+            .map(str::trim)
+            .collect();
+        assert_eq!(
+            units,
+            [
+                "<mda:meta type=\"keys\">0</mda:meta>",
+                "<source>No items</source>",
+                "<mda:meta type=\"keys\">one</mda:meta>",
+                "<source>One item</source>",
+                "<mda:meta type=\"keys\">few</mda:meta>",
+                "<source><ph id=\"1\" dataRef=\"d1\" disp=\"{$count}\"/> items</source>",
+                "<mda:meta type=\"keys\">many</mda:meta>",
+                "<source><ph id=\"1\" dataRef=\"d1\" disp=\"{$count}\"/> items</source>",
+                "<mda:meta type=\"keys\">*</mda:meta>",
+                "<source><ph id=\"1\" dataRef=\"d1\" disp=\"{$count}\"/> items</source>",
+            ]
+        );
+        assert!(document.contains(
+            "<mda:meta type=\"match\">.input {$count :number}\n.match {$count}</mda:meta>"
+        ));
+        assert!(!document.contains("<target>"));
+        assert_eq!(parse(&document).map(|catalog| catalog.len()), Ok(0));
+
+        // 2^13 combinations, so only the variants become units
+        let selectors: String = (0..13).map(|i| format!(" {{$v{i} :string}}")).collect();
+        let wide = format!(
+            ".match{selectors}\n{} {{{{a}}}}\n{} {{{{b}}}}",
+            ["a"; 13].join(" "),
+            ["*"; 13].join(" ")
+        );
+        let document = serialize(&catalog("en", &[("wide", &wide)]), &catalog("de", &[]));
+        assert_eq!(document.matches("<unit ").count(), 2);
+    }
+
+    #[test]
+    fn it_imports_translations() {
+        let source = catalog(
+            "en",
+            &[
+                (
+                    "items",
+                    ".match {$count :number}\none {{One {#i}item{/i}}}\n* {{{$count} items}}",
+                ),
+                ("total", ".local $sum = {$total :number}\n{{Total: {$sum}}}"),
+            ],
+        );
+        let document = serialize(&source, &catalog("de", &[]));
+        // A translation tool fills in the targets, keeping the codes
+        let translated = document
+            .replace(
+                "<source>One <pc id=\"1\" dataRefStart=\"d1\" dataRefEnd=\"d2\" dispStart=\"{#i}\" dispEnd=\"{/i}\">item</pc></source>",
+                "<source/><target>Ein <pc id=\"1\" dataRefStart=\"d1\" dataRefEnd=\"d2\">Artikel</pc> {}</target>",
+            )
+            .replace(
+                "<source><ph id=\"1\" dataRef=\"d1\" disp=\"{$count}\"/> items</source>",
+                "<source/><target><ph id=\"1\" dataRef=\"d1\"/> Artikel</target>",
+            )
+            .replace(
+                "<source>Total: <ph id=\"1\" dataRef=\"d1\" disp=\"{$sum}\"/></source>",
+                "<source/><target>Summe: <ph id=\"1\" dataRef=\"d1\"/></target>",
+            );
+
+        let catalog = parse(&translated).expect("valid translations");
+        assert_eq!(catalog.locale().to_string(), "de");
+        assert_eq!(
+            catalog.get("items").map(ToString::to_string).as_deref(),
+            Some(".match {$count :number}\none {{Ein {#i}Artikel{/i} \\{\\}}}\n* {{{$count} Artikel}}")
+        );
+        assert_eq!(
+            catalog.format("total", [("total", 3)]),
+            Ok("Summe: 3".into())
+        );
+
+        let invalid = translated.replace(
+            "<data id=\"d1\">{$sum}</data>",
+            "<data id=\"d1\">{$sum :number minimumFractionDigits=}</data>",
+        );
+        assert!(matches!(
+            parse(&invalid),
+            Err(XliffError::Message(CatalogError::Syntax { .. }))
+        ));
+        assert_eq!(
+            parse("<xliff trgLang=\"de\"><file><unit name=\"x\"><segment><source/><target><ph/></target></segment></unit></file></xliff>").err(),
+            Some(XliffError::Invalid("<ph> without original data".into()))
+        );
+        // Characters written as code points are text too
+        let catalog = parse("<xliff trgLang=\"de\"><file><unit name=\"x\"><segment><source/><target><cp hex=\"7B\"/>a<cp hex=\"5C\"/></target></segment></unit></file></xliff>")
+            .expect("valid translations");
+        assert_eq!(
+            catalog.get("x").map(ToString::to_string).as_deref(),
+            Some("\\{a\\\\")
+        );
+        assert_eq!(
+            parse("<xliff>").err().map(|error| error.to_string()),
+            Some("line 1: expected `</xliff>`".into())
+        );
+    }
+}
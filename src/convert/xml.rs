@@ -0,0 +1,316 @@
+//! A small XML reader and the escaping for the XML writers, enough for XLIFF documents, Android
+//! resources and property lists. Namespaces are not resolved, elements are matched by their local
+//! names.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    /// The name with its prefix, like `mda:meta`
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    Comment(String),
+}
+
+impl Element {
+    /// The name without its prefix
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or_default()
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The child elements with a local name
+    pub fn elements<'e>(&'e self, name: &'e str) -> impl Iterator<Item = &'e Self> {
+        self.children.iter().filter_map(move |child| match child {
+            Node::Element(element) if element.local_name() == name => Some(element),
+            _ => None,
+        })
+    }
+
+    pub fn element(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find_map(|child| match child {
+            Node::Element(element) if element.local_name() == name => Some(element),
+            _ => None,
+        })
+    }
+
+    /// All text in the element and its descendants
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                Node::Element(element) => text.push_str(&element.text()),
+                Node::Text(value) => text.push_str(value),
+                Node::Comment(_) => {}
+            }
+        }
+        text
+    }
+}
+
+/// Escapes text for element content and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Parses a document into its root element.
+///
+/// # Errors
+/// Returns the line and the reason if the document is not well-formed.
+pub fn parse(source: &str) -> Result<Element, (usize, String)> {
+    let mut parser = Parser {
+        source,
+        position: 0,
+        depth: 0,
+    };
+    parser.misc()?;
+    if parser.rest().starts_with("<!DOCTYPE") {
+        parser.skip_past(">")?;
+        parser.misc()?;
+    }
+    if !parser.rest().starts_with('<') {
+        return Err(parser.error("expected the root element"));
+    }
+    let root = parser.element()?;
+    parser.misc()?;
+    if parser.position < source.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+/// How deeply elements can be nested, so that untrusted documents can't overflow the stack
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'s> {
+    source: &'s str,
+    position: usize,
+    /// How many elements the parser is in
+    depth: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn error(&self, reason: &str) -> (usize, String) {
+        (
+            self.source[..self.position].matches('\n').count() + 1,
+            reason.into(),
+        )
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn blank(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<&'s str, (usize, String)> {
+        let rest = self.rest();
+        let length = rest
+            .find(end)
+            .ok_or_else(|| self.error(&format!("expected `{end}`")))?;
+        self.position += length + end.len();
+        Ok(&rest[..length])
+    }
+
+    /// Skips whitespace, comments and processing instructions, like the XML declaration
+    fn misc(&mut self) -> Result<(), (usize, String)> {
+        loop {
+            self.blank();
+            if self.eat("<!--") {
+                self.skip_past("-->")?;
+            } else if self.eat("<?") {
+                self.skip_past("?>")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, (usize, String)> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '/' | '>'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += length;
+        Ok(rest[..length].into())
+    }
+
+    fn element(&mut self) -> Result<Element, (usize, String)> {
+        self.eat("<");
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+        loop {
+            self.blank();
+            if self.eat("/>") {
+                return Ok(element);
+            }
+            if self.eat(">") {
+                break;
+            }
+            let attribute = self.name()?;
+            self.blank();
+            if !self.eat("=") {
+                return Err(self.error("expected `=`"));
+            }
+            self.blank();
+            let quote = if self.eat("\"") {
+                "\""
+            } else if self.eat("'") {
+                "'"
+            } else {
+                return Err(self.error("expected a quoted value"));
+            };
+            let value = self.skip_past(quote)?;
+            let value = self.unescape(value)?;
+            element.attributes.push((attribute, value));
+        }
+
+        loop {
+            if self.eat("</") {
+                let name = self.name()?;
+                self.blank();
+                if name != element.name || !self.eat(">") {
+                    return Err(self.error(&format!("expected `</{}>`", element.name)));
+                }
+                return Ok(element);
+            }
+            if self.eat("<!--") {
+                let comment = self.skip_past("-->")?;
+                element.children.push(Node::Comment(comment.into()));
+            } else if self.eat("<![CDATA[") {
+                let text = self.skip_past("]]>")?;
+                push_text(&mut element.children, text);
+            } else if self.eat("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with('<') {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("elements are nested too deeply"));
+                }
+                self.depth += 1;
+                let child = self.element()?;
+                self.depth -= 1;
+                element.children.push(Node::Element(child));
+            } else if self.rest().is_empty() {
+                return Err(self.error(&format!("expected `</{}>`", element.name)));
+            } else {
+                let rest = self.rest();
+                let length = rest.find('<').unwrap_or(rest.len());
+                self.position += length;
+                let text = self.unescape(&rest[..length])?;
+                push_text(&mut element.children, &text);
+            }
+        }
+    }
+
+    fn unescape(&self, text: &str) -> Result<String, (usize, String)> {
+        let mut value = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            value.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find(';')
+                .ok_or_else(|| self.error("unterminated entity"))?
+                + start;
+            let entity = &rest[start + 1..end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            value.push(c.ok_or_else(|| self.error(&format!("unknown entity `&{entity};`")))?);
+            rest = &rest[end + 1..];
+        }
+        value.push_str(rest);
+        Ok(value)
+    }
+}
+
+fn push_text(children: &mut Vec<Node>, text: &str) {
+    if let Some(Node::Text(last)) = children.last_mut() {
+        last.push_str(text);
+    } else {
+        children.push(Node::Text(text.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_documents() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!-- a comment -->\n<a:root x='1 &amp; 2'><b>&lt;&#65;<!-- B -->&#x42;<![CDATA[{$x}]]></b><c/></a:root>",
+        )
+        .expect("well-formed document");
+        assert_eq!(root.local_name(), "root");
+        assert_eq!(root.attribute("x"), Some("1 & 2"));
+        assert_eq!(
+            root.element("b").map(Element::text).as_deref(),
+            Some("<AB{$x}")
+        );
+        assert_eq!(root.elements("c").count(), 1);
+
+        assert_eq!(parse("<a>\n<b></a>"), Err((2, "expected `</b>`".into())));
+        assert_eq!(
+            parse("<a>&nbsp;</a>"),
+            Err((1, "unknown entity `&nbsp;`".into()))
+        );
+        assert_eq!(
+            parse(&"<a>".repeat(50_000)),
+            Err((1, "elements are nested too deeply".into()))
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Compares two messages by their structure instead of their syntax, eg. to show a reviewer that
+//! the text of one variant changed rather than a line diff of the serialized messages.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::data_model::elements::{
+    Declaration, Expression, Message, PatternElement, Variant, VariantKey,
+};
+
+/// A difference between an old and a new version of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A declaration of a variable that only the new message declares, or an unsupported statement
+    /// that only it has
+    DeclarationAdded(Declaration),
+    DeclarationRemoved(Declaration),
+    /// A variable that both messages declare, with a different declaration
+    DeclarationChanged {
+        old: Declaration,
+        new: Declaration,
+    },
+    /// The selectors are different. A pattern message has none.
+    SelectorsChanged {
+        old: Vec<Expression>,
+        new: Vec<Expression>,
+    },
+    /// A variant with keys that no variant of the old message has. The pattern of a pattern message
+    /// is a variant without keys.
+    VariantAdded(Variant),
+    VariantRemoved(Variant),
+    /// The pattern of the variant with `keys` changed
+    PatternChanged {
+        keys: Vec<VariantKey>,
+        edits: Vec<Edit>,
+    },
+}
+
+/// A run of pattern elements that was replaced, removed or inserted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// The index of the first removed element in the old pattern, or where elements were inserted
+    pub old_index: usize,
+    pub removed: Vec<PatternElement>,
+    /// The index of the first inserted element in the new pattern, or where elements were removed
+    pub new_index: usize,
+    pub inserted: Vec<PatternElement>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeclarationAdded(declaration) => write!(f, "declaration `{declaration}` added"),
+            Self::DeclarationRemoved(declaration) => {
+                write!(f, "declaration `{declaration}` removed")
+            }
+            Self::DeclarationChanged { old, new } => {
+                write!(f, "declaration `{old}` changed to `{new}`")
+            }
+            Self::SelectorsChanged { old, new } => {
+                write!(
+                    f,
+                    "selectors changed from `{}` to `{}`",
+                    Joined(old),
+                    Joined(new)
+                )
+            }
+            Self::VariantAdded(variant) if variant.keys.is_empty() => f.write_str("pattern added"),
+            Self::VariantAdded(variant) => write!(f, "variant `{}` added", Joined(&variant.keys)),
+            Self::VariantRemoved(variant) if variant.keys.is_empty() => {
+                f.write_str("pattern removed")
+            }
+            Self::VariantRemoved(variant) => {
+                write!(f, "variant `{}` removed", Joined(&variant.keys))
+            }
+            Self::PatternChanged { keys, .. } if keys.is_empty() => {
+                f.write_str("pattern text changed")
+            }
+            Self::PatternChanged { keys, .. } => {
+                write!(f, "variant `{}` text changed", Joined(keys))
+            }
+        }
+    }
+}
+
+/// Writes items separated by spaces
+struct Joined<'a, T>(&'a [T]);
+
+impl<T: fmt::Display> fmt::Display for Joined<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The changes from `old` to `new`: declarations matched by the variable they declare, then
+/// selectors, then variants matched by their keys. Reordering declarations or variants isn't a
+/// change.
+pub fn diff(old: &Message, new: &Message) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_declarations(declarations(old), declarations(new), &mut changes);
+
+    let (old_selectors, new_selectors) = (selectors(old), selectors(new));
+    if old_selectors != new_selectors {
+        changes.push(Change::SelectorsChanged {
+            old: old_selectors.to_vec(),
+            new: new_selectors.to_vec(),
+        });
+    }
+
+    let old_variants = variants(old);
+    let new_variants = variants(new);
+    let mut matched = vec![false; new_variants.len()];
+    for variant in &old_variants {
+        let counterpart = (0..new_variants.len())
+            .find(|&index| !matched[index] && new_variants[index].keys == variant.keys);
+        match counterpart {
+            Some(index) => {
+                matched[index] = true;
+                let edits = diff_patterns(&variant.value, &new_variants[index].value);
+                if !edits.is_empty() {
+                    changes.push(Change::PatternChanged {
+                        keys: variant.keys.clone(),
+                        edits,
+                    });
+                }
+            }
+            None => changes.push(Change::VariantRemoved(variant.clone())),
+        }
+    }
+    for (variant, matched) in new_variants.into_iter().zip(matched) {
+        if !matched {
+            changes.push(Change::VariantAdded(variant));
+        }
+    }
+    changes
+}
+
+fn declarations(message: &Message) -> &[Declaration] {
+    match message {
+        Message::Pattern(message) => &message.declarations,
+        Message::Select(message) => &message.declarations,
+    }
+}
+
+fn selectors(message: &Message) -> &[Expression] {
+    match message {
+        Message::Pattern(_) => &[],
+        Message::Select(message) => &message.selectors,
+    }
+}
+
+fn variants(message: &Message) -> Vec<Variant> {
+    match message {
+        Message::Pattern(message) => vec![Variant {
+            keys: Vec::new(),
+            value: message.pattern.clone(),
+        }],
+        Message::Select(message) => message.variants.clone(),
+    }
+}
+
+/// The variable a declaration declares. Unsupported statements are identified by their syntax.
+fn declared(declaration: &Declaration) -> String {
+    match declaration {
+        Declaration::Input(input) => input.name.clone(),
+        Declaration::Local(local) => local.name.clone(),
+        Declaration::UnsupportedStatement(statement) => alloc::format!("{statement}"),
+    }
+}
+
+fn diff_declarations(old: &[Declaration], new: &[Declaration], changes: &mut Vec<Change>) {
+    for declaration in old {
+        match new
+            .iter()
+            .find(|new| declared(new) == declared(declaration))
+        {
+            Some(new) if new != declaration => changes.push(Change::DeclarationChanged {
+                old: declaration.clone(),
+                new: new.clone(),
+            }),
+            Some(_) => {}
+            None => changes.push(Change::DeclarationRemoved(declaration.clone())),
+        }
+    }
+    for declaration in new {
+        if !old.iter().any(|old| declared(old) == declared(declaration)) {
+            changes.push(Change::DeclarationAdded(declaration.clone()));
+        }
+    }
+}
+
+/// The edits that turn one pattern into the other, keeping the longest common subsequence of
+/// elements
+fn diff_patterns(old: &[PatternElement], new: &[PatternElement]) -> Vec<Edit> {
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits: Vec<Edit> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        // Continue the edit that ends right here, or start a new one
+        let edit = match edits.last_mut() {
+            Some(edit)
+                if edit.old_index + edit.removed.len() == i
+                    && edit.new_index + edit.inserted.len() == j =>
+            {
+                edit
+            }
+            _ => {
+                edits.push(Edit {
+                    old_index: i,
+                    removed: Vec::new(),
+                    new_index: j,
+                    inserted: Vec::new(),
+                });
+                edits.last_mut().expect("an edit was just pushed")
+            }
+        };
+        if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            edit.removed.push(old[i].clone());
+            i += 1;
+        } else {
+            edit.inserted.push(new[j].clone());
+            j += 1;
+        }
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        let old: Message = old.parse().expect("valid message");
+        let new: Message = new.parse().expect("valid message");
+        diff(&old, &new).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn it_finds_no_changes_in_equal_messages() {
+        let source = ".input {$n :number}\n.match {$n}\none {{One}}\n* {{Many}}";
+        assert_eq!(changes(source, source), Vec::<String>::new());
+        assert_eq!(
+            changes(
+                source,
+                ".input {$n :number}\n.match {$n}\n* {{Many}}\none {{One}}"
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn it_finds_changed_declarations_selectors_and_variants() {
+        assert_eq!(
+            changes(
+                ".input {$n :number}\n.local $x = {$y}\n.match {$n} {$g :string}\none * {{One}}\n* male {{He}}\n* * {{Many}}",
+                ".input {$n :integer}\n.local $z = {$y}\n.match {$n} {$g :string}\none * {{One!}}\n* female {{She}}\n* * {{Many}}"
+            ),
+            vec![
+                "declaration `.input {$n :number}` changed to `.input {$n :integer}`",
+                "declaration `.local $x = {$y}` removed",
+                "declaration `.local $z = {$y}` added",
+                "variant `one *` text changed",
+                "variant `* male` removed",
+                "variant `* female` added",
+            ]
+        );
+        assert_eq!(
+            changes("Hello", ".input {$n :number}\n.match {$n}\n* {{Hello}}"),
+            vec![
+                "declaration `.input {$n :number}` added",
+                "selectors changed from `` to `{$n}`",
+                "pattern removed",
+                "variant `*` added",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_finds_edits_inside_patterns() {
+        let old: Message = "Hello {$name}, you have {$n} new {#b}messages{/b}"
+            .parse()
+            .expect("valid message");
+        let new: Message = "Hi {$name}, you have {$n} {#b}messages{/b}!"
+            .parse()
+            .expect("valid message");
+        let changes = diff(&old, &new);
+        let [Change::PatternChanged { keys, edits }] = changes.as_slice() else {
+            panic!("expected one change")
+        };
+        assert!(keys.is_empty());
+        let text = |text: &str| vec![PatternElement::Literal(text.into())];
+        assert_eq!(
+            edits,
+            &[
+                Edit {
+                    old_index: 0,
+                    removed: text("Hello "),
+                    new_index: 0,
+                    inserted: text("Hi "),
+                },
+                Edit {
+                    old_index: 4,
+                    removed: text(" new "),
+                    new_index: 4,
+                    inserted: text(" "),
+                },
+                Edit {
+                    old_index: 8,
+                    removed: Vec::new(),
+                    new_index: 8,
+                    inserted: text("!"),
+                },
+            ]
+        );
+    }
+}
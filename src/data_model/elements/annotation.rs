@@ -1,52 +1,49 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Annotation {
     Function(elements::FunctionAnnotation),
     Unsupported(elements::UnsupportedAnnotation),
 }
 
-impl ToString for Annotation {
-    fn to_string(&self) -> String {
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Annotation::Function(f) => f.to_string(),
-            Annotation::Unsupported(u) => u.to_string(),
+            Self::Function(func) => func.fmt(f),
+            Self::Unsupported(u) => u.fmt(f),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnsupportedAnnotation {
     pub source: String,
 }
 
-impl ToString for UnsupportedAnnotation {
-    fn to_string(&self) -> String {
-        self.source.clone()
+impl fmt::Display for UnsupportedAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionAnnotation {
     pub name: String,
     pub options: Vec<elements::Option>,
 }
 
-impl ToString for FunctionAnnotation {
-    fn to_string(&self) -> String {
-        if self.options.is_empty() {
-            return format!(":{}", self.name.clone());
+impl fmt::Display for FunctionAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ":{}", self.name)?;
+        for option in &self.options {
+            write!(f, " {option}")?;
         }
-
-        format!(
-            ":{} {}",
-            self.name,
-            self.options
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<String>>()
-                .join(" ")
-        )
+        Ok(())
     }
-}
\ No newline at end of file
+}
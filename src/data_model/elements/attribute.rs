@@ -1,33 +1,36 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::string::String;
+use core::fmt;
 
 /// Attributes are reserved for future standardization
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute {
     pub name: String,
     pub value: Option<elements::AttributeValue>,
 }
 
-impl ToString for Attribute {
-    fn to_string(&self) -> String {
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.value {
-            Some(v) => format!("@{}={}", self.name, v.to_string()),
-            None => self.name.clone(),
+            Some(v) => write!(f, "@{}={}", self.name, v),
+            None => write!(f, "@{}", self.name),
         }
     }
 }
 /// Attributes are reserved for future standardization
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttributeValue {
     Literal(elements::Literal),
     Variable(elements::VariableRef),
 }
 
-impl ToString for AttributeValue {
-    fn to_string(&self) -> String {
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Literal(l) => l.to_string(),
-            Self::Variable(v) => v.to_string(),
+            Self::Literal(l) => l.fmt(f),
+            Self::Variable(v) => v.fmt(f),
         }
     }
-}
\ No newline at end of file
+}
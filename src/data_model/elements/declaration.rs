@@ -1,6 +1,9 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Declaration {
     Input(elements::InputDeclaration),
@@ -8,16 +11,17 @@ pub enum Declaration {
     UnsupportedStatement(elements::UnsupportedStatement),
 }
 
-impl ToString for Declaration {
-    fn to_string(&self) -> String {
+impl fmt::Display for Declaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Input(i) => i.to_string(),
-            Self::Local(l) => l.to_string(),
-            Self::UnsupportedStatement(u) => u.to_string(),
+            Self::Input(i) => i.fmt(f),
+            Self::Local(l) => l.fmt(f),
+            Self::UnsupportedStatement(u) => u.fmt(f),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputDeclaration {
     /// The name of an InputDeclaration MUST be the same as the name in the VariableRef of its VariableExpression value
@@ -25,27 +29,26 @@ pub struct InputDeclaration {
     pub value: elements::VariableExpression,
 }
 
-impl ToString for InputDeclaration {
-    fn to_string(&self) -> String {
-        format!(".input {}={}", self.name, self.value.to_string())
+impl fmt::Display for InputDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ".input {}", self.value)
     }
 }
 
-
-
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocalDeclaration {
     pub name: String,
     pub value: elements::Expression,
 }
 
-impl ToString for LocalDeclaration {
-    fn to_string(&self) -> String {
-        format!(".local {}={}", self.name, self.value.to_string())
+impl fmt::Display for LocalDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ".local ${} = {}", self.name, self.value)
     }
 }
 
-
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnsupportedStatement {
     pub keyword: String,
@@ -53,12 +56,15 @@ pub struct UnsupportedStatement {
     pub expressions: Vec<elements::Expression>,
 }
 
-impl ToString for UnsupportedStatement {
-    fn to_string(&self) -> String {
-        format!(
-            ".{} {}",
-            self.keyword,
-            self.body.clone().unwrap_or("".into())
-        )
+impl fmt::Display for UnsupportedStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ".{}", self.keyword)?;
+        if let Some(body) = &self.body {
+            write!(f, " {body}")?;
+        }
+        for expression in &self.expressions {
+            write!(f, " {expression}")?;
+        }
+        Ok(())
     }
 }
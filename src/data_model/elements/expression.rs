@@ -1,7 +1,8 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::vec::Vec;
+use core::fmt;
 
-
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
     Literal(elements::LiteralExpression),
@@ -10,17 +11,18 @@ pub enum Expression {
     Unsupported(elements::UnsupportedExpression),
 }
 
-impl ToString for Expression {
-    fn to_string(&self) -> String {
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Expression::Literal(l) => l.to_string(),
-            Expression::Variable(v) => v.to_string(),
-            Expression::Function(f) => f.to_string(),
-            Expression::Unsupported(u) => u.to_string(),
+            Self::Literal(l) => l.fmt(f),
+            Self::Variable(v) => v.fmt(f),
+            Self::Function(func) => func.fmt(f),
+            Self::Unsupported(u) => u.fmt(f),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LiteralExpression {
     pub arg: elements::Literal,
@@ -30,17 +32,18 @@ pub struct LiteralExpression {
     pub attributes: Vec<elements::Attribute>,
 }
 
-impl ToString for LiteralExpression {
-    fn to_string(&self) -> String {
-        //TODO include attributes
-        match &self.annotation {
-            Some(annotation) => format!("{{{} {}}}", self.arg.value, annotation.to_string()),
-            None => format!("{{{}}}", self.arg.value),
+impl fmt::Display for LiteralExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.arg)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " {annotation}")?;
         }
+        write_attributes(f, &self.attributes)?;
+        f.write_str("}")
     }
 }
 
-
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableExpression {
     pub arg: elements::VariableRef,
@@ -50,16 +53,18 @@ pub struct VariableExpression {
     pub attributes: Vec<elements::Attribute>,
 }
 
-impl ToString for VariableExpression {
-    fn to_string(&self) -> String {
-        //TODO include attributes
-        match &self.annotation {
-            Some(annotation) => format!("{{{} {}}}", self.arg.to_string(), annotation.to_string()),
-            None => format!("{{{}}}", self.arg.to_string()),
+impl fmt::Display for VariableExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.arg)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " {annotation}")?;
         }
+        write_attributes(f, &self.attributes)?;
+        f.write_str("}")
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionExpression {
     pub annotation: elements::FunctionAnnotation,
@@ -68,13 +73,15 @@ pub struct FunctionExpression {
     pub attributes: Vec<elements::Attribute>,
 }
 
-impl ToString for FunctionExpression {
-    fn to_string(&self) -> String {
-        format!("{{{}}}", self.annotation.to_string()) //TODO include attributes
+impl fmt::Display for FunctionExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.annotation)?;
+        write_attributes(f, &self.attributes)?;
+        f.write_str("}")
     }
 }
 
-
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnsupportedExpression {
     pub annotation: elements::UnsupportedAnnotation,
@@ -83,8 +90,20 @@ pub struct UnsupportedExpression {
     pub attributes: Vec<elements::Attribute>,
 }
 
-impl ToString for UnsupportedExpression {
-    fn to_string(&self) -> String {
-        self.annotation.to_string() //TODO include attributes
+impl fmt::Display for UnsupportedExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.annotation)?;
+        write_attributes(f, &self.attributes)?;
+        f.write_str("}")
+    }
+}
+
+pub fn write_attributes(
+    f: &mut fmt::Formatter<'_>,
+    attributes: &[elements::Attribute],
+) -> fmt::Result {
+    for attribute in attributes {
+        write!(f, " {attribute}")?;
     }
+    Ok(())
 }
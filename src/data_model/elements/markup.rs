@@ -1,6 +1,9 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Markup {
     pub kind: MarkupKind,
@@ -11,50 +14,35 @@ pub struct Markup {
     pub attributes: Vec<elements::Attribute>,
 }
 
-impl ToString for Markup {
-    fn to_string(&self) -> String {
-        match self.kind {
-            // {#name} or {#name option1=value1 option2=value2 }
-            MarkupKind::Open => {
-                if self.options.is_empty() {
-                    return format!("{{#{}}}", self.name);
-                }
-
-                let serialized_options = self
-                    .options
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<String>>()
-                    .join(" ");
-
-                format!("{{#{} {} }}", self.name, serialized_options)
-            }
-
-            // {#tag option1=value1 option2=value2 /}
-            MarkupKind::Standalone => {
-                if self.options.is_empty() {
-                    return format!("{{#{}/}}", self.name);
-                }
-
-                let serialized_options = self
-                    .options
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<String>>()
-                    .join(" ");
-
-                format!("{{#{} {} /}}", self.name, serialized_options)
-            }
+impl fmt::Display for Markup {
+    /// Writes `{#name}`, `{#name /}` or `{/name}`, with a space before the end if there are options
+    /// or attributes, eg. `{#name option=value }`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sigil = if self.kind == MarkupKind::Close {
+            '/'
+        } else {
+            '#'
+        };
+        write!(f, "{{{sigil}{}", self.name)?;
+        for option in &self.options {
+            write!(f, " {option}")?;
+        }
+        elements::expression::write_attributes(f, &self.attributes)?;
 
-            // {/name}
-            MarkupKind::Close => format!("{{/{}}}", self.name),
+        if !self.options.is_empty() || !self.attributes.is_empty() {
+            f.write_str(" ")?;
+        }
+        match self.kind {
+            MarkupKind::Standalone => f.write_str("/}"),
+            MarkupKind::Open | MarkupKind::Close => f.write_str("}"),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MarkupKind {
     Open,
     Standalone,
     Close,
-}
\ No newline at end of file
+}
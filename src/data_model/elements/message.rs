@@ -1,60 +1,72 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::vec::Vec;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Message {
     Pattern(elements::PatternMessage),
     Select(elements::SelectMessage),
 }
 
-impl ToString for Message {
-    fn to_string(&self) -> String {
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Message::Pattern(p) => p.to_string(),
-            Message::Select(s) => s.to_string(),
+            Self::Pattern(p) => p.fmt(f),
+            Self::Select(s) => s.fmt(f),
         }
     }
 }
 
 /// A message without selectors and with a single pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PatternMessage {
     pub declarations: Vec<elements::Declaration>,
     pub pattern: Vec<elements::PatternElement>,
 }
 
-impl ToString for PatternMessage {
-    fn to_string(&self) -> String {
-        let serialized_pattern = self
+impl fmt::Display for PatternMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The first character of the serialized pattern after any whitespace decides if it could be
+        // mistaken for a keyword
+        let starts_with_dot = self
             .pattern
             .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join("");
+            .find_map(|element| match element {
+                elements::PatternElement::Literal(l) if l.trim_start().is_empty() => None,
+                elements::PatternElement::Literal(l) => Some(l.trim_start().starts_with('.')),
+                _ => Some(false),
+            })
+            .unwrap_or(false);
 
-        let should_quote = serialized_pattern.starts_with(".") || !self.declarations.is_empty();
-        let serialized_pattern: String = if should_quote {
-            format!("{{{{{}}}}}", serialized_pattern)
-        } else {
-            serialized_pattern
-        };
-
-        if self.declarations.is_empty() {
-            return serialized_pattern;
+        for (i, declaration) in self.declarations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{declaration}")?;
         }
 
-        let serialized_declarations = self
-            .declarations
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join("\n");
+        if !self.declarations.is_empty() {
+            f.write_str("\n\n")?;
+        }
 
-        format!("{}\n\n{}", serialized_declarations, serialized_pattern)
+        let should_quote = starts_with_dot || !self.declarations.is_empty();
+        if should_quote {
+            f.write_str("{{")?;
+        }
+        for element in &self.pattern {
+            write!(f, "{element}")?;
+        }
+        if should_quote {
+            f.write_str("}}")?;
+        }
+        Ok(())
     }
 }
 
 /// A message that includes selectors
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectMessage {
     pub declarations: Vec<elements::Declaration>,
@@ -62,35 +74,20 @@ pub struct SelectMessage {
     pub variants: Vec<elements::Variant>,
 }
 
-impl ToString for SelectMessage {
-    fn to_string(&self) -> String {
-        let serialized_selectors = self
-            .selectors
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        let serialized_variants = self
-            .variants
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        let serialized_declarations = self
-            .declarations
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join("\n");
+impl fmt::Display for SelectMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for declaration in &self.declarations {
+            writeln!(f, "{declaration}")?;
+        }
 
-        let serialized_match = format!(".match {}\n{}", serialized_selectors, serialized_variants);
+        f.write_str(".match")?;
+        for selector in &self.selectors {
+            write!(f, " {selector}")?;
+        }
 
-        if self.declarations.is_empty() {
-            return serialized_match;
-        } else {
-            format!("{}\n{}", serialized_declarations, serialized_match)
+        for variant in &self.variants {
+            write!(f, "\n{variant}")?;
         }
+        Ok(())
     }
 }
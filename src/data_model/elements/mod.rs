@@ -1,19 +1,21 @@
-mod message;
-mod declaration;
-mod variant;
-mod pattern_element;
-mod expression;
+mod annotation;
 mod attribute;
+mod declaration;
+pub(crate) mod expression;
 mod markup;
-mod annotation;
+mod message;
+mod pattern_element;
 mod primitives;
+mod variant;
 
-pub use message::{Message, PatternMessage,SelectMessage};
-pub use declaration::{Declaration, LocalDeclaration, InputDeclaration, UnsupportedStatement};
-pub use variant::{Variant, VariantKey};
-pub use pattern_element::PatternElement;
-pub use expression::{Expression, LiteralExpression, VariableExpression, FunctionExpression, UnsupportedExpression};
+pub use annotation::{Annotation, FunctionAnnotation, UnsupportedAnnotation};
 pub use attribute::{Attribute, AttributeValue};
+pub use declaration::{Declaration, InputDeclaration, LocalDeclaration, UnsupportedStatement};
+pub use expression::{
+    Expression, FunctionExpression, LiteralExpression, UnsupportedExpression, VariableExpression,
+};
 pub use markup::{Markup, MarkupKind};
-pub use annotation::{Annotation,UnsupportedAnnotation, FunctionAnnotation};
-pub use primitives::{VariableRef,Literal, Option, OptionValue};
\ No newline at end of file
+pub use message::{Message, PatternMessage, SelectMessage};
+pub use pattern_element::PatternElement;
+pub use primitives::{Literal, Option, OptionValue, VariableRef};
+pub use variant::{Variant, VariantKey};
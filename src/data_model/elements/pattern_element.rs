@@ -1,6 +1,8 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::string::String;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PatternElement {
     Literal(String),
@@ -8,12 +10,20 @@ pub enum PatternElement {
     Markup(elements::Markup),
 }
 
-impl ToString for PatternElement {
-    fn to_string(&self) -> String {
+impl fmt::Display for PatternElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PatternElement::Literal(l) => l.to_string(),
-            PatternElement::Expression(e) => e.to_string(),
-            PatternElement::Markup(m) => m.to_string(),
+            Self::Literal(l) => {
+                for c in l.chars() {
+                    if matches!(c, '\\' | '{' | '}') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                Ok(())
+            }
+            Self::Expression(e) => e.fmt(f),
+            Self::Markup(m) => m.fmt(f),
         }
     }
 }
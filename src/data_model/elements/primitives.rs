@@ -1,53 +1,67 @@
-use std::string::ToString;
+use alloc::string::String;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableRef {
     pub name: String,
 }
 
-impl ToString for VariableRef {
-    fn to_string(&self) -> String {
-        format!("${}", self.name)
+impl fmt::Display for VariableRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.name)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Literal {
     pub value: String,
 }
 
-impl ToString for Literal {
-    fn to_string(&self) -> String {
-        self.value.clone()
+impl fmt::Display for Literal {
+    /// Writes the literal as it is if it is a name or a number, and quoted otherwise
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if crate::parser::is_unquoted_literal(&self.value) {
+            return f.write_str(&self.value);
+        }
+        f.write_str("|")?;
+        for c in self.value.chars() {
+            if matches!(c, '\\' | '|') {
+                f.write_str("\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        f.write_str("|")
     }
 }
 
-
 /// An argument to a function. The spec calls this "option". Watch out for conflicts with Rusts `Option` type.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Option {
     pub name: String,
     pub value: OptionValue,
 }
 
-impl ToString for Option {
-    fn to_string(&self) -> String {
-        format!("{}={}", self.name, self.value.to_string())
+impl fmt::Display for Option {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OptionValue {
     Literal(Literal),
     Variable(VariableRef),
 }
 
-impl ToString for OptionValue {
-    fn to_string(&self) -> String {
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Literal(l) => l.to_string(),
-            Self::Variable(v) => v.to_string(),
+            Self::Literal(l) => l.fmt(f),
+            Self::Variable(v) => v.fmt(f),
         }
     }
 }
-
@@ -1,43 +1,43 @@
-use std::string::ToString;
 use crate::data_model::elements;
+use alloc::vec::Vec;
+use core::fmt;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variant {
     pub keys: Vec<elements::VariantKey>,
     pub value: Vec<elements::PatternElement>,
 }
 
-impl ToString for Variant {
-    fn to_string(&self) -> String {
-        let serialized_keys = self
-            .keys
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        let serialized_pattern = self
-            .value
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>()
-            .join("");
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}")?;
+        }
 
-        format!("{} {{{{{}}}}}", serialized_keys, serialized_pattern)
+        f.write_str(" {{")?;
+        for element in &self.value {
+            write!(f, "{element}")?;
+        }
+        f.write_str("}}")
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VariantKey {
     Literal(elements::Literal),
     Catchall,
 }
 
-impl ToString for VariantKey {
-    fn to_string(&self) -> String {
+impl fmt::Display for VariantKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            VariantKey::Literal(l) => l.to_string(),
-            VariantKey::Catchall => "*".into(),
+            Self::Literal(l) => l.fmt(f),
+            Self::Catchall => f.write_str("*"),
         }
     }
 }
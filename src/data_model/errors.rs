@@ -1,68 +1,149 @@
 // See: https://github.com/unicode-org/message-format-wg/blob/6d7b4ba213e686ff2d403d3025d38d76b42b75f7/spec/errors.md
 
+use alloc::string::String;
+use core::fmt;
 
 // Syntax Errors Errors
 
 /// Syntax Errors occur when the syntax representation of a message is not well-formed.
-#[derive(Debug)]
-pub struct SyntaxError;
-
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// The byte offset in the source at which the error was found
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl SyntaxError {
+    /// The 1-based line and column of the error in `source`, counted in characters
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        (line, column)
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "syntax error at offset {}: {}", self.offset, self.reason)
+    }
+}
+
+impl core::error::Error for SyntaxError {}
 
 // Data Model Errors
 
 /// A Variant Key Mismatch occurs when the number of keys on a variant does not equal the number of selectors.
-#[derive(Debug)]
-pub struct VariantKeyMismatch;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantKeyMismatch {
+    /// The index of the offending variant
+    pub variant: usize,
+    pub expected: usize,
+    pub found: usize,
+}
 
 /// A Missing Fallback Variant error occurs when the message does not include a variant with only catch-all keys.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MissingFallbackVariant;
 
 /// A Missing Selector Annotation error occurs when the message contains a selector that does not have an annotation, or contains a variable that does not directly or indirectly reference a declaration with an annotation.
-#[derive(Debug)]
-pub struct MissingSelectorAnnotation;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSelectorAnnotation {
+    /// The index of the offending selector
+    pub selector: usize,
+}
 
 /// A Duplicate Declaration error occurs when a variable is declared more than once. Note that an input variable is implicitly declared when it is first used, so explicitly declaring it after such use is also an error.
-#[derive(Debug)]
-pub struct DuplicateDeclaration;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateDeclaration {
+    pub name: String,
+}
 
 /// A Duplicate Option Name error occurs when the same identifier appears on the left-hand side of more than one option in the same expression.
-#[derive(Debug)]
-pub struct DuplicateOptionName;
-
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateOptionName {
+    pub name: String,
+}
+
+/// Any of the errors that make a message invalid according to the data model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataModelError {
+    VariantKeyMismatch(VariantKeyMismatch),
+    MissingFallbackVariant(MissingFallbackVariant),
+    MissingSelectorAnnotation(MissingSelectorAnnotation),
+    DuplicateDeclaration(DuplicateDeclaration),
+    DuplicateOptionName(DuplicateOptionName),
+}
+
+impl fmt::Display for DataModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VariantKeyMismatch(e) => write!(
+                f,
+                "variant {} has {} keys, but the message has {} selectors",
+                e.variant, e.found, e.expected
+            ),
+            Self::MissingFallbackVariant(_) => {
+                f.write_str("the message has no variant with only catch-all keys")
+            }
+            Self::MissingSelectorAnnotation(e) => {
+                write!(f, "selector {} has no annotation", e.selector)
+            }
+            Self::DuplicateDeclaration(e) => write!(f, "${} is declared more than once", e.name),
+            Self::DuplicateOptionName(e) => write!(f, "option {} is set more than once", e.name),
+        }
+    }
+}
+
+impl core::error::Error for DataModelError {}
 
 // Resolution Errors
 
 /// An Unresolved Variable error occurs when a variable reference cannot be resolved.
-#[derive(Debug)]
-pub struct UnresolvedVariable;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedVariable {
+    pub name: String,
+}
 
 /// An Unknown Function error occurs when an expression includes a reference to a function which cannot be resolved.
-#[derive(Debug)]
-pub struct UnknownFunction;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFunction {
+    pub name: String,
+}
 
 /// An Unsupported Expression error occurs when an expression uses syntax reserved for future standardization, or for private implementation use that is not supported by the current implementation.
-#[derive(Debug)]
-pub struct UnsupportedExpression;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedExpression {
+    pub source: String,
+}
 
 /// An Invalid Expression error occurs when a message includes an expression whose implementation-defined internal requirements produce an error during function resolution or when a function returns a value (such as null) that the implementation does not support.
-#[derive(Debug)]
-pub struct InvalidExpression;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidExpression {
+    pub function: String,
+    pub reason: String,
+}
 
 /// An Unsupported Statement error occurs when a message includes a reserved statement.
-#[derive(Debug)]
-pub struct UnsupportedStatement;
-
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedStatement {
+    pub keyword: String,
+}
 
 // Selection Errors
 
 /// Selection Errors occur when message selection fails.
-#[derive(Debug)]
-pub struct SelectionError;
-
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionError {
+    pub function: String,
+    pub reason: String,
+}
 
 // Formatting Errors
 
 /// Formatting Errors occur during the formatting of a resolved value, for example when encountering a value with an unsupported type or an internally inconsistent set of options.
-#[derive(Debug)]
-pub struct FormattingError;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattingError {
+    pub function: String,
+    pub reason: String,
+}
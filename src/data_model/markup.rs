@@ -0,0 +1,296 @@
+//! Pairs the open and close markup of a pattern, to check that it is well-formed and to turn the
+//! pattern into a tree.
+//!
+//! The spec doesn't require markup to be balanced, so none of this is a data model error. It is
+//! still almost always a mistake, eg. a translation where `{/link}` ended up before `{#link}`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::data_model::elements::{Expression, Markup, MarkupKind, Message, PatternElement};
+
+/// A problem with the markup of a pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkupError {
+    /// The index of the variant whose pattern has the problem, `None` in a pattern message
+    pub variant: Option<usize>,
+    /// The index of the offending markup in the pattern
+    pub index: usize,
+    /// The name of the offending markup
+    pub name: String,
+    pub kind: MarkupErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupErrorKind {
+    /// Open markup that is never closed
+    Unclosed,
+    /// Close markup that was never opened
+    Unopened,
+    /// Close markup while markup that was opened after it, `open`, is still open, eg. `{/a}` in
+    /// `{#a}{#b}{/a}{/b}`
+    Misnested { open: String },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MarkupErrorKind::Unclosed => write!(f, "{{#{}}} is never closed", self.name),
+            MarkupErrorKind::Unopened => {
+                write!(f, "{{/{}}} has no matching {{#{}}}", self.name, self.name)
+            }
+            MarkupErrorKind::Misnested { open } => {
+                write!(
+                    f,
+                    "{{/{}}} closes {{#{}}} before {{/{open}}}",
+                    self.name, self.name
+                )
+            }
+        }?;
+        match self.variant {
+            Some(variant) => write!(f, " (variant {variant}, element {})", self.index),
+            None => write!(f, " (element {})", self.index),
+        }
+    }
+}
+
+impl core::error::Error for MarkupError {}
+
+/// A node of a pattern tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<'p> {
+    Text(&'p str),
+    Expression(&'p Expression),
+    /// Standalone markup like `{#img /}`
+    Standalone(&'p Markup),
+    Span(Span<'p>),
+}
+
+/// Open markup, the pattern up to its close markup, and the close markup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span<'p> {
+    pub open: &'p Markup,
+    pub children: Vec<Node<'p>>,
+    pub close: &'p Markup,
+}
+
+impl Message {
+    /// Checks that the markup of every pattern is properly nested.
+    pub fn check_markup(&self) -> Vec<MarkupError> {
+        match self {
+            Self::Pattern(message) => check(&message.pattern),
+            Self::Select(message) => message
+                .variants
+                .iter()
+                .enumerate()
+                .flat_map(|(i, variant)| {
+                    check(&variant.value)
+                        .into_iter()
+                        .map(move |error| MarkupError {
+                            variant: Some(i),
+                            ..error
+                        })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Checks that the markup of a pattern is properly nested.
+pub fn check(pattern: &[PatternElement]) -> Vec<MarkupError> {
+    Builder::build(pattern).1
+}
+
+/// Turns a pattern into a tree where the elements between open and close markup are the children
+/// of a [`Span`].
+///
+/// # Errors
+/// Returns all problems with the markup if it isn't properly nested.
+pub fn tree(pattern: &[PatternElement]) -> Result<Vec<Node<'_>>, Vec<MarkupError>> {
+    match Builder::build(pattern) {
+        (nodes, errors) if errors.is_empty() => Ok(nodes),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Open markup whose close markup hasn't been found yet
+struct Frame<'p> {
+    open: &'p Markup,
+    index: usize,
+    children: Vec<Node<'p>>,
+}
+
+#[derive(Default)]
+struct Builder<'p> {
+    root: Vec<Node<'p>>,
+    stack: Vec<Frame<'p>>,
+    errors: Vec<MarkupError>,
+}
+
+impl<'p> Builder<'p> {
+    fn build(pattern: &'p [PatternElement]) -> (Vec<Node<'p>>, Vec<MarkupError>) {
+        let mut builder = Self::default();
+        for (index, element) in pattern.iter().enumerate() {
+            match element {
+                PatternElement::Literal(text) => builder.push(Node::Text(text)),
+                PatternElement::Expression(expression) => {
+                    builder.push(Node::Expression(expression))
+                }
+                PatternElement::Markup(markup) => match markup.kind {
+                    MarkupKind::Standalone => builder.push(Node::Standalone(markup)),
+                    MarkupKind::Open => builder.stack.push(Frame {
+                        open: markup,
+                        index,
+                        children: Vec::new(),
+                    }),
+                    MarkupKind::Close => builder.close(markup, index),
+                },
+            }
+        }
+
+        for frame in core::mem::take(&mut builder.stack) {
+            builder.error(&frame.open.name, frame.index, MarkupErrorKind::Unclosed);
+        }
+        (builder.root, builder.errors)
+    }
+
+    fn push(&mut self, node: Node<'p>) {
+        match self.stack.last_mut() {
+            Some(frame) => frame.children.push(node),
+            None => self.root.push(node),
+        }
+    }
+
+    fn close(&mut self, close: &'p Markup, index: usize) {
+        let Some(position) = self
+            .stack
+            .iter()
+            .rposition(|frame| frame.open.name == close.name)
+        else {
+            self.error(&close.name, index, MarkupErrorKind::Unopened);
+            return;
+        };
+
+        if let Some(inner) = self.stack.get(position + 1) {
+            let open = inner.open.name.clone();
+            self.error(&close.name, index, MarkupErrorKind::Misnested { open });
+            // The markup opened inside stays open, and takes over the children
+            let frame = self.stack.remove(position);
+            self.stack[position].children.splice(0..0, frame.children);
+            return;
+        }
+
+        if let Some(frame) = self.stack.pop() {
+            self.push(Node::Span(Span {
+                open: frame.open,
+                children: frame.children,
+                close,
+            }));
+        }
+    }
+
+    fn error(&mut self, name: &str, index: usize, kind: MarkupErrorKind) {
+        self.errors.push(MarkupError {
+            variant: None,
+            index,
+            name: name.into(),
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(source: &str) -> Vec<PatternElement> {
+        match source.parse().expect("valid message") {
+            Message::Pattern(message) => message.pattern,
+            Message::Select(_) => unreachable!(),
+        }
+    }
+
+    fn error(index: usize, name: &str, kind: MarkupErrorKind) -> MarkupError {
+        MarkupError {
+            variant: None,
+            index,
+            name: name.into(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn it_builds_a_tree() {
+        let pattern = pattern("Hi {#b}{$name}, see {#link}here{#img/}{/link}{/b}!");
+        let markup = |i: usize| match &pattern[i] {
+            PatternElement::Markup(markup) => markup,
+            _ => unreachable!(),
+        };
+        let PatternElement::Expression(name) = &pattern[2] else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            tree(&pattern),
+            Ok(vec![
+                Node::Text("Hi "),
+                Node::Span(Span {
+                    open: markup(1),
+                    children: vec![
+                        Node::Expression(name),
+                        Node::Text(", see "),
+                        Node::Span(Span {
+                            open: markup(4),
+                            children: vec![Node::Text("here"), Node::Standalone(markup(6))],
+                            close: markup(7),
+                        }),
+                    ],
+                    close: markup(8),
+                }),
+                Node::Text("!"),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_reports_badly_nested_markup() {
+        assert_eq!(
+            check(&pattern("{/a}{#b}x")),
+            [
+                error(0, "a", MarkupErrorKind::Unopened),
+                error(1, "b", MarkupErrorKind::Unclosed)
+            ]
+        );
+        assert_eq!(
+            check(&pattern("{#a}{#b}x{/a}{/b}")),
+            [error(
+                3,
+                "a",
+                MarkupErrorKind::Misnested { open: "b".into() }
+            )]
+        );
+        assert!(tree(&pattern("{#a}{#b}x{/a}{/b}")).is_err());
+        assert_eq!(check(&pattern("{#a}{#a}{/a}{/a}")), []);
+    }
+
+    #[test]
+    fn it_checks_every_variant() {
+        let message: Message =
+            ".input {$n :number}\n.match {$n}\none {{{#b}one{/b}}}\n* {{{#b}other}}"
+                .parse()
+                .expect("valid message");
+
+        assert_eq!(
+            message.check_markup(),
+            [MarkupError {
+                variant: Some(1),
+                ..error(0, "b", MarkupErrorKind::Unclosed)
+            }]
+        );
+        assert_eq!(
+            message.check_markup()[0].to_string(),
+            "{#b} is never closed (variant 1, element 0)"
+        );
+    }
+}
@@ -1,10 +1,14 @@
-pub mod errors;
+pub mod diff;
 pub mod elements;
+pub mod errors;
+pub mod markup;
+pub mod signature;
+mod validate;
 
 #[cfg(test)]
 mod tests {
     use std::vec;
-    
+
     use super::*;
     use elements::*;
 
@@ -141,6 +145,24 @@ mod tests {
         assert_eq!(needs_to_be_quoted.to_string(), "{{.local}}");
     }
 
+    #[test]
+    fn it_serializes_messages_that_parse_back() {
+        for source in [
+            "Hello {$name}!",
+            "{{  .not a keyword}}",
+            "Escaped \\\\ \\{ \\}",
+            "{|quoted literal| :string} {|a\\|b|} {-1.5e3} {$x :number minimumFractionDigits=|2 |}",
+            "{#link href=$url @translate=no }text{/link} {#img src=|a b.png| /} {$x @title=|T| @hidden}",
+            "{!reserved |body|} {^private @attr}",
+            ".input {$n :number}\n.local $m = {|x| :string}\n.when |x| {$n}\n{{{$n} {$m}}}",
+            ".input {$n :number}\n.match {$n}\n|1 0| {{ten}}\none {{one}}\n* {{\\{\\{ other}}",
+        ] {
+            let message: Message = source.parse().expect("valid message");
+            let serialized = message.to_string();
+            assert_eq!(serialized.parse(), Ok(message), "{serialized}");
+        }
+    }
+
     #[test]
     fn it_serializes_select_message() {
         let simple_message = SelectMessage {
@@ -183,12 +205,10 @@ mod tests {
             declarations: vec![Declaration::Local(LocalDeclaration {
                 name: "bar".into(),
                 value: Expression::Variable(VariableExpression {
-                    arg: VariableRef {
-                        name: "baz".into(),
-                    },
+                    arg: VariableRef { name: "baz".into() },
                     annotation: None,
                     attributes: vec![],
-                })
+                }),
             })],
             variants: vec![
                 Variant {
@@ -209,8 +229,9 @@ mod tests {
             simple_message.to_string(),
             ".match {$foo}\n1 {{bar}}\n* {{baz}}"
         );
-        assert_eq!(complex_message.to_string(),
-            ".local bar={$baz}\n.match {$fist} {:second}\n1 * {{bar}}\n* * {{baz}}"
+        assert_eq!(
+            complex_message.to_string(),
+            ".local $bar = {$baz}\n.match {$fist} {:second}\n1 * {{bar}}\n* * {{baz}}"
         )
     }
 }
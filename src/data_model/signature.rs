@@ -0,0 +1,306 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::data_model::elements::{
+    Annotation, Attribute, AttributeValue, Declaration, Expression, Message, OptionValue,
+    PatternElement, VariableExpression,
+};
+
+/// The external variables a message needs, in the order they are first referenced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signature {
+    parameters: Vec<Parameter>,
+}
+
+impl Signature {
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.parameters
+            .iter()
+            .map(|parameter| parameter.name.as_str())
+    }
+
+    pub const fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+}
+
+/// An external variable of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    pub argument_type: ArgumentType,
+}
+
+/// The kind of value a variable is expected to hold, based on the functions it is used with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentType {
+    /// The message doesn't constrain the value, or it uses it in conflicting ways
+    Any,
+    /// Used with `:string`
+    String,
+    /// Used with `:number` or `:integer`
+    Number,
+    /// Used with `:datetime`, `:date` or `:time`
+    DateTime,
+}
+
+impl ArgumentType {
+    /// The type a default function expects for its operand
+    pub fn of_function(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(Self::String),
+            "number" | "integer" => Some(Self::Number),
+            "datetime" | "date" | "time" => Some(Self::DateTime),
+            _ => None,
+        }
+    }
+
+    /// Combines the types of all functions a variable is used with
+    fn infer(used_with: &[Self]) -> Self {
+        let mut specific = used_with
+            .iter()
+            .copied()
+            .filter(|t| matches!(t, Self::Number | Self::DateTime));
+        match specific.next() {
+            Some(first) if specific.all(|t| t == first) => first,
+            Some(_) => Self::Any,
+            None if used_with.contains(&Self::String) => Self::String,
+            None => Self::Any,
+        }
+    }
+}
+
+impl Message {
+    /// Lists the external variables of the message: every variable that is referenced in a pattern,
+    /// selector, option or attribute and isn't bound by a `.local` declaration.
+    pub fn signature(&self) -> Signature {
+        let mut inference = Inference::default();
+        match self {
+            Self::Pattern(message) => {
+                inference.declarations(&message.declarations);
+                inference.pattern(&message.pattern);
+            }
+            Self::Select(message) => {
+                inference.declarations(&message.declarations);
+                for selector in &message.selectors {
+                    inference.expression(selector);
+                }
+                for variant in &message.variants {
+                    inference.pattern(&variant.value);
+                }
+            }
+        }
+        inference.finish()
+    }
+}
+
+#[derive(Default)]
+struct Inference {
+    /// External variables in the order they were first referenced
+    order: Vec<String>,
+    /// The types of the functions each external variable is used with
+    used_with: BTreeMap<String, Vec<ArgumentType>>,
+    /// The external variable each local variable holds the value of, if any
+    locals: BTreeMap<String, Option<String>>,
+}
+
+impl Inference {
+    fn finish(self) -> Signature {
+        let parameters = self
+            .order
+            .into_iter()
+            .map(|name| {
+                let argument_type = ArgumentType::infer(&self.used_with[&name]);
+                Parameter {
+                    name,
+                    argument_type,
+                }
+            })
+            .collect();
+        Signature { parameters }
+    }
+
+    /// Returns the external variable a reference resolves to
+    fn reference(&mut self, name: &str) -> Option<String> {
+        if let Some(source) = self.locals.get(name) {
+            return source.clone();
+        }
+        if !self.used_with.contains_key(name) {
+            self.order.push(name.into());
+            self.used_with.insert(name.into(), Vec::new());
+        }
+        Some(name.into())
+    }
+
+    fn declarations(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations {
+            match declaration {
+                Declaration::Input(input) => {
+                    self.variable_expression(&input.value);
+                }
+                Declaration::Local(local) => {
+                    let source = self.expression(&local.value);
+                    self.locals.insert(local.name.clone(), source);
+                }
+                Declaration::UnsupportedStatement(statement) => {
+                    for expression in &statement.expressions {
+                        self.expression(expression);
+                    }
+                }
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &[PatternElement]) {
+        for element in pattern {
+            match element {
+                PatternElement::Literal(_) => {}
+                PatternElement::Expression(expression) => {
+                    self.expression(expression);
+                }
+                PatternElement::Markup(markup) => {
+                    self.options(&markup.options);
+                    self.attributes(&markup.attributes);
+                }
+            }
+        }
+    }
+
+    /// Returns the external variable whose value the expression operates on, if any
+    fn expression(&mut self, expression: &Expression) -> Option<String> {
+        match expression {
+            Expression::Literal(e) => {
+                self.annotation(None, e.annotation.as_ref());
+                self.attributes(&e.attributes);
+                None
+            }
+            Expression::Variable(e) => self.variable_expression(e),
+            Expression::Function(e) => {
+                self.options(&e.annotation.options);
+                self.attributes(&e.attributes);
+                None
+            }
+            Expression::Unsupported(e) => {
+                self.attributes(&e.attributes);
+                None
+            }
+        }
+    }
+
+    fn variable_expression(&mut self, expression: &VariableExpression) -> Option<String> {
+        let operand = self.reference(&expression.arg.name);
+        self.annotation(operand.as_deref(), expression.annotation.as_ref());
+        self.attributes(&expression.attributes);
+        operand
+    }
+
+    fn annotation(&mut self, operand: Option<&str>, annotation: Option<&Annotation>) {
+        let Some(Annotation::Function(function)) = annotation else {
+            return;
+        };
+        if let (Some(operand), Some(argument_type)) =
+            (operand, ArgumentType::of_function(&function.name))
+        {
+            if let Some(used_with) = self.used_with.get_mut(operand) {
+                used_with.push(argument_type);
+            }
+        }
+        self.options(&function.options);
+    }
+
+    fn options(&mut self, options: &[crate::data_model::elements::Option]) {
+        for option in options {
+            if let OptionValue::Variable(variable) = &option.value {
+                self.reference(&variable.name);
+            }
+        }
+    }
+
+    fn attributes(&mut self, attributes: &[Attribute]) {
+        for attribute in attributes {
+            if let Some(AttributeValue::Variable(variable)) = &attribute.value {
+                self.reference(&variable.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(source: &str) -> Vec<(String, ArgumentType)> {
+        let message: Message = source.parse().expect("valid message");
+        message
+            .signature()
+            .parameters()
+            .iter()
+            .map(|parameter| (parameter.name.clone(), parameter.argument_type))
+            .collect()
+    }
+
+    #[test]
+    fn it_lists_external_variables_in_order() {
+        assert_eq!(
+            signature("{$b} {$a :string} {|x| :number minimumFractionDigits=$digits} {#link href=$url}{/link} {$b @title=$title}"),
+            [
+                ("b".into(), ArgumentType::Any),
+                ("a".into(), ArgumentType::String),
+                ("digits".into(), ArgumentType::Any),
+                ("url".into(), ArgumentType::Any),
+                ("title".into(), ArgumentType::Any),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_skips_local_variables() {
+        assert_eq!(
+            signature(".local $greeting = {|Hello|}\n.local $when = {$at :date}\n{{{$greeting} {$user}, {$when}}}"),
+            [
+                ("at".into(), ArgumentType::DateTime),
+                ("user".into(), ArgumentType::Any),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_infers_types_through_declarations() {
+        assert_eq!(
+            signature(".input {$count :integer}\n.local $n = {$amount}\n.match {$count} {$n :number}\n* * {{{$label :string}}}"),
+            [
+                ("count".into(), ArgumentType::Number),
+                ("amount".into(), ArgumentType::Number),
+                ("label".into(), ArgumentType::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_prefers_specific_types() {
+        assert_eq!(
+            signature("{$x :string} {$x :number}"),
+            [("x".into(), ArgumentType::Number)]
+        );
+        assert_eq!(
+            signature("{$x :date} {$x :number}"),
+            [("x".into(), ArgumentType::Any)]
+        );
+        assert_eq!(signature("{$x :custom}"), [("x".into(), ArgumentType::Any)]);
+    }
+}
@@ -0,0 +1,205 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::data_model::elements::{
+    Annotation, Declaration, Expression, Markup, Message, OptionValue, PatternElement,
+    SelectMessage, VariantKey,
+};
+use crate::data_model::errors::{
+    DataModelError, DuplicateDeclaration, DuplicateOptionName, MissingFallbackVariant,
+    MissingSelectorAnnotation, VariantKeyMismatch,
+};
+
+impl Message {
+    /// Checks the message for the Data Model Errors defined by the spec.
+    ///
+    /// # Errors
+    /// Returns the first error that is found.
+    pub fn validate(&self) -> Result<(), DataModelError> {
+        match self {
+            Self::Pattern(message) => {
+                validate_declarations(&message.declarations)?;
+                validate_pattern(&message.pattern)
+            }
+            Self::Select(message) => {
+                validate_declarations(&message.declarations)?;
+                for selector in &message.selectors {
+                    validate_expression(selector)?;
+                }
+                validate_variants(message)?;
+                validate_selector_annotations(message)?;
+                for variant in &message.variants {
+                    validate_pattern(&variant.value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn validate_declarations(declarations: &[Declaration]) -> Result<(), DataModelError> {
+    // Every variable that has been declared explicitly, or implicitly by being used
+    let mut seen = BTreeSet::new();
+
+    for declaration in declarations {
+        match declaration {
+            Declaration::Input(input) => {
+                validate_annotation(input.value.annotation.as_ref())?;
+                if !seen.insert(input.name.as_str()) {
+                    return Err(duplicate_declaration(&input.name));
+                }
+                seen.extend(annotation_variables(input.value.annotation.as_ref()));
+            }
+            Declaration::Local(local) => {
+                validate_expression(&local.value)?;
+                // A local declaration may not reference itself, so its variables count as used before the declaration
+                seen.extend(expression_variables(&local.value));
+                if !seen.insert(local.name.as_str()) {
+                    return Err(duplicate_declaration(&local.name));
+                }
+            }
+            Declaration::UnsupportedStatement(statement) => {
+                for expression in &statement.expressions {
+                    validate_expression(expression)?;
+                    seen.extend(expression_variables(expression));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn duplicate_declaration(name: &str) -> DataModelError {
+    DataModelError::DuplicateDeclaration(DuplicateDeclaration { name: name.into() })
+}
+
+fn validate_variants(message: &SelectMessage) -> Result<(), DataModelError> {
+    for (i, variant) in message.variants.iter().enumerate() {
+        if variant.keys.len() != message.selectors.len() {
+            return Err(DataModelError::VariantKeyMismatch(VariantKeyMismatch {
+                variant: i,
+                expected: message.selectors.len(),
+                found: variant.keys.len(),
+            }));
+        }
+    }
+
+    let has_fallback = message.variants.iter().any(|variant| {
+        variant
+            .keys
+            .iter()
+            .all(|key| matches!(key, VariantKey::Catchall))
+    });
+
+    if has_fallback {
+        Ok(())
+    } else {
+        Err(DataModelError::MissingFallbackVariant(
+            MissingFallbackVariant,
+        ))
+    }
+}
+
+fn validate_selector_annotations(message: &SelectMessage) -> Result<(), DataModelError> {
+    for (i, selector) in message.selectors.iter().enumerate() {
+        if !is_annotated(selector, &message.declarations) {
+            return Err(DataModelError::MissingSelectorAnnotation(
+                MissingSelectorAnnotation { selector: i },
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the expression has an annotation, or references a declaration that has one
+fn is_annotated(expression: &Expression, declarations: &[Declaration]) -> bool {
+    let variable = match expression {
+        Expression::Literal(e) => return e.annotation.is_some(),
+        Expression::Function(_) | Expression::Unsupported(_) => return true,
+        Expression::Variable(e) if e.annotation.is_some() => return true,
+        Expression::Variable(e) => &e.arg.name,
+    };
+
+    declarations.iter().any(|declaration| match declaration {
+        Declaration::Input(input) => input.name == *variable && input.value.annotation.is_some(),
+        Declaration::Local(local) => {
+            local.name == *variable && is_annotated(&local.value, declarations)
+        }
+        Declaration::UnsupportedStatement(_) => false,
+    })
+}
+
+fn validate_pattern(pattern: &[PatternElement]) -> Result<(), DataModelError> {
+    for element in pattern {
+        match element {
+            PatternElement::Literal(_) => {}
+            PatternElement::Expression(expression) => validate_expression(expression)?,
+            PatternElement::Markup(markup) => validate_markup(markup)?,
+        }
+    }
+    Ok(())
+}
+
+fn validate_expression(expression: &Expression) -> Result<(), DataModelError> {
+    match expression {
+        Expression::Literal(e) => validate_annotation(e.annotation.as_ref()),
+        Expression::Variable(e) => validate_annotation(e.annotation.as_ref()),
+        Expression::Function(e) => validate_options(&e.annotation.options),
+        Expression::Unsupported(_) => Ok(()),
+    }
+}
+
+fn validate_markup(markup: &Markup) -> Result<(), DataModelError> {
+    validate_options(&markup.options)
+}
+
+fn validate_annotation(annotation: Option<&Annotation>) -> Result<(), DataModelError> {
+    match annotation {
+        Some(Annotation::Function(function)) => validate_options(&function.options),
+        Some(Annotation::Unsupported(_)) | None => Ok(()),
+    }
+}
+
+fn validate_options(options: &[crate::data_model::elements::Option]) -> Result<(), DataModelError> {
+    let mut names = BTreeSet::new();
+    for option in options {
+        if !names.insert(option.name.as_str()) {
+            return Err(DataModelError::DuplicateOptionName(DuplicateOptionName {
+                name: option.name.clone(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// The names of all variables referenced by an expression
+fn expression_variables(expression: &Expression) -> Vec<&str> {
+    match expression {
+        Expression::Literal(e) => annotation_variables(e.annotation.as_ref()),
+        Expression::Variable(e) => {
+            let mut variables = annotation_variables(e.annotation.as_ref());
+            variables.push(&e.arg.name);
+            variables
+        }
+        Expression::Function(e) => option_variables(&e.annotation.options),
+        Expression::Unsupported(_) => Vec::new(),
+    }
+}
+
+fn annotation_variables(annotation: Option<&Annotation>) -> Vec<&str> {
+    match annotation {
+        Some(Annotation::Function(function)) => option_variables(&function.options),
+        Some(Annotation::Unsupported(_)) | None => Vec::new(),
+    }
+}
+
+fn option_variables(options: &[crate::data_model::elements::Option]) -> Vec<&str> {
+    options
+        .iter()
+        .filter_map(|option| match &option.value {
+            OptionValue::Variable(v) => Some(v.name.as_str()),
+            OptionValue::Literal(_) => None,
+        })
+        .collect()
+}
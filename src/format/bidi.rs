@@ -0,0 +1,123 @@
+//! Isolates placeholders from the surrounding text, so their direction doesn't mix with the message.
+//!
+//! See: https://github.com/unicode-org/message-format-wg/blob/main/spec/formatting.md#handling-bidirectional-text
+
+use crate::locale::Direction;
+
+const LRI: char = '\u{2066}';
+const RLI: char = '\u{2067}';
+const FSI: char = '\u{2068}';
+const PDI: char = '\u{2069}';
+const LRE: char = '\u{202A}';
+const RLE: char = '\u{202B}';
+const PDF: char = '\u{202C}';
+
+/// How formatted placeholders are isolated from the text around them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BidiIsolation {
+    /// Placeholders are written as they are
+    #[default]
+    None,
+    /// The spec's default strategy: placeholders are wrapped in isolates (LRI, RLI or FSI … PDI),
+    /// unless they are left-to-right in a left-to-right message.
+    Default,
+    /// Like `Default`, but with the older embedding controls (LRE or RLE … PDF) for text renderers
+    /// that don't support isolates. Placeholders of unknown direction are embedded based on their
+    /// first strong character.
+    Compatibility,
+}
+
+/// The direction of a formatted placeholder
+#[derive(Debug, Clone, Copy)]
+pub struct Placeholder {
+    /// `None` if the direction is unknown
+    direction: Option<Direction>,
+    /// Whether the direction was set explicitly with `u:dir`
+    explicit: bool,
+}
+
+impl Placeholder {
+    /// Reads the `u:dir` option, falling back to the direction of the formatted value
+    pub fn new(u_dir: Option<&str>, message: Direction, value: Option<Direction>) -> Self {
+        let (direction, explicit) = match u_dir {
+            Some("ltr") => (Some(Direction::LeftToRight), true),
+            Some("rtl") => (Some(Direction::RightToLeft), true),
+            Some("auto") => (None, true),
+            Some("inherit") => (Some(message), false),
+            _ => (value, false),
+        };
+        Self {
+            direction,
+            explicit,
+        }
+    }
+}
+
+/// What is written around a placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    None,
+    Wrap(char, char),
+    /// Depends on the formatted placeholder, see [`Isolation::of_text`]
+    FirstStrong,
+}
+
+impl Isolation {
+    /// How a placeholder is isolated according to `strategy`
+    pub fn new(strategy: BidiIsolation, message: Direction, placeholder: Placeholder) -> Self {
+        match (strategy, placeholder.direction) {
+            (BidiIsolation::None, _) => Self::None,
+            (BidiIsolation::Compatibility, None) => Self::FirstStrong,
+            (_, Some(direction)) => {
+                Self::of_direction(strategy, message, direction, placeholder.explicit)
+            }
+            (_, None) => Self::Wrap(FSI, PDI),
+        }
+    }
+
+    /// How a placeholder of unknown direction is isolated, based on its formatted text
+    pub fn of_text(strategy: BidiIsolation, message: Direction, formatted: &str) -> Self {
+        first_strong(formatted).map_or(Self::None, |direction| {
+            Self::of_direction(strategy, message, direction, false)
+        })
+    }
+
+    fn of_direction(
+        strategy: BidiIsolation,
+        message: Direction,
+        direction: Direction,
+        explicit: bool,
+    ) -> Self {
+        match (strategy, direction) {
+            (_, Direction::LeftToRight) if message == Direction::LeftToRight && !explicit => {
+                Self::None
+            }
+            (BidiIsolation::Compatibility, Direction::LeftToRight) => Self::Wrap(LRE, PDF),
+            (BidiIsolation::Compatibility, Direction::RightToLeft) => Self::Wrap(RLE, PDF),
+            (_, Direction::LeftToRight) => Self::Wrap(LRI, PDI),
+            (_, Direction::RightToLeft) => Self::Wrap(RLI, PDI),
+        }
+    }
+}
+
+/// The direction of the first character with a strong direction
+fn first_strong(text: &str) -> Option<Direction> {
+    text.chars().find_map(|c| {
+        if is_rtl(c) {
+            Some(Direction::RightToLeft)
+        } else if c.is_alphabetic() {
+            Some(Direction::LeftToRight)
+        } else {
+            None
+        }
+    })
+}
+
+const fn is_rtl(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}')
+}
@@ -0,0 +1,890 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write as _};
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::arguments::{Arguments, IntoArguments, Value};
+use crate::data_model::elements::{
+    self, Declaration, Expression, MarkupKind, Message, OptionValue, PatternElement, VariantKey,
+};
+use crate::data_model::errors::{
+    DataModelError, SelectionError, UnknownFunction, UnresolvedVariable, UnsupportedExpression,
+    UnsupportedStatement,
+};
+use crate::format::bidi::{BidiIsolation, Isolation, Placeholder};
+use crate::format::markup::{MarkupHandler, PlainText, Rendered};
+use crate::format::{functions, FormatError, Function, FunctionInput, FunctionRegistry, Options};
+use crate::locale::Locale;
+
+/// A message that has been prepared for formatting.
+///
+/// Function references are resolved, variables are assigned to slots, literal options are
+/// precomputed and the variant keys of select messages are turned into a lookup table, so
+/// formatting doesn't need to look at the [`Message`] anymore.
+#[derive(Debug)]
+pub struct CompiledMessage {
+    locale: Locale,
+    bidi_isolation: BidiIsolation,
+    strict: bool,
+    slots: Vec<Slot>,
+    body: Body,
+    unsupported_statements: Vec<String>,
+}
+
+/// A variable, either passed in as an argument or declared in the message
+#[derive(Debug)]
+struct Slot {
+    name: String,
+    source: SlotSource,
+}
+
+#[derive(Debug)]
+enum SlotSource {
+    Argument,
+    Declaration(CompiledExpression),
+}
+
+#[derive(Debug)]
+enum Body {
+    Pattern(Vec<Part>),
+    Select(SelectionTable),
+}
+
+#[derive(Debug)]
+struct SelectionTable {
+    selectors: Vec<CompiledExpression>,
+    /// The distinct literal keys of each selector
+    keys: Vec<Vec<String>>,
+    variants: Vec<CompiledVariant>,
+}
+
+#[derive(Debug)]
+struct CompiledVariant {
+    /// Indices into the keys of each selector, `None` for the catch-all key
+    keys: Vec<Option<usize>>,
+    pattern: Vec<Part>,
+}
+
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    Expression(CompiledExpression),
+    Markup(CompiledMarkup),
+}
+
+#[derive(Debug)]
+struct CompiledMarkup {
+    kind: MarkupKind,
+    name: String,
+    options: CompiledOptions,
+}
+
+#[derive(Debug)]
+struct CompiledExpression {
+    operand: Operand,
+    annotation: CompiledAnnotation,
+    /// What is written instead of the expression if it fails, without the braces
+    fallback: String,
+}
+
+#[derive(Debug)]
+enum Operand {
+    None,
+    Literal(Value),
+    Slot(usize),
+}
+
+#[derive(Debug)]
+enum CompiledAnnotation {
+    None,
+    Function(FunctionRef, CompiledOptions),
+    Unsupported(String),
+}
+
+struct FunctionRef {
+    name: String,
+    /// `None` if the registry doesn't contain the function
+    function: Option<Arc<dyn Function>>,
+}
+
+impl fmt::Debug for FunctionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ":{}", self.name)
+    }
+}
+
+#[derive(Debug)]
+struct CompiledOptions {
+    /// The literal options, resolved ahead of time
+    literal: Options,
+    /// Options whose value is a variable, by slot
+    variables: Vec<(String, usize)>,
+}
+
+/// A resolved value, ready to be formatted or used for selection
+#[derive(Clone)]
+struct Resolved<'r> {
+    value: Option<Cow<'r, Value>>,
+    function: Option<(&'r FunctionRef, Cow<'r, Options>)>,
+}
+
+impl Message {
+    /// Validates the message and prepares it for formatting with the functions from `registry`.
+    ///
+    /// Functions that are missing from the registry are not an error here, they fail when the
+    /// message is formatted.
+    ///
+    /// # Errors
+    /// Returns an error if the message is not valid according to the data model.
+    pub fn compile(
+        &self,
+        registry: &FunctionRegistry,
+        locale: Locale,
+    ) -> Result<CompiledMessage, DataModelError> {
+        self.validate()?;
+
+        let mut compiler = Compiler {
+            registry,
+            slots: Vec::new(),
+            scope: BTreeMap::new(),
+            unsupported_statements: Vec::new(),
+        };
+
+        let body = match self {
+            Self::Pattern(message) => {
+                compiler.declarations(&message.declarations);
+                Body::Pattern(compiler.pattern(&message.pattern))
+            }
+            Self::Select(message) => {
+                compiler.declarations(&message.declarations);
+                Body::Select(compiler.selection(message))
+            }
+        };
+
+        Ok(CompiledMessage {
+            locale,
+            bidi_isolation: BidiIsolation::None,
+            strict: false,
+            slots: compiler.slots,
+            body,
+            unsupported_statements: compiler.unsupported_statements,
+        })
+    }
+}
+
+struct Compiler<'a> {
+    registry: &'a FunctionRegistry,
+    slots: Vec<Slot>,
+    /// The slot each variable name currently refers to
+    scope: BTreeMap<String, usize>,
+    unsupported_statements: Vec<String>,
+}
+
+impl Compiler<'_> {
+    fn declarations(&mut self, declarations: &[Declaration]) {
+        for declaration in declarations {
+            match declaration {
+                Declaration::Input(input) => {
+                    let expression = self.variable_expression(&input.value);
+                    self.declare(&input.name, expression);
+                }
+                Declaration::Local(local) => {
+                    let expression = self.expression(&local.value);
+                    self.declare(&local.name, expression);
+                }
+                Declaration::UnsupportedStatement(statement) => {
+                    self.unsupported_statements.push(statement.keyword.clone());
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, expression: CompiledExpression) {
+        self.slots.push(Slot {
+            name: name.into(),
+            source: SlotSource::Declaration(expression),
+        });
+        self.scope.insert(name.into(), self.slots.len() - 1);
+    }
+
+    /// The slot for a variable. Variables that haven't been declared are arguments.
+    fn variable(&mut self, name: &str) -> usize {
+        if let Some(slot) = self.scope.get(name) {
+            return *slot;
+        }
+
+        self.slots.push(Slot {
+            name: name.into(),
+            source: SlotSource::Argument,
+        });
+        self.scope.insert(name.into(), self.slots.len() - 1);
+        self.slots.len() - 1
+    }
+
+    fn selection(&mut self, message: &elements::SelectMessage) -> SelectionTable {
+        let selectors = message
+            .selectors
+            .iter()
+            .map(|selector| self.expression(selector))
+            .collect();
+
+        let mut keys: Vec<Vec<String>> = vec![Vec::new(); message.selectors.len()];
+        let variants = message
+            .variants
+            .iter()
+            .map(|variant| CompiledVariant {
+                keys: variant
+                    .keys
+                    .iter()
+                    .zip(&mut keys)
+                    .map(|(key, selector_keys)| match key {
+                        VariantKey::Catchall => None,
+                        VariantKey::Literal(literal) => Some(
+                            selector_keys
+                                .iter()
+                                .position(|k| *k == literal.value)
+                                .unwrap_or_else(|| {
+                                    selector_keys.push(literal.value.clone());
+                                    selector_keys.len() - 1
+                                }),
+                        ),
+                    })
+                    .collect(),
+                pattern: self.pattern(&variant.value),
+            })
+            .collect();
+
+        SelectionTable {
+            selectors,
+            keys,
+            variants,
+        }
+    }
+
+    fn pattern(&mut self, pattern: &[PatternElement]) -> Vec<Part> {
+        let mut parts = Vec::new();
+        for element in pattern {
+            match element {
+                PatternElement::Literal(text) => match parts.last_mut() {
+                    Some(Part::Text(previous)) => previous.push_str(text),
+                    _ => parts.push(Part::Text(text.clone())),
+                },
+                PatternElement::Expression(expression) => {
+                    parts.push(Part::Expression(self.expression(expression)));
+                }
+                PatternElement::Markup(markup) => parts.push(Part::Markup(CompiledMarkup {
+                    kind: markup.kind.clone(),
+                    name: markup.name.clone(),
+                    options: self.options(&markup.options),
+                })),
+            }
+        }
+        parts
+    }
+
+    fn expression(&mut self, expression: &Expression) -> CompiledExpression {
+        match expression {
+            Expression::Literal(e) => CompiledExpression {
+                operand: Operand::Literal(Value::String(e.arg.value.clone())),
+                annotation: self.annotation(e.annotation.as_ref()),
+                fallback: literal_fallback(&e.arg.value),
+            },
+            Expression::Variable(e) => self.variable_expression(e),
+            Expression::Function(e) => CompiledExpression {
+                operand: Operand::None,
+                annotation: self.function(&e.annotation),
+                fallback: format!(":{}", e.annotation.name),
+            },
+            Expression::Unsupported(e) => CompiledExpression {
+                operand: Operand::None,
+                annotation: CompiledAnnotation::Unsupported(e.annotation.source.clone()),
+                // The spec's fallback for an unsupported annotation is its sigil, eg. `{!}`
+                fallback: e.annotation.source.chars().take(1).collect(),
+            },
+        }
+    }
+
+    fn variable_expression(
+        &mut self,
+        expression: &elements::VariableExpression,
+    ) -> CompiledExpression {
+        CompiledExpression {
+            operand: Operand::Slot(self.variable(&expression.arg.name)),
+            annotation: self.annotation(expression.annotation.as_ref()),
+            fallback: format!("${}", expression.arg.name),
+        }
+    }
+
+    fn annotation(&mut self, annotation: Option<&elements::Annotation>) -> CompiledAnnotation {
+        match annotation {
+            None => CompiledAnnotation::None,
+            Some(elements::Annotation::Function(function)) => self.function(function),
+            Some(elements::Annotation::Unsupported(unsupported)) => {
+                CompiledAnnotation::Unsupported(unsupported.source.clone())
+            }
+        }
+    }
+
+    fn function(&mut self, annotation: &elements::FunctionAnnotation) -> CompiledAnnotation {
+        CompiledAnnotation::Function(
+            FunctionRef {
+                name: annotation.name.clone(),
+                function: self.registry.get(&annotation.name).cloned(),
+            },
+            self.options(&annotation.options),
+        )
+    }
+
+    fn options(&mut self, options: &[elements::Option]) -> CompiledOptions {
+        let mut literal = Options::new();
+        let mut variables = Vec::new();
+        for option in options {
+            match &option.value {
+                OptionValue::Literal(l) => literal.insert(option.name.clone(), l.value.clone()),
+                OptionValue::Variable(v) => {
+                    variables.push((option.name.clone(), self.variable(&v.name)))
+                }
+            }
+        }
+        CompiledOptions { literal, variables }
+    }
+}
+
+impl CompiledMessage {
+    pub const fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Sets how placeholders are isolated from the surrounding text. Defaults to [`BidiIsolation::None`].
+    #[must_use]
+    pub const fn bidi_isolation(mut self, strategy: BidiIsolation) -> Self {
+        self.bidi_isolation = strategy;
+        self
+    }
+
+    /// Sets whether formatting stops at the first error. Defaults to `false`: like the spec
+    /// describes, expressions that fail are replaced by a fallback like `{$user}` and formatting continues.
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Formats the message into a string.
+    ///
+    /// # Errors
+    /// In strict mode, returns the first error that occurs while resolving, selecting or formatting.
+    /// Otherwise the errors are replaced by fallbacks, use [`CompiledMessage::format_with_errors`]
+    /// to see them.
+    pub fn format<'a>(&self, arguments: impl IntoArguments<'a>) -> Result<String, FormatError> {
+        let mut out = String::new();
+        self.format_to(arguments, &mut out)?;
+        Ok(out)
+    }
+
+    /// Formats the message into `out`, without building the formatted message in memory first.
+    ///
+    /// # Errors
+    /// Like [`CompiledMessage::format`]. In strict mode, the output up to the error has already been
+    /// written. Returns [`FormatError::Write`] if writing to `out` fails.
+    pub fn format_to<'a>(
+        &self,
+        arguments: impl IntoArguments<'a>,
+        out: &mut impl fmt::Write,
+    ) -> Result<(), FormatError> {
+        self.render_to(arguments, &mut PlainText, out)
+    }
+
+    /// Formats the message into a string, passing all text and markup through `handler`.
+    ///
+    /// # Errors
+    /// Like [`CompiledMessage::format`].
+    pub fn render<'a>(
+        &self,
+        arguments: impl IntoArguments<'a>,
+        handler: &mut impl MarkupHandler,
+    ) -> Result<String, FormatError> {
+        let mut out = String::new();
+        self.render_to(arguments, handler, &mut out)?;
+        Ok(out)
+    }
+
+    /// Formats the message into `out`, passing all text and markup through `handler`.
+    ///
+    /// # Errors
+    /// Like [`CompiledMessage::format_to`].
+    pub fn render_to<'a>(
+        &self,
+        arguments: impl IntoArguments<'a>,
+        handler: &mut impl MarkupHandler,
+        out: &mut impl fmt::Write,
+    ) -> Result<(), FormatError> {
+        let arguments = arguments.into_arguments();
+        self.write(&arguments, handler, out, &mut Errors::new(self.strict))
+    }
+
+    /// Formats the message as UTF-8 into `out`. Wrap unbuffered writers in a [`std::io::BufWriter`],
+    /// the message is written in many small pieces.
+    ///
+    /// # Errors
+    /// Like [`CompiledMessage::format_to`]. The I/O error is returned as [`FormatError::Io`].
+    #[cfg(feature = "std")]
+    pub fn format_to_writer<'a>(
+        &self,
+        arguments: impl IntoArguments<'a>,
+        out: &mut impl io::Write,
+    ) -> Result<(), FormatError> {
+        let mut adapter = IoAdapter {
+            inner: out,
+            error: None,
+        };
+        match self.format_to(arguments, &mut adapter) {
+            Err(FormatError::Write) => Err(adapter
+                .error
+                .map_or(FormatError::Write, |e| FormatError::Io(e.kind()))),
+            result => result,
+        }
+    }
+
+    /// Formats the message into a string, replacing expressions that fail by their fallback.
+    /// Returns all errors that occurred, even in strict mode.
+    pub fn format_with_errors<'a>(
+        &self,
+        arguments: impl IntoArguments<'a>,
+    ) -> (String, Vec<FormatError>) {
+        let arguments = arguments.into_arguments();
+        let mut out = String::new();
+        let mut errors = Errors::new(false);
+        if let Err(error) = self.write(&arguments, &mut PlainText, &mut out, &mut errors) {
+            errors.errors.push(error);
+        }
+        (out, errors.errors)
+    }
+
+    fn write(
+        &self,
+        arguments: &Arguments<'_>,
+        handler: &mut dyn MarkupHandler,
+        out: &mut dyn fmt::Write,
+        errors: &mut Errors,
+    ) -> Result<(), FormatError> {
+        handler.start();
+        for keyword in &self.unsupported_statements {
+            errors.report(FormatError::UnsupportedStatement(UnsupportedStatement {
+                keyword: keyword.clone(),
+            }))?;
+        }
+
+        let environment = self.environment(arguments);
+        let pattern = match &self.body {
+            Body::Pattern(pattern) => pattern,
+            Body::Select(table) => self.select(table, &environment, errors)?,
+        };
+
+        let out = &mut Rendered { handler, out };
+        let direction = self.locale.direction();
+        for part in pattern {
+            match part {
+                Part::Text(text) => out.write_str(text).map_err(|_| FormatError::Write)?,
+                Part::Markup(markup) => {
+                    let options =
+                        Self::resolve_options(&markup.options, &environment).or_else(|error| {
+                            errors
+                                .report(error)
+                                .map(|()| Cow::Borrowed(&markup.options.literal))
+                        })?;
+                    match markup.kind {
+                        MarkupKind::Open => out.handler.open(&markup.name, &options, out.out),
+                        MarkupKind::Close => out.handler.close(&markup.name, &options, out.out),
+                        MarkupKind::Standalone => {
+                            out.handler.standalone(&markup.name, &options, out.out)
+                        }
+                    }
+                    .map_err(|_| FormatError::Write)?;
+                }
+                Part::Expression(expression) => {
+                    let resolved = self.resolve(expression, &environment);
+                    let placeholder = resolved.as_ref().map_or_else(
+                        |_| Placeholder::new(None, direction, None),
+                        |resolved| self.placeholder(resolved),
+                    );
+
+                    match Isolation::new(self.bidi_isolation, direction, placeholder) {
+                        Isolation::None => {
+                            self.write_placeholder(resolved, expression, out, errors)?
+                        }
+                        Isolation::Wrap(start, end) => {
+                            out.write_char(start).map_err(|_| FormatError::Write)?;
+                            self.write_placeholder(resolved, expression, out, errors)?;
+                            out.write_char(end).map_err(|_| FormatError::Write)?;
+                        }
+                        Isolation::FirstStrong => {
+                            let mut formatted = String::new();
+                            self.write_placeholder(resolved, expression, &mut formatted, errors)?;
+                            match Isolation::of_text(self.bidi_isolation, direction, &formatted) {
+                                Isolation::Wrap(start, end) => {
+                                    write!(out, "{start}{formatted}{end}")
+                                }
+                                _ => out.write_str(&formatted),
+                            }
+                            .map_err(|_| FormatError::Write)?;
+                        }
+                    }
+                }
+            }
+        }
+        out.handler.finish(out.out).map_err(|_| FormatError::Write)
+    }
+
+    /// Writes a resolved expression, or its fallback if it failed
+    fn write_placeholder(
+        &self,
+        resolved: Result<Resolved<'_>, FormatError>,
+        expression: &CompiledExpression,
+        out: &mut dyn fmt::Write,
+        errors: &mut Errors,
+    ) -> Result<(), FormatError> {
+        match resolved.and_then(|resolved| self.write_resolved(&resolved, out)) {
+            Ok(()) => Ok(()),
+            Err(FormatError::Write) => Err(FormatError::Write),
+            Err(error) => {
+                errors.report(error)?;
+                write!(out, "{{{}}}", expression.fallback).map_err(|_| FormatError::Write)
+            }
+        }
+    }
+
+    /// Resolves every slot. Errors are kept until the slot is used.
+    fn environment<'r>(
+        &'r self,
+        arguments: &'r Arguments<'_>,
+    ) -> Vec<Result<Resolved<'r>, FormatError>> {
+        let mut environment: Vec<Result<Resolved<'r>, FormatError>> =
+            Vec::with_capacity(self.slots.len());
+        for slot in &self.slots {
+            let resolved = match &slot.source {
+                SlotSource::Argument => arguments
+                    .get(&slot.name)
+                    .map(|value| Resolved {
+                        value: Some(value),
+                        function: None,
+                    })
+                    .ok_or_else(|| {
+                        FormatError::UnresolvedVariable(UnresolvedVariable {
+                            name: slot.name.clone(),
+                        })
+                    }),
+                SlotSource::Declaration(expression) => self.resolve(expression, &environment),
+            };
+            environment.push(resolved);
+        }
+        environment
+    }
+
+    fn resolve<'r>(
+        &'r self,
+        expression: &'r CompiledExpression,
+        environment: &[Result<Resolved<'r>, FormatError>],
+    ) -> Result<Resolved<'r>, FormatError> {
+        let operand = match &expression.operand {
+            Operand::None => None,
+            Operand::Literal(value) => Some(Resolved {
+                value: Some(Cow::Borrowed(value)),
+                function: None,
+            }),
+            Operand::Slot(slot) => Some(environment[*slot].clone()?),
+        };
+
+        let (function, options) = match &expression.annotation {
+            CompiledAnnotation::None => {
+                return Ok(operand.unwrap_or(Resolved {
+                    value: None,
+                    function: None,
+                }))
+            }
+            CompiledAnnotation::Unsupported(source) => {
+                return Err(FormatError::UnsupportedExpression(UnsupportedExpression {
+                    source: source.clone(),
+                }))
+            }
+            CompiledAnnotation::Function(function, options) => (function, options),
+        };
+
+        if function.function.is_none() {
+            return Err(FormatError::UnknownFunction(UnknownFunction {
+                name: function.name.clone(),
+            }));
+        }
+
+        let resolved_options = Self::resolve_options(options, environment)?;
+
+        let Some(operand) = operand else {
+            return Ok(Resolved {
+                value: None,
+                function: Some((function, resolved_options)),
+            });
+        };
+
+        // Calling the same function again keeps the options of the previous call
+        let resolved_options = match operand.function {
+            Some((previous, previous_options)) if previous.name == function.name => {
+                let mut merged = previous_options.into_owned();
+                merged.merge(&resolved_options);
+                Cow::Owned(merged)
+            }
+            _ => resolved_options,
+        };
+
+        Ok(Resolved {
+            value: operand.value,
+            function: Some((function, resolved_options)),
+        })
+    }
+
+    fn resolve_options<'r>(
+        options: &'r CompiledOptions,
+        environment: &[Result<Resolved<'r>, FormatError>],
+    ) -> Result<Cow<'r, Options>, FormatError> {
+        let mut resolved = Cow::Borrowed(&options.literal);
+        for (name, slot) in &options.variables {
+            let option = environment[*slot].clone()?;
+            if let Some(value) = option.value {
+                resolved.to_mut().insert(name.clone(), value.into_owned());
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn select<'r>(
+        &'r self,
+        table: &'r SelectionTable,
+        environment: &[Result<Resolved<'r>, FormatError>],
+        errors: &mut Errors,
+    ) -> Result<&'r [Part], FormatError> {
+        let mut preferences = Vec::with_capacity(table.selectors.len());
+        for (selector, keys) in table.selectors.iter().zip(&table.keys) {
+            // A selector that fails matches no keys, so only the catch-all key applies
+            match self.select_keys(selector, keys, environment) {
+                Ok(matches) => preferences.push(matches),
+                Err(error) => {
+                    errors.report(error)?;
+                    preferences.push(Vec::new());
+                }
+            }
+        }
+
+        // Validation guarantees a variant with only catch-all keys, which always matches
+        let variant = select_variant(&preferences, &table.variants);
+        Ok(variant.map_or(&[][..], |i| table.variants[i].pattern.as_slice()))
+    }
+
+    fn select_keys<'r>(
+        &'r self,
+        selector: &'r CompiledExpression,
+        keys: &[String],
+        environment: &[Result<Resolved<'r>, FormatError>],
+    ) -> Result<Vec<usize>, FormatError> {
+        let resolved = self.resolve(selector, environment)?;
+        let Some((function, options)) = &resolved.function else {
+            return Err(FormatError::Selection(SelectionError {
+                function: String::new(),
+                reason: "the selector has no annotation".into(),
+            }));
+        };
+
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let input = FunctionInput {
+            operand: resolved.value.as_deref(),
+            options,
+            locale: &self.locale,
+        };
+        function
+            .function
+            .as_ref()
+            .map_or(Ok(Vec::new()), |f| f.select(input, &keys))
+            .map_err(|e| e.into_selection_error(&function.name))
+    }
+
+    /// The direction of a placeholder. Numbers and dates are formatted in the direction of the locale,
+    /// anything else could be in any direction.
+    fn placeholder(&self, resolved: &Resolved<'_>) -> Placeholder {
+        let message = self.locale.direction();
+        let localized = match &resolved.function {
+            Some((function, _)) => matches!(
+                function.name.as_str(),
+                "number" | "integer" | "datetime" | "date" | "time"
+            ),
+            None => resolved
+                .value
+                .as_deref()
+                .is_some_and(|value| value.is_numeric() || matches!(value, Value::DateTime(_))),
+        };
+        let u_dir = resolved
+            .function
+            .as_ref()
+            .and_then(|(_, options)| options.get_str("u:dir"));
+        Placeholder::new(u_dir.as_deref(), message, localized.then_some(message))
+    }
+
+    fn write_resolved(
+        &self,
+        resolved: &Resolved<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FormatError> {
+        match (&resolved.function, &resolved.value) {
+            (Some((function, options)), value) => {
+                let input = FunctionInput {
+                    operand: value.as_deref(),
+                    options,
+                    locale: &self.locale,
+                };
+                // Functions can fail after writing some of their output, and a failed expression
+                // is only written as its fallback
+                let mut formatted = String::new();
+                function
+                    .function
+                    .as_ref()
+                    .map_or(Ok(()), |f| f.format(input, &mut formatted))
+                    .map_err(|e| e.into_format_error(&function.name))?;
+                out.write_str(&formatted).map_err(|_| FormatError::Write)
+            }
+            (None, Some(value)) => write_value(value, &self.locale, out),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Writes formatted text to an `io::Write`, keeping the error that `fmt::Write` can't carry
+#[cfg(feature = "std")]
+struct IoAdapter<'w, W: io::Write> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Collects the errors that occur while formatting
+struct Errors {
+    strict: bool,
+    errors: Vec<FormatError>,
+}
+
+impl Errors {
+    const fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records an error. In strict mode, the error is returned instead to stop formatting.
+    fn report(&mut self, error: FormatError) -> Result<(), FormatError> {
+        if self.strict {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+}
+
+/// The fallback of a literal, like `|literal|`
+fn literal_fallback(value: &str) -> String {
+    let mut fallback = String::with_capacity(value.len() + 2);
+    fallback.push('|');
+    for c in value.chars() {
+        if matches!(c, '\\' | '|') {
+            fallback.push('\\');
+        }
+        fallback.push(c);
+    }
+    fallback.push('|');
+    fallback
+}
+
+impl AsRef<[Option<usize>]> for CompiledVariant {
+    fn as_ref(&self) -> &[Option<usize>] {
+        &self.keys
+    }
+}
+
+/// Picks the best variant, given the keys each selector matched, best match first.
+///
+/// The keys of each variant are indices into the keys of each selector, `None` for the catch-all key.
+///
+/// See: https://github.com/unicode-org/message-format-wg/blob/main/spec/formatting.md#pattern-selection
+pub fn select_variant<P, K>(preferences: &[P], variants: &[K]) -> Option<usize>
+where
+    P: AsRef<[usize]>,
+    K: AsRef<[Option<usize>]>,
+{
+    let mut candidates: Vec<usize> = (0..variants.len())
+        .filter(|&variant| {
+            variants[variant]
+                .as_ref()
+                .iter()
+                .zip(preferences)
+                .all(|(key, matches)| key.is_none_or(|key| matches.as_ref().contains(&key)))
+        })
+        .collect();
+
+    for (i, matches) in preferences.iter().enumerate().rev() {
+        let matches = matches.as_ref();
+        candidates.sort_by_key(|&variant| {
+            variants[variant].as_ref()[i].map_or(matches.len(), |key| {
+                matches
+                    .iter()
+                    .position(|m| *m == key)
+                    .unwrap_or(matches.len())
+            })
+        });
+    }
+
+    candidates.first().copied()
+}
+
+/// Formats a value that has no annotation
+pub fn write_value(
+    value: &Value,
+    locale: &Locale,
+    out: &mut dyn fmt::Write,
+) -> Result<(), FormatError> {
+    let options = Options::new();
+    let input = FunctionInput {
+        operand: Some(value),
+        options: &options,
+        locale,
+    };
+
+    match value {
+        Value::Integer(_) | Value::Float(_) | Value::Decimal(_) => functions::Number
+            .format(input, out)
+            .map_err(|e| e.into_format_error("number")),
+        Value::DateTime(_) => functions::DateTimeFunction::DATETIME
+            .format(input, out)
+            .map_err(|e| e.into_format_error("datetime")),
+        Value::List(items) => {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.write_str(", ").map_err(|_| FormatError::Write)?;
+                }
+                write_value(item, locale, out)?;
+            }
+            Ok(())
+        }
+        other => write!(out, "{other}").map_err(|_| FormatError::Write),
+    }
+}
@@ -0,0 +1,113 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use crate::data_model::errors::{
+    FormattingError, InvalidExpression, SelectionError, UnknownFunction, UnresolvedVariable,
+    UnsupportedExpression, UnsupportedStatement,
+};
+
+/// An error that occurred while formatting a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    UnresolvedVariable(UnresolvedVariable),
+    UnknownFunction(UnknownFunction),
+    UnsupportedExpression(UnsupportedExpression),
+    UnsupportedStatement(UnsupportedStatement),
+    InvalidExpression(InvalidExpression),
+    Selection(SelectionError),
+    Formatting(FormattingError),
+    /// The formatted message could not be written to the output
+    Write,
+    /// The formatted message could not be written to an `io::Write`
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnresolvedVariable(e) => write!(f, "unresolved variable ${}", e.name),
+            Self::UnknownFunction(e) => write!(f, "unknown function :{}", e.name),
+            Self::UnsupportedExpression(e) => write!(f, "unsupported expression {}", e.source),
+            Self::UnsupportedStatement(e) => write!(f, "unsupported statement .{}", e.keyword),
+            Self::InvalidExpression(e) => {
+                write!(f, "invalid expression :{}: {}", e.function, e.reason)
+            }
+            Self::Selection(e) => write!(f, "selection with :{} failed: {}", e.function, e.reason),
+            Self::Formatting(e) => {
+                write!(f, "formatting with :{} failed: {}", e.function, e.reason)
+            }
+            Self::Write => f.write_str("the output could not be written"),
+            #[cfg(feature = "std")]
+            Self::Io(kind) => write!(f, "the output could not be written: {kind}"),
+        }
+    }
+}
+
+impl core::error::Error for FormatError {}
+
+/// An error returned by a [`Function`](crate::format::Function) implementation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunctionError {
+    /// The function requires an operand, but none was given
+    MissingOperand,
+    /// The operand has a type or value the function does not support
+    InvalidOperand(String),
+    /// An option has a value the function does not support
+    InvalidOption { name: String, reason: String },
+    /// The function can only be used for formatting, not for selection
+    NotSelectable,
+    /// Writing the formatted value failed
+    Write,
+}
+
+impl FunctionError {
+    /// Maps the error to the spec error for a call of the function `function` during formatting
+    pub fn into_format_error(self, function: &str) -> FormatError {
+        let function = function.to_string();
+        match self {
+            Self::MissingOperand | Self::InvalidOperand(_) | Self::InvalidOption { .. } => {
+                FormatError::InvalidExpression(InvalidExpression {
+                    function,
+                    reason: self.to_string(),
+                })
+            }
+            Self::NotSelectable => FormatError::Selection(SelectionError {
+                function,
+                reason: self.to_string(),
+            }),
+            Self::Write => FormatError::Write,
+        }
+    }
+
+    /// Maps the error to the spec error for a call of the function `function` during selection
+    pub fn into_selection_error(self, function: &str) -> FormatError {
+        match self {
+            Self::NotSelectable => FormatError::Selection(SelectionError {
+                function: function.into(),
+                reason: self.to_string(),
+            }),
+            other => other.into_format_error(function),
+        }
+    }
+}
+
+impl fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingOperand => f.write_str("an operand is required"),
+            Self::InvalidOperand(reason) => write!(f, "invalid operand: {reason}"),
+            Self::InvalidOption { name, reason } => write!(f, "invalid option {name}: {reason}"),
+            Self::NotSelectable => f.write_str("the function does not support selection"),
+            Self::Write => f.write_str("the output could not be written"),
+        }
+    }
+}
+
+impl core::error::Error for FunctionError {}
+
+impl From<fmt::Error> for FunctionError {
+    fn from(_: fmt::Error) -> Self {
+        Self::Write
+    }
+}
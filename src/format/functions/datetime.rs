@@ -0,0 +1,352 @@
+use alloc::format;
+use core::fmt;
+
+use crate::arguments::{DateTime, Value};
+use crate::format::{Function, FunctionError, FunctionInput, Options};
+use crate::locale::Locale;
+
+/// `:datetime`, `:date` and `:time` format dates and times.
+///
+/// `:datetime` accepts the `dateStyle` and `timeStyle` options, `:date` and `:time` accept `style`.
+/// Each takes `full`, `long`, `medium` or `short`.
+#[derive(Debug, Clone, Copy)]
+pub struct DateTimeFunction {
+    date: bool,
+    time: bool,
+}
+
+impl DateTimeFunction {
+    pub const DATETIME: Self = Self {
+        date: true,
+        time: true,
+    };
+    pub const DATE: Self = Self {
+        date: true,
+        time: false,
+    };
+    pub const TIME: Self = Self {
+        date: false,
+        time: true,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Full,
+    Long,
+    Medium,
+    Short,
+}
+
+const STYLES: [&str; 4] = ["full", "long", "medium", "short"];
+
+impl Style {
+    fn read(options: &Options, name: &str) -> Result<Option<Self>, FunctionError> {
+        Ok(options
+            .get_keyword(name, &STYLES)?
+            .map(|style| match style {
+                "full" => Self::Full,
+                "long" => Self::Long,
+                "medium" => Self::Medium,
+                _ => Self::Short,
+            }))
+    }
+}
+
+impl Function for DateTimeFunction {
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError> {
+        let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+        let value = match operand {
+            Value::DateTime(value) => *value,
+            Value::String(s) => s.parse().map_err(|_| invalid_operand(operand))?,
+            _ => return Err(invalid_operand(operand)),
+        };
+
+        let (date_style, time_style) = match (self.date, self.time) {
+            (true, true) => {
+                let date_style = Style::read(input.options, "dateStyle")?;
+                let time_style = Style::read(input.options, "timeStyle")?;
+                if date_style.is_none() && time_style.is_none() {
+                    (Some(Style::Medium), Some(Style::Short))
+                } else {
+                    (date_style, time_style)
+                }
+            }
+            (true, false) => (
+                Some(Style::read(input.options, "style")?.unwrap_or(Style::Medium)),
+                None,
+            ),
+            _ => (
+                None,
+                Some(Style::read(input.options, "style")?.unwrap_or(Style::Short)),
+            ),
+        };
+
+        let patterns = Patterns::for_locale(input.locale);
+        if let Some(style) = date_style {
+            (patterns.date)(&value, style, out)?;
+        }
+        if let (Some(date_style), Some(_)) = (date_style, time_style) {
+            let long = matches!(date_style, Style::Full | Style::Long);
+            out.write_str(if long {
+                patterns.long_separator
+            } else {
+                patterns.separator
+            })?;
+        }
+        if let Some(style) = time_style {
+            (patterns.time)(&value, style, out)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid_operand(value: &Value) -> FunctionError {
+    FunctionError::InvalidOperand(format!("{value} is not a date"))
+}
+
+type WritePattern = fn(&DateTime, Style, &mut dyn fmt::Write) -> fmt::Result;
+
+/// How a locale writes dates and times
+struct Patterns {
+    date: WritePattern,
+    time: WritePattern,
+    separator: &'static str,
+    long_separator: &'static str,
+}
+
+impl Patterns {
+    fn for_locale(locale: &Locale) -> Self {
+        match locale.language() {
+            "en" => Self {
+                date: english_date,
+                time: english_time,
+                separator: ", ",
+                long_separator: " at ",
+            },
+            "de" => Self {
+                date: german_date,
+                time: iso_time,
+                separator: ", ",
+                long_separator: " um ",
+            },
+            _ => Self {
+                date: iso_date,
+                time: iso_time,
+                separator: " ",
+                long_separator: " ",
+            },
+        }
+    }
+}
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const ENGLISH_WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+const GERMAN_WEEKDAYS: [&str; 7] = [
+    "Sonntag",
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+];
+
+fn english_date(value: &DateTime, style: Style, out: &mut dyn fmt::Write) -> fmt::Result {
+    let month = ENGLISH_MONTHS[usize::from(value.month - 1)];
+    match style {
+        Style::Short => write!(
+            out,
+            "{}/{}/{:02}",
+            value.month,
+            value.day,
+            value.year.rem_euclid(100)
+        ),
+        Style::Medium => write!(out, "{} {}, {}", &month[..3], value.day, value.year),
+        Style::Long => write!(out, "{} {}, {}", month, value.day, value.year),
+        Style::Full => write!(
+            out,
+            "{}, {} {}, {}",
+            ENGLISH_WEEKDAYS[weekday(value)],
+            month,
+            value.day,
+            value.year
+        ),
+    }
+}
+
+fn english_time(value: &DateTime, style: Style, out: &mut dyn fmt::Write) -> fmt::Result {
+    let hour = match value.hour % 12 {
+        0 => 12,
+        hour => hour,
+    };
+    let period = if value.hour < 12 { "AM" } else { "PM" };
+    match style {
+        Style::Short => write!(out, "{}:{:02}\u{202f}{}", hour, value.minute, period),
+        _ => write!(
+            out,
+            "{}:{:02}:{:02}\u{202f}{}",
+            hour, value.minute, value.second, period
+        ),
+    }
+}
+
+fn german_date(value: &DateTime, style: Style, out: &mut dyn fmt::Write) -> fmt::Result {
+    let month = GERMAN_MONTHS[usize::from(value.month - 1)];
+    match style {
+        Style::Short => write!(
+            out,
+            "{:02}.{:02}.{:02}",
+            value.day,
+            value.month,
+            value.year.rem_euclid(100)
+        ),
+        Style::Medium => write!(out, "{:02}.{:02}.{}", value.day, value.month, value.year),
+        Style::Long => write!(out, "{}. {} {}", value.day, month, value.year),
+        Style::Full => write!(
+            out,
+            "{}, {}. {} {}",
+            GERMAN_WEEKDAYS[weekday(value)],
+            value.day,
+            month,
+            value.year
+        ),
+    }
+}
+
+fn iso_date(value: &DateTime, _: Style, out: &mut dyn fmt::Write) -> fmt::Result {
+    write!(out, "{:04}-{:02}-{:02}", value.year, value.month, value.day)
+}
+
+fn iso_time(value: &DateTime, style: Style, out: &mut dyn fmt::Write) -> fmt::Result {
+    match style {
+        Style::Short => write!(out, "{:02}:{:02}", value.hour, value.minute),
+        _ => write!(
+            out,
+            "{:02}:{:02}:{:02}",
+            value.hour, value.minute, value.second
+        ),
+    }
+}
+
+/// The day of the week, 0 is Sunday
+fn weekday(value: &DateTime) -> usize {
+    // See: https://en.wikipedia.org/wiki/Determination_of_the_day_of_the_week#Sakamoto's_methods
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let year = if value.month < 3 {
+        value.year - 1
+    } else {
+        value.year
+    };
+    let day = year + year.div_euclid(4) - year.div_euclid(100)
+        + year.div_euclid(400)
+        + OFFSETS[usize::from(value.month - 1)]
+        + i32::from(value.day);
+    usize::try_from(day.rem_euclid(7)).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(function: DateTimeFunction, options: &[(&str, &str)], locale: &str) -> String {
+        let value = Value::from(
+            DateTime::from_ymd(2024, 3, 1)
+                .and_then(|d| d.with_hms(13, 5, 9))
+                .expect("valid date"),
+        );
+        let options: Options = options.iter().copied().collect();
+        let locale = locale.parse().expect("valid locale");
+        let mut out = String::new();
+        function
+            .format(
+                FunctionInput {
+                    operand: Some(&value),
+                    options: &options,
+                    locale: &locale,
+                },
+                &mut out,
+            )
+            .expect("formatting succeeds");
+        out
+    }
+
+    #[test]
+    fn it_formats_english_dates() {
+        assert_eq!(
+            format(DateTimeFunction::DATE, &[("style", "short")], "en"),
+            "3/1/24"
+        );
+        assert_eq!(
+            format(DateTimeFunction::DATE, &[("style", "full")], "en"),
+            "Friday, March 1, 2024"
+        );
+        assert_eq!(format(DateTimeFunction::TIME, &[], "en"), "1:05\u{202f}PM");
+        assert_eq!(
+            format(DateTimeFunction::DATETIME, &[], "en"),
+            "Mar 1, 2024, 1:05\u{202f}PM"
+        );
+    }
+
+    #[test]
+    fn it_formats_german_and_fallback_dates() {
+        assert_eq!(
+            format(DateTimeFunction::DATE, &[("style", "long")], "de"),
+            "1. März 2024"
+        );
+        assert_eq!(
+            format(
+                DateTimeFunction::DATETIME,
+                &[("dateStyle", "short"), ("timeStyle", "medium")],
+                "de"
+            ),
+            "01.03.24, 13:05:09"
+        );
+        assert_eq!(
+            format(DateTimeFunction::DATETIME, &[], "ja"),
+            "2024-03-01 13:05"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! The default functions from the spec
+
+mod datetime;
+mod number;
+mod string;
+
+pub use datetime::DateTimeFunction;
+pub(crate) use number::{is_integer_literal, is_number_literal};
+pub use number::{Integer, Number};
+pub use string::StringFunction;
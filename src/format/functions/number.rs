@@ -0,0 +1,580 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::arguments::Value;
+use crate::format::plural::{self, PluralOperands, PluralRuleType};
+use crate::format::{Function, FunctionError, FunctionInput, Options};
+use crate::locale::Locale;
+
+/// `:number` formats numbers and selects variants by exact value or plural category
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Number;
+
+/// `:integer` is `:number` without fraction digits
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Integer;
+
+impl Function for Number {
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError> {
+        let options = NumberOptions::read(input.options, false)?;
+        let digits = resolve(input, &options)?;
+        write_number(&digits, &options, input.locale, out)
+    }
+
+    fn select(&self, input: FunctionInput<'_>, keys: &[&str]) -> Result<Vec<usize>, FunctionError> {
+        let options = NumberOptions::read(input.options, false)?;
+        let digits = resolve(input, &options)?;
+        Ok(select(&digits, &options, input.locale, keys))
+    }
+}
+
+impl Function for Integer {
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError> {
+        let options = NumberOptions::read(input.options, true)?;
+        let digits = resolve(input, &options)?;
+        write_number(&digits, &options, input.locale, out)
+    }
+
+    fn select(&self, input: FunctionInput<'_>, keys: &[&str]) -> Result<Vec<usize>, FunctionError> {
+        let options = NumberOptions::read(input.options, true)?;
+        let digits = resolve(input, &options)?;
+        Ok(select(&digits, &options, input.locale, keys))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignDisplay {
+    Auto,
+    Always,
+    Never,
+    ExceptZero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rounding {
+    /// Ties go to the even digit, the default of `:number`
+    HalfEven,
+    /// Ties go away from zero, like `:integer` in ICU
+    HalfExpand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Selection {
+    Plural,
+    Ordinal,
+    Exact,
+}
+
+struct NumberOptions {
+    integer: bool,
+    percent: bool,
+    minimum_integer_digits: usize,
+    minimum_fraction_digits: usize,
+    maximum_fraction_digits: usize,
+    use_grouping: bool,
+    sign_display: SignDisplay,
+    select: Selection,
+}
+
+impl NumberOptions {
+    fn read(options: &Options, integer: bool) -> Result<Self, FunctionError> {
+        let percent = options.get_keyword("style", &["decimal", "percent"])? == Some("percent");
+
+        let (minimum_fraction_digits, maximum_fraction_digits) = if integer {
+            (0, 0)
+        } else {
+            let minimum = options.get_digits("minimumFractionDigits")?.unwrap_or(0);
+            let maximum = options
+                .get_digits("maximumFractionDigits")?
+                .unwrap_or(if percent { 0 } else { 3 })
+                .max(minimum);
+            (minimum, maximum)
+        };
+
+        let use_grouping = !matches!(
+            options.get_keyword(
+                "useGrouping",
+                &["auto", "always", "min2", "never", "true", "false"]
+            )?,
+            Some("never" | "false")
+        );
+
+        let sign_display = match options.get_keyword(
+            "signDisplay",
+            &["auto", "always", "never", "exceptZero", "negative"],
+        )? {
+            Some("always") => SignDisplay::Always,
+            Some("never") => SignDisplay::Never,
+            Some("exceptZero") => SignDisplay::ExceptZero,
+            _ => SignDisplay::Auto,
+        };
+
+        let select = match options.get_keyword("select", &["plural", "ordinal", "exact"])? {
+            Some("ordinal") => Selection::Ordinal,
+            Some("exact") => Selection::Exact,
+            _ => Selection::Plural,
+        };
+
+        Ok(Self {
+            integer,
+            percent,
+            minimum_integer_digits: options.get_digits("minimumIntegerDigits")?.unwrap_or(1),
+            minimum_fraction_digits,
+            maximum_fraction_digits,
+            use_grouping,
+            sign_display,
+            select,
+        })
+    }
+}
+
+/// Reads the operand and rounds it to the digits that will be displayed
+fn resolve(input: FunctionInput<'_>, options: &NumberOptions) -> Result<Digits, FunctionError> {
+    let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+    let mut digits = Digits::from_value(operand)?;
+
+    if options.percent {
+        digits.shift(2);
+    }
+    if options.integer {
+        digits.round(0, Rounding::HalfExpand);
+    } else {
+        digits.round(options.maximum_fraction_digits, Rounding::HalfEven);
+    }
+    digits.trim(options.minimum_fraction_digits);
+    Ok(digits)
+}
+
+fn select(digits: &Digits, options: &NumberOptions, locale: &Locale, keys: &[&str]) -> Vec<usize> {
+    let mut matches: Vec<usize> = keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| Digits::parse(key).is_some_and(|key| key.numerically_eq(digits)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let rule_type = match options.select {
+        Selection::Exact => return matches,
+        Selection::Plural => PluralRuleType::Cardinal,
+        Selection::Ordinal => PluralRuleType::Ordinal,
+    };
+
+    let operands = PluralOperands::new(&digits.integer, &digits.fraction);
+    let category = plural::category(locale.language(), rule_type, &operands);
+    matches.extend(keys.iter().position(|key| *key == category.as_str()));
+    matches
+}
+
+/// Whether a variant key is a number literal, which `:number` and `:integer` can select by value
+pub fn is_number_literal(key: &str) -> bool {
+    Digits::parse(key).is_some()
+}
+
+/// Whether a variant key is a number literal without a fraction, like `1`, `1.0` or `1.5e1`, which
+/// `:integer` can select by value
+pub fn is_integer_literal(key: &str) -> bool {
+    Digits::parse(key).is_some_and(|digits| digits.fraction.bytes().all(|b| b == b'0'))
+}
+
+fn write_number(
+    digits: &Digits,
+    options: &NumberOptions,
+    locale: &Locale,
+    out: &mut dyn fmt::Write,
+) -> Result<(), FunctionError> {
+    let symbols = Symbols::for_locale(locale);
+    let zero = digits.is_zero();
+
+    let sign = match (options.sign_display, digits.negative && !zero) {
+        (SignDisplay::Never, _) => "",
+        (_, true) => "-",
+        (SignDisplay::Always, false) => "+",
+        (SignDisplay::ExceptZero, false) if !zero => "+",
+        _ => "",
+    };
+    out.write_str(sign)?;
+
+    let padding = options
+        .minimum_integer_digits
+        .saturating_sub(digits.integer.len());
+    let integer = "0".repeat(padding) + &digits.integer;
+
+    let grouped = options.use_grouping && integer.len() >= 4 + symbols.minimum_grouping_digits - 1;
+    for (i, digit) in integer.chars().enumerate() {
+        let remaining = integer.len() - i;
+        if grouped && i > 0 && remaining.is_multiple_of(3) {
+            out.write_str(symbols.group)?;
+        }
+        out.write_char(digit)?;
+    }
+
+    if !digits.fraction.is_empty() {
+        out.write_str(symbols.decimal)?;
+        out.write_str(&digits.fraction)?;
+    }
+
+    if options.percent {
+        out.write_str(symbols.percent)?;
+    }
+    Ok(())
+}
+
+/// The separators a locale uses for numbers
+struct Symbols {
+    decimal: &'static str,
+    group: &'static str,
+    percent: &'static str,
+    /// The minimum number of digits before the first separator, eg. Spanish doesn't group 1234
+    minimum_grouping_digits: usize,
+}
+
+impl Symbols {
+    fn for_locale(locale: &Locale) -> Self {
+        let (decimal, group) = match (locale.language(), locale.region()) {
+            ("de", Some("CH" | "LI")) => (".", "’"),
+            (
+                "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => (",", "."),
+            ("fr", _) => (",", "\u{202f}"),
+            (
+                "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "bg" | "hu"
+                | "lt" | "lv" | "et",
+                _,
+            ) => (",", "\u{a0}"),
+            _ => (".", ","),
+        };
+
+        let percent = match locale.language() {
+            "de" | "fr" | "sv" | "nb" | "no" | "fi" | "da" | "cs" | "sk" | "ru" | "uk" => "\u{a0}%",
+            _ => "%",
+        };
+
+        Self {
+            decimal,
+            group,
+            percent,
+            minimum_grouping_digits: if matches!(locale.language(), "es" | "pl") {
+                2
+            } else {
+                1
+            },
+        }
+    }
+}
+
+/// The largest exponent of a number literal, like in ICU. Larger exponents would make the digits
+/// take up arbitrary amounts of memory.
+const MAXIMUM_EXPONENT: u32 = 999;
+
+/// A number as decimal digits, so that no precision is lost while formatting
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Digits {
+    negative: bool,
+    /// The integer digits without leading zeros, `"0"` for zero
+    integer: String,
+    fraction: String,
+}
+
+impl Digits {
+    fn from_value(value: &Value) -> Result<Self, FunctionError> {
+        let digits = match value {
+            Value::Integer(i) => Self {
+                negative: *i < 0,
+                integer: i.unsigned_abs().to_string(),
+                fraction: String::new(),
+            },
+            Value::Decimal(d) => Self {
+                negative: d.is_negative(),
+                integer: d.integer_digits(),
+                fraction: d.fraction_digits(),
+            },
+            Value::Float(f) if f.is_finite() => {
+                Self::parse(&f.to_string()).ok_or_else(|| invalid_operand(value))?
+            }
+            Value::String(s) => Self::parse(s).ok_or_else(|| invalid_operand(value))?,
+            _ => return Err(invalid_operand(value)),
+        };
+        Ok(digits.normalized())
+    }
+
+    /// Parses a number literal: `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][-+]?[0-9]+)?`, with an exponent
+    /// of at most [`MAXIMUM_EXPONENT`]
+    fn parse(s: &str) -> Option<Self> {
+        let (negative, unsigned) = s.strip_prefix('-').map_or((false, s), |rest| (true, rest));
+        let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                let exponent = exponent.strip_prefix('+').unwrap_or(exponent);
+                let exponent = exponent.parse::<i32>().ok()?;
+                if exponent.unsigned_abs() > MAXIMUM_EXPONENT {
+                    return None;
+                }
+                (mantissa, exponent)
+            }
+            None => (unsigned, 0),
+        };
+
+        let (integer, fraction) = match mantissa.split_once('.') {
+            Some((integer, fraction)) if !fraction.is_empty() => (integer, fraction),
+            Some(_) => return None,
+            None => (mantissa, ""),
+        };
+
+        let valid_integer = integer == "0" || (!integer.is_empty() && !integer.starts_with('0'));
+        let all_digits = integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit());
+        if !valid_integer || !all_digits {
+            return None;
+        }
+
+        let mut digits = Self {
+            negative,
+            integer: integer.into(),
+            fraction: fraction.into(),
+        };
+        digits.shift(exponent);
+        Some(digits.normalized())
+    }
+
+    /// Multiplies the number by `10^exponent`
+    fn shift(&mut self, exponent: i32) {
+        let places = exponent.unsigned_abs() as usize;
+        if exponent > 0 {
+            let moved = places.min(self.fraction.len());
+            let mut integer = self.integer.clone() + &self.fraction[..moved];
+            integer.push_str(&"0".repeat(places - moved));
+            self.fraction = self.fraction[moved..].to_string();
+            self.integer = integer;
+        } else if exponent < 0 {
+            let padded = "0".repeat(places.saturating_sub(self.integer.len())) + &self.integer;
+            let split = padded.len() - places;
+            self.fraction = padded[split..].to_string() + &self.fraction;
+            self.integer = padded[..split].to_string();
+        }
+        *self = core::mem::take(self).normalized();
+    }
+
+    /// Rounds to at most `maximum` fraction digits
+    fn round(&mut self, maximum: usize, rounding: Rounding) {
+        if self.fraction.len() <= maximum {
+            return;
+        }
+
+        let rest = self.fraction.split_off(maximum);
+        let mut rest = rest.bytes();
+        let first_dropped = rest.next().unwrap_or(b'0');
+        let more_dropped = rest.any(|b| b != b'0');
+        let last_kept = self
+            .fraction
+            .bytes()
+            .last()
+            .or_else(|| self.integer.bytes().last())
+            .unwrap_or(b'0');
+
+        let round_up = match first_dropped.cmp(&b'5') {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match rounding {
+                Rounding::HalfEven => more_dropped || (last_kept - b'0') % 2 == 1,
+                Rounding::HalfExpand => true,
+            },
+        };
+        if round_up {
+            self.increment();
+        }
+    }
+
+    /// Adds one to the last kept digit
+    fn increment(&mut self) {
+        let fraction_len = self.fraction.len();
+        let mut all: Vec<u8> = format!("{}{}", self.integer, self.fraction).into_bytes();
+
+        let mut carry = true;
+        for digit in all.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            all.insert(0, b'1');
+        }
+
+        let all = String::from_utf8(all).unwrap_or_default();
+        let split = all.len() - fraction_len;
+        self.integer = all[..split].to_string();
+        self.fraction = all[split..].to_string();
+    }
+
+    /// Removes trailing zeros from the fraction, but keeps at least `minimum` fraction digits
+    fn trim(&mut self, minimum: usize) {
+        let significant = self.fraction.trim_end_matches('0').len();
+        self.fraction
+            .truncate(significant.max(minimum.min(self.fraction.len())));
+        while self.fraction.len() < minimum {
+            self.fraction.push('0');
+        }
+    }
+
+    fn normalized(mut self) -> Self {
+        let integer = self.integer.trim_start_matches('0');
+        self.integer = if integer.is_empty() {
+            "0".into()
+        } else {
+            integer.into()
+        };
+        self
+    }
+
+    fn is_zero(&self) -> bool {
+        self.integer == "0" && self.fraction.bytes().all(|b| b == b'0')
+    }
+
+    fn numerically_eq(&self, other: &Self) -> bool {
+        let zero = self.is_zero();
+        zero == other.is_zero()
+            && (zero || self.negative == other.negative)
+            && self.integer == other.integer
+            && self.fraction.trim_end_matches('0') == other.fraction.trim_end_matches('0')
+    }
+}
+
+fn invalid_operand(value: &Value) -> FunctionError {
+    FunctionError::InvalidOperand(format!("{value} is not a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(
+        function: &dyn Function,
+        value: impl Into<Value>,
+        options: &[(&str, &str)],
+        locale: &str,
+    ) -> String {
+        let value = value.into();
+        let options: Options = options.iter().copied().collect();
+        let locale = locale.parse().expect("valid locale");
+        let mut out = String::new();
+        function
+            .format(
+                FunctionInput {
+                    operand: Some(&value),
+                    options: &options,
+                    locale: &locale,
+                },
+                &mut out,
+            )
+            .expect("formatting succeeds");
+        out
+    }
+
+    #[test]
+    fn it_formats_numbers_with_locale_separators() {
+        assert_eq!(format(&Number, 1234.5, &[], "en"), "1,234.5");
+        assert_eq!(format(&Number, 1234.5, &[], "de"), "1.234,5");
+        assert_eq!(format(&Number, 1234, &[], "es"), "1234");
+        assert_eq!(format(&Number, -1_234_567, &[], "en"), "-1,234,567");
+        assert_eq!(
+            format(&Number, 1234, &[("useGrouping", "never")], "en"),
+            "1234"
+        );
+    }
+
+    #[test]
+    fn it_rounds_half_to_even() {
+        assert_eq!(format(&Number, 2.0005, &[], "en"), "2");
+        assert_eq!(format(&Number, 2.0015, &[], "en"), "2.002");
+        assert_eq!(format(&Number, 0.9999, &[], "en"), "1");
+        assert_eq!(
+            format(&Number, "1.25", &[("maximumFractionDigits", "1")], "en"),
+            "1.2"
+        );
+        assert_eq!(
+            format(&Number, "1.35", &[("maximumFractionDigits", "1")], "en"),
+            "1.4"
+        );
+    }
+
+    #[test]
+    fn it_applies_digit_options() {
+        assert_eq!(
+            format(&Number, 1.5, &[("minimumFractionDigits", "2")], "en"),
+            "1.50"
+        );
+        assert_eq!(
+            format(&Number, 7, &[("minimumIntegerDigits", "3")], "en"),
+            "007"
+        );
+        assert_eq!(format(&Number, 5, &[("signDisplay", "always")], "en"), "+5");
+        assert_eq!(format(&Number, 0.25, &[("style", "percent")], "en"), "25%");
+        assert_eq!(format(&Integer, 4.9, &[], "en"), "5");
+        assert_eq!(format(&Integer, -2.5, &[], "en"), "-3");
+        assert_eq!(format(&Integer, 2.4, &[], "en"), "2");
+        assert_eq!(
+            format(&Integer, 0.125, &[("style", "percent")], "en"),
+            "13%"
+        );
+    }
+
+    #[test]
+    fn it_parses_number_literals() {
+        assert_eq!(format(&Number, "1e3", &[], "en"), "1,000");
+        assert_eq!(format(&Number, "-2.5E-2", &[], "en"), "-0.025");
+        assert!(Digits::parse("01").is_none());
+        assert!(Digits::parse("1.").is_none());
+        assert!(Digits::parse("abc").is_none());
+        assert!(Digits::parse("1e999").is_some());
+        assert!(Digits::parse("1e1000").is_none());
+        assert!(Digits::parse("1e-2147483648").is_none());
+    }
+
+    #[test]
+    fn it_rejects_too_many_digits() {
+        let value = Value::from(7);
+        let locale = "en".parse().expect("valid locale");
+        let options: Options = core::iter::once(("minimumIntegerDigits", "1000")).collect();
+        let input = FunctionInput {
+            operand: Some(&value),
+            options: &options,
+            locale: &locale,
+        };
+        assert!(matches!(
+            Number.format(input, &mut String::new()),
+            Err(FunctionError::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn it_prefers_exact_matches_over_plural_categories() {
+        let value = Value::from(1);
+        let options = Options::new();
+        let locale = "en".parse().expect("valid locale");
+        let input = FunctionInput {
+            operand: Some(&value),
+            options: &options,
+            locale: &locale,
+        };
+
+        assert_eq!(Number.select(input, &["one", "1", "other"]), Ok(vec![1, 0]));
+        assert_eq!(Number.select(input, &["few", "other"]), Ok(vec![]));
+    }
+}
@@ -0,0 +1,32 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::format::Function;
+use crate::format::{FunctionError, FunctionInput};
+
+/// `:string` formats its operand as a string and selects the variant whose key equals it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringFunction;
+
+impl Function for StringFunction {
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError> {
+        let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+        write!(out, "{operand}")?;
+        Ok(())
+    }
+
+    fn select(&self, input: FunctionInput<'_>, keys: &[&str]) -> Result<Vec<usize>, FunctionError> {
+        let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+        let value = operand.to_string();
+        Ok(keys
+            .iter()
+            .position(|key| *key == value)
+            .into_iter()
+            .collect())
+    }
+}
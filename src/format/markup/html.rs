@@ -0,0 +1,186 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::format::markup::MarkupHandler;
+use crate::format::Options;
+
+/// Elements that have no closing tag
+const VOID_ELEMENTS: [&str; 6] = ["br", "hr", "img", "input", "meta", "wbr"];
+
+/// Attributes that hold a URL, which may only use safe schemes
+const URL_ATTRIBUTES: [&str; 3] = ["href", "src", "cite"];
+
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+
+/// Renders markup as HTML tags and HTML-escapes all text.
+///
+/// Only markup that has been registered with [`HtmlRenderer::tag`] is rendered, everything else is
+/// dropped. Options become attributes if they are allowed for the tag, and URL attributes like `href`
+/// are dropped unless they use `http`, `https`, `mailto` or `tel`, or are relative.
+///
+/// Close markup without a matching open is dropped, and tags that are still open at the end of the
+/// message are closed, so the output is always balanced.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlRenderer {
+    tags: BTreeMap<String, HtmlTag>,
+    /// The markup names that are currently open
+    open: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct HtmlTag {
+    element: String,
+    attributes: Vec<String>,
+}
+
+impl HtmlRenderer {
+    /// A renderer that doesn't render any markup
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A renderer for `b`, `i`, `em`, `strong`, `u`, `br`, `p`, `span`, `link` (as `a` with `href` and
+    /// `title`) and `img` (with `src`, `alt`, `width` and `height`)
+    pub fn with_default_tags() -> Self {
+        let mut renderer = Self::new();
+        for name in ["b", "i", "em", "strong", "u", "br", "p", "span"] {
+            renderer = renderer.tag(name, name, &[]);
+        }
+        renderer.tag("link", "a", &["href", "title"]).tag(
+            "img",
+            "img",
+            &["src", "alt", "width", "height"],
+        )
+    }
+
+    /// Renders markup called `name` as `element`, with the given options as attributes in this order
+    #[must_use]
+    pub fn tag(
+        mut self,
+        name: impl Into<String>,
+        element: impl Into<String>,
+        attributes: &[&str],
+    ) -> Self {
+        self.tags.insert(
+            name.into(),
+            HtmlTag {
+                element: element.into(),
+                attributes: attributes.iter().map(|a| (*a).to_string()).collect(),
+            },
+        );
+        self
+    }
+
+    fn start_tag(tag: &HtmlTag, options: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "<{}", tag.element)?;
+        for name in &tag.attributes {
+            let Some(value) = options.get(name) else {
+                continue;
+            };
+            let value = value.to_string();
+            if URL_ATTRIBUTES.contains(&name.as_str()) && !is_safe_url(&value) {
+                continue;
+            }
+            write!(out, " {name}=\"")?;
+            escape(&value, out)?;
+            out.write_char('"')?;
+        }
+        out.write_char('>')
+    }
+}
+
+impl MarkupHandler for HtmlRenderer {
+    fn start(&mut self) {
+        self.open.clear();
+    }
+
+    fn text(&mut self, text: &str, out: &mut dyn fmt::Write) -> fmt::Result {
+        escape(text, out)
+    }
+
+    fn open(&mut self, name: &str, options: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+        let Some(tag) = self.tags.get(name) else {
+            return Ok(());
+        };
+        Self::start_tag(tag, options, out)?;
+        if !VOID_ELEMENTS.contains(&tag.element.as_str()) {
+            self.open.push(name.into());
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, name: &str, _options: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+        let Some(position) = self.open.iter().rposition(|open| open == name) else {
+            return Ok(());
+        };
+        // Tags opened inside this one are closed first
+        for open in self.open.drain(position..).rev() {
+            if let Some(tag) = self.tags.get(&open) {
+                write!(out, "</{}>", tag.element)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn standalone(
+        &mut self,
+        name: &str,
+        options: &Options,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let Some(tag) = self.tags.get(name) else {
+            return Ok(());
+        };
+        Self::start_tag(tag, options, out)?;
+        if VOID_ELEMENTS.contains(&tag.element.as_str()) {
+            Ok(())
+        } else {
+            write!(out, "</{}>", tag.element)
+        }
+    }
+
+    fn finish(&mut self, out: &mut dyn fmt::Write) -> fmt::Result {
+        for open in core::mem::take(&mut self.open).into_iter().rev() {
+            if let Some(tag) = self.tags.get(&open) {
+                write!(out, "</{}>", tag.element)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escape(text: &str, out: &mut dyn fmt::Write) -> fmt::Result {
+    let mut rest = text;
+    while let Some(i) = rest.find(['&', '<', '>', '"', '\'']) {
+        out.write_str(&rest[..i])?;
+        out.write_str(match rest.as_bytes()[i] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'"' => "&quot;",
+            _ => "&#39;",
+        })?;
+        rest = &rest[i + 1..];
+    }
+    out.write_str(rest)
+}
+
+/// Whether a URL is relative or uses a safe scheme
+fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in the scheme
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url.as_bytes()[i] == b':' => {
+            let scheme = &url[..i];
+            SAFE_SCHEMES
+                .iter()
+                .any(|safe| safe.eq_ignore_ascii_case(scheme))
+        }
+        _ => true,
+    }
+}
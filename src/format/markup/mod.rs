@@ -0,0 +1,210 @@
+//! Rendering of markup like `{#b}`, `{/b}` and `{#img src=$url /}`.
+//!
+//! When a message is formatted with [`CompiledMessage::format`](crate::format::CompiledMessage::format),
+//! markup has no output. [`CompiledMessage::render`](crate::format::CompiledMessage::render) passes
+//! all output through a [`MarkupHandler`] instead, which decides what markup turns into.
+
+mod html;
+
+use core::fmt;
+
+use crate::format::Options;
+
+pub use html::HtmlRenderer;
+
+/// Decides how a formatted message and its markup are written.
+///
+/// All text goes through [`MarkupHandler::text`], both the text of the message and the formatted
+/// placeholders. This is where a handler escapes text for its output format.
+pub trait MarkupHandler {
+    /// Called before a message is written, so a handler can be reused after a message failed to
+    /// format. The default does nothing.
+    fn start(&mut self) {}
+
+    /// Writes text. The default writes it as it is.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    fn text(&mut self, text: &str, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(text)
+    }
+
+    /// Called for markup like `{#b}`. The default writes nothing.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    fn open(&mut self, name: &str, options: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = (name, options, out);
+        Ok(())
+    }
+
+    /// Called for markup like `{/b}`. The default writes nothing.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    fn close(&mut self, name: &str, options: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = (name, options, out);
+        Ok(())
+    }
+
+    /// Called for markup like `{#img /}`. The default writes nothing.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    fn standalone(
+        &mut self,
+        name: &str,
+        options: &Options,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let _ = (name, options, out);
+        Ok(())
+    }
+
+    /// Called once the whole message has been written. The default writes nothing.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    fn finish(&mut self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let _ = out;
+        Ok(())
+    }
+}
+
+/// Writes text as it is and ignores markup. This is what [`CompiledMessage::format`](crate::format::CompiledMessage::format) uses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainText;
+
+impl MarkupHandler for PlainText {}
+
+/// Passes everything that is written through a handler
+pub(crate) struct Rendered<'h, 'o> {
+    pub handler: &'h mut dyn MarkupHandler,
+    pub out: &'o mut dyn fmt::Write,
+}
+
+impl fmt::Write for Rendered<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.handler.text(s, self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arguments::Arguments;
+    use crate::data_model::elements::Message;
+    use crate::format::{CompiledMessage, FunctionRegistry};
+
+    fn compile(source: &str) -> CompiledMessage {
+        let message: Message = source.parse().expect("valid message");
+        message
+            .compile(
+                &FunctionRegistry::new(),
+                "en".parse().expect("valid locale"),
+            )
+            .expect("valid message")
+    }
+
+    #[test]
+    fn it_passes_markup_to_the_handler() {
+        struct Brackets;
+
+        impl MarkupHandler for Brackets {
+            fn open(
+                &mut self,
+                name: &str,
+                options: &Options,
+                out: &mut dyn fmt::Write,
+            ) -> fmt::Result {
+                write!(out, "[{name}")?;
+                for (option, value) in options.iter() {
+                    write!(out, " {option}={value}")?;
+                }
+                out.write_char(']')
+            }
+
+            fn close(&mut self, name: &str, _: &Options, out: &mut dyn fmt::Write) -> fmt::Result {
+                write!(out, "[/{name}]")
+            }
+
+            fn standalone(
+                &mut self,
+                name: &str,
+                _: &Options,
+                out: &mut dyn fmt::Write,
+            ) -> fmt::Result {
+                write!(out, "[{name}/]")
+            }
+        }
+
+        let compiled = compile("{#b}Hi{/b} {$name}{#br/}{#a href=$url}x{/a}");
+        let arguments = [("name", "Ann"), ("url", "/home")];
+
+        assert_eq!(
+            compiled.render(arguments, &mut Brackets),
+            Ok("[b]Hi[/b] Ann[br/][a href=/home]x[/a]".into())
+        );
+        assert_eq!(compiled.format(arguments), Ok("Hi Annx".into()));
+    }
+
+    #[test]
+    fn it_renders_html() {
+        let compiled = compile("{#b}Hello {$name}{/b}, see {#link href=$url title=|Docs|}the docs{/link}{#img src=$src alt=|<logo>| /}");
+        let mut html = HtmlRenderer::with_default_tags();
+
+        let arguments = Arguments::new()
+            .with("name", "<script>alert('x')</script>")
+            .with("url", "https://example.com/?a=1&b=2")
+            .with("src", "logo.png");
+        assert_eq!(
+            compiled.render(arguments, &mut html),
+            Ok(
+                "<b>Hello &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</b>, see \
+                <a href=\"https://example.com/?a=1&amp;b=2\" title=\"Docs\">the docs</a>\
+                <img src=\"logo.png\" alt=\"&lt;logo&gt;\">"
+                    .into()
+            )
+        );
+
+        let arguments = Arguments::new()
+            .with("name", "Ann")
+            .with("url", " java\tscript:alert(1)")
+            .with("src", "data:image/png;base64,AAAA");
+        assert_eq!(
+            compiled.render(arguments, &mut html),
+            Ok(
+                "<b>Hello Ann</b>, see <a title=\"Docs\">the docs</a><img alt=\"&lt;logo&gt;\">"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn it_keeps_html_balanced() {
+        let mut html = HtmlRenderer::with_default_tags();
+
+        assert_eq!(
+            compile("{/b}{#blink}a{/blink} {#b}{#i}b{/b} {#em}c").render((), &mut html),
+            Ok("a <b><i>b</i></b> <em>c</em>".into())
+        );
+        assert_eq!(
+            compile("{#b class=|x| onclick=|evil()|}a{/b}").render((), &mut html),
+            Ok("<b>a</b>".into())
+        );
+    }
+
+    #[test]
+    fn it_forgets_tags_of_failed_messages() {
+        let mut html = HtmlRenderer::with_default_tags();
+
+        assert!(compile("{#b}{$missing}")
+            .strict(true)
+            .render((), &mut html)
+            .is_err());
+        assert_eq!(
+            compile("{#i}a").render((), &mut html),
+            Ok("<i>a</i>".into())
+        );
+    }
+}
@@ -0,0 +1,385 @@
+mod bidi;
+mod compile;
+mod errors;
+pub mod functions;
+pub mod markup;
+pub mod plural;
+mod registry;
+
+pub use bidi::BidiIsolation;
+pub use compile::CompiledMessage;
+pub(crate) use compile::{select_variant, write_value};
+pub use errors::{FormatError, FunctionError};
+pub use registry::{Function, FunctionInput, FunctionRegistry, Options};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arguments::{Arguments, Value};
+    use crate::data_model::elements::*;
+    use crate::data_model::errors::{DataModelError, UnknownFunction, UnresolvedVariable};
+
+    fn variable(name: &str, function: std::option::Option<&str>) -> Expression {
+        Expression::Variable(VariableExpression {
+            arg: VariableRef { name: name.into() },
+            annotation: function.map(|name| {
+                Annotation::Function(FunctionAnnotation {
+                    name: name.into(),
+                    options: vec![],
+                })
+            }),
+            attributes: vec![],
+        })
+    }
+
+    fn key(value: &str) -> VariantKey {
+        VariantKey::Literal(Literal {
+            value: value.into(),
+        })
+    }
+
+    fn text(value: &str) -> Vec<PatternElement> {
+        vec![PatternElement::Literal(value.into())]
+    }
+
+    fn compile(message: &Message, locale: &str) -> CompiledMessage {
+        message
+            .compile(
+                &FunctionRegistry::new(),
+                locale.parse().expect("valid locale"),
+            )
+            .expect("valid message")
+    }
+
+    fn items_message() -> Message {
+        Message::Select(SelectMessage {
+            declarations: vec![Declaration::Input(InputDeclaration {
+                name: "count".into(),
+                value: VariableExpression {
+                    arg: VariableRef {
+                        name: "count".into(),
+                    },
+                    annotation: Some(Annotation::Function(FunctionAnnotation {
+                        name: "number".into(),
+                        options: vec![],
+                    })),
+                    attributes: vec![],
+                },
+            })],
+            selectors: vec![variable("count", None)],
+            variants: vec![
+                Variant {
+                    keys: vec![key("0")],
+                    value: text("No items"),
+                },
+                Variant {
+                    keys: vec![key("one")],
+                    value: vec![
+                        PatternElement::Expression(variable("count", None)),
+                        PatternElement::Literal(" item".into()),
+                    ],
+                },
+                Variant {
+                    keys: vec![VariantKey::Catchall],
+                    value: vec![
+                        PatternElement::Expression(variable("count", None)),
+                        PatternElement::Literal(" items".into()),
+                    ],
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn it_formats_a_pattern_message() {
+        let message = Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern: vec![
+                PatternElement::Literal("Hello ".into()),
+                PatternElement::Expression(variable("name", None)),
+                PatternElement::Literal("!".into()),
+            ],
+        });
+
+        let compiled = compile(&message, "en");
+
+        assert_eq!(
+            compiled.format([("name", "World")]),
+            Ok("Hello World!".into())
+        );
+        assert_eq!(compiled.format(()), Ok("Hello {$name}!".into()));
+        assert_eq!(
+            compiled.strict(true).format(()),
+            Err(FormatError::UnresolvedVariable(UnresolvedVariable {
+                name: "name".into()
+            }))
+        );
+    }
+
+    #[test]
+    fn it_selects_variants_by_exact_value_and_plural_category() {
+        let compiled = compile(&items_message(), "en");
+
+        assert_eq!(compiled.format([("count", 0)]), Ok("No items".into()));
+        assert_eq!(compiled.format([("count", 1)]), Ok("1 item".into()));
+        assert_eq!(compiled.format([("count", 1234)]), Ok("1,234 items".into()));
+    }
+
+    #[test]
+    fn it_uses_the_locale_for_plural_rules() {
+        let compiled = compile(&items_message(), "ja");
+
+        assert_eq!(compiled.format([("count", 1)]), Ok("1 items".into()));
+    }
+
+    #[test]
+    fn it_resolves_local_declarations_and_variable_options() {
+        let message = Message::Pattern(PatternMessage {
+            declarations: vec![Declaration::Local(LocalDeclaration {
+                name: "price".into(),
+                value: Expression::Variable(VariableExpression {
+                    arg: VariableRef {
+                        name: "amount".into(),
+                    },
+                    annotation: Some(Annotation::Function(FunctionAnnotation {
+                        name: "number".into(),
+                        options: vec![Option {
+                            name: "minimumFractionDigits".into(),
+                            value: OptionValue::Variable(VariableRef {
+                                name: "digits".into(),
+                            }),
+                        }],
+                    })),
+                    attributes: vec![],
+                }),
+            })],
+            pattern: vec![PatternElement::Expression(variable(
+                "price",
+                Some("number"),
+            ))],
+        });
+
+        let compiled = compile(&message, "de");
+        let arguments = Arguments::new().with("amount", 4.5).with("digits", 2);
+
+        assert_eq!(compiled.format(arguments), Ok("4,50".into()));
+    }
+
+    #[test]
+    fn it_selects_with_multiple_selectors() {
+        let message = Message::Select(SelectMessage {
+            declarations: vec![],
+            selectors: vec![variable("a", Some("string")), variable("b", Some("string"))],
+            variants: vec![
+                Variant {
+                    keys: vec![VariantKey::Catchall, key("x")],
+                    value: text("* x"),
+                },
+                Variant {
+                    keys: vec![key("x"), VariantKey::Catchall],
+                    value: text("x *"),
+                },
+                Variant {
+                    keys: vec![VariantKey::Catchall, VariantKey::Catchall],
+                    value: text("* *"),
+                },
+            ],
+        });
+
+        let compiled = compile(&message, "en");
+
+        assert_eq!(compiled.format([("a", "x"), ("b", "x")]), Ok("x *".into()));
+        assert_eq!(compiled.format([("a", "y"), ("b", "x")]), Ok("* x".into()));
+        assert_eq!(compiled.format([("a", "y"), ("b", "y")]), Ok("* *".into()));
+    }
+
+    #[test]
+    fn it_rejects_invalid_messages() {
+        let message = Message::Select(SelectMessage {
+            declarations: vec![],
+            selectors: vec![variable("a", None)],
+            variants: vec![Variant {
+                keys: vec![VariantKey::Catchall],
+                value: text(""),
+            }],
+        });
+
+        let error = message
+            .compile(&FunctionRegistry::new(), crate::locale::Locale::und())
+            .map(|_| ());
+        assert!(matches!(
+            error,
+            Err(DataModelError::MissingSelectorAnnotation(_))
+        ));
+    }
+
+    #[test]
+    fn it_reports_unknown_functions_when_formatting() {
+        let message = Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern: vec![PatternElement::Expression(variable("x", Some("missing")))],
+        });
+
+        let compiled = compile(&message, "en").strict(true);
+
+        assert_eq!(
+            compiled.format([("x", Value::Integer(1))]),
+            Err(FormatError::UnknownFunction(UnknownFunction {
+                name: "missing".into()
+            }))
+        );
+    }
+
+    #[test]
+    fn it_supports_custom_functions() {
+        struct Shout;
+
+        impl Function for Shout {
+            fn format(
+                &self,
+                input: FunctionInput<'_>,
+                out: &mut dyn std::fmt::Write,
+            ) -> Result<(), FunctionError> {
+                let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+                write!(out, "{}!", operand.to_string().to_uppercase())?;
+                Ok(())
+            }
+        }
+
+        let mut registry = FunctionRegistry::new();
+        registry.register("shout", Shout);
+
+        let message = Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern: vec![PatternElement::Expression(variable("x", Some("shout")))],
+        });
+        let compiled = message
+            .compile(&registry, crate::locale::Locale::und())
+            .expect("valid message");
+
+        assert_eq!(compiled.format([("x", "hey")]), Ok("HEY!".into()));
+
+        struct Stutter;
+
+        impl Function for Stutter {
+            fn format(
+                &self,
+                _: FunctionInput<'_>,
+                out: &mut dyn std::fmt::Write,
+            ) -> Result<(), FunctionError> {
+                out.write_str("st-")?;
+                Err(FunctionError::InvalidOperand("no words".into()))
+            }
+        }
+
+        registry.register("stutter", Stutter);
+        let message: Message = "Say {$x :stutter}!".parse().expect("valid message");
+        let compiled = message
+            .compile(&registry, crate::locale::Locale::und())
+            .expect("valid message");
+        let (formatted, errors) = compiled.format_with_errors([("x", "hey")]);
+        assert_eq!(formatted, "Say {$x}!");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn it_isolates_placeholders() {
+        let isolated = |source: &str, locale: &str, strategy: BidiIsolation| {
+            let message: Message = source.parse().expect("valid message");
+            compile(&message, locale)
+                .bidi_isolation(strategy)
+                .format([("name", Value::from("Ann")), ("n", Value::from(3))])
+                .expect("formats")
+        };
+
+        assert_eq!(
+            isolated("Hi {$name}, {$n}", "en", BidiIsolation::None),
+            "Hi Ann, 3"
+        );
+        assert_eq!(
+            isolated("Hi {$name}, {$n}", "en", BidiIsolation::Default),
+            "Hi \u{2068}Ann\u{2069}, 3"
+        );
+        assert_eq!(
+            isolated("{$name} {$n :number}", "ar", BidiIsolation::Default),
+            "\u{2068}Ann\u{2069} \u{2067}3\u{2069}"
+        );
+        assert_eq!(
+            isolated(
+                "{$name :string u:dir=ltr} {$n :number u:dir=inherit}",
+                "en",
+                BidiIsolation::Default
+            ),
+            "\u{2066}Ann\u{2069} 3"
+        );
+        assert_eq!(
+            isolated("{$name} {$n}", "he", BidiIsolation::Compatibility),
+            "\u{202A}Ann\u{202C} \u{202B}3\u{202C}"
+        );
+    }
+
+    #[test]
+    fn it_writes_fallbacks_for_failed_expressions() {
+        let message: Message = "{|a\\|b| :missing} {:missing} {!reserved} {$x :number} {$x}"
+            .parse()
+            .expect("valid message");
+        let compiled = compile(&message, "en");
+
+        let (formatted, errors) = compiled.format_with_errors([("x", "not a number")]);
+        assert_eq!(formatted, "{|a\\|b|} {:missing} {!} {$x} not a number");
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(errors[3], FormatError::InvalidExpression(_)));
+
+        let message: Message =
+            ".local $y = {$x :number}\n.match {$y}\none {{one {$y}}}\n* {{other {$y}}}"
+                .parse()
+                .expect("valid message");
+        let compiled = compile(&message, "en");
+
+        let (formatted, errors) = compiled.format_with_errors(());
+        assert_eq!(formatted, "other {$y}");
+        assert_eq!(
+            errors,
+            [
+                FormatError::UnresolvedVariable(UnresolvedVariable { name: "x".into() }),
+                FormatError::UnresolvedVariable(UnresolvedVariable { name: "x".into() }),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_formats_into_writers() {
+        struct Broken;
+
+        impl std::io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let message: Message = "Hello {$name}!".parse().expect("valid message");
+        let compiled = compile(&message, "en");
+
+        let mut out = String::from("> ");
+        compiled
+            .format_to([("name", "Ann")], &mut out)
+            .expect("formats");
+        assert_eq!(out, "> Hello Ann!");
+
+        let mut bytes = Vec::new();
+        compiled
+            .format_to_writer([("name", "Bo")], &mut bytes)
+            .expect("formats");
+        assert_eq!(bytes, b"Hello Bo!");
+
+        assert_eq!(
+            compiled.format_to_writer([("name", "Bo")], &mut Broken),
+            Err(FormatError::Io(std::io::ErrorKind::BrokenPipe))
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! CLDR plural rules for the languages that are supported out of the box.
+//!
+//! See: https://www.unicode.org/cldr/charts/latest/supplemental/language_plural_rules.html
+
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub const ALL: [Self; 6] = [
+        Self::Zero,
+        Self::One,
+        Self::Two,
+        Self::Few,
+        Self::Many,
+        Self::Other,
+    ];
+
+    /// The name of the category, as it is used in variant keys
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == name)
+    }
+}
+
+impl fmt::Display for PluralCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralRuleType {
+    /// Plural forms of quantities, eg. "1 item", "2 items"
+    Cardinal,
+    /// Ordinal numbers, eg. "1st", "2nd"
+    Ordinal,
+}
+
+/// The operands the plural rules are defined in terms of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluralOperands {
+    /// The integer digits
+    pub i: u64,
+    /// The number of visible fraction digits, with trailing zeros
+    pub v: usize,
+    /// The visible fraction digits, with trailing zeros
+    pub f: u64,
+    /// The visible fraction digits, without trailing zeros
+    pub t: u64,
+}
+
+impl PluralOperands {
+    /// Computes the operands from the digits of a formatted number. The sign is ignored.
+    pub fn new(integer_digits: &str, fraction_digits: &str) -> Self {
+        Self {
+            i: parse_digits(integer_digits),
+            v: fraction_digits.len(),
+            f: parse_digits(fraction_digits),
+            t: parse_digits(fraction_digits.trim_end_matches('0')),
+        }
+    }
+
+    pub const fn from_integer(i: u64) -> Self {
+        Self {
+            i,
+            v: 0,
+            f: 0,
+            t: 0,
+        }
+    }
+
+    /// If the number has no fraction, its value. Rules of the form `n = 1` only apply to these.
+    const fn integer_value(&self) -> Option<u64> {
+        if self.f == 0 {
+            Some(self.i)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses the trailing digits that fit into an `u64`. Longer numbers keep their remainders for
+/// the modulo based rules, but are never equal to small numbers.
+fn parse_digits(digits: &str) -> u64 {
+    const MAX_DIGITS: usize = 18;
+    let start = digits.len().saturating_sub(MAX_DIGITS);
+    let value = digits[start..].parse().unwrap_or(0);
+    if start > 0 {
+        value + 10_u64.pow(18)
+    } else {
+        value
+    }
+}
+
+/// Selects the plural category of a number for the given language
+pub fn category(
+    language: &str,
+    rule_type: PluralRuleType,
+    operands: &PluralOperands,
+) -> PluralCategory {
+    match rule_type {
+        PluralRuleType::Cardinal => cardinal(language, operands),
+        PluralRuleType::Ordinal => ordinal(language, operands),
+    }
+}
+
+/// The categories a language distinguishes, in the order of [`PluralCategory`]
+pub fn categories(language: &str, rule_type: PluralRuleType) -> Vec<PluralCategory> {
+    use PluralCategory::{Few, Many, One, Other, Two, Zero};
+
+    let categories: &[PluralCategory] = match rule_type {
+        PluralRuleType::Cardinal => match language {
+            "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "my" | "lo" | "km" | "yue" => &[Other],
+            "ru" | "uk" | "be" | "pl" | "cs" | "sk" => &[One, Few, Many, Other],
+            "ar" => &[Zero, One, Two, Few, Many, Other],
+            "he" => &[One, Two, Other],
+            _ => &[One, Other],
+        },
+        PluralRuleType::Ordinal => match language {
+            "en" => &[One, Two, Few, Other],
+            "fr" => &[One, Other],
+            _ => &[Other],
+        },
+    };
+    categories.to_vec()
+}
+
+fn cardinal(language: &str, op: &PluralOperands) -> PluralCategory {
+    use PluralCategory::{Few, Many, One, Other, Two, Zero};
+
+    let n = op.integer_value();
+    let i10 = op.i % 10;
+    let i100 = op.i % 100;
+
+    match language {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "my" | "lo" | "km" | "yue" => Other,
+        "fr" | "pt" if op.i <= 1 => One,
+        "hi" | "bn" | "fa" | "gu" | "kn" | "mr" | "zu" if op.i == 0 || n == Some(1) => One,
+        "da" if n == Some(1) || (op.t != 0 && op.i <= 1) => One,
+        "es" | "el" | "hu" | "tr" | "bg" | "nb" | "no" | "ka" | "sq" if n == Some(1) => One,
+        "fr" | "pt" | "hi" | "bn" | "fa" | "gu" | "kn" | "mr" | "zu" | "da" | "es" | "el"
+        | "hu" | "tr" | "bg" | "nb" | "no" | "ka" | "sq" => Other,
+        "ru" | "uk" | "be" => {
+            if op.v != 0 {
+                Other
+            } else if i10 == 1 && i100 != 11 {
+                One
+            } else if (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                Few
+            } else {
+                Many
+            }
+        }
+        "pl" => {
+            if op.v != 0 {
+                Other
+            } else if op.i == 1 {
+                One
+            } else if (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                Few
+            } else {
+                Many
+            }
+        }
+        "cs" | "sk" => match (op.i, op.v) {
+            (1, 0) => One,
+            (2..=4, 0) => Few,
+            (_, 0) => Other,
+            _ => Many,
+        },
+        "ar" => match n {
+            Some(0) => Zero,
+            Some(1) => One,
+            Some(2) => Two,
+            Some(n) if (3..=10).contains(&(n % 100)) => Few,
+            Some(n) if (11..=99).contains(&(n % 100)) => Many,
+            _ => Other,
+        },
+        "he" => match (op.i, op.v) {
+            (1, 0) | (0, 1..) => One,
+            (2, 0) => Two,
+            _ => Other,
+        },
+        // The rule shared by English, German, Dutch, Swedish, Italian and many more
+        _ => {
+            if op.i == 1 && op.v == 0 {
+                One
+            } else {
+                Other
+            }
+        }
+    }
+}
+
+fn ordinal(language: &str, op: &PluralOperands) -> PluralCategory {
+    use PluralCategory::{Few, One, Other, Two};
+
+    let Some(n) = op.integer_value() else {
+        return Other;
+    };
+
+    match language {
+        "en" => match (n % 10, n % 100) {
+            (1, 11) | (2, 12) | (3, 13) => Other,
+            (1, _) => One,
+            (2, _) => Two,
+            (3, _) => Few,
+            _ => Other,
+        },
+        "fr" if n == 1 => One,
+        _ => Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cardinal(language: &str, number: &str) -> PluralCategory {
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        category(
+            language,
+            PluralRuleType::Cardinal,
+            &PluralOperands::new(integer, fraction),
+        )
+    }
+
+    #[test]
+    fn it_selects_english_categories() {
+        assert_eq!(cardinal("en", "1"), PluralCategory::One);
+        assert_eq!(cardinal("en", "1.0"), PluralCategory::Other);
+        assert_eq!(cardinal("en", "0"), PluralCategory::Other);
+
+        let ordinal = |n| {
+            category(
+                "en",
+                PluralRuleType::Ordinal,
+                &PluralOperands::from_integer(n),
+            )
+        };
+        assert_eq!(ordinal(1), PluralCategory::One);
+        assert_eq!(ordinal(22), PluralCategory::Two);
+        assert_eq!(ordinal(113), PluralCategory::Other);
+        assert_eq!(ordinal(103), PluralCategory::Few);
+    }
+
+    #[test]
+    fn it_selects_slavic_categories() {
+        assert_eq!(cardinal("ru", "21"), PluralCategory::One);
+        assert_eq!(cardinal("ru", "11"), PluralCategory::Many);
+        assert_eq!(cardinal("ru", "3"), PluralCategory::Few);
+        assert_eq!(cardinal("ru", "1.5"), PluralCategory::Other);
+        assert_eq!(cardinal("pl", "22"), PluralCategory::Few);
+        assert_eq!(cardinal("cs", "1.5"), PluralCategory::Many);
+    }
+
+    #[test]
+    fn it_selects_arabic_categories() {
+        assert_eq!(cardinal("ar", "0"), PluralCategory::Zero);
+        assert_eq!(cardinal("ar", "2"), PluralCategory::Two);
+        assert_eq!(cardinal("ar", "105"), PluralCategory::Few);
+        assert_eq!(cardinal("ar", "111"), PluralCategory::Many);
+        assert_eq!(cardinal("ar", "100"), PluralCategory::Other);
+    }
+
+    #[test]
+    fn it_keeps_large_numbers_apart_from_small_ones() {
+        assert_eq!(
+            cardinal("en", "1000000000000000000001"),
+            PluralCategory::Other
+        );
+        assert_eq!(
+            cardinal("ru", "1000000000000000000001"),
+            PluralCategory::One
+        );
+    }
+}
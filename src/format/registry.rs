@@ -0,0 +1,215 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::arguments::Value;
+use crate::format::errors::FunctionError;
+use crate::format::functions;
+use crate::locale::Locale;
+
+/// A function that can be called from a message, like `:number` in `{$count :number}`.
+pub trait Function: Send + Sync {
+    /// Formats the operand into `out`
+    ///
+    /// # Errors
+    /// Returns an error if the operand or the options are not supported. Anything written before
+    /// the error is discarded, and the fallback of the expression is written instead.
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError>;
+
+    /// Returns the indices of the `keys` that match the operand, the best match first.
+    ///
+    /// # Errors
+    /// Returns an error if the function does not support selection, or if the operand or options are not supported.
+    fn select(&self, input: FunctionInput<'_>, keys: &[&str]) -> Result<Vec<usize>, FunctionError> {
+        let _ = (input, keys);
+        Err(FunctionError::NotSelectable)
+    }
+}
+
+/// Everything a function is called with
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionInput<'a> {
+    /// The operand, if the expression has one. `{$count :number}` has one, `{:now}` doesn't.
+    pub operand: Option<&'a Value>,
+    pub options: &'a Options,
+    pub locale: &'a Locale,
+}
+
+/// The resolved options of a function call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    entries: Vec<(String, Value)>,
+}
+
+impl Options {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets an option, replacing any previous value
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        let name = name.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies all options from `other` on top of these
+    pub fn merge(&mut self, other: &Self) {
+        for (name, value) in &other.entries {
+            self.insert(name.clone(), value.clone());
+        }
+    }
+
+    /// Reads an option as a string. Non-string values are formatted.
+    pub fn get_str(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.get(name).map(|value| match value {
+            Value::String(s) => Cow::Borrowed(s.as_str()),
+            other => Cow::Owned(other.to_string()),
+        })
+    }
+
+    /// Reads an option that must be a number of digits from 0 to 999, like
+    /// `minimumFractionDigits`. ICU has the same limit.
+    ///
+    /// # Errors
+    /// Returns an error if the option is set to something else.
+    pub fn get_digits(&self, name: &str) -> Result<Option<usize>, FunctionError> {
+        let invalid = || FunctionError::InvalidOption {
+            name: name.into(),
+            reason: "expected an integer from 0 to 999".into(),
+        };
+
+        let digits = match self.get(name) {
+            None => return Ok(None),
+            Some(Value::Integer(i)) => usize::try_from(*i).map_err(|_| invalid())?,
+            Some(Value::String(s)) => s.parse().map_err(|_| invalid())?,
+            Some(_) => return Err(invalid()),
+        };
+        if digits > 999 {
+            return Err(invalid());
+        }
+        Ok(Some(digits))
+    }
+
+    /// Reads an option that must be one of a fixed set of keywords
+    ///
+    /// # Errors
+    /// Returns an error if the option is set to something else.
+    pub fn get_keyword<'k>(
+        &self,
+        name: &str,
+        allowed: &[&'k str],
+    ) -> Result<Option<&'k str>, FunctionError> {
+        let Some(value) = self.get_str(name) else {
+            return Ok(None);
+        };
+
+        allowed
+            .iter()
+            .find(|keyword| **keyword == value)
+            .map(|keyword| Some(*keyword))
+            .ok_or_else(|| FunctionError::InvalidOption {
+                name: name.into(),
+                reason: format!("expected one of {}", allowed.join(", ")),
+            })
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Options {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut options = Self::new();
+        for (name, value) in iter {
+            options.insert(name, value);
+        }
+        options
+    }
+}
+
+/// The functions that are available to messages, by name.
+///
+/// [`FunctionRegistry::new`] includes the default functions from the spec: `:string`, `:number`,
+/// `:integer`, `:datetime`, `:date` and `:time`.
+#[derive(Clone)]
+pub struct FunctionRegistry {
+    functions: BTreeMap<String, Arc<dyn Function>>,
+}
+
+impl FunctionRegistry {
+    /// A registry with the default functions
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register("string", functions::StringFunction);
+        registry.register("number", functions::Number);
+        registry.register("integer", functions::Integer);
+        registry.register("datetime", functions::DateTimeFunction::DATETIME);
+        registry.register("date", functions::DateTimeFunction::DATE);
+        registry.register("time", functions::DateTimeFunction::TIME);
+        registry
+    }
+
+    /// A registry without any functions
+    pub fn empty() -> Self {
+        Self {
+            functions: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a function, replacing any existing function with the same name
+    pub fn register(&mut self, name: impl Into<String>, function: impl Function + 'static) {
+        self.functions.insert(name.into(), Arc::new(function));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Function>> {
+        self.functions.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        f.debug_struct("FunctionRegistry")
+            .field("functions", &names)
+            .finish()
+    }
+}
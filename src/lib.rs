@@ -1 +1,24 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod arguments;
+pub mod catalog;
+pub mod codegen;
+pub mod convert;
 pub mod data_model;
+pub mod format;
+pub mod lint;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+pub mod load;
+pub mod locale;
+pub mod parser;
+pub mod pseudo;
+pub mod resource;
+
+/// Paths for the code that macros expand to, which can't name `alloc` in `no_std` crates
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::String;
+    pub use alloc::vec;
+}
@@ -0,0 +1,263 @@
+//! Checks messages for mistakes that are valid MessageFormat 2, or that the data model checks
+//! only report one at a time, like a `.local` declaration that is never used.
+//!
+//! Each [`Rule`] has a [`Severity`], which a [`Linter`] can change, like the lint levels of Rust:
+//!
+//! ```
+//! # use icu_messageformat_2::data_model::elements::Message;
+//! # use icu_messageformat_2::lint::{Linter, Rule, Severity};
+//! let message: Message = ".local $unused = {|x|}\n{{Hello $name}}".parse().unwrap();
+//! let linter = Linter::new().deny(Rule::LiteralPlaceholder);
+//! let diagnostics = linter.check(&message);
+//! assert_eq!(diagnostics[0].rule, Rule::UnusedLocal);
+//! assert_eq!(diagnostics[1].severity, Severity::Deny);
+//! assert_eq!(diagnostics[1].to_string(), "error[literal-placeholder]: $name: this looks like a variable, but is text");
+//! ```
+
+mod rules;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::data_model::elements::Message;
+
+/// What a rule checks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// A `.local` declaration whose variable is never used
+    UnusedLocal,
+    /// A `.local` declaration with the name of an input variable, which the spec forbids as a
+    /// duplicate declaration. A common mistake is `.local $count = {$count :number}`.
+    ShadowedInput,
+    /// A variant with a key that its selector never matches, like `few` on a `:number` selector
+    /// with `select=exact` or `1.5` on an `:integer` selector. Selection prefers more specific
+    /// keys regardless of the order of the variants, so a catch-all variant never hides one that
+    /// comes after it, and there is no rule for that.
+    ImpossibleKey,
+    /// A variant with the same keys as an earlier one, which is never selected
+    DuplicateVariant,
+    /// A pattern without any text, expressions or markup
+    EmptyPattern,
+    /// A selector on a variable without a function annotation, neither in the selector nor in a
+    /// declaration, which the spec forbids as a missing selector annotation
+    UnannotatedSelector,
+    /// Text like `$name`, which is probably a placeholder that is missing its braces
+    LiteralPlaceholder,
+}
+
+impl Rule {
+    pub const ALL: [Self; 7] = [
+        Self::UnusedLocal,
+        Self::ShadowedInput,
+        Self::ImpossibleKey,
+        Self::DuplicateVariant,
+        Self::EmptyPattern,
+        Self::UnannotatedSelector,
+        Self::LiteralPlaceholder,
+    ];
+
+    /// The name of the rule in kebab case, eg. `unused-local`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::UnusedLocal => "unused-local",
+            Self::ShadowedInput => "shadowed-input",
+            Self::ImpossibleKey => "impossible-key",
+            Self::DuplicateVariant => "duplicate-variant",
+            Self::EmptyPattern => "empty-pattern",
+            Self::UnannotatedSelector => "unannotated-selector",
+            Self::LiteralPlaceholder => "literal-placeholder",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+
+    /// Rules for what the spec considers data model errors are denied, the others warn
+    pub const fn default_severity(self) -> Severity {
+        match self {
+            Self::ShadowedInput | Self::UnannotatedSelector => Severity::Deny,
+            _ => Severity::Warn,
+        }
+    }
+
+    const fn description(self) -> &'static str {
+        match self {
+            Self::UnusedLocal => "the variable is never used",
+            Self::ShadowedInput => "the variable has the name of an input variable",
+            Self::ImpossibleKey => "a key of the variant never matches its selector",
+            Self::DuplicateVariant => "an earlier variant has the same keys",
+            Self::EmptyPattern => "the pattern is empty",
+            Self::UnannotatedSelector => "the selector has no function to select with",
+            Self::LiteralPlaceholder => "this looks like a variable, but is text",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How seriously the problems a rule finds are taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The rule is off
+    Allow,
+    /// Problems are reported as warnings
+    Warn,
+    /// Problems are reported as errors
+    Deny,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Warn => "warning",
+            Self::Deny => "error",
+        })
+    }
+}
+
+/// A problem that a rule found in a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// The index of the variant with the problem, `None` in declarations, selectors and the pattern
+    /// of a pattern message
+    pub variant: Option<usize>,
+    /// The offending part of the message, in MessageFormat 2 syntax. Variants are written as their
+    /// keys.
+    pub node: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            self.severity,
+            self.rule,
+            self.node,
+            self.rule.description()
+        )
+    }
+}
+
+/// Configures the severities of rules. Rules that aren't configured have their default severity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Linter {
+    severities: BTreeMap<Rule, Severity>,
+}
+
+impl Linter {
+    pub const fn new() -> Self {
+        Self {
+            severities: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the severity of a rule
+    #[must_use]
+    pub fn severity(mut self, rule: Rule, severity: Severity) -> Self {
+        self.severities.insert(rule, severity);
+        self
+    }
+
+    /// Turns a rule off
+    #[must_use]
+    pub fn allow(self, rule: Rule) -> Self {
+        self.severity(rule, Severity::Allow)
+    }
+
+    /// Reports the problems of a rule as warnings
+    #[must_use]
+    pub fn warn(self, rule: Rule) -> Self {
+        self.severity(rule, Severity::Warn)
+    }
+
+    /// Reports the problems of a rule as errors
+    #[must_use]
+    pub fn deny(self, rule: Rule) -> Self {
+        self.severity(rule, Severity::Deny)
+    }
+
+    /// The severity a rule has with this configuration
+    pub fn severity_of(&self, rule: Rule) -> Severity {
+        self.severities
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+
+    /// Checks a message with every rule that isn't allowed. Problems in declarations come first,
+    /// then those in selectors, then those in variants in order.
+    pub fn check(&self, message: &Message) -> Vec<Diagnostic> {
+        rules::check(message)
+            .into_iter()
+            .filter_map(|(rule, variant, node)| {
+                let severity = self.severity_of(rule);
+                (severity != Severity::Allow).then_some(Diagnostic {
+                    rule,
+                    severity,
+                    variant,
+                    node,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_configures_severities() {
+        let message: Message = ".input {$n :number}\n.local $n = {$n}\n.match {$n}\n* {{}}"
+            .parse()
+            .expect("valid syntax");
+        let rules = |linter: &Linter| -> Vec<(Rule, Severity)> {
+            linter
+                .check(&message)
+                .iter()
+                .map(|diagnostic| (diagnostic.rule, diagnostic.severity))
+                .collect()
+        };
+        assert_eq!(
+            rules(&Linter::new()),
+            [
+                (Rule::ShadowedInput, Severity::Deny),
+                (Rule::EmptyPattern, Severity::Warn)
+            ]
+        );
+        assert_eq!(
+            rules(
+                &Linter::new()
+                    .warn(Rule::ShadowedInput)
+                    .deny(Rule::EmptyPattern)
+            ),
+            [
+                (Rule::ShadowedInput, Severity::Warn),
+                (Rule::EmptyPattern, Severity::Deny)
+            ]
+        );
+        assert_eq!(
+            rules(&Linter::new().allow(Rule::ShadowedInput)),
+            [(Rule::EmptyPattern, Severity::Warn)]
+        );
+    }
+
+    #[test]
+    fn it_names_rules() {
+        for rule in Rule::ALL {
+            assert_eq!(Rule::from_name(rule.name()), Some(rule));
+        }
+        assert_eq!(Rule::from_name("unused_local"), None);
+    }
+}
@@ -0,0 +1,410 @@
+//! The checks of the rules. They find problems regardless of their severity.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::Rule;
+use crate::data_model::elements::{
+    Annotation, AttributeValue, Declaration, Expression, FunctionAnnotation, Markup, Message,
+    OptionValue, PatternElement, Variant, VariantKey,
+};
+use crate::format::functions::{is_integer_literal, is_number_literal};
+use crate::format::plural::PluralCategory;
+
+/// A problem: the rule, the index of the variant and the offending node
+pub type Finding = (Rule, Option<usize>, String);
+
+pub fn check(message: &Message) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    match message {
+        Message::Pattern(message) => {
+            declarations(
+                &message.declarations,
+                &[],
+                &[&message.pattern],
+                &mut findings,
+            );
+            pattern(&message.pattern, None, &mut findings);
+        }
+        Message::Select(message) => {
+            let patterns: Vec<&[PatternElement]> = message
+                .variants
+                .iter()
+                .map(|variant| variant.value.as_slice())
+                .collect();
+            declarations(
+                &message.declarations,
+                &message.selectors,
+                &patterns,
+                &mut findings,
+            );
+            for selector in &message.selectors {
+                if let Expression::Variable(variable) = selector {
+                    if !is_annotated(selector, &message.declarations) {
+                        findings.push((
+                            Rule::UnannotatedSelector,
+                            None,
+                            format!("${}", variable.arg.name),
+                        ));
+                    }
+                }
+            }
+            for (index, variant) in message.variants.iter().enumerate() {
+                let node = keys(variant);
+                if message.variants[..index]
+                    .iter()
+                    .any(|earlier| earlier.keys == variant.keys)
+                {
+                    findings.push((Rule::DuplicateVariant, Some(index), node));
+                } else if has_impossible_key(variant, &message.selectors, &message.declarations) {
+                    findings.push((Rule::ImpossibleKey, Some(index), node));
+                }
+                pattern(&variant.value, Some(index), &mut findings);
+            }
+        }
+    }
+    findings
+}
+
+fn keys(variant: &Variant) -> String {
+    variant
+        .keys
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn declarations(
+    declarations: &[Declaration],
+    selectors: &[Expression],
+    patterns: &[&[PatternElement]],
+    findings: &mut Vec<Finding>,
+) {
+    let inputs: BTreeSet<&str> = declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::Input(input) => Some(input.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    // Variables that were used before they could be declared, so they are input variables
+    let mut external: BTreeSet<&str> = BTreeSet::new();
+    let mut locals: BTreeSet<&str> = BTreeSet::new();
+
+    for (index, declaration) in declarations.iter().enumerate() {
+        let Declaration::Local(local) = declaration else {
+            for variable in declaration_variables(declaration) {
+                if !locals.contains(variable) {
+                    external.insert(variable);
+                }
+            }
+            continue;
+        };
+        external.extend(
+            expression_variables(&local.value)
+                .into_iter()
+                .filter(|variable| !locals.contains(variable)),
+        );
+        let name = local.name.as_str();
+        if inputs.contains(name) || external.contains(name) {
+            findings.push((Rule::ShadowedInput, None, declaration.to_string()));
+        }
+        locals.insert(name);
+
+        let used_later = declarations[index + 1..]
+            .iter()
+            .flat_map(declaration_variables);
+        let used_in_selectors = selectors.iter().flat_map(expression_variables);
+        let used_in_patterns = patterns
+            .iter()
+            .flat_map(|pattern| pattern_variables(pattern));
+        if !used_later
+            .chain(used_in_selectors)
+            .chain(used_in_patterns)
+            .any(|variable| variable == name)
+        {
+            findings.push((Rule::UnusedLocal, None, declaration.to_string()));
+        }
+    }
+}
+
+fn pattern(pattern: &[PatternElement], variant: Option<usize>, findings: &mut Vec<Finding>) {
+    if pattern
+        .iter()
+        .all(|element| matches!(element, PatternElement::Literal(text) if text.is_empty()))
+    {
+        findings.push((Rule::EmptyPattern, variant, "{{}}".into()));
+    }
+    for element in pattern {
+        if let PatternElement::Literal(text) = element {
+            for name in placeholders(text) {
+                findings.push((Rule::LiteralPlaceholder, variant, format!("${name}")));
+            }
+        }
+    }
+}
+
+/// The variable names in text that look like placeholders, like `name` in `Hello $name!`
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        rest = &rest[start + 1..];
+        if rest.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(rest.len());
+            names.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    names
+}
+
+fn declaration_variables(declaration: &Declaration) -> Vec<&str> {
+    match declaration {
+        Declaration::Input(input) => {
+            let mut variables = annotation_variables(input.value.annotation.as_ref());
+            variables.extend(attribute_variables(&input.value.attributes));
+            variables
+        }
+        Declaration::Local(local) => expression_variables(&local.value),
+        Declaration::UnsupportedStatement(statement) => statement
+            .expressions
+            .iter()
+            .flat_map(expression_variables)
+            .collect(),
+    }
+}
+
+fn pattern_variables(pattern: &[PatternElement]) -> Vec<&str> {
+    pattern
+        .iter()
+        .flat_map(|element| match element {
+            PatternElement::Literal(_) => Vec::new(),
+            PatternElement::Expression(expression) => expression_variables(expression),
+            PatternElement::Markup(markup) => markup_variables(markup),
+        })
+        .collect()
+}
+
+fn expression_variables(expression: &Expression) -> Vec<&str> {
+    let mut variables = match expression {
+        Expression::Literal(e) => annotation_variables(e.annotation.as_ref()),
+        Expression::Variable(e) => {
+            let mut variables = annotation_variables(e.annotation.as_ref());
+            variables.push(&e.arg.name);
+            variables
+        }
+        Expression::Function(e) => option_variables(&e.annotation.options),
+        Expression::Unsupported(_) => Vec::new(),
+    };
+    let attributes = match expression {
+        Expression::Literal(e) => &e.attributes,
+        Expression::Variable(e) => &e.attributes,
+        Expression::Function(e) => &e.attributes,
+        Expression::Unsupported(e) => &e.attributes,
+    };
+    variables.extend(attribute_variables(attributes));
+    variables
+}
+
+fn markup_variables(markup: &Markup) -> Vec<&str> {
+    let mut variables = option_variables(&markup.options);
+    variables.extend(attribute_variables(&markup.attributes));
+    variables
+}
+
+fn annotation_variables(annotation: Option<&Annotation>) -> Vec<&str> {
+    match annotation {
+        Some(Annotation::Function(function)) => option_variables(&function.options),
+        Some(Annotation::Unsupported(_)) | None => Vec::new(),
+    }
+}
+
+fn option_variables(options: &[crate::data_model::elements::Option]) -> Vec<&str> {
+    options
+        .iter()
+        .filter_map(|option| match &option.value {
+            OptionValue::Variable(variable) => Some(variable.name.as_str()),
+            OptionValue::Literal(_) => None,
+        })
+        .collect()
+}
+
+fn attribute_variables(attributes: &[crate::data_model::elements::Attribute]) -> Vec<&str> {
+    attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.value {
+            Some(AttributeValue::Variable(variable)) => Some(variable.name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The declaration of a variable, and the declarations before it
+fn declaration<'m>(
+    name: &str,
+    declarations: &'m [Declaration],
+) -> Option<(&'m Declaration, &'m [Declaration])> {
+    let index = declarations
+        .iter()
+        .position(|declaration| match declaration {
+            Declaration::Input(input) => input.name == name,
+            Declaration::Local(local) => local.name == name,
+            Declaration::UnsupportedStatement(_) => false,
+        })?;
+    Some((&declarations[index], &declarations[..index]))
+}
+
+/// Whether the expression has an annotation, or references a declaration that has one
+fn is_annotated(expression: &Expression, declarations: &[Declaration]) -> bool {
+    match expression {
+        Expression::Literal(e) => e.annotation.is_some(),
+        Expression::Function(_) | Expression::Unsupported(_) => true,
+        Expression::Variable(e) if e.annotation.is_some() => true,
+        Expression::Variable(e) => match declaration(&e.arg.name, declarations) {
+            Some((Declaration::Input(input), _)) => input.value.annotation.is_some(),
+            Some((Declaration::Local(local), earlier)) => is_annotated(&local.value, earlier),
+            _ => false,
+        },
+    }
+}
+
+/// The function that an expression is formatted or selected with, following declarations
+fn function<'m>(
+    expression: &'m Expression,
+    declarations: &'m [Declaration],
+) -> Option<&'m FunctionAnnotation> {
+    let annotation = match expression {
+        Expression::Literal(e) => e.annotation.as_ref(),
+        Expression::Variable(e) => e.annotation.as_ref(),
+        Expression::Function(e) => return Some(&e.annotation),
+        Expression::Unsupported(_) => return None,
+    };
+    match (annotation, expression) {
+        (Some(Annotation::Function(function)), _) => Some(function),
+        (None, Expression::Variable(e)) => match declaration(&e.arg.name, declarations)? {
+            (Declaration::Input(input), _) => match &input.value.annotation {
+                Some(Annotation::Function(function)) => Some(function),
+                _ => None,
+            },
+            (Declaration::Local(local), earlier) => function(&local.value, earlier),
+            (Declaration::UnsupportedStatement(_), _) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether a key of the variant is one that the `:number` or `:integer` function of its selector
+/// never matches, like a key with a fraction on `:integer`. Keys of other functions can't be
+/// checked.
+fn has_impossible_key(
+    variant: &Variant,
+    selectors: &[Expression],
+    declarations: &[Declaration],
+) -> bool {
+    variant.keys.iter().zip(selectors).any(|(key, selector)| {
+        let VariantKey::Literal(key) = key else {
+            return false;
+        };
+        let Some(function) = function(selector, declarations) else {
+            return false;
+        };
+        if !matches!(function.name.as_str(), "number" | "integer") {
+            return false;
+        }
+        let select = function
+            .options
+            .iter()
+            .find(|option| option.name == "select")
+            .map(|option| &option.value);
+        let categories = match select {
+            None => true,
+            Some(OptionValue::Literal(select)) => select.value != "exact",
+            // The selection is only known when formatting
+            Some(OptionValue::Variable(_)) => return false,
+        };
+        let matches_value = if function.name == "integer" {
+            is_integer_literal(&key.value)
+        } else {
+            is_number_literal(&key.value)
+        };
+        let matches_category = categories && PluralCategory::from_name(&key.value).is_some();
+        !(matches_value || matches_category)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn findings(source: &str) -> Vec<(Rule, Option<usize>, String)> {
+        check(&source.parse().expect("valid syntax"))
+    }
+
+    #[test]
+    fn it_finds_problems_with_declarations() {
+        assert_eq!(
+            findings(".input {$n :number}\n.local $unused = {$n}\n.local $used = {$n :integer}\n.local $n = {|1|}\n{{{$used}}}"),
+            vec![
+                (Rule::UnusedLocal, None, ".local $unused = {$n}".into()),
+                (Rule::ShadowedInput, None, ".local $n = {1}".into()),
+                (Rule::UnusedLocal, None, ".local $n = {1}".into()),
+            ]
+        );
+        assert_eq!(
+            findings(".local $count = {$count :number}\n.local $x = {|x|}\n.local $y = {$x}\n{{{$y} {$count}}}"),
+            vec![(Rule::ShadowedInput, None, ".local $count = {$count :number}".into())]
+        );
+    }
+
+    #[test]
+    fn it_finds_problems_with_variants() {
+        assert_eq!(
+            findings(
+                ".input {$n :number select=exact}\n.local $m = {$n :integer}\n.match {$n} {$m} {$s}\n\
+                 1 one x {{One}}\n1 one x {{Again}}\nfew * * {{Few}}\n* * * {{}}"
+            ),
+            vec![
+                (Rule::UnannotatedSelector, None, "$s".into()),
+                (Rule::DuplicateVariant, Some(1), "1 one x".into()),
+                (Rule::ImpossibleKey, Some(2), "few * *".into()),
+                (Rule::EmptyPattern, Some(3), "{{}}".into()),
+            ]
+        );
+        assert_eq!(
+            findings(".match {$n :number}\nmany {{Many}}\n1.5 {{Half}}\n* {{Other}}"),
+            vec![]
+        );
+        assert_eq!(
+            findings(".match {$n :number}\nones {{Ones}}\n* {{Other}}"),
+            vec![(Rule::ImpossibleKey, Some(0), "ones".into())]
+        );
+        assert_eq!(
+            findings(
+                ".match {$n :integer}\n1.0 {{One}}\n1.5e1 {{Fifteen}}\n1.5 {{Half}}\n* {{Other}}"
+            ),
+            vec![(Rule::ImpossibleKey, Some(2), "1.5".into())]
+        );
+    }
+
+    #[test]
+    fn it_finds_text_that_looks_like_placeholders() {
+        assert_eq!(
+            findings("Hello $name, you owe $5 to $first_name! {$ok}"),
+            vec![
+                (Rule::LiteralPlaceholder, None, "$name".into()),
+                (Rule::LiteralPlaceholder, None, "$first_name".into()),
+            ]
+        );
+        assert_eq!(
+            findings(""),
+            vec![(Rule::EmptyPattern, None, "{{}}".into())]
+        );
+    }
+}
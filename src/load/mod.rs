@@ -0,0 +1,357 @@
+//! Loads messages from JSON, TOML and YAML resource files.
+//!
+//! A resource file maps message ids to the source of the message. Nested maps are namespaces, their
+//! keys are joined with dots:
+//!
+//! ```json
+//! {
+//!     "greeting": "Hello {$name}!",
+//!     "cart": { "empty": "Your cart is empty" }
+//! }
+//! ```
+//!
+//! holds the messages `greeting` and `cart.empty`. Each format is behind a cargo feature of the same
+//! name: `json`, `toml` and `yaml`.
+
+pub(crate) mod tree;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::catalog::{Catalog, CatalogError};
+use crate::data_model::elements::Message;
+use crate::data_model::errors::{DataModelError, SyntaxError};
+
+use tree::Node;
+
+/// The format of a resource file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl Format {
+    /// The format for a file extension like `json` or `yml`, if it is enabled
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "json")]
+            "json" => Some(Self::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Self::Toml),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, source: &str) -> Result<Node, String> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::from_str(source).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::from_str(source).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml_ng::from_str(source).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Parses the messages of a resource file, in the order they appear in the file. `file` is only
+/// used for error messages.
+///
+/// # Errors
+/// Returns the first problem with the file or one of its messages.
+pub fn parse(
+    format: Format,
+    file: &str,
+    source: &str,
+) -> Result<Vec<(String, Message)>, LoadError> {
+    let error = |kind| LoadError {
+        file: file.into(),
+        kind,
+    };
+
+    let entries = match format
+        .parse(source)
+        .map_err(|reason| error(LoadErrorKind::Parse(reason)))?
+    {
+        Node::Namespace(entries) => entries,
+        Node::Message(_) | Node::Invalid(_) => {
+            return Err(error(LoadErrorKind::Parse(
+                "expected a map of messages at the top level".into(),
+            )))
+        }
+    };
+
+    let mut messages = Vec::new();
+    flatten(None, entries, &mut messages).map_err(error)?;
+
+    let mut ids = BTreeSet::new();
+    messages
+        .into_iter()
+        .map(|(id, source)| {
+            if !ids.insert(id.clone()) {
+                return Err(error(LoadErrorKind::DuplicateKey { key: id }));
+            }
+            match source.parse::<Message>() {
+                Ok(message) => Ok((id, message)),
+                Err(syntax_error) => {
+                    let (line, column) = syntax_error.line_column(&source);
+                    Err(error(LoadErrorKind::Syntax {
+                        key: id,
+                        error: syntax_error,
+                        line,
+                        column,
+                    }))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Collects the messages under `prefix` with their full ids
+fn flatten(
+    prefix: Option<&str>,
+    entries: Vec<(String, Node)>,
+    out: &mut Vec<(String, String)>,
+) -> Result<(), LoadErrorKind> {
+    for (key, node) in entries {
+        let id = prefix.map_or_else(|| key.clone(), |prefix| format!("{prefix}.{key}"));
+        match node {
+            Node::Message(source) => out.push((id, source)),
+            Node::Namespace(entries) => flatten(Some(&id), entries, out)?,
+            Node::Invalid(found) => {
+                return Err(LoadErrorKind::InvalidValue {
+                    key: id,
+                    found: found.into(),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a resource file, picking the format by its extension.
+///
+/// # Errors
+/// Returns an error if the file can't be read, has an unknown extension, or can't be parsed.
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<(String, Message)>, LoadError> {
+    let path = path.as_ref();
+    let file = path.display().to_string();
+    let error = |kind| LoadError {
+        file: file.clone(),
+        kind,
+    };
+
+    let format = path
+        .extension()
+        .and_then(|extension| Format::from_extension(&extension.to_string_lossy()))
+        .ok_or_else(|| error(LoadErrorKind::UnknownFormat))?;
+    let source = std::fs::read_to_string(path).map_err(|e| error(LoadErrorKind::Io(e.kind())))?;
+    parse(format, &file, &source)
+}
+
+impl Catalog {
+    /// Parses a resource file and adds all of its messages, see [`parse`].
+    ///
+    /// # Errors
+    /// Returns the first problem with the file or one of its messages. The catalog is left
+    /// unchanged, no message of the file is added.
+    pub fn load(&mut self, format: Format, file: &str, source: &str) -> Result<(), LoadError> {
+        self.insert_file(file, parse(format, file, source)?)
+    }
+
+    /// Reads a resource file and adds all of its messages, see [`read`].
+    ///
+    /// # Errors
+    /// Like [`Catalog::load`].
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<(), LoadError> {
+        let file = path.as_ref().display().to_string();
+        self.insert_file(&file, read(path)?)
+    }
+
+    fn insert_file(
+        &mut self,
+        file: &str,
+        messages: Vec<(String, Message)>,
+    ) -> Result<(), LoadError> {
+        self.insert_all(messages).map_err(|error| LoadError {
+            file: file.into(),
+            kind: match error {
+                CatalogError::DataModel { id, error } => {
+                    LoadErrorKind::DataModel { key: id, error }
+                }
+                error => LoadErrorKind::Catalog(error),
+            },
+        })
+    }
+}
+
+/// A problem with a resource file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    /// The name of the file, as it was passed in
+    pub file: String,
+    pub kind: LoadErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadErrorKind {
+    /// The file could not be read
+    Io(io::ErrorKind),
+    /// The file extension isn't one of the enabled formats
+    UnknownFormat,
+    /// The file isn't valid in its format, or doesn't hold a map
+    Parse(String),
+    /// The value of a key is neither a message nor a map of messages
+    InvalidValue { key: String, found: String },
+    /// Two keys have the same id once namespaces are flattened, eg. `"a.b"` and `{ "a": { "b" } }`
+    DuplicateKey { key: String },
+    /// A message could not be parsed. `line` and `column` are the 1-based position in the message.
+    Syntax {
+        key: String,
+        error: SyntaxError,
+        line: usize,
+        column: usize,
+    },
+    /// A message isn't valid according to the data model
+    DataModel { key: String, error: DataModelError },
+    /// The catalog rejected a message for another reason
+    Catalog(CatalogError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.file)?;
+        match &self.kind {
+            LoadErrorKind::Io(kind) => write!(f, "could not be read: {kind}"),
+            LoadErrorKind::UnknownFormat => f.write_str("unknown file format"),
+            LoadErrorKind::Parse(reason) => f.write_str(reason),
+            LoadErrorKind::InvalidValue { key, found } => {
+                write!(
+                    f,
+                    "{key}: expected a message or a map of messages, found {found}"
+                )
+            }
+            LoadErrorKind::DuplicateKey { key } => {
+                write!(f, "{key}: the message is defined more than once")
+            }
+            LoadErrorKind::Syntax {
+                key,
+                error,
+                line,
+                column,
+            } => write!(f, "{key}:{line}:{column}: {}", error.reason),
+            LoadErrorKind::DataModel { key, error } => write!(f, "{key}: {error}"),
+            LoadErrorKind::Catalog(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(messages: &[(String, Message)]) -> Vec<&str> {
+        messages.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn it_loads_json() {
+        let messages = parse(
+            Format::Json,
+            "en.json",
+            r#"{ "greeting": "Hello {$name}!", "cart": { "empty": "Empty", "items": { "one": "{$n} item" } } }"#,
+        )
+        .expect("valid file");
+
+        assert_eq!(ids(&messages), ["greeting", "cart.empty", "cart.items.one"]);
+        assert_eq!(messages[0].1.to_string(), "Hello {$name}!");
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn it_loads_toml() {
+        let messages = parse(
+            Format::Toml,
+            "en.toml",
+            "greeting = \"Hello {$name}!\"\n\n[cart]\nempty = \"Empty\"\n\"items.one\" = \"{$n} item\"\n",
+        )
+        .expect("valid file");
+
+        assert_eq!(ids(&messages), ["greeting", "cart.empty", "cart.items.one"]);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn it_loads_yaml() {
+        let messages = parse(
+            Format::Yaml,
+            "en.yaml",
+            "greeting: Hello {$name}!\ncart:\n  empty: Empty\n  summary: |-\n    .input {$n :number}\n    {{{$n} items}}\n",
+        )
+        .expect("valid file");
+
+        assert_eq!(ids(&messages), ["greeting", "cart.empty", "cart.summary"]);
+        assert_eq!(
+            Ok(&messages[2].1),
+            ".input {$n :number}\n{{{$n} items}}".parse().as_ref()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn it_reports_the_file_key_and_position() {
+        let error = parse(
+            Format::Json,
+            "de.json",
+            r#"{ "cart": { "items": "{$n} Artikel\n{$n" } }"#,
+        )
+        .expect_err("invalid message");
+
+        assert!(matches!(
+            &error.kind,
+            LoadErrorKind::Syntax { key, line: 2, column: 4, .. } if key == "cart.items"
+        ));
+        assert!(error.to_string().starts_with("de.json: cart.items:2:4: "));
+
+        let error =
+            parse(Format::Json, "de.json", r#"{ "a": { "b": 1 } }"#).expect_err("invalid value");
+        assert_eq!(
+            error.to_string(),
+            "de.json: a.b: expected a message or a map of messages, found a number"
+        );
+
+        let error = parse(
+            Format::Json,
+            "de.json",
+            r#"{ "a.b": "x", "a": { "b": "y" } }"#,
+        )
+        .expect_err("duplicate");
+        assert_eq!(
+            error.kind,
+            LoadErrorKind::DuplicateKey { key: "a.b".into() }
+        );
+
+        let mut catalog = Catalog::new("de".parse().expect("valid locale"));
+        let error = catalog
+            .load(
+                Format::Json,
+                "de.json",
+                r#"{ "ok": "Gut", "bad": ".input {$n :number}\n.match {$n}\none {{eins}}" }"#,
+            )
+            .expect_err("invalid message");
+        assert!(matches!(error.kind, LoadErrorKind::DataModel { ref key, .. } if key == "bad"));
+        assert!(!catalog.contains("ok"));
+    }
+}
@@ -0,0 +1,310 @@
+mod parents;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// A BCP 47 language tag, eg. `en`, `de-CH` or `zh-Hant-TW`.
+///
+/// Only the language, script, region and variant subtags are interpreted. Extensions and private use
+/// subtags are kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Locale {
+    language: String,
+    script: Option<String>,
+    region: Option<String>,
+    variants: Vec<String>,
+    extensions: Option<String>,
+}
+
+impl Locale {
+    /// The undetermined locale, `und`
+    pub fn und() -> Self {
+        Self {
+            language: "und".into(),
+            script: None,
+            region: None,
+            variants: Vec::new(),
+            extensions: None,
+        }
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn variants(&self) -> &[String] {
+        &self.variants
+    }
+
+    /// The direction text in this locale is written in, based on the script or the language
+    pub fn direction(&self) -> Direction {
+        const RTL_SCRIPTS: [&str; 9] = [
+            "Adlm", "Arab", "Hebr", "Mand", "Nkoo", "Rohg", "Samr", "Syrc", "Thaa",
+        ];
+        const RTL_LANGUAGES: [&str; 14] = [
+            "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ji", "ps", "sd", "syr", "ug", "ur", "yi",
+        ];
+
+        let rtl = self.script.as_deref().map_or_else(
+            || RTL_LANGUAGES.contains(&self.language.as_str()),
+            |script| RTL_SCRIPTS.contains(&script),
+        );
+        if rtl {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+}
+
+impl Locale {
+    /// The locale to fall back to when something is missing for this one, or `None` if that is the
+    /// root locale.
+    ///
+    /// Extensions, variants, the region and the script are removed in that order, unless CLDR
+    /// defines another parent, eg. `es-419` for `es-MX` or `en-001` for `en-GB`. Locales with a script
+    /// that isn't the default for the language, like `sr-Latn`, fall back to the root locale directly.
+    pub fn parent(&self) -> Option<Self> {
+        if self.extensions.is_some() {
+            return Some(Self {
+                extensions: None,
+                ..self.clone()
+            });
+        }
+
+        // Chinese in these regions is written in the Traditional script, which isn't the default
+        if self.language == "zh" && self.script.is_none() && self.variants.is_empty() {
+            let region = match self.region.as_deref() {
+                Some("TW") => Some(None),
+                Some(region @ ("HK" | "MO")) => Some(Some(region.into())),
+                _ => None,
+            };
+            if let Some(region) = region {
+                return Some(Self {
+                    script: Some("Hant".into()),
+                    region,
+                    ..self.clone()
+                });
+            }
+        }
+
+        let tag = self.to_string();
+        if let Ok(i) =
+            parents::PARENT_LOCALES.binary_search_by_key(&tag.as_str(), |(locale, _)| locale)
+        {
+            let parent = parents::PARENT_LOCALES[i].1;
+            return (parent != "und").then(|| parent.parse().ok()).flatten();
+        }
+
+        let mut parent = self.clone();
+        if parent.variants.pop().is_none()
+            && parent.region.take().is_none()
+            && parent.script.take().is_none()
+        {
+            return None;
+        }
+        Some(parent)
+    }
+
+    /// This locale followed by its parents, eg. `de-CH` and `de`, or `es-MX`, `es-419` and `es`.
+    /// The root locale is not included.
+    pub fn fallback_chain(&self) -> Vec<Self> {
+        let mut chain = vec![self.clone()];
+        while let Some(parent) = chain.last().and_then(Self::parent) {
+            chain.push(parent);
+        }
+        chain
+    }
+}
+
+/// The direction of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::und()
+    }
+}
+
+/// The error returned when a string is not a well-formed language tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLocaleError {
+    pub tag: String,
+}
+
+impl fmt::Display for ParseLocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid language tag", self.tag)
+    }
+}
+
+impl core::error::Error for ParseLocaleError {}
+
+impl FromStr for Locale {
+    type Err = ParseLocaleError;
+
+    /// Parses a language tag. Both `-` and `_` are accepted as separators and the casing is normalized.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseLocaleError { tag: s.into() };
+        let mut subtags = s.split(['-', '_']).peekable();
+
+        let language = subtags
+            .next()
+            .filter(|l| is_alpha(l, 2, 8) && l.len() != 4)
+            .ok_or_else(error)?
+            .to_ascii_lowercase();
+
+        let script = subtags
+            .next_if(|s| is_alpha(s, 4, 4))
+            .map(|s| s[..1].to_ascii_uppercase() + &s[1..].to_ascii_lowercase());
+
+        let region = subtags
+            .next_if(|r| {
+                is_alpha(r, 2, 2) || (r.len() == 3 && r.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .map(str::to_ascii_uppercase);
+
+        let mut variants = Vec::new();
+        while let Some(variant) = subtags.next_if(|v| is_variant(v)) {
+            variants.push(variant.to_ascii_lowercase());
+        }
+
+        let rest: Vec<&str> = subtags.collect();
+        if rest
+            .iter()
+            .any(|s| s.is_empty() || s.len() > 8 || !s.bytes().all(|b| b.is_ascii_alphanumeric()))
+        {
+            return Err(error());
+        }
+        if rest.first().is_some_and(|s| s.len() != 1) {
+            return Err(error());
+        }
+
+        Ok(Self {
+            language,
+            script,
+            region,
+            variants,
+            extensions: (!rest.is_empty()).then(|| rest.join("-").to_ascii_lowercase()),
+        })
+    }
+}
+
+fn is_alpha(subtag: &str, min: usize, max: usize) -> bool {
+    (min..=max).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+fn is_variant(subtag: &str) -> bool {
+    let alphanumeric = subtag.bytes().all(|b| b.is_ascii_alphanumeric());
+    match subtag.len() {
+        5..=8 => alphanumeric,
+        4 => alphanumeric && subtag.as_bytes()[0].is_ascii_digit(),
+        _ => false,
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.language)?;
+        for subtag in self
+            .script
+            .iter()
+            .chain(&self.region)
+            .chain(&self.variants)
+            .chain(&self.extensions)
+        {
+            write!(f, "-{subtag}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_and_normalizes_language_tags() {
+        let locale: Locale = "zh_hant_tw".parse().expect("valid tag");
+
+        assert_eq!(locale.language(), "zh");
+        assert_eq!(locale.script(), Some("Hant"));
+        assert_eq!(locale.region(), Some("TW"));
+        assert_eq!(locale.to_string(), "zh-Hant-TW");
+    }
+
+    #[test]
+    fn it_parses_variants_and_extensions() {
+        let locale: Locale = "de-CH-1996-u-co-phonebk".parse().expect("valid tag");
+
+        assert_eq!(locale.region(), Some("CH"));
+        assert_eq!(locale.variants(), ["1996"]);
+        assert_eq!(locale.to_string(), "de-CH-1996-u-co-phonebk");
+        assert_eq!(
+            "es-419".parse::<Locale>().map(|l| l.to_string()),
+            Ok("es-419".into())
+        );
+    }
+
+    #[test]
+    fn it_knows_the_direction() {
+        let direction = |tag: &str| tag.parse::<Locale>().expect("valid tag").direction();
+
+        assert_eq!(direction("en"), Direction::LeftToRight);
+        assert_eq!(direction("ar-EG"), Direction::RightToLeft);
+        assert_eq!(direction("he"), Direction::RightToLeft);
+        assert_eq!(direction("pa-Arab"), Direction::RightToLeft);
+        assert_eq!(direction("az-Latn"), Direction::LeftToRight);
+    }
+
+    #[test]
+    fn it_computes_fallback_chains() {
+        let chain = |tag: &str| {
+            tag.parse::<Locale>()
+                .expect("valid tag")
+                .fallback_chain()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(chain("de-CH"), ["de-CH", "de"]);
+        assert_eq!(
+            chain("de-CH-1996-u-co-phonebk"),
+            ["de-CH-1996-u-co-phonebk", "de-CH-1996", "de-CH", "de"]
+        );
+        assert_eq!(chain("es-MX"), ["es-MX", "es-419", "es"]);
+        assert_eq!(chain("en-CH"), ["en-CH", "en-150", "en-001", "en"]);
+        assert_eq!(chain("pt-AO"), ["pt-AO", "pt-PT", "pt"]);
+        assert_eq!(chain("sr-Latn-RS"), ["sr-Latn-RS", "sr-Latn"]);
+        assert_eq!(chain("zh-TW"), ["zh-TW", "zh-Hant"]);
+        assert_eq!(
+            chain("zh-MO"),
+            ["zh-MO", "zh-Hant-MO", "zh-Hant-HK", "zh-Hant"]
+        );
+        assert_eq!(chain("nn"), ["nn", "no"]);
+    }
+
+    #[test]
+    fn it_rejects_malformed_tags() {
+        assert!("".parse::<Locale>().is_err());
+        assert!("e".parse::<Locale>().is_err());
+        assert!("en-".parse::<Locale>().is_err());
+        assert!("en-US-toolongsubtag".parse::<Locale>().is_err());
+    }
+}
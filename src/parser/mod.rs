@@ -0,0 +1,887 @@
+//! Parses messages from the MessageFormat 2.0 syntax.
+//! See: https://github.com/unicode-org/message-format-wg/blob/6d7b4ba213e686ff2d403d3025d38d76b42b75f7/spec/syntax.md
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::data_model::elements::{
+    self, Annotation, Attribute, AttributeValue, Declaration, Expression, FunctionAnnotation,
+    FunctionExpression, InputDeclaration, Literal, LiteralExpression, LocalDeclaration, Markup,
+    MarkupKind, Message, OptionValue, PatternElement, PatternMessage, SelectMessage,
+    UnsupportedAnnotation, UnsupportedExpression, UnsupportedStatement, VariableExpression,
+    VariableRef, Variant, VariantKey,
+};
+use crate::data_model::errors::SyntaxError;
+
+/// Parses a message. This only checks the syntax, use [`Message::validate`] to check for data model errors.
+///
+/// # Errors
+/// Returns an error if the message is not well-formed.
+pub fn parse(source: &str) -> Result<Message, SyntaxError> {
+    Parser {
+        source,
+        position: 0,
+    }
+    .message()
+}
+
+impl FromStr for Message {
+    type Err = SyntaxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+/// Whether a literal can be written without quotes, because it is a name or a number
+pub(crate) fn is_unquoted_literal(value: &str) -> bool {
+    let mut parser = Parser {
+        source: value,
+        position: 0,
+    };
+    let valid = match value.chars().next() {
+        Some(c) if is_name_start(c) => parser.name().is_ok(),
+        Some(c) if c == '-' || c.is_ascii_digit() => parser.number_literal().is_ok(),
+        _ => false,
+    };
+    valid && parser.position == value.len()
+}
+
+/// Whether a string is a number literal, like `-1.5e3`
+pub(crate) fn is_number_literal(value: &str) -> bool {
+    let mut parser = Parser {
+        source: value,
+        position: 0,
+    };
+    parser.number_literal().is_ok() && parser.position == value.len()
+}
+
+/// Whether a string can be used as the name of a variable, option or function
+pub(crate) fn is_name(value: &str) -> bool {
+    let mut parser = Parser {
+        source: value,
+        position: 0,
+    };
+    parser.name().is_ok() && parser.position == value.len()
+}
+
+/// Characters that start a reserved or private-use annotation
+const UNSUPPORTED_SIGILS: [char; 10] = ['!', '%', '*', '+', '<', '>', '?', '~', '^', '&'];
+
+const fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n' | '\u{3000}')
+}
+
+const fn is_name_start(c: char) -> bool {
+    matches!(c,
+        'a'..='z' | 'A'..='Z' | '_'
+        | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFC}' | '\u{10000}'..='\u{EFFFF}')
+}
+
+const fn is_name_char(c: char) -> bool {
+    is_name_start(c)
+        || matches!(c, '0'..='9' | '-' | '.' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    /// The byte offset of the next character
+    position: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> SyntaxError {
+        SyntaxError {
+            offset: self.position,
+            reason: reason.into(),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SyntaxError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    /// Skips optional whitespace. Returns whether there was any.
+    fn whitespace(&mut self) -> bool {
+        let start = self.position;
+        while self.peek().is_some_and(is_whitespace) {
+            self.bump();
+        }
+        self.position > start
+    }
+
+    /// Skips whitespace, but only if it is followed by a character matching `next`
+    fn whitespace_before(&mut self, next: impl Fn(char) -> bool) -> bool {
+        let start = self.position;
+        if self.whitespace() && self.peek().is_some_and(next) {
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    /// Skips whitespace if it is followed by an expression. Returns whether it is.
+    fn at_expression(&mut self) -> bool {
+        let start = self.position;
+        self.whitespace();
+        if self.rest().starts_with('{') && !self.rest().starts_with("{{") {
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    fn message(mut self) -> Result<Message, SyntaxError> {
+        self.whitespace();
+        if self.rest().starts_with('.') || self.rest().starts_with("{{") {
+            let message = self.complex_message()?;
+            self.whitespace();
+            if self.peek().is_some() {
+                return Err(self.error("unexpected content after the end of the message"));
+            }
+            return Ok(message);
+        }
+
+        self.position = 0;
+        let pattern = self.pattern()?;
+        if self.peek().is_some() {
+            return Err(self.error("unescaped `}` in pattern"));
+        }
+        Ok(Message::Pattern(PatternMessage {
+            declarations: vec![],
+            pattern,
+        }))
+    }
+
+    fn complex_message(&mut self) -> Result<Message, SyntaxError> {
+        let mut declarations = Vec::new();
+        loop {
+            self.whitespace();
+            if self.rest().starts_with("{{") {
+                let pattern = self.quoted_pattern()?;
+                return Ok(Message::Pattern(PatternMessage {
+                    declarations,
+                    pattern,
+                }));
+            }
+
+            let start = self.position;
+            if !self.eat('.') {
+                return Err(self.error("expected a declaration, `.match` or a quoted pattern"));
+            }
+            let keyword = self.name()?;
+            match keyword.as_str() {
+                "input" => {
+                    self.whitespace();
+                    let expression_start = self.position;
+                    match self.expression()? {
+                        Expression::Variable(value) => {
+                            declarations.push(Declaration::Input(InputDeclaration {
+                                name: value.arg.name.clone(),
+                                value,
+                            }));
+                        }
+                        _ => {
+                            return Err(SyntaxError {
+                                offset: expression_start,
+                                reason: "`.input` must be followed by a variable expression".into(),
+                            })
+                        }
+                    }
+                }
+                "local" => {
+                    if !self.whitespace() {
+                        return Err(self.error("expected whitespace after `.local`"));
+                    }
+                    let name = self.variable()?.name;
+                    self.whitespace();
+                    self.expect('=')?;
+                    self.whitespace();
+                    let value = self.expression()?;
+                    declarations.push(Declaration::Local(LocalDeclaration { name, value }));
+                }
+                "match" => return self.matcher(declarations),
+                _ => {
+                    let body = self
+                        .whitespace_before(|c| c != '{')
+                        .then(|| self.reserved_body())
+                        .filter(|body| !body.is_empty());
+                    let mut expressions = Vec::new();
+                    while self.at_expression() {
+                        expressions.push(self.expression()?);
+                    }
+                    if expressions.is_empty() {
+                        return Err(SyntaxError {
+                            offset: start,
+                            reason: format!("`.{keyword}` must be followed by an expression"),
+                        });
+                    }
+                    declarations.push(Declaration::UnsupportedStatement(UnsupportedStatement {
+                        keyword,
+                        body,
+                        expressions,
+                    }));
+                }
+            }
+        }
+    }
+
+    fn matcher(&mut self, declarations: Vec<Declaration>) -> Result<Message, SyntaxError> {
+        let mut selectors = Vec::new();
+        while self.at_expression() {
+            selectors.push(self.expression()?);
+        }
+        if selectors.is_empty() {
+            return Err(self.error("expected a selector after `.match`"));
+        }
+
+        let mut variants = Vec::new();
+        loop {
+            self.whitespace();
+            if self.peek().is_none() {
+                break;
+            }
+            variants.push(self.variant()?);
+        }
+        if variants.is_empty() {
+            return Err(self.error("expected a variant"));
+        }
+
+        Ok(Message::Select(SelectMessage {
+            declarations,
+            selectors,
+            variants,
+        }))
+    }
+
+    fn variant(&mut self) -> Result<Variant, SyntaxError> {
+        let mut keys = vec![self.key()?];
+        loop {
+            let separated = self.whitespace();
+            if self.rest().starts_with('{') {
+                break;
+            }
+            if !separated {
+                return Err(self.error("expected whitespace between variant keys"));
+            }
+            keys.push(self.key()?);
+        }
+        let value = self.quoted_pattern()?;
+        Ok(Variant { keys, value })
+    }
+
+    fn key(&mut self) -> Result<VariantKey, SyntaxError> {
+        if self.eat('*') {
+            Ok(VariantKey::Catchall)
+        } else {
+            self.literal().map(VariantKey::Literal)
+        }
+    }
+
+    fn quoted_pattern(&mut self) -> Result<Vec<PatternElement>, SyntaxError> {
+        if !self.rest().starts_with("{{") {
+            return Err(self.error("expected `{{`"));
+        }
+        self.position += 2;
+        let pattern = self.pattern()?;
+        if !self.rest().starts_with("}}") {
+            return Err(self.error("expected `}}`"));
+        }
+        self.position += 2;
+        Ok(pattern)
+    }
+
+    /// Parses a pattern up to the next unescaped `}` or the end of the source
+    fn pattern(&mut self) -> Result<Vec<PatternElement>, SyntaxError> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None | Some('}') => break,
+                Some('\\') => text.push(self.escape()?),
+                Some('{') => {
+                    if !text.is_empty() {
+                        elements.push(PatternElement::Literal(core::mem::take(&mut text)));
+                    }
+                    elements.push(self.placeholder()?);
+                }
+                Some(c) => {
+                    self.bump();
+                    text.push(c);
+                }
+            }
+        }
+        if !text.is_empty() {
+            elements.push(PatternElement::Literal(text));
+        }
+        Ok(elements)
+    }
+
+    fn escape(&mut self) -> Result<char, SyntaxError> {
+        let start = self.position;
+        self.expect('\\')?;
+        match self.bump() {
+            Some(c @ ('\\' | '{' | '|' | '}')) => Ok(c),
+            _ => Err(SyntaxError {
+                offset: start,
+                reason: "invalid escape sequence, only `\\\\`, `\\{`, `\\|` and `\\}` are allowed"
+                    .into(),
+            }),
+        }
+    }
+
+    fn placeholder(&mut self) -> Result<PatternElement, SyntaxError> {
+        let start = self.position;
+        self.expect('{')?;
+        self.whitespace();
+        let is_markup = matches!(self.peek(), Some('#' | '/'));
+        self.position = start;
+
+        if is_markup {
+            self.markup().map(PatternElement::Markup)
+        } else {
+            self.expression().map(PatternElement::Expression)
+        }
+    }
+
+    fn markup(&mut self) -> Result<Markup, SyntaxError> {
+        self.expect('{')?;
+        self.whitespace();
+        let close = self.eat('/');
+        if !close {
+            self.expect('#')?;
+        }
+        let name = self.identifier()?;
+        let options = self.options()?;
+        let attributes = self.attributes()?;
+        self.whitespace();
+
+        let kind = if close {
+            MarkupKind::Close
+        } else if self.eat('/') {
+            MarkupKind::Standalone
+        } else {
+            MarkupKind::Open
+        };
+        self.expect('}')?;
+
+        Ok(Markup {
+            kind,
+            name,
+            options,
+            attributes,
+        })
+    }
+
+    fn expression(&mut self) -> Result<Expression, SyntaxError> {
+        self.expect('{')?;
+        self.whitespace();
+
+        let expression = match self.peek() {
+            Some('$') => {
+                let arg = self.variable()?;
+                let annotation = self.trailing_annotation()?;
+                Expression::Variable(VariableExpression {
+                    arg,
+                    annotation,
+                    attributes: self.attributes()?,
+                })
+            }
+            Some(c) if c == ':' || UNSUPPORTED_SIGILS.contains(&c) => match self.annotation()? {
+                Annotation::Function(annotation) => Expression::Function(FunctionExpression {
+                    annotation,
+                    attributes: self.attributes()?,
+                }),
+                Annotation::Unsupported(annotation) => {
+                    Expression::Unsupported(UnsupportedExpression {
+                        annotation,
+                        attributes: self.attributes()?,
+                    })
+                }
+            },
+            Some(c) if c == '|' || c == '-' || c.is_ascii_digit() || is_name_start(c) => {
+                let arg = self.literal()?;
+                let annotation = self.trailing_annotation()?;
+                Expression::Literal(LiteralExpression {
+                    arg,
+                    annotation,
+                    attributes: self.attributes()?,
+                })
+            }
+            _ => return Err(self.error("expected a literal, a variable or an annotation")),
+        };
+
+        self.whitespace();
+        self.expect('}')?;
+        Ok(expression)
+    }
+
+    fn trailing_annotation(&mut self) -> Result<Option<Annotation>, SyntaxError> {
+        if self.whitespace_before(|c| c == ':' || UNSUPPORTED_SIGILS.contains(&c)) {
+            self.annotation().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn annotation(&mut self) -> Result<Annotation, SyntaxError> {
+        if self.eat(':') {
+            let name = self.identifier()?;
+            let options = self.options()?;
+            return Ok(Annotation::Function(FunctionAnnotation { name, options }));
+        }
+
+        let start = self.position;
+        match self.bump() {
+            Some(c) if UNSUPPORTED_SIGILS.contains(&c) => {
+                self.reserved_body();
+                Ok(Annotation::Unsupported(UnsupportedAnnotation {
+                    source: self.source[start..self.position].into(),
+                }))
+            }
+            _ => {
+                self.position = start;
+                Err(self.error("expected an annotation"))
+            }
+        }
+    }
+
+    /// Skips the body of a reserved annotation or statement and returns its source, without trailing whitespace
+    fn reserved_body(&mut self) -> String {
+        let start = self.position;
+        let mut end = self.position;
+        loop {
+            match self.peek() {
+                None | Some('{' | '}' | '@') => break,
+                Some(c) if is_whitespace(c) => {
+                    self.bump();
+                    continue;
+                }
+                Some('\\') => {
+                    if self.escape().is_err() {
+                        self.position = end;
+                        break;
+                    }
+                }
+                Some('|') => {
+                    if self.literal().is_err() {
+                        self.position = end;
+                        break;
+                    }
+                }
+                Some(_) => {
+                    self.bump();
+                }
+            }
+            end = self.position;
+        }
+        self.position = end;
+        self.source[start..end].into()
+    }
+
+    fn options(&mut self) -> Result<Vec<elements::Option>, SyntaxError> {
+        let mut options = Vec::new();
+        while self.whitespace_before(is_name_start) {
+            let name = self.identifier()?;
+            self.whitespace();
+            self.expect('=')?;
+            self.whitespace();
+            let value = if self.peek() == Some('$') {
+                OptionValue::Variable(self.variable()?)
+            } else {
+                OptionValue::Literal(self.literal()?)
+            };
+            options.push(elements::Option { name, value });
+        }
+        Ok(options)
+    }
+
+    fn attributes(&mut self) -> Result<Vec<Attribute>, SyntaxError> {
+        let mut attributes = Vec::new();
+        while self.whitespace_before(|c| c == '@') {
+            self.expect('@')?;
+            let name = self.identifier()?;
+
+            let start = self.position;
+            self.whitespace();
+            let value = if self.eat('=') {
+                self.whitespace();
+                if self.peek() == Some('$') {
+                    Some(AttributeValue::Variable(self.variable()?))
+                } else {
+                    Some(AttributeValue::Literal(self.literal()?))
+                }
+            } else {
+                self.position = start;
+                None
+            };
+            attributes.push(Attribute { name, value });
+        }
+        Ok(attributes)
+    }
+
+    fn variable(&mut self) -> Result<VariableRef, SyntaxError> {
+        self.expect('$')?;
+        Ok(VariableRef { name: self.name()? })
+    }
+
+    fn literal(&mut self) -> Result<Literal, SyntaxError> {
+        match self.peek() {
+            Some('|') => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.peek() {
+                        None => return Err(self.error("unterminated quoted literal")),
+                        Some('|') => {
+                            self.bump();
+                            break;
+                        }
+                        Some('\\') => value.push(self.escape()?),
+                        Some(c) => {
+                            self.bump();
+                            value.push(c);
+                        }
+                    }
+                }
+                Ok(Literal { value })
+            }
+            Some(c) if is_name_start(c) => Ok(Literal {
+                value: self.name()?,
+            }),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number_literal(),
+            _ => Err(self.error("expected a literal")),
+        }
+    }
+
+    fn number_literal(&mut self) -> Result<Literal, SyntaxError> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let start = parser.position;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.bump();
+            }
+            parser.position > start
+        };
+        let invalid = |parser: &Self| SyntaxError {
+            offset: start,
+            reason: format!(
+                "invalid number literal `{}`",
+                &parser.source[start..parser.position]
+            ),
+        };
+
+        self.eat('-');
+        if self.eat('0') {
+            if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(invalid(self));
+            }
+        } else if !digits(self) {
+            return Err(invalid(self));
+        }
+        if self.eat('.') && !digits(self) {
+            return Err(invalid(self));
+        }
+        if self.eat('e') || self.eat('E') {
+            let _ = self.eat('+') || self.eat('-');
+            if !digits(self) {
+                return Err(invalid(self));
+            }
+        }
+        if self.peek().is_some_and(is_name_char) {
+            self.bump();
+            return Err(invalid(self));
+        }
+
+        Ok(Literal {
+            value: self.source[start..self.position].into(),
+        })
+    }
+
+    /// A name with an optional namespace, like `foo:bar`
+    fn identifier(&mut self) -> Result<String, SyntaxError> {
+        let mut identifier = self.name()?;
+        if self.eat(':') {
+            identifier.push(':');
+            identifier.push_str(&self.name()?);
+        }
+        Ok(identifier)
+    }
+
+    fn name(&mut self) -> Result<String, SyntaxError> {
+        let start = self.position;
+        if !self.peek().is_some_and(is_name_start) {
+            return Err(self.error("expected a name"));
+        }
+        while self.peek().is_some_and(is_name_char) {
+            self.bump();
+        }
+        Ok(self.source[start..self.position].into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> PatternElement {
+        PatternElement::Literal(value.into())
+    }
+
+    fn variable(name: &str) -> PatternElement {
+        PatternElement::Expression(Expression::Variable(VariableExpression {
+            arg: VariableRef { name: name.into() },
+            annotation: None,
+            attributes: vec![],
+        }))
+    }
+
+    fn number(name: &str, options: Vec<(&str, &str)>) -> Expression {
+        Expression::Variable(VariableExpression {
+            arg: VariableRef { name: name.into() },
+            annotation: Some(Annotation::Function(FunctionAnnotation {
+                name: "number".into(),
+                options: options
+                    .into_iter()
+                    .map(|(name, value)| elements::Option {
+                        name: name.into(),
+                        value: OptionValue::Literal(Literal {
+                            value: value.into(),
+                        }),
+                    })
+                    .collect(),
+            })),
+            attributes: vec![],
+        })
+    }
+
+    fn pattern(message: &Message) -> &[PatternElement] {
+        match message {
+            Message::Pattern(message) => &message.pattern,
+            Message::Select(_) => panic!("expected a pattern message"),
+        }
+    }
+
+    #[test]
+    fn it_parses_simple_messages() {
+        let message = parse("Hello {$name}, \\{escaped\\} {|a \\| b|}!").expect("valid message");
+
+        assert_eq!(
+            pattern(&message),
+            [
+                text("Hello "),
+                variable("name"),
+                text(", {escaped} "),
+                PatternElement::Expression(Expression::Literal(LiteralExpression {
+                    arg: Literal {
+                        value: "a | b".into()
+                    },
+                    annotation: None,
+                    attributes: vec![],
+                })),
+                text("!"),
+            ]
+        );
+        assert_eq!(pattern(&parse("").expect("valid message")), []);
+        assert_eq!(pattern(&parse("  ").expect("valid message")), [text("  ")]);
+    }
+
+    #[test]
+    fn it_parses_declarations() {
+        let message = parse(".input {$count :number}\n.local $total = {$count :number maximumFractionDigits=2}\n{{{$total}}}")
+            .expect("valid message");
+
+        let Message::Pattern(message) = message else {
+            panic!("expected a pattern message");
+        };
+        assert_eq!(
+            message.declarations,
+            [
+                Declaration::Input(InputDeclaration {
+                    name: "count".into(),
+                    value: match number("count", vec![]) {
+                        Expression::Variable(v) => v,
+                        _ => unreachable!(),
+                    },
+                }),
+                Declaration::Local(LocalDeclaration {
+                    name: "total".into(),
+                    value: number("count", vec![("maximumFractionDigits", "2")]),
+                }),
+            ]
+        );
+        assert_eq!(message.pattern, [variable("total")]);
+    }
+
+    #[test]
+    fn it_parses_select_messages() {
+        let message =
+            parse(".match {$count :number}\n0 {{none}}\none {{one}}\n* {{{$count} many}}")
+                .expect("valid message");
+
+        let Message::Select(message) = message else {
+            panic!("expected a select message");
+        };
+        assert_eq!(message.selectors, [number("count", vec![])]);
+        assert_eq!(
+            message.variants,
+            [
+                Variant {
+                    keys: vec![VariantKey::Literal(Literal { value: "0".into() })],
+                    value: vec![text("none")],
+                },
+                Variant {
+                    keys: vec![VariantKey::Literal(Literal {
+                        value: "one".into()
+                    })],
+                    value: vec![text("one")],
+                },
+                Variant {
+                    keys: vec![VariantKey::Catchall],
+                    value: vec![variable("count"), text(" many")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_markup_and_attributes() {
+        let message =
+            parse("{#b class=bold}Hi{/b}{#br/}{$x @translate=no}").expect("valid message");
+
+        assert_eq!(
+            pattern(&message),
+            [
+                PatternElement::Markup(Markup {
+                    kind: MarkupKind::Open,
+                    name: "b".into(),
+                    options: vec![elements::Option {
+                        name: "class".into(),
+                        value: OptionValue::Literal(Literal {
+                            value: "bold".into()
+                        }),
+                    }],
+                    attributes: vec![],
+                }),
+                text("Hi"),
+                PatternElement::Markup(Markup {
+                    kind: MarkupKind::Close,
+                    name: "b".into(),
+                    options: vec![],
+                    attributes: vec![],
+                }),
+                PatternElement::Markup(Markup {
+                    kind: MarkupKind::Standalone,
+                    name: "br".into(),
+                    options: vec![],
+                    attributes: vec![],
+                }),
+                PatternElement::Expression(Expression::Variable(VariableExpression {
+                    arg: VariableRef { name: "x".into() },
+                    annotation: None,
+                    attributes: vec![Attribute {
+                        name: "translate".into(),
+                        value: Some(AttributeValue::Literal(Literal { value: "no".into() })),
+                    }],
+                })),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_unsupported_syntax() {
+        let message = parse(".when |x| {$a}\n{{{$x !reserved |body| text} {^private}}}")
+            .expect("valid message");
+
+        let Message::Pattern(message) = message else {
+            panic!("expected a pattern message");
+        };
+        assert!(matches!(
+            &message.declarations[..],
+            [Declaration::UnsupportedStatement(UnsupportedStatement { keyword, body: Some(body), expressions })]
+                if keyword == "when" && body == "|x|" && expressions.len() == 1
+        ));
+        assert!(matches!(
+            &message.pattern[0],
+            PatternElement::Expression(Expression::Variable(VariableExpression {
+                annotation: Some(Annotation::Unsupported(UnsupportedAnnotation { source })),
+                ..
+            })) if source == "!reserved |body| text"
+        ));
+        assert!(matches!(
+            &message.pattern[2],
+            PatternElement::Expression(Expression::Unsupported(UnsupportedExpression {
+                annotation: UnsupportedAnnotation { source },
+                ..
+            })) if source == "^private"
+        ));
+    }
+
+    #[test]
+    fn it_parses_number_literals() {
+        for literal in ["0", "-1", "1.5", "2e10", "1.25E-3"] {
+            let message = parse(&format!("{{{literal}}}")).expect("valid message");
+            assert!(matches!(
+                &pattern(&message)[0],
+                PatternElement::Expression(Expression::Literal(LiteralExpression { arg, .. })) if arg.value == literal
+            ));
+        }
+
+        for literal in ["01", "1.", "-", "1e", "1x"] {
+            assert!(parse(&format!("{{{literal}}}")).is_err(), "{literal}");
+        }
+    }
+
+    #[test]
+    fn it_reports_syntax_errors() {
+        let error = parse("Hello {$name").expect_err("invalid message");
+        assert_eq!(error.offset, 12);
+        assert_eq!(error.reason, "expected `}`");
+
+        let source = ".local $x = {1}\n{{ok}} trailing";
+        let error = parse(source).expect_err("invalid message");
+        assert_eq!(error.line_column(source), (2, 8));
+
+        assert!(parse("a } b").is_err());
+        assert!(parse("\\n").is_err());
+        assert!(parse(".input {|literal|}{{}}").is_err());
+        assert!(parse(".match {$x :number} one*{{}}").is_err());
+        assert!(parse(".match {$x :number}").is_err());
+        assert!(parse("{$x:number}").is_err());
+    }
+}
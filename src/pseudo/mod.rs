@@ -0,0 +1,294 @@
+//! Pseudo-localizes messages for testing, so hard-coded strings, truncated text and layouts that
+//! break for right-to-left languages show up before there are translations.
+//!
+//! Only the text of patterns is rewritten. Expressions, markup, declarations, selectors and variant
+//! keys stay as they are, so the message still formats with the same arguments:
+//!
+//! ```
+//! # use icu_messageformat_2::data_model::elements::Message;
+//! # use icu_messageformat_2::pseudo::Pseudolocalizer;
+//! let message: Message = "Settings for {$name}".parse().unwrap();
+//! let pseudo = Pseudolocalizer::new().accents(true).brackets(true).transform(&message);
+//! assert_eq!(pseudo.to_string(), "[Ŝéţţîñĝš ƒöŕ {$name}]");
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::data_model::elements::{
+    Message, PatternElement, PatternMessage, SelectMessage, Variant,
+};
+
+const RLM: char = '\u{200F}';
+const RLO: char = '\u{202E}';
+const PDF: char = '\u{202C}';
+
+/// The text that expanded patterns are padded with, repeated as needed
+const PADDING: &str = " one two three four five six seven eight nine ten";
+
+/// Configures how messages are pseudo-localized. Nothing is changed until an option is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pseudolocalizer {
+    accents: bool,
+    expansion: u16,
+    brackets: bool,
+    rtl: bool,
+}
+
+impl Pseudolocalizer {
+    pub const fn new() -> Self {
+        Self {
+            accents: false,
+            expansion: 0,
+            brackets: false,
+            rtl: false,
+        }
+    }
+
+    /// Accented text that is padded by 30% and wrapped in brackets, like the `en-XA` pseudo-locale
+    pub const fn accented() -> Self {
+        Self::new().accents(true).expansion(30).brackets(true)
+    }
+
+    /// Text that is displayed right-to-left, like the `ar-XB` pseudo-locale
+    pub const fn bidi() -> Self {
+        Self::new().rtl(true)
+    }
+
+    /// Replaces ASCII letters with accented ones that look alike, eg. `Settings` with `Ŝéţţîñĝš`
+    #[must_use]
+    pub const fn accents(mut self, accents: bool) -> Self {
+        self.accents = accents;
+        self
+    }
+
+    /// Pads each pattern by `percent` of the length of its text, rounded up, to test layouts with
+    /// languages that need more space
+    #[must_use]
+    pub const fn expansion(mut self, percent: u16) -> Self {
+        self.expansion = percent;
+        self
+    }
+
+    /// Wraps each pattern in `[` and `]`, so text that is cut off or put together from several
+    /// messages is easy to spot
+    #[must_use]
+    pub const fn brackets(mut self, brackets: bool) -> Self {
+        self.brackets = brackets;
+        self
+    }
+
+    /// Makes text display right-to-left: each word is wrapped in a right-to-left override, and each
+    /// pattern in right-to-left marks
+    #[must_use]
+    pub const fn rtl(mut self, rtl: bool) -> Self {
+        self.rtl = rtl;
+        self
+    }
+
+    /// Pseudo-localizes the text of a message and the text of its variants
+    pub fn transform(&self, message: &Message) -> Message {
+        match message {
+            Message::Pattern(message) => Message::Pattern(PatternMessage {
+                declarations: message.declarations.clone(),
+                pattern: self.pattern(&message.pattern),
+            }),
+            Message::Select(message) => Message::Select(SelectMessage {
+                declarations: message.declarations.clone(),
+                selectors: message.selectors.clone(),
+                variants: message
+                    .variants
+                    .iter()
+                    .map(|variant| Variant {
+                        keys: variant.keys.clone(),
+                        value: self.pattern(&variant.value),
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    fn pattern(&self, pattern: &[PatternElement]) -> Vec<PatternElement> {
+        let mut length = 0;
+        let mut transformed: Vec<PatternElement> = pattern
+            .iter()
+            .map(|element| match element {
+                PatternElement::Literal(text) => {
+                    length += text.chars().count();
+                    PatternElement::Literal(self.text(text))
+                }
+                element => element.clone(),
+            })
+            .collect();
+
+        let mut start = String::new();
+        let mut end = String::new();
+        if self.rtl {
+            start.push(RLM);
+        }
+        if self.brackets {
+            start.push('[');
+        }
+        let padding: String = PADDING
+            .chars()
+            .cycle()
+            .take(
+                length
+                    .saturating_mul(usize::from(self.expansion))
+                    .div_ceil(100),
+            )
+            .collect();
+        end.push_str(&self.text(&padding));
+        if self.brackets {
+            end.push(']');
+        }
+        if self.rtl {
+            end.push(RLM);
+        }
+
+        if !start.is_empty() {
+            match transformed.first_mut() {
+                Some(PatternElement::Literal(text)) => text.insert_str(0, &start),
+                _ => transformed.insert(0, PatternElement::Literal(start)),
+            }
+        }
+        if !end.is_empty() {
+            match transformed.last_mut() {
+                Some(PatternElement::Literal(text)) => text.push_str(&end),
+                _ => transformed.push(PatternElement::Literal(end)),
+            }
+        }
+        transformed
+    }
+
+    fn text(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut in_word = false;
+        for c in text.chars() {
+            if self.rtl && c.is_whitespace() == in_word {
+                out.push(if in_word { PDF } else { RLO });
+                in_word = !in_word;
+            }
+            out.push(if self.accents { accent(c) } else { c });
+        }
+        if in_word {
+            out.push(PDF);
+        }
+        out
+    }
+}
+
+/// An accented letter that looks like an ASCII letter
+const fn accent(c: char) -> char {
+    match c {
+        'a' => 'å',
+        'b' => 'ƀ',
+        'c' => 'ç',
+        'd' => 'ð',
+        'e' => 'é',
+        'f' => 'ƒ',
+        'g' => 'ĝ',
+        'h' => 'ĥ',
+        'i' => 'î',
+        'j' => 'ĵ',
+        'k' => 'ķ',
+        'l' => 'ļ',
+        'm' => 'ɱ',
+        'n' => 'ñ',
+        'o' => 'ö',
+        'p' => 'þ',
+        'q' => 'ǫ',
+        'r' => 'ŕ',
+        's' => 'š',
+        't' => 'ţ',
+        'u' => 'û',
+        'v' => 'ṽ',
+        'w' => 'ŵ',
+        'x' => 'ẋ',
+        'y' => 'ý',
+        'z' => 'ž',
+        'A' => 'Å',
+        'B' => 'Ɓ',
+        'C' => 'Ç',
+        'D' => 'Ð',
+        'E' => 'É',
+        'F' => 'Ƒ',
+        'G' => 'Ĝ',
+        'H' => 'Ĥ',
+        'I' => 'Î',
+        'J' => 'Ĵ',
+        'K' => 'Ķ',
+        'L' => 'Ļ',
+        'M' => 'Ṁ',
+        'N' => 'Ñ',
+        'O' => 'Ö',
+        'P' => 'Þ',
+        'Q' => 'Ǫ',
+        'R' => 'Ŕ',
+        'S' => 'Ŝ',
+        'T' => 'Ţ',
+        'U' => 'Û',
+        'V' => 'Ṽ',
+        'W' => 'Ŵ',
+        'X' => 'Ẋ',
+        'Y' => 'Ý',
+        'Z' => 'Ž',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(pseudo: Pseudolocalizer, source: &str) -> String {
+        let message: Message = source.parse().expect("valid message");
+        pseudo.transform(&message).to_string()
+    }
+
+    #[test]
+    fn it_only_changes_text() {
+        let source = ".input {$count :number}\n.local $x = {text :string}\n.match {$count}\n\
+                      one {{{#b class=one}One{/b} item for {$name}}}\n* {{{$count} items}}";
+        assert_eq!(
+            transform(Pseudolocalizer::new().accents(true), source),
+            ".input {$count :number}\n.local $x = {text :string}\n.match {$count}\n\
+             one {{{#b class=one }Öñé{/b} îţéɱ ƒöŕ {$name}}}\n* {{{$count} îţéɱš}}"
+        );
+        assert_eq!(
+            transform(Pseudolocalizer::new(), source),
+            source.replace("class=one", "class=one ")
+        );
+    }
+
+    #[test]
+    fn it_pads_and_brackets_patterns() {
+        assert_eq!(transform(Pseudolocalizer::accented(), "Save"), "[Ŝåṽé ö]");
+        assert_eq!(
+            transform(
+                Pseudolocalizer::new().expansion(50).brackets(true),
+                "{$n} files"
+            ),
+            "[{$n} files on]"
+        );
+        assert_eq!(
+            transform(Pseudolocalizer::new().brackets(true), "{$a}{#b}{/b}"),
+            "[{$a}{#b}{/b}]"
+        );
+        assert_eq!(transform(Pseudolocalizer::new().brackets(true), ""), "[]");
+        assert_eq!(
+            transform(Pseudolocalizer::new().expansion(u16::MAX), "Hi")
+                .chars()
+                .count(),
+            2 + 1311
+        );
+    }
+
+    #[test]
+    fn it_simulates_right_to_left_text() {
+        assert_eq!(
+            transform(Pseudolocalizer::bidi(), "Hi {$name}, bye"),
+            "\u{200F}\u{202E}Hi\u{202C} {$name}\u{202E},\u{202C} \u{202E}bye\u{202C}\u{200F}"
+        );
+    }
+}
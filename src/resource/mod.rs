@@ -0,0 +1,512 @@
+//! The MessageFormat 2 resource file format, which holds many messages with comments and sections:
+//!
+//! ```text
+//! # Messages of the shop
+//!
+//! # Shown on the home page
+//! greeting = Hello {$name}!
+//!
+//! [cart]
+//! empty = Your cart is empty
+//! items =
+//!   .input {$count :number}
+//!   .match {$count}
+//!   one {{{$count} item}}
+//!   * {{{$count} items}}
+//! ```
+//!
+//! Each entry is a key, `=` and a message. A message continues on the following lines if they are
+//! indented; the indentation of the first of them is removed from all of them. `[section]` headers
+//! put the entries that follow under a path, the ids of the entries above are `greeting`,
+//! `cart.empty` and `cart.items`. Comment lines start with `#` and belong to the entry or section
+//! that follows them. A comment at the start of the file that is followed by an empty line belongs
+//! to the whole file.
+//!
+//! See: https://github.com/eemeli/message-resource-wg
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::str::FromStr;
+
+use crate::catalog::{Catalog, CatalogError};
+use crate::data_model::elements::Message;
+use crate::data_model::errors::SyntaxError;
+use crate::locale::Locale;
+
+/// The messages of a resource file, with their comments and sections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// The comment about the whole file
+    pub comment: Option<String>,
+    /// The sections in file order. The first one has an empty path and holds the entries before the
+    /// first section header.
+    pub sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub path: Vec<String>,
+    pub comment: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The key of the entry, which may contain dots
+    pub key: String,
+    pub comment: Option<String>,
+    pub message: Message,
+}
+
+impl Resource {
+    pub fn new() -> Self {
+        Self {
+            comment: None,
+            sections: vec![Section {
+                path: Vec::new(),
+                comment: None,
+                entries: Vec::new(),
+            }],
+        }
+    }
+
+    /// All entries with their ids, which are the section path and the key joined by dots
+    pub fn entries(&self) -> impl Iterator<Item = (String, &Entry)> {
+        self.sections.iter().flat_map(|section| {
+            section
+                .entries
+                .iter()
+                .map(move |entry| (id(&section.path, &entry.key), entry))
+        })
+    }
+
+    /// Puts all messages into a catalog for `locale`. Comments and sections are dropped.
+    ///
+    /// # Errors
+    /// Returns an error if a message is not valid according to the data model.
+    pub fn into_catalog(self, locale: Locale) -> Result<Catalog, CatalogError> {
+        let mut catalog = Catalog::new(locale);
+        for section in self.sections {
+            for entry in section.entries {
+                catalog.insert(id(&section.path, &entry.key), entry.message)?;
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// A resource with all messages of a catalog, keyed by their ids
+    pub fn from_catalog(catalog: &Catalog) -> Self {
+        let mut resource = Self::new();
+        resource.sections[0].entries = catalog
+            .iter()
+            .map(|(id, message)| Entry {
+                key: id.into(),
+                comment: None,
+                message: message.clone(),
+            })
+            .collect();
+        resource
+    }
+}
+
+impl Default for Resource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error in a resource file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceError {
+    /// The 1-based line of the error
+    pub line: usize,
+    /// The 1-based column of the error, counted in characters
+    pub column: usize,
+    pub kind: ResourceErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceErrorKind {
+    /// A line is not an entry, a section header or a comment
+    InvalidLine(String),
+    /// Two entries have the same id
+    DuplicateId { id: String },
+    /// The message of an entry could not be parsed
+    Syntax { id: String, error: SyntaxError },
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ResourceErrorKind::InvalidLine(reason) => f.write_str(reason),
+            ResourceErrorKind::DuplicateId { id } => write!(f, "{id} is defined more than once"),
+            ResourceErrorKind::Syntax { id, error } => write!(f, "{id}: {}", error.reason),
+        }
+    }
+}
+
+impl core::error::Error for ResourceError {}
+
+impl FromStr for Resource {
+    type Err = ResourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+/// Parses a resource file.
+///
+/// # Errors
+/// Returns the first error in the file.
+pub fn parse(source: &str) -> Result<Resource, ResourceError> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut resource = Resource::new();
+    let mut comment: Vec<&str> = Vec::new();
+    let mut ids = BTreeSet::new();
+    let mut started = false;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let number = i + 1;
+        i += 1;
+
+        if line.trim().is_empty() {
+            if !started && resource.comment.is_none() && !comment.is_empty() {
+                resource.comment = take_comment(&mut comment);
+            }
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment.push(text.strip_prefix(' ').unwrap_or(text));
+            continue;
+        }
+        started = true;
+        if line.starts_with(is_indentation) {
+            return Err(invalid(
+                number,
+                1,
+                "unexpected indented line, only messages can continue on indented lines",
+            ));
+        }
+
+        if let Some(header) = line.trim_end().strip_prefix('[') {
+            let path = header.strip_suffix(']').ok_or_else(|| {
+                invalid(number, line.trim_end().chars().count() + 1, "expected `]`")
+            })?;
+            let path: Vec<String> = path
+                .split('.')
+                .map(|name| name.trim().to_string())
+                .collect();
+            if !path.iter().all(|name| is_key(name)) {
+                return Err(invalid(number, 2, "invalid section name"));
+            }
+            resource.sections.push(Section {
+                path,
+                comment: take_comment(&mut comment),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid(number, line.chars().count() + 1, "expected `=`"))?;
+        let key = key.trim_end();
+        if !is_key(key) {
+            return Err(invalid(number, 1, "invalid key"));
+        }
+
+        // The message starts after the `=` and continues on indented lines
+        let first = value.trim_start();
+        let first_column = line.len() - first.len();
+        let mut continuation = Vec::new();
+        while let Some(next) = lines[i..]
+            .iter()
+            .position(|line| !line.trim().is_empty())
+            .map(|n| i + n)
+        {
+            if !lines[next].starts_with(is_indentation) {
+                break;
+            }
+            continuation.extend(lines[i..=next].iter().copied());
+            i = next + 1;
+        }
+        let indentation = continuation
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .map_or(0, |line| {
+                line.len() - line.trim_start_matches(is_indentation).len()
+            });
+        let continuation: Vec<&str> = continuation
+            .iter()
+            .map(|line| {
+                let indented = line.len() - line.trim_start_matches(is_indentation).len();
+                &line[indented.min(indentation)..]
+            })
+            .collect();
+
+        // Blank lines between `key =` and the message are not part of it
+        let blank = if first.is_empty() {
+            continuation
+                .iter()
+                .take_while(|line| line.trim().is_empty())
+                .count()
+        } else {
+            0
+        };
+
+        let mut message_source = String::from(first);
+        for (n, line) in continuation[blank..].iter().enumerate() {
+            if n > 0 || !first.is_empty() {
+                message_source.push('\n');
+            }
+            message_source.push_str(line);
+        }
+
+        let section = resource
+            .sections
+            .last_mut()
+            .expect("there is always a root section");
+        let id = id(&section.path, key);
+        if !ids.insert(id.clone()) {
+            return Err(ResourceError {
+                line: number,
+                column: 1,
+                kind: ResourceErrorKind::DuplicateId { id },
+            });
+        }
+
+        let message = message_source.parse().map_err(|error: SyntaxError| {
+            // Map the position in the message to the position in the file
+            let (message_line, column) = error.line_column(&message_source);
+            let (line, column) = match (first.is_empty(), message_line) {
+                (false, 1) => (number, line[..first_column].chars().count() + column),
+                (false, n) => (number + n - 1, indentation + column),
+                (true, n) => (number + blank + n, indentation + column),
+            };
+            ResourceError {
+                line,
+                column,
+                kind: ResourceErrorKind::Syntax { id, error },
+            }
+        })?;
+        section.entries.push(Entry {
+            key: key.into(),
+            comment: take_comment(&mut comment),
+            message,
+        });
+    }
+
+    Ok(resource)
+}
+
+/// The id of an entry, which is the path of its section and its key joined by dots
+fn id(path: &[String], key: &str) -> String {
+    path.iter()
+        .map(String::as_str)
+        .chain([key])
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+const fn is_indentation(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Keys are names like in messages, which may contain dots
+fn is_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn take_comment(lines: &mut Vec<&str>) -> Option<String> {
+    (!lines.is_empty()).then(|| core::mem::take(lines).join("\n"))
+}
+
+fn invalid(line: usize, column: usize, reason: &str) -> ResourceError {
+    ResourceError {
+        line,
+        column,
+        kind: ResourceErrorKind::InvalidLine(reason.into()),
+    }
+}
+
+impl fmt::Display for Resource {
+    /// Writes the resource in a form that parses back into the same resource
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(comment) = &self.comment {
+            write_comment(f, comment)?;
+        }
+        let mut first = self.comment.is_none();
+
+        for section in &self.sections {
+            if section.path.is_empty() && section.comment.is_none() && section.entries.is_empty() {
+                continue;
+            }
+            if !first {
+                f.write_str("\n")?;
+            }
+            first = false;
+
+            if let Some(comment) = &section.comment {
+                write_comment(f, comment)?;
+            }
+            if !section.path.is_empty() {
+                writeln!(f, "[{}]", section.path.join("."))?;
+            }
+            for entry in &section.entries {
+                if let Some(comment) = &entry.comment {
+                    write_comment(f, comment)?;
+                }
+                write_entry(f, entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_comment(f: &mut fmt::Formatter<'_>, comment: &str) -> fmt::Result {
+    for line in comment.split('\n') {
+        if line.is_empty() {
+            f.write_str("#\n")?;
+        } else {
+            writeln!(f, "# {line}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_entry(f: &mut fmt::Formatter<'_>, entry: &Entry) -> fmt::Result {
+    let mut source = entry.message.to_string();
+    // Whitespace around a simple message would be lost, so it is quoted
+    if matches!(&entry.message, Message::Pattern(message) if message.declarations.is_empty())
+        && source.trim() != source
+        && !source.starts_with("{{")
+    {
+        source = format!("{{{{{source}}}}}");
+    }
+
+    if !source.contains('\n') {
+        return writeln!(f, "{} = {source}", entry.key);
+    }
+    writeln!(f, "{} =", entry.key)?;
+    for line in source.split('\n') {
+        if line.is_empty() {
+            f.write_str("\n")?;
+        } else {
+            writeln!(f, "  {line}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOP: &str = "# Messages of the shop
+
+# Shown on the home page
+greeting = Hello {$name}!
+
+# Everything about the cart
+[cart]
+empty = Your cart is empty
+items =
+    .input {$count :number}
+    .match {$count}
+    one {{{$count} item}}
+
+    * {{{$count} items}}
+# The total
+total.label = Total: {$sum}
+";
+
+    #[test]
+    fn it_parses_entries_sections_and_comments() {
+        let resource: Resource = SHOP.parse().expect("valid resource");
+
+        assert_eq!(resource.comment.as_deref(), Some("Messages of the shop"));
+        assert_eq!(
+            resource
+                .entries()
+                .map(|(id, entry)| (id, entry.comment.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("greeting".into(), Some("Shown on the home page")),
+                ("cart.empty".into(), None),
+                ("cart.items".into(), None),
+                ("cart.total.label".into(), Some("The total")),
+            ]
+        );
+        assert_eq!(
+            resource.sections[1].comment.as_deref(),
+            Some("Everything about the cart")
+        );
+        assert_eq!(
+            Ok(&resource.sections[1].entries[1].message),
+            ".input {$count :number}\n.match {$count}\none {{{$count} item}}\n\n* {{{$count} items}}"
+                .parse()
+                .as_ref()
+        );
+
+        // Blank lines before the message are not part of it
+        let blank: Resource = "a =\n\n  Hello\n".parse().expect("valid resource");
+        assert_eq!(blank.sections[0].entries[0].message.to_string(), "Hello");
+    }
+
+    #[test]
+    fn it_writes_resources_that_parse_back() {
+        let resource: Resource = SHOP.parse().expect("valid resource");
+        let written = resource.to_string();
+
+        assert_eq!(written.parse(), Ok(resource));
+        assert!(written.starts_with(
+            "# Messages of the shop\n\n# Shown on the home page\ngreeting = Hello {$name}!\n"
+        ));
+        assert!(written
+            .contains("[cart]\nempty = Your cart is empty\nitems =\n  .input {$count :number}\n"));
+
+        let mut catalog = Catalog::new("en".parse().expect("valid locale"));
+        catalog
+            .insert_source("padded", "  both ends  ")
+            .expect("valid message");
+        let written = Resource::from_catalog(&catalog).to_string();
+        assert_eq!(written, "padded = {{  both ends  }}\n");
+        let catalog = written
+            .parse::<Resource>()
+            .expect("valid resource")
+            .into_catalog("en".parse().expect("valid locale"))
+            .expect("valid messages");
+        assert_eq!(catalog.format("padded", ()), Ok("  both ends  ".into()));
+    }
+
+    #[test]
+    fn it_reports_positions_in_the_file() {
+        let error = parse("a = ok\n\n[s]\nb =\n  .input {$x :number}\n  {{{$x}\n")
+            .expect_err("invalid message");
+        assert_eq!((error.line, error.column), (6, 9));
+        assert!(matches!(error.kind, ResourceErrorKind::Syntax { ref id, .. } if id == "s.b"));
+
+        let error = parse("a =\n\n  {$x").expect_err("invalid message");
+        assert_eq!((error.line, error.column), (3, 6));
+
+        let error = parse("a = {$x").expect_err("invalid message");
+        assert_eq!((error.line, error.column), (1, 8));
+
+        let error = parse("a = x\n[s]\nb = y\n[s]\nb = z").expect_err("duplicate id");
+        assert_eq!(error.to_string(), "5:1: s.b is defined more than once");
+
+        let error = parse("just text").expect_err("invalid line");
+        assert_eq!(error.to_string(), "1:10: expected `=`");
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use icu_messageformat_2::codegen::Codegen;
+use icu_messageformat_2::data_model::elements::*;
+use icu_messageformat_2::format::{Function, FunctionError, FunctionInput};
+use icu_messageformat_2::locale::Locale;
+
+mod generated {
+    include!("codegen/generated.rs");
+}
+
+/// Formats its operand in upper case
+pub struct Shout;
+
+impl Function for Shout {
+    fn format(
+        &self,
+        input: FunctionInput<'_>,
+        out: &mut dyn fmt::Write,
+    ) -> Result<(), FunctionError> {
+        let operand = input.operand.ok_or(FunctionError::MissingOperand)?;
+        write!(out, "{}", operand.to_string().to_uppercase())?;
+        Ok(())
+    }
+}
+
+fn variable(name: &str, function: std::option::Option<FunctionAnnotation>) -> Expression {
+    Expression::Variable(VariableExpression {
+        arg: VariableRef { name: name.into() },
+        annotation: function.map(Annotation::Function),
+        attributes: vec![],
+    })
+}
+
+fn function(name: &str, options: Vec<(&str, &str)>) -> FunctionAnnotation {
+    FunctionAnnotation {
+        name: name.into(),
+        options: options
+            .into_iter()
+            .map(|(name, value)| Option {
+                name: name.into(),
+                value: OptionValue::Literal(Literal {
+                    value: value.into(),
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// ```text
+/// .input {$count :number}
+/// .local $name = {$user-name :string}
+/// .match $count
+/// 0 {{{$name} has no items}}
+/// one {{{$name} has {$count} item}}
+/// * {{{$name} has {$count :number minimumFractionDigits=1} items}}
+/// ```
+fn items() -> Message {
+    let name = || PatternElement::Expression(variable("name", None));
+    let count = || PatternElement::Expression(variable("count", None));
+
+    Message::Select(SelectMessage {
+        declarations: vec![
+            Declaration::Input(InputDeclaration {
+                name: "count".into(),
+                value: VariableExpression {
+                    arg: VariableRef {
+                        name: "count".into(),
+                    },
+                    annotation: Some(Annotation::Function(function("number", vec![]))),
+                    attributes: vec![],
+                },
+            }),
+            Declaration::Local(LocalDeclaration {
+                name: "name".into(),
+                value: variable("user-name", Some(function("string", vec![]))),
+            }),
+        ],
+        selectors: vec![variable("count", None)],
+        variants: vec![
+            Variant {
+                keys: vec![VariantKey::Literal(Literal { value: "0".into() })],
+                value: vec![name(), PatternElement::Literal(" has no items".into())],
+            },
+            Variant {
+                keys: vec![VariantKey::Literal(Literal {
+                    value: "one".into(),
+                })],
+                value: vec![
+                    name(),
+                    PatternElement::Literal(" has ".into()),
+                    count(),
+                    PatternElement::Literal(" item".into()),
+                ],
+            },
+            Variant {
+                keys: vec![VariantKey::Catchall],
+                value: vec![
+                    name(),
+                    PatternElement::Literal(" has ".into()),
+                    PatternElement::Expression(variable(
+                        "count",
+                        Some(function("number", vec![("minimumFractionDigits", "1")])),
+                    )),
+                    PatternElement::Literal(" items".into()),
+                ],
+            },
+        ],
+    })
+}
+
+/// `Hey {$name :shout}!`
+fn greeting() -> Message {
+    Message::Pattern(PatternMessage {
+        declarations: vec![],
+        pattern: vec![
+            PatternElement::Literal("Hey ".into()),
+            PatternElement::Expression(variable("name", Some(function("shout", vec![])))),
+            PatternElement::Literal("!".into()),
+        ],
+    })
+}
+
+fn generate() -> String {
+    let codegen = Codegen::new().function("shout", "crate::Shout");
+    let mut source = String::new();
+    for (name, message) in [("items", items()), ("greeting", greeting())] {
+        if !source.is_empty() {
+            source.push('\n');
+        }
+        source.push_str(&codegen.generate(name, &message).expect("valid message"));
+    }
+    source
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    assert_eq!(generate(), include_str!("codegen/generated.rs"));
+}
+
+#[test]
+fn generated_code_formats_messages() {
+    let en: Locale = "en".parse().expect("valid locale");
+    let de: Locale = "de".parse().expect("valid locale");
+
+    assert_eq!(
+        generated::items(&en, 0, "Ann"),
+        Ok("Ann has no items".into())
+    );
+    assert_eq!(generated::items(&en, 1, "Ann"), Ok("Ann has 1 item".into()));
+    assert_eq!(
+        generated::items(&en, 1500, "Ann"),
+        Ok("Ann has 1,500.0 items".into())
+    );
+    assert_eq!(
+        generated::items(&de, 2.5, "Ann"),
+        Ok("Ann has 2,5 items".into())
+    );
+    assert_eq!(generated::greeting(&en, "world"), Ok("Hey WORLD!".into()));
+}
@@ -0,0 +1,59 @@
+/// Formats the message:
+/// ```text
+/// .input {$count :number}
+/// .local $name = {$user-name :string}
+/// .match {$count}
+/// 0 {{{$name} has no items}}
+/// one {{{$name} has {$count} item}}
+/// * {{{$name} has {$count :number minimumFractionDigits=1} items}}
+/// ```
+pub fn items(
+    locale: &::icu_messageformat_2::locale::Locale,
+    count: impl ::icu_messageformat_2::codegen::support::Number,
+    user_name: &str,
+) -> ::core::result::Result<::icu_messageformat_2::codegen::support::String, ::icu_messageformat_2::format::FormatError> {
+    let count: ::icu_messageformat_2::arguments::Value = count.into();
+    let user_name: ::icu_messageformat_2::arguments::Value = ::icu_messageformat_2::arguments::Value::from(user_name);
+    let mut __mf2_out = ::icu_messageformat_2::codegen::support::String::new();
+    let __mf2_o1 = ::icu_messageformat_2::format::Options::new();
+    let __mf2_o2 = ::icu_messageformat_2::format::Options::new();
+    let __mf2_p3 = ::icu_messageformat_2::format::Function::select(&::icu_messageformat_2::format::functions::Number, ::icu_messageformat_2::format::FunctionInput { operand: Some(&count), options: &__mf2_o1, locale }, &["0", "one"]).map_err(|e| e.into_selection_error("number"))?;
+    match ::icu_messageformat_2::codegen::support::select_variant(&[&__mf2_p3[..]], &[&[Some(0)][..], &[Some(1)][..], &[None][..]]) {
+        Some(0) => {
+            ::icu_messageformat_2::format::Function::format(&::icu_messageformat_2::format::functions::StringFunction, ::icu_messageformat_2::format::FunctionInput { operand: Some(&user_name), options: &__mf2_o2, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("string"))?;
+            __mf2_out.push_str(" has no items");
+        }
+        Some(1) => {
+            ::icu_messageformat_2::format::Function::format(&::icu_messageformat_2::format::functions::StringFunction, ::icu_messageformat_2::format::FunctionInput { operand: Some(&user_name), options: &__mf2_o2, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("string"))?;
+            __mf2_out.push_str(" has ");
+            ::icu_messageformat_2::format::Function::format(&::icu_messageformat_2::format::functions::Number, ::icu_messageformat_2::format::FunctionInput { operand: Some(&count), options: &__mf2_o1, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("number"))?;
+            __mf2_out.push_str(" item");
+        }
+        _ => {
+            ::icu_messageformat_2::format::Function::format(&::icu_messageformat_2::format::functions::StringFunction, ::icu_messageformat_2::format::FunctionInput { operand: Some(&user_name), options: &__mf2_o2, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("string"))?;
+            __mf2_out.push_str(" has ");
+            let mut __mf2_o4 = __mf2_o1.clone();
+            __mf2_o4.insert("minimumFractionDigits", ::icu_messageformat_2::arguments::Value::from("1"));
+            ::icu_messageformat_2::format::Function::format(&::icu_messageformat_2::format::functions::Number, ::icu_messageformat_2::format::FunctionInput { operand: Some(&count), options: &__mf2_o4, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("number"))?;
+            __mf2_out.push_str(" items");
+        }
+    }
+    ::core::result::Result::Ok(__mf2_out)
+}
+
+/// Formats the message:
+/// ```text
+/// Hey {$name :shout}!
+/// ```
+pub fn greeting(
+    locale: &::icu_messageformat_2::locale::Locale,
+    name: impl ::core::convert::Into<::icu_messageformat_2::arguments::Value>,
+) -> ::core::result::Result<::icu_messageformat_2::codegen::support::String, ::icu_messageformat_2::format::FormatError> {
+    let name: ::icu_messageformat_2::arguments::Value = name.into();
+    let mut __mf2_out = ::icu_messageformat_2::codegen::support::String::new();
+    __mf2_out.push_str("Hey ");
+    let __mf2_o1 = ::icu_messageformat_2::format::Options::new();
+    ::icu_messageformat_2::format::Function::format(&crate::Shout, ::icu_messageformat_2::format::FunctionInput { operand: Some(&name), options: &__mf2_o1, locale }, &mut __mf2_out).map_err(|e| e.into_format_error("shout"))?;
+    __mf2_out.push('!');
+    ::core::result::Result::Ok(__mf2_out)
+}